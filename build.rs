@@ -3,6 +3,24 @@ use std::path;
 use std::process::Command;
 use std::process::exit;
 
+fn compile_test_file(target_dir: &str, cargo_manifest_dir: &str, src_name: &str, output_name: &str, crate_type: Option<&str>) -> bool
+{
+    let sep = path::MAIN_SEPARATOR;
+    let rustc = env::var("RUSTC").unwrap_or(String::from("rustc"));
+    let rustflags = env::var("RUSTFLAGS").unwrap_or(String::new());
+    let rustflags_vec = rustflags.split_whitespace();
+    let mut cmd = Command::new(rustc);
+    cmd.args(rustflags_vec);
+    if let Some(crate_type) = crate_type {
+        cmd.arg("--crate-type").arg(crate_type);
+    }
+    cmd.arg("-o").arg(format!("{}{}{}", target_dir, sep, output_name));
+    cmd.arg(format!("{}{}src{}test_bin{}{}", cargo_manifest_dir, sep, sep, sep, src_name));
+    let status = cmd.status().unwrap();
+    println!("cargo:rerun-if-changed={}{}src{}test_bin{}{}", cargo_manifest_dir, sep, sep, sep, src_name);
+    status.success()
+}
+
 fn main()
 {
     let profile = env::var("PROFILE").unwrap();
@@ -10,19 +28,12 @@ fn main()
     let sep = path::MAIN_SEPARATOR;
     if profile == String::from("debug") {
         let target_dir = format!("{}{}target{}debug", cargo_manifest_dir, sep, sep);
-        let rustc = env::var("RUSTC").unwrap_or(String::from("rustc"));
-        let rustflags = env::var("RUSTFLAGS").unwrap_or(String::new());
-        let rustflags_vec = rustflags.split_whitespace();
-        let mut cmd = Command::new(rustc);
-        cmd.args(rustflags_vec);
-        cmd.arg("-o").arg(format!("{}{}rsush_test", target_dir, sep));
-        cmd.arg(format!("{}{}src{}test_bin{}rsush_test.rs", cargo_manifest_dir, sep, sep, sep));
-        let status = cmd.status().unwrap();
-        println!("cargo:rerun-if-changed={}{}src{}test_bin{}rsush_test.rs", cargo_manifest_dir, sep, sep, sep);
-        if status.success() {
-            exit(0);
-        } else {
+        if !compile_test_file(target_dir.as_str(), cargo_manifest_dir.as_str(), "rsush_test.rs", "rsush_test", None) {
+            exit(1);
+        }
+        if !compile_test_file(target_dir.as_str(), cargo_manifest_dir.as_str(), "rsush_test_builtin.rs", "librsush_test_builtin.so", Some("cdylib")) {
             exit(1);
         }
+        exit(0);
     }
 }
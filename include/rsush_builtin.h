@@ -0,0 +1,52 @@
+/*
+ * Rsush - Rust single unix shell.
+ * Copyright (C) 2022 Łukasz Szpakowski
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+#ifndef _RSUSH_BUILTIN_H
+#define _RSUSH_BUILTIN_H
+
+#include <stddef.h>
+#include <sys/types.h>
+
+/*
+ * The context of a loadable built-in command. The get_var function returns a variable value
+ * that is valid until the built-in command returns or NULL if the variable isn't set. The
+ * set_var function returns -1 if the variable is read only. The write function writes to the
+ * file descriptor of the shell and returns the number of written bytes or -1 on error.
+ */
+struct rsush_context
+{
+    void *data;
+    const char *(*get_var)(void *data, const char *name);
+    int (*set_var)(void *data, const char *name, const char *value);
+    ssize_t (*write)(void *data, int fd, const char *buf, size_t len);
+};
+
+/*
+ * A loadable built-in command is exported as the rsush_builtin_NAME symbol where NAME is the
+ * name of the built-in command. The name field must be equal to NAME. The main function
+ * receives the arguments with the built-in command name as the first argument and returns
+ * the exit status.
+ */
+struct rsush_builtin
+{
+    const char *name;
+    int (*main)(int argc, char **argv, struct rsush_context *ctx);
+};
+
+#define RSUSH_BUILTIN(name, main) struct rsush_builtin rsush_builtin_##name = { #name, main }
+
+#endif
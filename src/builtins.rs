@@ -15,19 +15,29 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::cell::*;
+use std::rc::*;
 use crate::env::*;
 use crate::exec::*;
 use crate::interp::*;
 use crate::settings::*;
+use crate::xcfprint;
+use crate::xsfprintln;
 
 mod dot;
 mod colon;
 mod alias;
 mod bg;
 mod r#break;
+mod caller;
 mod cd;
 mod command;
+mod compgen;
+mod complete;
 mod r#continue;
+mod dirs;
+mod disown;
+mod enable;
 mod eval;
 mod exec;
 mod exit;
@@ -35,11 +45,14 @@ mod export;
 mod fg;
 mod getopts;
 mod jobs;
+mod popd;
+mod pushd;
 mod read;
 mod readonly;
 mod r#return;
 mod set;
 mod shift;
+mod suspend;
 mod times;
 mod trap;
 mod umask;
@@ -49,6 +62,80 @@ mod wait;
 
 pub type BuiltinFunction = fn(&[(String, String)], &[String], &mut Interpreter, &mut Executor, &mut Environment, &mut Settings) -> i32;
 
+pub type ClosureBuiltinFunction = Box<dyn FnMut(&mut Context, &[String]) -> i32>;
+
+pub struct Context<'a>
+{
+    vars: &'a [(String, String)],
+    interp: &'a mut Interpreter,
+    exec: &'a mut Executor,
+    env: &'a mut Environment,
+    settings: &'a mut Settings,
+}
+
+impl<'a> Context<'a>
+{
+    pub fn vars(&self) -> &[(String, String)]
+    { self.vars }
+
+    pub fn get_var(&self, name: &str) -> Option<String>
+    {
+        match self.vars.iter().rev().find(|p| p.0 == name) {
+            Some((_, value)) => Some(value.clone()),
+            None => self.env.var(name),
+        }
+    }
+
+    pub fn set_var(&mut self, name: &str, value: &str) -> bool
+    {
+        if self.env.read_only_var_attr(name) {
+            return false;
+        }
+        self.env.set_var(name, value, self.settings);
+        true
+    }
+
+    pub fn last_status(&self) -> i32
+    { self.interp.last_status() }
+
+    pub fn print_stdout(&mut self, s: &str)
+    { xcfprint!(self.exec, 1, "{}", s); }
+
+    pub fn print_stderr(&mut self, s: &str)
+    { xcfprint!(self.exec, 2, "{}", s); }
+
+    pub fn exit(&mut self, status: i32) -> i32
+    { self.interp.exit(status, false) }
+}
+
+pub fn call_closure_builtin_fun(closure_builtin_fun: &Rc<RefCell<ClosureBuiltinFunction>>, vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    if interp.has_special_builtin_fun(args[0].as_str(), env) {
+        for (name, value) in vars.iter() {
+            if env.read_only_var_attr(name) {
+                xsfprintln!(exec, 2, "{}: Is read only", name);
+                return interp.exit(1, false);
+            }
+            env.unset_unexported_var(name.as_str());
+            env.set_exported_var(name.as_str(), value.as_str());
+        }
+    }
+    let mut context = Context {
+        vars,
+        interp,
+        exec,
+        env,
+        settings,
+    };
+    match closure_builtin_fun.try_borrow_mut() {
+        Ok(mut closure_builtin_fun_r) => (*closure_builtin_fun_r)(&mut context, args),
+        Err(_) => {
+            xsfprintln!(context.exec, 2, "{}: Is already running", args[0]);
+            1
+        },
+    }
+}
+
 pub fn initialize_builtin_funs(env: &mut Environment)
 {
     env.set_builtin_fun(".", dot::main);
@@ -56,9 +143,15 @@ pub fn initialize_builtin_funs(env: &mut Environment)
     env.set_builtin_fun("alias", alias::main);
     env.set_builtin_fun("bg", bg::main);
     env.set_builtin_fun("break", r#break::main);
+    env.set_builtin_fun("caller", caller::main);
     env.set_builtin_fun("cd", cd::main);
     env.set_builtin_fun("command", command::main);
+    env.set_builtin_fun("compgen", compgen::main);
+    env.set_builtin_fun("complete", complete::main);
     env.set_builtin_fun("continue", r#continue::main);
+    env.set_builtin_fun("dirs", dirs::main);
+    env.set_builtin_fun("disown", disown::main);
+    env.set_builtin_fun("enable", enable::main);
     env.set_builtin_fun("eval", eval::main);
     env.set_builtin_fun("exec", exec::main);
     env.set_builtin_fun("exit", exit::main);
@@ -66,6 +159,8 @@ pub fn initialize_builtin_funs(env: &mut Environment)
     env.set_builtin_fun("fg", fg::main);
     env.set_builtin_fun("getopts", getopts::main);
     env.set_builtin_fun("jobs", jobs::main);
+    env.set_builtin_fun("popd", popd::main);
+    env.set_builtin_fun("pushd", pushd::main);
     env.set_builtin_fun("read", read::main);
     env.set_builtin_fun("readonly", readonly::main);
     env.set_builtin_fun("return", r#return::main);
@@ -73,6 +168,8 @@ pub fn initialize_builtin_funs(env: &mut Environment)
     env.set_builtin_fun("times", times::main);
     env.set_builtin_fun("trap", trap::main);
     env.set_builtin_fun("shift", shift::main);
+    env.set_builtin_fun("source", dot::main);
+    env.set_builtin_fun("suspend", suspend::main);
     env.set_builtin_fun("umask", umask::main);
     env.set_builtin_fun("unalias", unalias::main);
     env.set_builtin_fun("unset", unset::main);
@@ -0,0 +1,53 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, _env: &mut Environment, _settings: &mut Settings) -> i32
+{
+    if args.len() > 2 {
+        xcfprintln!(exec, 2, "Too many arguments");
+        return 1;
+    }
+    let n = match args.get(1) {
+        Some(arg) => {
+            match arg.parse::<usize>() {
+                Ok(n) => Some(n),
+                Err(_) => {
+                    xcfprintln!(exec, 2, "Invalid number");
+                    return 1;
+                },
+            }
+        },
+        None => None,
+    };
+    let call_stack = interp.call_stack();
+    match call_stack.len().checked_sub(n.unwrap_or(0) + 1).and_then(|i| call_stack.get(i)) {
+        Some(frame) => {
+            match n {
+                Some(_) => xcfprintln!(exec, 1, "{} {} {}", frame.call_pos.line, frame.name, frame.call_path),
+                None => xcfprintln!(exec, 1, "{} {}", frame.call_pos.line, frame.call_path),
+            }
+            0
+        },
+        None => 1,
+    }
+}
@@ -27,8 +27,8 @@ use crate::interp::*;
 use crate::settings::*;
 use crate::xcfprintln;
 
-#[derive(Eq, PartialEq)]
-enum PathFlag
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum PathFlag
 {
     None,
     Logical,
@@ -40,6 +40,36 @@ struct Options
     path_flag: PathFlag,
 }
 
+pub fn change_current_dir(path: &Path, path_flag: PathFlag, exec: &mut Executor, env: &mut Environment, settings: &Settings) -> Option<PathBuf>
+{
+    let mut path_buf = path.to_path_buf();
+    if path_flag != PathFlag::Physical {
+        match fs::canonicalize(path_buf.as_path()) {
+            Ok(tmp_path_buf) => path_buf = tmp_path_buf,
+            Err(err) => {
+                xcfprintln!(exec, 2, "{}: {}", path_buf.as_path().to_string_lossy(), err);
+                return None;
+            },
+        }
+    }
+    match env::set_current_dir(path_buf.as_path()) {
+        Ok(())   => (),
+        Err(err) => {
+            xcfprintln!(exec, 2, "{}: {}", path_buf.as_path().to_string_lossy(), err);
+            return None;
+        },
+    }
+    match env::current_dir() {
+        Ok(tmp_path_buf) => path_buf = tmp_path_buf,
+        Err(err) => {
+            xcfprintln!(exec, 2, "{}", err);
+            return None;
+        },
+    }
+    env.set_var("PWD", path_buf.as_path().to_string_lossy().into_owned().as_str(), settings);
+    Some(path_buf)
+}
+
 pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
 {
     let mut opt_parser = getopt::Parser::new(args, "LP");
@@ -62,7 +92,7 @@ pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpret
         }
     }
     let paths: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
-    let (mut path_buf, is_pwd) = match paths.get(0) {
+    let (path_buf, is_pwd) = match paths.get(0) {
         Some(path) => {
             if paths.len() > 1 {
                 xcfprintln!(exec, 2, "Too many arguments");
@@ -87,30 +117,10 @@ pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpret
             (PathBuf::from(home), false)
         },
     };
-    if opts.path_flag != PathFlag::Physical {
-        match fs::canonicalize(path_buf.as_path()) {
-            Ok(tmp_path_buf) => path_buf = tmp_path_buf,
-            Err(err) => {
-                xcfprintln!(exec, 2, "{}: {}", path_buf.as_path().to_string_lossy(), err);
-                return 1;
-            },
-        }
-    }
-    match env::set_current_dir(path_buf.as_path()) {
-        Ok(())   => (),
-        Err(err) => {
-            xcfprintln!(exec, 2, "{}: {}", path_buf.as_path().to_string_lossy(), err);
-            return 1;
-        },
-    }
-    match env::current_dir() {
-        Ok(tmp_path_buf) => path_buf = tmp_path_buf,
-        Err(err) => {
-            xcfprintln!(exec, 2, "{}", err);
-            return 1;
-        },
-    }
-    env.set_var("PWD", path_buf.as_path().to_string_lossy().into_owned().as_str(), settings);
+    let path_buf = match change_current_dir(path_buf.as_path(), opts.path_flag, exec, env, settings) {
+        Some(tmp_path_buf) => tmp_path_buf,
+        None => return 1,
+    };
     if is_pwd {
         xcfprintln!(exec, 1, "{}", path_buf.as_path().to_string_lossy());
     }
@@ -105,7 +105,7 @@ pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter
             let mut status = 0;
             let names: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
             for name in &names {
-                match env.builtin_fun(name.as_str()) {
+                match env.builtin_fun(name.as_str()).map(|_| ()).or_else(|| env.closure_builtin_fun(name.as_str()).map(|_| ())) {
                     Some(_) => {
                         match opts.verbose_flag {
                             VerboseFlag::Name => xcfprintln!(exec, 1, "{}", name),
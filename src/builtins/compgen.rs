@@ -0,0 +1,220 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use getopt;
+use getopt::Opt;
+use crate::completion::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, _settings: &mut Settings) -> i32
+{
+    let mut opt_parser = getopt::Parser::new(args, "cdfvW:");
+    let mut spec = CompletionSpec::new();
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('c', _))) => spec.actions.push(CompletionAction::Command),
+            Some(Ok(Opt('d', _))) => spec.actions.push(CompletionAction::Directory),
+            Some(Ok(Opt('f', _))) => spec.actions.push(CompletionAction::File),
+            Some(Ok(Opt('v', _))) => spec.actions.push(CompletionAction::Variable),
+            Some(Ok(Opt('W', Some(opt_arg)))) => spec.word_list = Some(opt_arg),
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if args.len() > opt_parser.index() + 1 {
+        xcfprintln!(exec, 2, "Too many arguments");
+        return 1;
+    }
+    let word = args.get(opt_parser.index()).map(|s| s.as_str()).unwrap_or("");
+    let names = generate_completions(spec.actions.as_slice(), spec.word_list.as_deref(), word, env);
+    for name in &names {
+        xcfprintln!(exec, 1, "{}", name);
+    }
+    if !names.is_empty() { 0 } else { 1 }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::rc::*;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_compgen_builtin_function_generates_words_from_word_list()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("compgen"),
+            String::from("-W"),
+            String::from("start stop status restart"),
+            String::from("--"),
+            String::from("st")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("compgen"),
+            String::from("-W"),
+            String::from("start stop"),
+            String::from("xxx")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+start
+status
+stop
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_compgen_builtin_function_generates_files_and_directories()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        make_dir("dir");
+        make_dir("dir/subdir");
+        write_file("dir/file.txt", "abc\n");
+        write_file("dir/.hidden", "def\n");
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("compgen"),
+            String::from("-f"),
+            String::from("dir/")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("compgen"),
+            String::from("-d"),
+            String::from("dir/")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+dir/file.txt
+dir/subdir
+dir/subdir
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_compgen_builtin_function_generates_commands_and_variables()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        env.set_alias("compalias", "ls");
+        env.set_var("PATH", "bin", &settings);
+        env.set_var("COMPVAR1", "abc", &settings);
+        env.set_var("COMPVAR2", "def", &settings);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("compgen"),
+            String::from("-c"),
+            String::from("comp")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("compgen"),
+            String::from("-v"),
+            String::from("COMPV")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+compalias
+compgen
+complete
+COMPVAR1
+COMPVAR2
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+    }
+}
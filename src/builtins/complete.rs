@@ -0,0 +1,227 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use getopt;
+use getopt::Opt;
+use crate::completion::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+struct Options
+{
+    print_flag: bool,
+    removal_flag: bool,
+}
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, _settings: &mut Settings) -> i32
+{
+    let mut opt_parser = getopt::Parser::new(args, "cdfvprW:F:");
+    let mut opts = Options {
+        print_flag: false,
+        removal_flag: false,
+    };
+    let mut spec = CompletionSpec::new();
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('c', _))) => spec.actions.push(CompletionAction::Command),
+            Some(Ok(Opt('d', _))) => spec.actions.push(CompletionAction::Directory),
+            Some(Ok(Opt('f', _))) => spec.actions.push(CompletionAction::File),
+            Some(Ok(Opt('v', _))) => spec.actions.push(CompletionAction::Variable),
+            Some(Ok(Opt('p', _))) => opts.print_flag = true,
+            Some(Ok(Opt('r', _))) => opts.removal_flag = true,
+            Some(Ok(Opt('W', Some(opt_arg)))) => spec.word_list = Some(opt_arg),
+            Some(Ok(Opt('F', Some(opt_arg)))) => spec.fun_name = Some(opt_arg),
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    let names: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
+    if opts.removal_flag {
+        if names.is_empty() {
+            env.unset_all_completion_specs();
+        } else {
+            for name in &names {
+                env.unset_completion_spec(name.as_str());
+            }
+        }
+        return 0;
+    }
+    if opts.print_flag || (names.is_empty() && spec.is_empty()) {
+        let mut status = 0;
+        if names.is_empty() {
+            let mut pairs: Vec<(&String, &CompletionSpec)> = env.completion_specs().iter().collect();
+            pairs.sort_by(|p1, p2| p1.0.cmp(p2.0));
+            for (name, spec) in &pairs {
+                xcfprintln!(exec, 1, "{}", spec.to_command_string(name.as_str()));
+            }
+        } else {
+            for name in &names {
+                match env.completion_spec(name.as_str()) {
+                    Some(spec) => xcfprintln!(exec, 1, "{}", spec.to_command_string(name.as_str())),
+                    None => {
+                        xcfprintln!(exec, 2, "{}: No completion specification", name);
+                        status = 1;
+                    },
+                }
+            }
+        }
+        return status;
+    }
+    if names.is_empty() {
+        xcfprintln!(exec, 2, "No command names");
+        return 1;
+    }
+    for name in &names {
+        env.set_completion_spec(name.as_str(), spec.clone());
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::rc::*;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_complete_builtin_function_sets_and_prints_completion_specs()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("complete"),
+            String::from("-F"),
+            String::from("_deploy"),
+            String::from("deploy"),
+            String::from("ship")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("complete"),
+            String::from("-d"),
+            String::from("-W"),
+            String::from("start stop"),
+            String::from("service")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("complete"),
+            String::from("-p")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status3);
+        let args4 = vec![
+            String::from("complete"),
+            String::from("-p"),
+            String::from("service"),
+            String::from("xxx")
+        ];
+        let status4 = main(&[], args4.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status4);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+complete -F _deploy deploy
+complete -d -W 'start stop' service
+complete -F _deploy ship
+complete -d -W 'start stop' service
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("xxx: No completion specification\n"), read_file("stderr2.txt"));
+        let mut expected_spec = CompletionSpec::new();
+        expected_spec.fun_name = Some(String::from("_deploy"));
+        assert_eq!(Some(expected_spec), env.completion_spec("deploy"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_complete_builtin_function_removes_completion_specs()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("complete"),
+            String::from("-f"),
+            String::from("abc"),
+            String::from("def")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("complete"),
+            String::from("-r"),
+            String::from("abc")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert!(env.completion_spec("abc").is_none());
+        assert!(env.completion_spec("def").is_some());
+    }
+}
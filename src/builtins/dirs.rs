@@ -0,0 +1,334 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::path::*;
+use getopt;
+use getopt::Opt;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+use super::cd::*;
+
+struct Options
+{
+    clear_flag: bool,
+    long_flag: bool,
+    line_flag: bool,
+    verbose_flag: bool,
+}
+
+pub fn current_dir_str(env: &Environment) -> String
+{
+    match env.var("PWD") {
+        Some(pwd) => pwd,
+        None => env::current_dir().map(|path_buf| path_buf.as_path().to_string_lossy().into_owned()).unwrap_or(String::from(".")),
+    }
+}
+
+pub fn dirs(env: &Environment) -> Vec<String>
+{
+    let mut dirs = vec![current_dir_str(env)];
+    dirs.extend(env.dir_stack().iter().cloned());
+    dirs
+}
+
+pub fn update_dir_stack_var(env: &mut Environment, settings: &Settings)
+{
+    let dir_stack_str = dirs(env).join(" ");
+    env.set_var("DIRSTACK", dir_stack_str.as_str(), settings);
+}
+
+fn abbreviate_home(dir: &str, env: &Environment) -> String
+{
+    match env.var("HOME") {
+        Some(home) if !home.is_empty() && home != "/" => {
+            match dir.strip_prefix(home.as_str()) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+                _ => String::from(dir),
+            }
+        },
+        _ => String::from(dir),
+    }
+}
+
+pub fn parse_dir_stack_index(arg: &str, len: usize) -> Option<Option<usize>>
+{
+    let (is_plus, n_str) = match arg.strip_prefix('+') {
+        Some(n_str) => (true, n_str),
+        None => {
+            match arg.strip_prefix('-') {
+                Some(n_str) => (false, n_str),
+                None => return None,
+            }
+        },
+    };
+    if n_str.is_empty() || !n_str.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let n = match n_str.parse::<usize>() {
+        Ok(tmp_n) => tmp_n,
+        Err(_) => return Some(None),
+    };
+    if n >= len {
+        Some(None)
+    } else if is_plus {
+        Some(Some(n))
+    } else {
+        Some(Some(len - 1 - n))
+    }
+}
+
+pub fn parse_path_flag_and_args<'a>(args: &'a [String], exec: &mut Executor) -> Option<(PathFlag, Vec<&'a String>)>
+{
+    let mut path_flag = PathFlag::None;
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == &String::from("--") {
+            i += 1;
+            break;
+        }
+        match arg.strip_prefix('-') {
+            Some(opt_chars) if !opt_chars.is_empty() && !opt_chars.chars().all(|c| c.is_ascii_digit()) => {
+                for c in opt_chars.chars() {
+                    match c {
+                        'L' => path_flag = PathFlag::Logical,
+                        'P' => path_flag = PathFlag::Physical,
+                        _ => {
+                            xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                            return None;
+                        },
+                    }
+                }
+            },
+            _ => break,
+        }
+        i += 1;
+    }
+    Some((path_flag, args[i..].iter().collect()))
+}
+
+pub fn change_current_dir_for_dir_stack(path: &Path, path_flag: PathFlag, exec: &mut Executor, env: &mut Environment, settings: &Settings) -> bool
+{
+    let old_dir = current_dir_str(env);
+    match change_current_dir(path, path_flag, exec, env, settings) {
+        Some(_) => {
+            env.set_var("OLDPWD", old_dir.as_str(), settings);
+            true
+        },
+        None => false,
+    }
+}
+
+pub fn print_dirs(exec: &mut Executor, env: &Environment, is_long: bool, is_line: bool, is_verbose: bool)
+{
+    let dirs: Vec<String> = dirs(env).iter().map(|dir| if is_long { dir.clone() } else { abbreviate_home(dir.as_str(), env) }).collect();
+    if is_verbose {
+        for (i, dir) in dirs.iter().enumerate() {
+            xcfprintln!(exec, 1, "{:2}  {}", i, dir);
+        }
+    } else if is_line {
+        for dir in &dirs {
+            xcfprintln!(exec, 1, "{}", dir);
+        }
+    } else {
+        xcfprintln!(exec, 1, "{}", dirs.join(" "));
+    }
+}
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    let index_arg = args.iter().skip(1).find(|arg| parse_dir_stack_index(arg.as_str(), usize::MAX).is_some());
+    let opt_args: Vec<String> = args.iter().filter(|arg| Some(*arg) != index_arg).cloned().collect();
+    let mut opt_parser = getopt::Parser::new(opt_args.as_slice(), "clpv");
+    let mut opts = Options {
+        clear_flag: false,
+        long_flag: false,
+        line_flag: false,
+        verbose_flag: false,
+    };
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('c', _))) => opts.clear_flag = true,
+            Some(Ok(Opt('l', _))) => opts.long_flag = true,
+            Some(Ok(Opt('p', _))) => opts.line_flag = true,
+            Some(Ok(Opt('v', _))) => opts.verbose_flag = true,
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    if opt_args.len() > opt_parser.index() {
+        xcfprintln!(exec, 2, "Too many arguments");
+        return 1;
+    }
+    if opts.clear_flag {
+        env.set_dir_stack(Vec::new());
+        update_dir_stack_var(env, settings);
+        return 0;
+    }
+    match index_arg {
+        Some(index_arg) => {
+            let dirs = dirs(env);
+            match parse_dir_stack_index(index_arg.as_str(), dirs.len()) {
+                Some(Some(i)) => {
+                    let dir = if opts.long_flag { dirs[i].clone() } else { abbreviate_home(dirs[i].as_str(), env) };
+                    if opts.verbose_flag {
+                        xcfprintln!(exec, 1, "{:2}  {}", i, dir);
+                    } else {
+                        xcfprintln!(exec, 1, "{}", dir);
+                    }
+                },
+                _ => {
+                    xcfprintln!(exec, 2, "{}: Directory stack index out of range", index_arg);
+                    return 1;
+                },
+            }
+        },
+        None => print_dirs(exec, env, opts.long_flag, opts.line_flag, opts.verbose_flag),
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::rc::*;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dirs_builtin_function_prints_directory_stack()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        env.set_var("PWD", "/home/user/src", &settings);
+        env.set_var("HOME", "/home/user", &settings);
+        env.set_dir_stack(vec![String::from("/home/user"), String::from("/tmp")]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("dirs")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("dirs"),
+            String::from("-v")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("dirs"),
+            String::from("-l"),
+            String::from("-p")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status3);
+        let args4 = vec![
+            String::from("dirs"),
+            String::from("-1")
+        ];
+        let status4 = main(&[], args4.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status4);
+        let args5 = vec![
+            String::from("dirs"),
+            String::from("+3")
+        ];
+        let status5 = main(&[], args5.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status5);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+~/src ~ /tmp
+ 0  ~/src
+ 1  ~
+ 2  /tmp
+/home/user/src
+/home/user
+/tmp
+~
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("+3: Directory stack index out of range\n"), read_file("stderr2.txt"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dirs_builtin_function_clears_directory_stack()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        env.set_var("PWD", "/home/user/src", &settings);
+        env.set_dir_stack(vec![String::from("/home/user"), String::from("/tmp")]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("dirs"),
+            String::from("-c")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert!(env.dir_stack().is_empty());
+        assert_eq!(Some(String::from("/home/user/src")), env.var("DIRSTACK"));
+    }
+}
@@ -0,0 +1,218 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use getopt;
+use getopt::Opt;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+struct Options
+{
+    all_flag: bool,
+    nohup_flag: bool,
+}
+
+fn disown_job(job_id: u32, opts: &Options, exec: &mut Executor) -> bool
+{
+    if !exec.jobs().contains_key(&job_id) {
+        xcfprintln!(exec, 2, "{}: No job", job_id);
+        return false;
+    }
+    if opts.nohup_flag {
+        exec.set_job_nohup_flag(job_id, true);
+    } else {
+        exec.remove_job(job_id);
+    }
+    true
+}
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, _env: &mut Environment, _settings: &mut Settings) -> i32
+{
+    let mut opt_parser = getopt::Parser::new(args, "ah");
+    let mut opts = Options {
+        all_flag: false,
+        nohup_flag: false,
+    };
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('a', _))) => opts.all_flag = true,
+            Some(Ok(Opt('h', _))) => opts.nohup_flag = true,
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    let mut status = 0;
+    let args: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
+    if opts.all_flag {
+        let mut job_ids: Vec<u32> = exec.jobs().keys().copied().collect();
+        job_ids.sort();
+        for job_id in &job_ids {
+            disown_job(*job_id, &opts, exec);
+        }
+    } else if !args.is_empty() {
+        for arg in &args {
+            match exec.parse_job_id(arg.as_str()) {
+                Ok(job_id) => {
+                    if !disown_job(job_id, &opts, exec) {
+                        status = 1;
+                    }
+                },
+                Err(JobIdError::NoPercent) => {
+                    match arg.parse::<u32>() {
+                        Ok(job_id) => {
+                            if !disown_job(job_id, &opts, exec) {
+                                status = 1;
+                            }
+                        },
+                        Err(_) => {
+                            xcfprintln!(exec, 2, "Invalid number");
+                            status = 1;
+                        },
+                    }
+                },
+                Err(err) => {
+                    xcfprintln!(exec, 2, "{}", err);
+                    status = 1;
+                },
+            }
+        }
+    } else {
+        match exec.current_job_id() {
+            Some(job_id) => {
+                if !disown_job(job_id, &opts, exec) {
+                    status = 1;
+                }
+            },
+            None => {
+                xcfprintln!(exec, 2, "No job");
+                status = 1;
+            },
+        }
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::rc::*;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_disown_builtin_function_removes_jobs()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        exec.add_job(&Job::new(1234, "sleep 10"));
+        exec.add_job(&Job::new(2345, "cat file.txt"));
+        exec.add_job(&Job::new(3456, "less file2.txt"));
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("disown"),
+            String::from("%?at")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("disown")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("disown"),
+            String::from("2")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status3);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("2: No job\n"), read_file("stderr2.txt"));
+        assert_eq!(1, exec.jobs().len());
+        assert_eq!(true, exec.jobs().contains_key(&1));
+        assert_eq!(Some(1), exec.current_job_id());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_disown_builtin_function_marks_all_jobs_with_nohup_flag()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        exec.add_job(&Job::new(1234, "sleep 10"));
+        exec.add_job(&Job::new(2345, "cat file.txt"));
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("disown"),
+            String::from("-h"),
+            String::from("-a")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert_eq!(2, exec.jobs().len());
+        assert_eq!(true, exec.jobs().values().all(|job| job.nohup_flag));
+    }
+}
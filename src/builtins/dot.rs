@@ -17,6 +17,9 @@
 //
 use std::fs::*;
 use std::io::*;
+use std::path;
+use std::path::*;
+use crate::args::*;
 use crate::env::*;
 use crate::exec::*;
 use crate::interp::*;
@@ -27,6 +30,22 @@ use crate::settings::*;
 use crate::xsfprint;
 use crate::xsfprintln;
 
+fn find_file(name: &str, env: &Environment) -> PathBuf
+{
+    if !name.contains(path::MAIN_SEPARATOR) {
+        let path = env.var("PATH").unwrap_or(String::from("/bin:/usr/bin"));
+        for dir_path in path.split(':') {
+            let mut file_path_buf = PathBuf::from(dir_path);
+            file_path_buf.push(name);
+            match metadata(file_path_buf.as_path()) {
+                Ok(metadata) if metadata.file_type().is_file() => return file_path_buf,
+                _ => (),
+            }
+        }
+    }
+    PathBuf::from(name)
+}
+
 pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
 {
     for (name, value) in vars.iter() {
@@ -38,15 +57,23 @@ pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter
         env.set_exported_var(name.as_str(), value.as_str());
     }
     match args.get(1) {
-        Some(path) => {
-            match File::open(path) {
+        Some(name) => {
+            let path = find_file(name, env).to_string_lossy().into_owned();
+            match File::open(path.as_str()) {
                 Ok(mut file) => {
                     let mut br = BufReader::new(&mut file);
                     let mut cr = CharReader::new(&mut br);
-                    let mut lexer = Lexer::new(path, &Position::new(1, 1), &mut cr, 0, false);
+                    let mut lexer = Lexer::new(path.as_str(), &Position::new(1, 1), &mut cr, 0, false);
                     let mut parser = Parser::new();
                     let mut status = 0;
-                    loop {
+                    interp.push_call_frame(args[0].as_str(), path.as_str());
+                    let is_args = args.len() > 2;
+                    if is_args {
+                        let mut tmp_args = Arguments::new();
+                        tmp_args.set_args(args[2..].iter().map(|a| a.clone()).collect());
+                        settings.push_args(tmp_args);
+                    }
+                    let status = loop {
                         match parser.parse_logical_commands_for_line(&mut lexer, settings) {
                             Ok(None) => break status,
                             Ok(Some(commands)) => {
@@ -54,7 +81,7 @@ pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter
                                     xsfprint!(exec, 2, "{}", lexer.content_for_verbose());
                                     lexer.clear_content_for_verbose();
                                 }
-                                status = interp.interpret_logical_commands(exec, commands.as_slice(), env, settings);
+                                status = exec.interpret_or(true, |exec| interp.interpret_logical_commands(exec, commands.as_slice(), env, settings));
                                 if interp.has_break_or_continue_or_return_or_exit() {
                                     break status;
                                 }
@@ -64,7 +91,13 @@ pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter
                                 break interp.exit(1, false);
                             },
                         }
+                    };
+                    let res = interp.do_action(exec, RETURN_SIG, env, settings);
+                    if is_args {
+                        settings.pop_args();
                     }
+                    interp.pop_call_frame();
+                    res.unwrap_or(status)
                 },
                 Err(err) => {
                     xsfprintln!(exec, 2, "{}: {}", path, err);
@@ -148,6 +181,45 @@ jkl
         assert_eq!(String::new(), read_file("stderr2.txt"));
     }
 
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dot_builtin_function_interprets_commands_from_file_for_executing_last_command()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let s = "
+./rsush_test exit 1
+./rsush_test args abc def
+";
+        write_file("test.sh", &s[1..]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("."),
+            String::from("test.sh")
+        ];
+        let status = exec.interpret_as_last(|exec| main(&[], args.as_slice(), &mut interp, exec, &mut env, &mut settings));
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+abc
+def
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+    }
+
     #[sealed_test(before=setup(), after=teardown())]
     fn test_dot_builtin_function_interprets_commands_from_file_for_verbose_that_is_set()
     {
@@ -249,6 +321,146 @@ jkl
         assert_eq!(Some(String::from("ghi")), env.exported_var("VAR2"));
     }
     
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dot_builtin_function_sets_arguments()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        settings.current_args_mut().set_args(vec![String::from("xxx"), String::from("yyy")]);
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let s = "
+echo $#
+echo $1
+echo $2
+";
+        write_file("test.sh", &s[1..]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("."),
+            String::from("test.sh"),
+            String::from("abc"),
+            String::from("def")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+2
+abc
+def
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert_eq!(vec![String::from("xxx"), String::from("yyy")], settings.current_args().args().to_vec());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dot_builtin_function_searches_file_in_path()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let path = format!("dir1:dir2:{}", env.var("PATH").unwrap_or(String::from("/bin:/usr/bin")));
+        env.set_var("PATH", path.as_str(), &settings);
+        let s = "
+echo abc
+echo def
+";
+        make_dir("dir1");
+        make_dir("dir2");
+        make_dir("dir1/test.sh");
+        write_file("dir2/test.sh", &s[1..]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("."),
+            String::from("test.sh")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+abc
+def
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dot_builtin_function_searches_file_in_path_and_sets_arguments_for_source()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        settings.current_args_mut().set_args(vec![String::from("xxx"), String::from("yyy")]);
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let path = format!("dir1:{}", env.var("PATH").unwrap_or(String::from("/bin:/usr/bin")));
+        env.set_var("PATH", path.as_str(), &settings);
+        let s = "
+echo $#
+echo $1
+echo $VAR1
+";
+        make_dir("dir1");
+        write_file("dir1/test.sh", &s[1..]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let vars = vec![
+            (String::from("VAR1"), String::from("def"))
+        ];
+        let args = vec![
+            String::from("source"),
+            String::from("test.sh"),
+            String::from("abc")
+        ];
+        let status = main(vars.as_slice(), args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+1
+abc
+def
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert_eq!(Some(String::from("def")), env.exported_var("VAR1"));
+        assert_eq!(vec![String::from("xxx"), String::from("yyy")], settings.current_args().args().to_vec());
+    }
+
     #[sealed_test(before=setup(), after=teardown())]
     fn test_dot_builtin_function_complains_on_parser_error()
     {
@@ -288,6 +500,51 @@ abc
         assert_eq!(String::new(), read_file("stderr2.txt"));
     }
 
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_dot_builtin_function_complains_on_parser_error_for_file_in_path()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        settings.current_args_mut().set_args(vec![String::from("xxx"), String::from("yyy")]);
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let path = format!("dir1:{}", env.var("PATH").unwrap_or(String::from("/bin:/usr/bin")));
+        env.set_var("PATH", path.as_str(), &settings);
+        let s = "
+echo $1
+echo def )
+echo ghi
+";
+        make_dir("dir1");
+        write_file("dir1/test.sh", &s[1..]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("."),
+            String::from("test.sh"),
+            String::from("abc")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status);
+        assert!(interp.has_exit_with(false));
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+abc
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::from("dir1/test.sh: 2.10: unexpected token\n"), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert_eq!(vec![String::from("xxx"), String::from("yyy")], settings.current_args().args().to_vec());
+    }
+
     #[sealed_test(before=setup(), after=teardown())]
     fn test_dot_builtin_function_complains_on_no_file()
     {
@@ -0,0 +1,536 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::rc::*;
+use getopt;
+use getopt::Opt;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::loadable;
+use crate::loadable::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+struct Options
+{
+    all_flag: bool,
+    delete_flag: bool,
+    disable_flag: bool,
+    file: Option<String>,
+}
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, _settings: &mut Settings) -> i32
+{
+    let mut opt_parser = getopt::Parser::new(args, "adf:n");
+    let mut opts = Options {
+        all_flag: false,
+        delete_flag: false,
+        disable_flag: false,
+        file: None,
+    };
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('a', _))) => opts.all_flag = true,
+            Some(Ok(Opt('d', _))) => opts.delete_flag = true,
+            Some(Ok(Opt('f', Some(opt_arg)))) => opts.file = Some(opt_arg),
+            Some(Ok(Opt('n', _))) => opts.disable_flag = true,
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    let names: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
+    if names.is_empty() {
+        if opts.file.is_some() || opts.delete_flag {
+            xcfprintln!(exec, 2, "No built-in command names");
+            return 1;
+        }
+        let mut pairs: Vec<(&String, bool)> = Vec::new();
+        if opts.all_flag || !opts.disable_flag {
+            pairs.extend(env.builtin_funs().keys().map(|n| (n, true)));
+            pairs.extend(env.closure_builtin_funs().keys().map(|n| (n, true)));
+        }
+        if opts.all_flag || opts.disable_flag {
+            pairs.extend(env.disabled_builtin_funs().keys().map(|n| (n, false)));
+            pairs.extend(env.disabled_closure_builtin_funs().keys().map(|n| (n, false)));
+        }
+        pairs.sort();
+        pairs.dedup();
+        for (name, is_enabled) in &pairs {
+            if *is_enabled {
+                xcfprintln!(exec, 1, "enable {}", name);
+            } else {
+                xcfprintln!(exec, 1, "enable -n {}", name);
+            }
+        }
+        return 0;
+    }
+    let mut status = 0;
+    match &opts.file {
+        Some(path) => {
+            let library = match Library::open(path) {
+                Ok(tmp_library) => Rc::new(tmp_library),
+                Err(err) => {
+                    xcfprintln!(exec, 2, "{}: {}", path, err);
+                    return 1;
+                },
+            };
+            for name in &names {
+                match LoadableBuiltin::load(&library, name.as_str()) {
+                    Ok(loadable_builtin) => {
+                        if env.loadable_builtin(name.as_str()).is_some() {
+                            env.unset_builtin_fun(name.as_str());
+                        } else {
+                            env.shadow_builtin_fun(name.as_str());
+                        }
+                        env.set_loadable_builtin(name.as_str(), loadable_builtin);
+                        env.set_builtin_fun(name.as_str(), loadable::main);
+                    },
+                    Err(err) => {
+                        xcfprintln!(exec, 2, "{}: {}", name, err);
+                        status = 1;
+                    },
+                }
+            }
+        },
+        None => {
+            for name in &names {
+                if opts.delete_flag {
+                    if env.loadable_builtin(name.as_str()).is_some() {
+                        env.unset_loadable_builtin(name.as_str());
+                        env.restore_builtin_fun(name.as_str());
+                    } else {
+                        xcfprintln!(exec, 2, "{}: Not dynamically loaded", name);
+                        status = 1;
+                    }
+                } else if opts.disable_flag {
+                    if !env.disable_builtin_fun(name.as_str()) {
+                        xcfprintln!(exec, 2, "{}: Not a shell builtin", name);
+                        status = 1;
+                    }
+                } else {
+                    if !env.enable_builtin_fun(name.as_str()) {
+                        xcfprintln!(exec, 2, "{}: Not a shell builtin", name);
+                        status = 1;
+                    }
+                }
+            }
+        },
+    }
+    status
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::env;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    fn test_builtin_library_path() -> String
+    {
+        let cargo_manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+        format!("{}/target/debug/librsush_test_builtin.so", cargo_manifest_dir)
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_loads_builtin_functions()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        env.set_var("NAME", "abc", &settings);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            test_builtin_library_path(),
+            String::from("hello"),
+            String::from("fail")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        assert!(env.loadable_builtin("hello").is_some());
+        assert!(env.loadable_builtin("fail").is_some());
+        let args2 = vec![
+            String::from("hello"),
+            String::from("def"),
+            String::from("ghi")
+        ];
+        let hello_fun = env.builtin_fun("hello").unwrap();
+        let status2 = hello_fun(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("fail")
+        ];
+        let fail_fun = env.builtin_fun("fail").unwrap();
+        let status3 = fail_fun(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(2, status3);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+Hello, def ghi!
+NAME=abc
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("fail: error\n"), read_file("stderr2.txt"));
+        assert_eq!(Some(String::from("world")), env.var("HELLO"));
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_complains_on_invalid_builtin_function_name()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            test_builtin_library_path(),
+            String::from("bad"),
+            String::from("hello")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("bad: Invalid built-in command name\n"), read_file("stderr2.txt"));
+        assert!(env.builtin_fun("bad").is_none());
+        assert!(env.builtin_fun("hello").is_some());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_complains_on_non_existent_library()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            String::from("./libxxx.so"),
+            String::from("hello")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert!(read_file("stderr2.txt").starts_with("./libxxx.so: "));
+        assert!(env.builtin_fun("hello").is_none());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_complains_on_nul_bytes()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            String::from("./lib\0xxx.so"),
+            String::from("hello")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(1, status);
+        let args2 = vec![
+            String::from("enable"),
+            String::from("-f"),
+            test_builtin_library_path(),
+            String::from("hel\0lo")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("./lib\0xxx.so: Path contains nul byte\nhel\0lo: Name contains nul byte\n"), read_file("stderr2.txt"));
+        assert!(env.builtin_fun("hello").is_none());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_deletes_builtin_function()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            test_builtin_library_path(),
+            String::from("hello")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("enable"),
+            String::from("-d"),
+            String::from("hello"),
+            String::from("cd")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(1, status2);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::new(), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("cd: Not dynamically loaded\n"), read_file("stderr2.txt"));
+        assert!(env.builtin_fun("hello").is_none());
+        assert!(env.loadable_builtin("hello").is_none());
+        assert!(env.builtin_fun("cd").is_some());
+    }
+
+    fn hello_builtin_fun(_vars: &[(String, String)], _args: &[String], _interp: &mut Interpreter, _exec: &mut Executor, _env: &mut Environment, _settings: &mut Settings) -> i32
+    { 42 }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_restores_shadowed_builtin_function_after_deleting()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        env.set_builtin_fun("hello", hello_builtin_fun);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-f"),
+            test_builtin_library_path(),
+            String::from("hello")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let status2 = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("hello")
+        ];
+        let hello_fun = env.builtin_fun("hello").unwrap();
+        let status3 = hello_fun(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status3);
+        let args4 = vec![
+            String::from("enable"),
+            String::from("-d"),
+            String::from("hello")
+        ];
+        let status4 = main(&[], args4.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status4);
+        let hello_fun2 = env.builtin_fun("hello").unwrap();
+        let status5 = hello_fun2(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(42, status5);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        assert_eq!(String::from("Hello, !\n"), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert!(env.loadable_builtin("hello").is_none());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_disables_and_enables_builtin_functions()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-n"),
+            String::from("cd"),
+            String::from("umask"),
+            String::from("xxx")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(1, status);
+        assert!(env.builtin_fun("cd").is_none());
+        assert!(env.builtin_fun("umask").is_none());
+        let args2 = vec![
+            String::from("enable"),
+            String::from("-n")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("enable"),
+            String::from("umask")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status3);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+enable -n cd
+enable -n umask
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("xxx: Not a shell builtin\n"), read_file("stderr2.txt"));
+        assert!(env.builtin_fun("cd").is_none());
+        assert!(env.builtin_fun("umask").is_some());
+    }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_enable_builtin_function_lists_disables_and_enables_closure_builtin_functions()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_vars(&mut env);
+        env.set_builtin_fun("enable", main);
+        env.set_closure_builtin_fun("closure1", Box::new(|_, _| 0));
+        env.set_closure_builtin_fun("closure2", Box::new(|_, _| 0));
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("enable"),
+            String::from("-n"),
+            String::from("closure1"),
+            String::from("closure2")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        assert!(!env.has_builtin_fun("closure1"));
+        assert!(!env.has_builtin_fun("closure2"));
+        let args2 = vec![
+            String::from("enable"),
+            String::from("closure2")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("enable"),
+            String::from("-a")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        assert_eq!(0, status3);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = "
+enable -n closure1
+enable closure2
+enable enable
+";
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::new(), read_file("stderr2.txt"));
+        assert!(!env.has_builtin_fun("closure1"));
+        assert!(env.has_builtin_fun("closure2"));
+    }
+}
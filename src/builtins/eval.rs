@@ -49,6 +49,10 @@ pub fn main(vars: &[(String, String)], args: &[String], interp: &mut Interpreter
         Ok(commands) => interp.interpret_logical_commands(exec, commands.as_slice(), env, settings),
         Err(err) => {
             xsfprintln!(exec, 2, "{}", err);
+            match err.pos().map(|pos| caret_lines(s.as_str(), &pos, settings)).flatten() {
+                Some(lines) => xsfprintln!(exec, 2, "{}", lines),
+                None => (),
+            }
             interp.exit(1, false)
         },
     }
@@ -206,7 +210,7 @@ abc def ghi
         assert!(interp.has_exit_with(false));
         assert_eq!(false, interp.exec_redirect_flag());
         assert_eq!(String::new(), read_file("stdout.txt"));
-        assert_eq!(String::from("(evaluation): 1.10: unexpected token\n"), read_file("stderr.txt"));
+        assert_eq!(String::from("(evaluation): 1.10: unexpected token\necho abc )\n         ^\n"), read_file("stderr.txt"));
         assert_eq!(String::new(), read_file("stderr2.txt"));
     }
 
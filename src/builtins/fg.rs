@@ -65,7 +65,7 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
             return 1;
         },
     };
-    exec.set_foreground_for_process(job.pgid, settings);
+    exec.set_foreground_for_job(&job, settings);
     let mut is_success = true;
     let mut pids: Vec<Option<i32>> = Vec::new();
     for (i, (pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
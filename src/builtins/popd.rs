@@ -0,0 +1,153 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::path::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+use super::dirs::*;
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    let (path_flag, args) = match parse_path_flag_and_args(args, exec) {
+        Some(tmp_path_flag_and_args) => tmp_path_flag_and_args,
+        None => return 1,
+    };
+    if args.len() > 1 {
+        xcfprintln!(exec, 2, "Too many arguments");
+        return 1;
+    }
+    let arg = args.first();
+    let mut dir_stack = env.dir_stack().to_vec();
+    if dir_stack.is_empty() {
+        xcfprintln!(exec, 2, "Directory stack empty");
+        return 1;
+    }
+    let i = match arg {
+        Some(arg) => {
+            match parse_dir_stack_index(arg.as_str(), dir_stack.len() + 1) {
+                Some(Some(i)) => i,
+                Some(None) => {
+                    xcfprintln!(exec, 2, "{}: Directory stack index out of range", arg);
+                    return 1;
+                },
+                None => {
+                    xcfprintln!(exec, 2, "{}: Invalid argument", arg);
+                    return 1;
+                },
+            }
+        },
+        None => 0,
+    };
+    if i == 0 {
+        if !change_current_dir_for_dir_stack(Path::new(dir_stack[0].as_str()), path_flag, exec, env, settings) {
+            return 1;
+        }
+        dir_stack.remove(0);
+    } else {
+        dir_stack.remove(i - 1);
+    }
+    env.set_dir_stack(dir_stack);
+    update_dir_stack_var(env, settings);
+    print_dirs(exec, env, false, false, false);
+    0
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::*;
+    use std::fs;
+    use std::rc::*;
+    use super::*;
+    use crate::builtins::*;
+    use crate::test_builtins::*;
+    use crate::vars::*;
+    use crate::test_helpers::*;
+    use sealed_test::prelude::*;
+
+    fn setup()
+    { symlink_rsush_test(); }
+
+    fn teardown()
+    { remove_rsush_test(); }
+
+    #[sealed_test(before=setup(), after=teardown())]
+    fn test_popd_builtin_function_pops_directories()
+    {
+        let mut exec = Executor::new();
+        let mut interp = Interpreter::new();
+        let mut env = Environment::new();
+        let mut settings = Settings::new();
+        settings.arg0 = String::from("rsush");
+        initialize_builtin_funs(&mut env);
+        initialize_test_builtin_funs(&mut env);
+        initialize_vars(&mut env);
+        let saved_dir = current_dir();
+        let base = fs::canonicalize(saved_dir.as_path()).unwrap().as_path().to_string_lossy().into_owned();
+        env.set_var("PWD", base.as_str(), &settings);
+        env.set_var("HOME", "/nonexistent", &settings);
+        make_dir_all("a");
+        make_dir_all("b");
+        env.set_dir_stack(vec![format!("{}/a", base), format!("{}/b", base), base.clone()]);
+        write_file("stdin.txt", "Some line\nSecond line\n");
+        exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+        exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+        exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+        exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+        let args = vec![
+            String::from("popd"),
+            String::from("+2")
+        ];
+        let status = main(&[], args.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status);
+        let args2 = vec![
+            String::from("popd")
+        ];
+        let status2 = main(&[], args2.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status2);
+        let args3 = vec![
+            String::from("popd"),
+            String::from("-0")
+        ];
+        let status3 = main(&[], args3.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        assert_eq!(0, status3);
+        let args4 = vec![
+            String::from("popd")
+        ];
+        let status4 = main(&[], args4.as_slice(), &mut interp, &mut exec, &mut env, &mut settings);
+        exec.clear_files();
+        let new_dir = current_dir();
+        set_current_dir(saved_dir.as_path());
+        assert_eq!(1, status4);
+        assert!(interp.has_none());
+        assert_eq!(false, interp.exec_redirect_flag());
+        let expected_stdout_content = format!("
+{0} {0}/a {0}
+{0}/a {0}
+{0}/a
+", base);
+        assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+        assert_eq!(String::new(), read_file("stderr.txt"));
+        assert_eq!(String::from("Directory stack empty\n"), read_file("stderr2.txt"));
+        assert_eq!(PathBuf::from(format!("{}/a", base)), new_dir);
+        assert!(env.dir_stack().is_empty());
+        assert_eq!(Some(format!("{}/a", base)), env.var("DIRSTACK"));
+    }
+}
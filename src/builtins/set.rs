@@ -62,6 +62,8 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
                     xcfprintln!(exec, 1, "xtrace          {}", on_or_off(settings.xtrace_flag));
                     xcfprintln!(exec, 1, "strlossy        {}", on_or_off(settings.strlossy_flag));
                     xcfprintln!(exec, 1, "extxtrace       {}", on_or_off(settings.extxtrace_flag));
+                    xcfprintln!(exec, 1, "stacktrace      {}", on_or_off(settings.stacktrace_flag));
+                    xcfprintln!(exec, 1, "histexpand      {}", on_or_off(settings.histexpand_flag));
                     true
                 },
                 (OptionType::Plus, 'o') => {
@@ -81,6 +83,8 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
                     xcfprintln!(exec, 1, "set {}o xtrace", minus_or_plus(settings.xtrace_flag));
                     xcfprintln!(exec, 1, "set {}o strlossy", minus_or_plus(settings.strlossy_flag));
                     xcfprintln!(exec, 1, "set {}o extxtrace", minus_or_plus(settings.extxtrace_flag));
+                    xcfprintln!(exec, 1, "set {}o stacktrace", minus_or_plus(settings.stacktrace_flag));
+                    xcfprintln!(exec, 1, "set {}o histexpand", minus_or_plus(settings.histexpand_flag));
                     true
                 },
                 _ => false,
@@ -435,6 +439,8 @@ emacs           off
 xtrace          off
 strlossy        off
 extxtrace       off
+stacktrace      off
+histexpand      on
 ";
         assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
         assert_eq!(String::new(), read_file("stderr.txt"));
@@ -496,6 +502,8 @@ set +o emacs
 set +o xtrace
 set +o strlossy
 set +o extxtrace
+set +o stacktrace
+set -o histexpand
 ";
         assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
         assert_eq!(String::new(), read_file("stderr.txt"));
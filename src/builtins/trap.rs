@@ -30,6 +30,9 @@ use crate::xsfprintln;
 pub fn initialize_signals(sigs: &mut HashMap<String, i32>)
 {
     sigs.insert(String::from("EXIT"), 0);
+    sigs.insert(String::from("ERR"), ERR_SIG);
+    sigs.insert(String::from("DEBUG"), DEBUG_SIG);
+    sigs.insert(String::from("RETURN"), RETURN_SIG);
     sigs.insert(String::from("ABRT"), libc::SIGABRT);
     sigs.insert(String::from("ALRM"), libc::SIGALRM);
     sigs.insert(String::from("BUS"), libc::SIGBUS);
@@ -67,36 +70,88 @@ fn initialize_signal_names(sigs: &HashMap<String, i32>, sig_names: &mut HashMap<
     }
 }
 
+fn print_action(exec: &Executor, sig: i32, action: &str, sig_names: &HashMap<i32, String>)
+{
+    let mut sig_name = format!("{}", sig);
+    match sig_names.get(&sig) {
+        Some(tmp_sig_name) => sig_name = tmp_sig_name.clone(),
+        None => (),
+    }
+    xcfprintln!(exec, 1, "trap -- {} {}", singly_quote_str(action), sig_name);
+}
+
+fn parse_signal(arg: &str, sigs: &HashMap<String, i32>) -> Option<i32>
+{
+    match arg.parse::<i32>() {
+        Ok(tmp_sig) if tmp_sig >= 0 => Some(tmp_sig),
+        Ok(_) => None,
+        Err(_) => sigs.get(arg).map(|sig| *sig),
+    }
+}
+
 pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, _env: &mut Environment, settings: &mut Settings) -> i32
 {
     let mut sigs: HashMap<String, i32> = HashMap::new();
     initialize_signals(&mut sigs);
     let mut arg_iter = PushbackIter::new(args.iter().skip(1));
+    let mut is_printing = false;
     match arg_iter.next() {
         Some(arg) => {
-            if arg != &String::from("--") {
+            if arg == &String::from("-p") {
+                is_printing = true;
+                match arg_iter.next() {
+                    Some(arg2) => {
+                        if arg2 != &String::from("--") {
+                            arg_iter.undo(arg2);
+                        }
+                    },
+                    None => (),
+                }
+            } else if arg != &String::from("--") {
                 arg_iter.undo(arg);
             }
         },
         None => (),
     }
+    if is_printing {
+        let mut sig_names: HashMap<i32, String> = HashMap::new();
+        initialize_signal_names(&sigs, &mut sig_names);
+        let mut status = 0;
+        let mut is_arg = false;
+        for arg in arg_iter {
+            is_arg = true;
+            match parse_signal(arg.as_str(), &sigs) {
+                Some(sig) => {
+                    match interp.actions().get(&sig) {
+                        Some(action) => print_action(exec, sig, action.as_str(), &sig_names),
+                        None => (),
+                    }
+                },
+                None => {
+                    xsfprintln!(exec, 2, "Invalid signal");
+                    status = 1;
+                },
+            }
+        }
+        if !is_arg {
+            for (sig, action) in interp.actions().iter() {
+                print_action(exec, *sig, action.as_str(), &sig_names);
+            }
+        }
+        return status;
+    }
     match arg_iter.next() {
         Some(action) => {
             for arg in arg_iter {
-                let sig = match arg.parse::<i32>() {
-                    Ok(tmp_sig) => tmp_sig,
-                    Err(_) => {
-                        match sigs.get(arg) {
-                            Some(tmp_sig) => *tmp_sig,
-                            None => {
-                                xsfprintln!(exec, 2, "Invalid signal");
-                                return interp.exit(1, false);
-                            },
-                        }
+                let sig = match parse_signal(arg.as_str(), &sigs) {
+                    Some(tmp_sig) => tmp_sig,
+                    None => {
+                        xsfprintln!(exec, 2, "Invalid signal");
+                        return interp.exit(1, false);
                     },
                 };
                 if action != &String::from("-") {
-                    if sig != 0 {
+                    if sig > 0 {
                         match set_signal(sig, true, settings.interactive_flag) {
                             Ok(()) => interp.set_action(sig, action.clone()),
                             Err(err) => {
@@ -108,7 +163,7 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
                         interp.set_action(sig, action.clone());
                     }
                 } else {
-                    if sig != 0 {
+                    if sig > 0 {
                         match set_signal(sig, false, settings.interactive_flag) {
                             Ok(()) => interp.unset_action(sig),
                             Err(err) => {
@@ -127,12 +182,7 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
             let mut sig_names: HashMap<i32, String> = HashMap::new();
             initialize_signal_names(&sigs, &mut sig_names);
             for (sig, action) in interp.actions().iter() {
-                let mut sig_name = format!("{}", sig);
-                match sig_names.get(sig) {
-                    Some(tmp_sig_name) => sig_name = tmp_sig_name.clone(),
-                    None => (),
-                }
-                xcfprintln!(exec, 1, "trap -- {} {}", singly_quote_str(action.as_str()), sig_name);
+                print_action(exec, *sig, action.as_str(), &sig_names);
             }
             0
         },
@@ -15,59 +15,94 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::time::Duration;
+use std::time::Instant;
+use getopt;
+use getopt::Opt;
+use libc;
 use crate::env::*;
 use crate::exec::*;
 use crate::interp::*;
 use crate::settings::*;
+use crate::utils::*;
 use crate::xcfprintln;
 
-fn wait_for_process(pid: i32, interp: &mut Interpreter, exec: &mut Executor, settings: &Settings) -> i32
+struct Options
 {
-    interp.wait_for_process(exec, Some(pid), false, false, settings, || String::new(), |exec, wait_status| {
-            let mut job_id_and_pid_idx: Option<(u32, Option<usize>)> = None;
-            for (job_id, job) in exec.jobs().iter() {
-                let mut is_stop = false;
-                for (i, (tmp_pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
-                    match status {
-                        WaitStatus::None | WaitStatus::Stopped(_) => {
-                            if pid == *tmp_pid {
-                                job_id_and_pid_idx = Some((*job_id, Some(i)));
-                                is_stop = true;
-                                break;
-                            }
-                        },
-                        _ => (),
-                    }
-                }
-                if is_stop { break; }
-                match job.last_status {
-                    WaitStatus::None | WaitStatus::Stopped(_) => {
-                        if pid == job.last_pid {
-                            job_id_and_pid_idx = Some((*job_id, None));
-                            is_stop = true;
-                        }
-                    },
-                    _ => (),
-                }
-                if is_stop { break; }
-            }
-            match job_id_and_pid_idx {
-                Some((job_id, Some(i))) => exec.set_job_status(job_id, i, wait_status),
-                Some((job_id, None)) => exec.set_job_last_status(job_id, wait_status),
-                None => (),
-            }
-            match job_id_and_pid_idx {
-                Some((job_id, _)) => {
-                    let is_done = match exec.jobs().get(&job_id) {
-                        Some(job) => job.is_done(),
-                        None => false,
-                    };
-                    if is_done {
-                        exec.remove_job(job_id);
+    next_flag: bool,
+    var_name: Option<String>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Copy, Clone)]
+enum WaitTarget
+{
+    Job(u32),
+    Process(i32),
+}
+
+fn wait_status_to_status(wait_status: WaitStatus) -> i32
+{
+    match wait_status {
+        WaitStatus::Exited(status) => status,
+        WaitStatus::Signaled(sig, _) => sig + 128,
+        WaitStatus::Stopped(sig) => sig + 128,
+        WaitStatus::None => 0,
+    }
+}
+
+fn set_process_status(pid: i32, wait_status: WaitStatus, exec: &mut Executor)
+{
+    let mut job_id_and_pid_idx: Option<(u32, Option<usize>)> = None;
+    for (job_id, job) in exec.jobs().iter() {
+        let mut is_stop = false;
+        for (i, (tmp_pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
+            match status {
+                WaitStatus::None | WaitStatus::Stopped(_) => {
+                    if pid == *tmp_pid {
+                        job_id_and_pid_idx = Some((*job_id, Some(i)));
+                        is_stop = true;
+                        break;
                     }
                 },
-                None => (),
+                _ => (),
             }
+        }
+        if is_stop { break; }
+        match job.last_status {
+            WaitStatus::None | WaitStatus::Stopped(_) => {
+                if pid == job.last_pid {
+                    job_id_and_pid_idx = Some((*job_id, None));
+                    is_stop = true;
+                }
+            },
+            _ => (),
+        }
+        if is_stop { break; }
+    }
+    match job_id_and_pid_idx {
+        Some((job_id, Some(i))) => exec.set_job_status(job_id, i, wait_status),
+        Some((job_id, None)) => exec.set_job_last_status(job_id, wait_status),
+        None => (),
+    }
+    match job_id_and_pid_idx {
+        Some((job_id, _)) => {
+            let is_done = match exec.jobs().get(&job_id) {
+                Some(job) => job.is_done(),
+                None => false,
+            };
+            if is_done {
+                exec.remove_job(job_id);
+            }
+        },
+        None => (),
+    }
+}
+
+fn wait_for_process(pid: i32, interp: &mut Interpreter, exec: &mut Executor, settings: &Settings) -> i32
+{
+    interp.wait_for_process(exec, Some(pid), false, false, settings, || String::new(), |exec, wait_status| {
+            set_process_status(pid, wait_status, exec);
     }).unwrap_or(1)
 }
 
@@ -95,11 +130,177 @@ fn wait_for_job(job_id: u32, interp: &mut Interpreter, exec: &mut Executor, sett
     interp.wait_for_processes(exec, pids.as_slice(), Some(job.pgid), pids.len(), false, false, settings, |_: usize| (Vec::new(), String::new(), String::new())).0.unwrap_or(1)
 }
 
-pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, _env: &mut Environment, settings: &mut Settings) -> i32
+fn poll_process(pid: i32, exec: &mut Executor, settings: &Settings) -> Option<i32>
 {
+    match exec.wait_for_process(Some(pid), false, false, false, settings) {
+        Ok(WaitStatus::None) => None,
+        Ok(wait_status) => {
+            set_process_status(pid, wait_status, exec);
+            Some(wait_status_to_status(wait_status))
+        },
+        Err(err) => {
+            xcfprintln!(exec, 2, "{}: {}", pid, err);
+            Some(1)
+        },
+    }
+}
+
+fn poll_job(job_id: u32, exec: &mut Executor, settings: &Settings) -> Option<(i32, i32)>
+{
+    let job = match exec.jobs().get(&job_id) {
+        Some(tmp_job) => tmp_job.clone(),
+        None => return Some((0, 1)),
+    };
+    for (i, (pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
+        match status {
+            WaitStatus::None | WaitStatus::Stopped(_) => {
+                match exec.wait_for_process(Some(*pid), false, false, false, settings) {
+                    Ok(WaitStatus::None) => (),
+                    Ok(wait_status) => exec.set_job_status(job_id, i, wait_status),
+                    Err(err) => {
+                        xcfprintln!(exec, 2, "{}: {}", pid, err);
+                        exec.set_job_status(job_id, i, WaitStatus::Exited(1));
+                    },
+                }
+            },
+            _ => (),
+        }
+    }
+    match job.last_status {
+        WaitStatus::None | WaitStatus::Stopped(_) => {
+            match exec.wait_for_process(Some(job.last_pid), false, false, false, settings) {
+                Ok(WaitStatus::None) => (),
+                Ok(wait_status) => exec.set_job_last_status(job_id, wait_status),
+                Err(err) => {
+                    xcfprintln!(exec, 2, "{}: {}", job.last_pid, err);
+                    exec.set_job_last_status(job_id, WaitStatus::Exited(1));
+                },
+            }
+        },
+        _ => (),
+    }
+    let last_status = match exec.jobs().get(&job_id) {
+        Some(tmp_job) if tmp_job.is_done() => tmp_job.last_status,
+        _ => return None,
+    };
+    exec.remove_job(job_id);
+    Some((job.last_pid, wait_status_to_status(last_status)))
+}
+
+fn poll_for_targets(targets: &[WaitTarget], opts: &Options, exec: &mut Executor, settings: &Settings) -> (i32, Option<i32>)
+{
+    let old_sigmask = match block_sigchld() {
+        Ok(tmp_old_sigmask) => tmp_old_sigmask,
+        Err(err) => {
+            xcfprintln!(exec, 2, "{}", err);
+            return (1, None);
+        },
+    };
+    let res = wait_for_targets_with_sigchld(targets, opts, exec, settings);
+    match set_sigmask(&old_sigmask) {
+        Ok(()) => (),
+        Err(err) => xcfprintln!(exec, 2, "{}", err),
+    }
+    res
+}
+
+fn wait_for_targets_with_sigchld(targets: &[WaitTarget], opts: &Options, exec: &mut Executor, settings: &Settings) -> (i32, Option<i32>)
+{
+    let instant = Instant::now();
+    let mut targets = targets.to_vec();
     let mut status = 0;
-    if args.len() > 1 {
-        for arg in &args[1..] {
+    let mut last_pid: Option<i32> = None;
+    loop {
+        let mut i = 0;
+        while i < targets.len() {
+            let res = match targets[i] {
+                WaitTarget::Job(job_id) => poll_job(job_id, exec, settings),
+                WaitTarget::Process(pid) => poll_process(pid, exec, settings).map(|tmp_status| (pid, tmp_status)),
+            };
+            match res {
+                Some((pid, tmp_status)) => {
+                    targets.remove(i);
+                    if opts.next_flag {
+                        return (tmp_status, Some(pid));
+                    }
+                    if tmp_status != 0 {
+                        status = tmp_status;
+                    }
+                    last_pid = Some(pid);
+                },
+                None => i += 1,
+            }
+        }
+        if targets.is_empty() {
+            return (status, last_pid);
+        }
+        let timeout = match opts.timeout {
+            Some(timeout) => {
+                match timeout.checked_sub(instant.elapsed()) {
+                    Some(tmp_timeout) if !tmp_timeout.is_zero() => Some(tmp_timeout),
+                    _ => return (libc::SIGALRM + 128, None),
+                }
+            },
+            None => None,
+        };
+        match wait_for_sigchld(timeout) {
+            Ok(_) => (),
+            Err(err) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return (1, None);
+            },
+        }
+    }
+}
+
+fn parse_timeout(s: &str) -> Option<Duration>
+{
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Some(Duration::from_secs_f64(secs)),
+        _ => None,
+    }
+}
+
+fn parse_wait_target(arg: &str, exec: &mut Executor) -> Option<WaitTarget>
+{
+    match exec.parse_job_id(arg) {
+        Ok(job_id) => {
+            if exec.jobs().contains_key(&job_id) {
+                Some(WaitTarget::Job(job_id))
+            } else {
+                xcfprintln!(exec, 2, "{}: No job", job_id);
+                None
+            }
+        },
+        Err(JobIdError::NoPercent) => {
+            match arg.parse::<i32>() {
+                Ok(pid) if pid < 0 => {
+                    xcfprintln!(exec, 2, "PID is negative");
+                    None
+                },
+                Ok(0) => {
+                    xcfprintln!(exec, 2, "PID is zero");
+                    None
+                },
+                Ok(pid) => Some(WaitTarget::Process(pid)),
+                Err(_) => {
+                    xcfprintln!(exec, 2, "Invalid number");
+                    None
+                },
+            }
+        },
+        Err(err) => {
+            xcfprintln!(exec, 2, "{}", err);
+            None
+        },
+    }
+}
+
+fn wait_for_jobs_and_processes(args: &[&String], interp: &mut Interpreter, exec: &mut Executor, settings: &Settings) -> i32
+{
+    let mut status = 0;
+    if !args.is_empty() {
+        for arg in args {
             match exec.parse_job_id(arg.as_str()) {
                 Ok(job_id) => {
                     let tmp_status = wait_for_job(job_id, interp, exec, settings);
@@ -146,3 +347,81 @@ pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interprete
     }
     status
 }
+
+pub fn main(_vars: &[(String, String)], args: &[String], interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    let mut opt_parser = getopt::Parser::new(args, "np:t:");
+    let mut opts = Options {
+        next_flag: false,
+        var_name: None,
+        timeout: None,
+    };
+    loop {
+        match opt_parser.next() {
+            Some(Ok(Opt('n', _))) => opts.next_flag = true,
+            Some(Ok(Opt('p', Some(opt_arg)))) => opts.var_name = Some(opt_arg),
+            Some(Ok(Opt('t', Some(opt_arg)))) => {
+                match parse_timeout(opt_arg.as_str()) {
+                    Some(timeout) => opts.timeout = Some(timeout),
+                    None => {
+                        xcfprintln!(exec, 2, "{}: Invalid timeout", opt_arg);
+                        return 1;
+                    },
+                }
+            },
+            Some(Ok(Opt(c, _))) => {
+                xcfprintln!(exec, 2, "unknown option -- {:?}", c);
+                return 1;
+            },
+            Some(Err(err)) => {
+                xcfprintln!(exec, 2, "{}", err);
+                return 1;
+            },
+            None => break,
+        }
+    }
+    match &opts.var_name {
+        Some(var_name) => {
+            if !is_name_str(var_name.as_str()) {
+                xcfprintln!(exec, 2, "{}: Invalid variable name", var_name);
+                return 1;
+            }
+            if env.read_only_var_attr(var_name.as_str()) {
+                xcfprintln!(exec, 2, "{}: Is read only", var_name);
+                return 1;
+            }
+        },
+        None => (),
+    }
+    let args: Vec<&String> = args.iter().skip(opt_parser.index()).collect();
+    if !opts.next_flag && opts.var_name.is_none() && opts.timeout.is_none() {
+        return wait_for_jobs_and_processes(args.as_slice(), interp, exec, settings);
+    }
+    let mut status = 0;
+    let mut targets: Vec<WaitTarget> = Vec::new();
+    if !args.is_empty() {
+        for arg in &args {
+            match parse_wait_target(arg.as_str(), exec) {
+                Some(target) => targets.push(target),
+                None => status = 1,
+            }
+        }
+    } else {
+        let mut job_ids: Vec<u32> = exec.jobs().keys().copied().collect();
+        job_ids.sort();
+        targets.extend(job_ids.iter().map(|job_id| WaitTarget::Job(*job_id)));
+    }
+    if targets.is_empty() {
+        return if opts.next_flag { 127 } else { status };
+    }
+    let (tmp_status, pid) = poll_for_targets(targets.as_slice(), &opts, exec, settings);
+    match (&opts.var_name, pid) {
+        (Some(var_name), Some(pid)) => env.set_var(var_name.as_str(), format!("{}", pid).as_str(), settings),
+        (Some(var_name), None) => env.unset_var(var_name.as_str()),
+        (None, _) => (),
+    }
+    if tmp_status != 0 || opts.next_flag {
+        status = tmp_status;
+    }
+    status
+}
@@ -0,0 +1,220 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::env;
+use std::fs;
+use std::path::*;
+use libc;
+use crate::args::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::utils::*;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CompletionAction
+{
+    Command,
+    Directory,
+    File,
+    Variable,
+}
+
+impl CompletionAction
+{
+    pub fn option_char(&self) -> char
+    {
+        match self {
+            CompletionAction::Command => 'c',
+            CompletionAction::Directory => 'd',
+            CompletionAction::File => 'f',
+            CompletionAction::Variable => 'v',
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompletionSpec
+{
+    pub actions: Vec<CompletionAction>,
+    pub word_list: Option<String>,
+    pub fun_name: Option<String>,
+}
+
+impl CompletionSpec
+{
+    pub fn new() -> CompletionSpec
+    {
+        CompletionSpec {
+            actions: Vec::new(),
+            word_list: None,
+            fun_name: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool
+    { self.actions.is_empty() && self.word_list.is_none() && self.fun_name.is_none() }
+
+    pub fn to_command_string(&self, name: &str) -> String
+    {
+        let mut s = String::from("complete");
+        for action in &self.actions {
+            s.push_str(format!(" -{}", action.option_char()).as_str());
+        }
+        match &self.word_list {
+            Some(word_list) => s.push_str(format!(" -W {}", singly_quote_str(word_list.as_str())).as_str()),
+            None => (),
+        }
+        match &self.fun_name {
+            Some(fun_name) => s.push_str(format!(" -F {}", fun_name).as_str()),
+            None => (),
+        }
+        s.push(' ');
+        s.push_str(name);
+        s
+    }
+}
+
+pub fn is_executable_file<P: AsRef<Path>>(path: P) -> bool
+{
+    match fs::metadata(path.as_ref()) {
+        Ok(metadata) => !metadata.file_type().is_dir() && access(path.as_ref(), libc::X_OK).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+pub fn path_command_names(path: &str, prefix: &str) -> Vec<String>
+{
+    let mut names: Vec<String> = Vec::new();
+    for dir_path in path.split(':') {
+        match fs::read_dir(dir_path) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        Ok(entry) => {
+                            let name = entry.file_name().to_string_lossy().into_owned();
+                            if name.starts_with(prefix) && is_executable_file(entry.path()) {
+                                names.push(name);
+                            }
+                        },
+                        Err(_) => (),
+                    }
+                }
+            },
+            Err(_) => (),
+        }
+    }
+    names
+}
+
+fn path_names(word: &str, is_dir_only: bool) -> Vec<String>
+{
+    let (dir_prefix, name_prefix) = match word.rfind('/') {
+        Some(i) => (&word[..(i + 1)], &word[(i + 1)..]),
+        None => ("", word),
+    };
+    let dir_path = if dir_prefix.is_empty() { "." } else { dir_prefix };
+    let mut names: Vec<String> = Vec::new();
+    match fs::read_dir(dir_path) {
+        Ok(entries) => {
+            for entry in entries {
+                match entry {
+                    Ok(entry) => {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        if name.starts_with(name_prefix) && (!name.starts_with('.') || name_prefix.starts_with('.')) && (!is_dir_only || entry.path().is_dir()) {
+                            names.push(format!("{}{}", dir_prefix, name));
+                        }
+                    },
+                    Err(_) => (),
+                }
+            }
+        },
+        Err(_) => (),
+    }
+    names
+}
+
+pub fn generate_completions(actions: &[CompletionAction], word_list: Option<&str>, word: &str, env: &Environment) -> Vec<String>
+{
+    let mut names: Vec<String> = Vec::new();
+    for action in actions {
+        match action {
+            CompletionAction::Command => {
+                names.extend(env.builtin_funs().keys().filter(|name| name.starts_with(word)).map(|name| name.clone()));
+                names.extend(env.closure_builtin_funs().keys().filter(|name| name.starts_with(word)).map(|name| name.clone()));
+                names.extend(env.funs().keys().filter(|name| name.starts_with(word)).map(|name| name.clone()));
+                names.extend(env.aliases().keys().filter(|name| name.starts_with(word)).map(|name| name.clone()));
+                let path = env.var("PATH").unwrap_or(String::from("/bin:/usr/bin"));
+                names.extend(path_command_names(path.as_str(), word));
+            },
+            CompletionAction::Directory => names.extend(path_names(word, true)),
+            CompletionAction::File => names.extend(path_names(word, false)),
+            CompletionAction::Variable => {
+                names.extend(env.unexported_vars().keys().filter(|name| name.starts_with(word)).map(|name| name.clone()));
+                names.extend(env::vars().map(|(name, _)| name).filter(|name| name.starts_with(word)));
+            },
+        }
+    }
+    match word_list {
+        Some(word_list) => {
+            let ifs = env.var("IFS").unwrap_or(String::from(DEFAULT_IFS));
+            names.extend(split_str_for_ifs(word_list, ifs.as_str()).iter().filter(|name| name.starts_with(word)).map(|name| String::from(*name)));
+        },
+        None => (),
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn call_completion_fun(fun_name: &str, comp_words: &[String], comp_cword: usize, comp_line: &str, comp_point: usize, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> Vec<String>
+{
+    let fun_body = match env.fun(fun_name) {
+        Some(tmp_fun_body) => tmp_fun_body,
+        None => return Vec::new(),
+    };
+    let cur = comp_words.get(comp_cword).map(|s| s.clone()).unwrap_or_default();
+    let prev = comp_cword.checked_sub(1).and_then(|i| comp_words.get(i)).map(|s| s.clone()).unwrap_or_default();
+    let cmd = comp_words.first().map(|s| s.clone()).unwrap_or_default();
+    env.set_var("COMP_WORDS", comp_words.join("\n").as_str(), settings);
+    env.set_var("COMP_CWORD", format!("{}", comp_cword).as_str(), settings);
+    env.set_var("COMP_LINE", comp_line, settings);
+    env.set_var("COMP_POINT", format!("{}", comp_point).as_str(), settings);
+    env.unset_var("COMPREPLY");
+    let mut tmp_args = Arguments::new();
+    tmp_args.set_args(vec![cmd, cur, prev]);
+    settings.push_args(tmp_args);
+    interp.interpret_fun_body_for_completion(exec, fun_name, &(*fun_body), env, settings);
+    settings.pop_args();
+    let comp_reply = env.var("COMPREPLY").unwrap_or_default();
+    for name in ["COMP_WORDS", "COMP_CWORD", "COMP_LINE", "COMP_POINT", "COMPREPLY"] {
+        env.unset_var(name);
+    }
+    comp_reply.split('\n').filter(|name| !name.is_empty()).map(|name| String::from(name)).collect()
+}
+
+pub fn complete_with_spec(spec: &CompletionSpec, comp_words: &[String], comp_cword: usize, comp_line: &str, comp_point: usize, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> Vec<String>
+{
+    let word = comp_words.get(comp_cword).map(|s| s.as_str()).unwrap_or("");
+    let mut names = generate_completions(spec.actions.as_slice(), spec.word_list.as_deref(), word, env);
+    match &spec.fun_name {
+        Some(fun_name) => names.extend(call_completion_fun(fun_name.as_str(), comp_words, comp_cword, comp_line, comp_point, interp, exec, env, settings)),
+        None => (),
+    }
+    names
+}
@@ -0,0 +1,294 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::io::*;
+use std::rc::*;
+use crate::io::*;
+use crate::json::*;
+use crate::lexer::*;
+use crate::parser::*;
+use crate::settings::*;
+
+fn node_to_json(node_type: &str, span: Option<(&Position, &Position)>, mut fields: Vec<(String, JsonValue)>) -> JsonValue
+{
+    let mut node_fields = vec![(String::from("type"), JsonValue::from_str(node_type))];
+    match span {
+        Some((pos, end_pos)) => {
+            node_fields.push((String::from("line"), JsonValue::Integer(pos.line as i64)));
+            node_fields.push((String::from("column"), JsonValue::Integer(pos.column as i64)));
+            node_fields.push((String::from("offset"), JsonValue::Integer(pos.offset as i64)));
+            node_fields.push((String::from("end_line"), JsonValue::Integer(end_pos.line as i64)));
+            node_fields.push((String::from("end_column"), JsonValue::Integer(end_pos.column as i64)));
+            node_fields.push((String::from("end_offset"), JsonValue::Integer(end_pos.offset as i64)));
+        },
+        None => (),
+    }
+    node_fields.append(&mut fields);
+    JsonValue::Object(node_fields)
+}
+
+fn field(name: &str, value: JsonValue) -> (String, JsonValue)
+{ (String::from(name), value) }
+
+fn opt_fd_to_json(n: &Option<i32>) -> JsonValue
+{
+    match n {
+        Some(n) => JsonValue::Integer(*n as i64),
+        None => JsonValue::Null,
+    }
+}
+
+pub fn param_name_to_json(param_name: &ParameterName) -> JsonValue
+{
+    match param_name {
+        ParameterName::Variable(name) => node_to_json("Variable", None, vec![field("name", JsonValue::from_str(name.as_str()))]),
+        ParameterName::Argument(n) => node_to_json("Argument", None, vec![field("number", JsonValue::Integer(*n as i64))]),
+        ParameterName::Special(name) => node_to_json("Special", None, vec![field("name", JsonValue::String(format!("{}", name)))]),
+    }
+}
+
+pub fn simple_word_elem_to_json(simple_word_elem: &SimpleWordElement) -> JsonValue
+{
+    match simple_word_elem {
+        SimpleWordElement::String(s) => node_to_json("String", None, vec![field("string", JsonValue::from_str(s.as_str()))]),
+        SimpleWordElement::Parameter(param_name, None) => {
+            node_to_json("Parameter", None, vec![
+                    field("name", param_name_to_json(param_name)),
+                    field("modifier", JsonValue::Null),
+                    field("words", JsonValue::Array(Vec::new()))
+            ])
+        },
+        SimpleWordElement::Parameter(param_name, Some((modifier, words))) => {
+            node_to_json("Parameter", None, vec![
+                    field("name", param_name_to_json(param_name)),
+                    field("modifier", JsonValue::String(format!("{}", modifier))),
+                    field("words", words_to_json(words.as_slice()))
+            ])
+        },
+        SimpleWordElement::ParameterLength(param_name) => node_to_json("ParameterLength", None, vec![field("name", param_name_to_json(param_name))]),
+        SimpleWordElement::Command(commands) => node_to_json("CommandSubstitution", None, vec![field("commands", logical_commands_to_json(commands.as_slice()))]),
+        SimpleWordElement::ArithmeticExpression(expr) => node_to_json("ArithmeticExpansion", None, vec![field("expression", arith_expr_to_json(expr))]),
+    }
+}
+
+fn simple_word_elems_to_json(simple_word_elems: &[SimpleWordElement]) -> JsonValue
+{ JsonValue::Array(simple_word_elems.iter().map(simple_word_elem_to_json).collect()) }
+
+pub fn word_elem_to_json(word_elem: &WordElement) -> JsonValue
+{
+    match word_elem {
+        WordElement::Simple(simple_word_elem) => simple_word_elem_to_json(simple_word_elem),
+        WordElement::SinglyQuoted(s) => node_to_json("SinglyQuoted", None, vec![field("string", JsonValue::from_str(s.as_str()))]),
+        WordElement::DoublyQuoted(simple_word_elems) => node_to_json("DoublyQuoted", None, vec![field("elements", simple_word_elems_to_json(simple_word_elems.as_slice()))]),
+    }
+}
+
+pub fn word_to_json(word: &Word) -> JsonValue
+{
+    node_to_json("Word", Some((&word.pos, &word.end_pos)), vec![
+            field("elements", JsonValue::Array(word.word_elems.iter().map(word_elem_to_json).collect()))
+    ])
+}
+
+fn words_to_json(words: &[Rc<Word>]) -> JsonValue
+{ JsonValue::Array(words.iter().map(|w| word_to_json(&(*w))).collect()) }
+
+pub fn redirect_to_json(redirect: &Redirection) -> JsonValue
+{
+    match redirect {
+        Redirection::Input(_, pos, end_pos, n, word) => node_to_json("Input", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word)))]),
+        Redirection::Output(_, pos, end_pos, n, word, is_bar) => node_to_json("Output", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word))), field("is_bar", JsonValue::Bool(*is_bar))]),
+        Redirection::InputAndOutput(_, pos, end_pos, n, word) => node_to_json("InputAndOutput", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word)))]),
+        Redirection::Appending(_, pos, end_pos, n, word) => node_to_json("Appending", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word)))]),
+        Redirection::InputDuplicating(_, pos, end_pos, n, word) => node_to_json("InputDuplicating", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word)))]),
+        Redirection::OutputDuplicating(_, pos, end_pos, n, word) => node_to_json("OutputDuplicating", Some((pos, end_pos)), vec![field("fd", opt_fd_to_json(n)), field("word", word_to_json(&(*word)))]),
+        Redirection::HereDocument(_, pos, end_pos, n, here_doc) => {
+            let here_doc_r = here_doc.borrow();
+            node_to_json("HereDocument", Some((pos, end_pos)), vec![
+                    field("fd", opt_fd_to_json(n)),
+                    field("delimiter", JsonValue::from_str(here_doc_r.delim.as_str())),
+                    field("has_minus", JsonValue::Bool(here_doc_r.has_minus)),
+                    field("has_quoted", JsonValue::Bool(here_doc_r.has_quoted)),
+                    field("elements", simple_word_elems_to_json(here_doc_r.simple_word_elems.as_slice()))
+            ])
+        },
+    }
+}
+
+fn redirects_to_json(redirects: &[Rc<Redirection>]) -> JsonValue
+{ JsonValue::Array(redirects.iter().map(|r| redirect_to_json(&(*r))).collect()) }
+
+pub fn compound_command_to_json(compound_command: &CompoundCommand) -> JsonValue
+{
+    match compound_command {
+        CompoundCommand::BraceGroup(commands) => node_to_json("BraceGroup", None, vec![field("commands", logical_commands_to_json(commands.as_slice()))]),
+        CompoundCommand::Subshell(commands) => node_to_json("Subshell", None, vec![field("commands", logical_commands_to_json(commands.as_slice()))]),
+        CompoundCommand::For(name_word, words, commands) => {
+            let words_json = match words {
+                Some(words) => words_to_json(words.as_slice()),
+                None => JsonValue::Null,
+            };
+            node_to_json("For", None, vec![
+                    field("name", word_to_json(&(*name_word))),
+                    field("words", words_json),
+                    field("commands", logical_commands_to_json(commands.as_slice()))
+            ])
+        },
+        CompoundCommand::Case(word, pairs) => {
+            let pairs_json: Vec<JsonValue> = pairs.iter().map(|p| {
+                    node_to_json("CasePair", None, vec![
+                            field("pattern_words", words_to_json(p.pattern_words.as_slice())),
+                            field("commands", logical_commands_to_json(p.commands.as_slice()))
+                    ])
+            }).collect();
+            node_to_json("Case", None, vec![field("word", word_to_json(&(*word))), field("pairs", JsonValue::Array(pairs_json))])
+        },
+        CompoundCommand::If(cond_commands, commands, elif_pairs, else_commands) => {
+            let elif_pairs_json: Vec<JsonValue> = elif_pairs.iter().map(|p| {
+                    node_to_json("ElifPair", None, vec![
+                            field("cond_commands", logical_commands_to_json(p.cond_commands.as_slice())),
+                            field("commands", logical_commands_to_json(p.commands.as_slice()))
+                    ])
+            }).collect();
+            let else_commands_json = match else_commands {
+                Some(else_commands) => logical_commands_to_json(else_commands.as_slice()),
+                None => JsonValue::Null,
+            };
+            node_to_json("If", None, vec![
+                    field("cond_commands", logical_commands_to_json(cond_commands.as_slice())),
+                    field("commands", logical_commands_to_json(commands.as_slice())),
+                    field("elif_pairs", JsonValue::Array(elif_pairs_json)),
+                    field("else_commands", else_commands_json)
+            ])
+        },
+        CompoundCommand::While(cond_commands, commands) => {
+            node_to_json("While", None, vec![
+                    field("cond_commands", logical_commands_to_json(cond_commands.as_slice())),
+                    field("commands", logical_commands_to_json(commands.as_slice()))
+            ])
+        },
+        CompoundCommand::Until(cond_commands, commands) => {
+            node_to_json("Until", None, vec![
+                    field("cond_commands", logical_commands_to_json(cond_commands.as_slice())),
+                    field("commands", logical_commands_to_json(commands.as_slice()))
+            ])
+        },
+    }
+}
+
+pub fn fun_body_to_json(fun_body: &FunctionBody) -> JsonValue
+{
+    node_to_json("FunctionBody", Some((&fun_body.pos, &fun_body.end_pos)), vec![
+            field("command", compound_command_to_json(&fun_body.command)),
+            field("redirects", redirects_to_json(fun_body.redirects.as_slice()))
+    ])
+}
+
+pub fn command_to_json(command: &Command) -> JsonValue
+{
+    match command {
+        Command::Simple(_, pos, end_pos, simple_command) => {
+            node_to_json("SimpleCommand", Some((pos, end_pos)), vec![
+                    field("words", words_to_json(simple_command.words.as_slice())),
+                    field("redirects", redirects_to_json(simple_command.redirects.as_slice()))
+            ])
+        },
+        Command::Compound(_, pos, end_pos, compound_command, redirects) => {
+            node_to_json("CompoundCommand", Some((pos, end_pos)), vec![
+                    field("command", compound_command_to_json(compound_command)),
+                    field("redirects", redirects_to_json(redirects.as_slice()))
+            ])
+        },
+        Command::FunctionDefinition(_, pos, end_pos, name_word, fun_body) => {
+            node_to_json("FunctionDefinition", Some((pos, end_pos)), vec![
+                    field("name", word_to_json(&(*name_word))),
+                    field("body", fun_body_to_json(&(*fun_body)))
+            ])
+        },
+    }
+}
+
+pub fn pipe_command_to_json(pipe_command: &PipeCommand) -> JsonValue
+{
+    node_to_json("PipeCommand", Some((&pipe_command.pos, &pipe_command.end_pos)), vec![
+            field("is_negative", JsonValue::Bool(pipe_command.is_negative)),
+            field("commands", JsonValue::Array(pipe_command.commands.iter().map(|c| command_to_json(&(*c))).collect()))
+    ])
+}
+
+pub fn logical_command_to_json(logical_command: &LogicalCommand) -> JsonValue
+{
+    let pairs_json: Vec<JsonValue> = logical_command.pairs.iter().map(|p| {
+            node_to_json("LogicalPair", None, vec![
+                    field("op", JsonValue::String(format!("{}", p.op))),
+                    field("command", pipe_command_to_json(&(*p.command)))
+            ])
+    }).collect();
+    node_to_json("LogicalCommand", Some((&logical_command.pos, &logical_command.end_pos)), vec![
+            field("first_command", pipe_command_to_json(&(*logical_command.first_command))),
+            field("pairs", JsonValue::Array(pairs_json)),
+            field("is_in_background", JsonValue::Bool(logical_command.is_in_background))
+    ])
+}
+
+pub fn logical_commands_to_json(commands: &[Rc<LogicalCommand>]) -> JsonValue
+{ JsonValue::Array(commands.iter().map(|c| logical_command_to_json(&(*c))).collect()) }
+
+pub fn arith_expr_to_json(expr: &ArithmeticExpression) -> JsonValue
+{
+    match expr {
+        ArithmeticExpression::Number(_, pos, end_pos, n) => node_to_json("Number", Some((pos, end_pos)), vec![field("value", JsonValue::Integer(*n as i64))]),
+        ArithmeticExpression::Parameter(_, pos, end_pos, param_name) => node_to_json("Parameter", Some((pos, end_pos)), vec![field("name", param_name_to_json(param_name))]),
+        ArithmeticExpression::Unary(_, pos, end_pos, op, expr1) => {
+            node_to_json("Unary", Some((pos, end_pos)), vec![
+                    field("op", JsonValue::String(format!("{}", op))),
+                    field("expression", arith_expr_to_json(&(*expr1)))
+            ])
+        },
+        ArithmeticExpression::Binary(_, pos, end_pos, expr1, op, expr2) => {
+            node_to_json("Binary", Some((pos, end_pos)), vec![
+                    field("left", arith_expr_to_json(&(*expr1))),
+                    field("op", JsonValue::String(format!("{}", op))),
+                    field("right", arith_expr_to_json(&(*expr2)))
+            ])
+        },
+        ArithmeticExpression::Conditional(_, pos, end_pos, expr1, expr2, expr3) => {
+            node_to_json("Conditional", Some((pos, end_pos)), vec![
+                    field("condition", arith_expr_to_json(&(*expr1))),
+                    field("then", arith_expr_to_json(&(*expr2))),
+                    field("else", arith_expr_to_json(&(*expr3)))
+            ])
+        },
+    }
+}
+
+pub fn dump_str(path: &str, s: &str, settings: &Settings) -> ParserResult<JsonValue>
+{
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new(path, &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    parser.set_error_cont(false);
+    let commands = parser.parse_logical_commands(&mut lexer, settings)?;
+    Ok(JsonValue::Object(vec![
+                field("path", JsonValue::from_str(path)),
+                field("commands", logical_commands_to_json(commands.as_slice()))
+    ]))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,82 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+
+fn dump_test_str(s: &str) -> String
+{
+    let settings = Settings::new();
+    match dump_str("test.sh", s, &settings) {
+        Ok(value) => format!("{}", value),
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[test]
+fn test_dump_str_dumps_simple_command()
+{
+    let expected_s = "{\"path\":\"test.sh\",\"commands\":[{\"type\":\"LogicalCommand\",\"line\":1,\"column\":1,\"offset\":0,\"end_line\":1,\"end_column\":13,\"end_offset\":12,\"first_command\":{\"type\":\"PipeCommand\",\"line\":1,\"column\":1,\"offset\":0,\"end_line\":1,\"end_column\":13,\"end_offset\":12,\"is_negative\":false,\"commands\":[{\"type\":\"SimpleCommand\",\"line\":1,\"column\":1,\"offset\":0,\"end_line\":1,\"end_column\":13,\"end_offset\":12,\"words\":[{\"type\":\"Word\",\"line\":1,\"column\":1,\"offset\":0,\"end_line\":1,\"end_column\":5,\"end_offset\":4,\"elements\":[{\"type\":\"String\",\"string\":\"echo\"}]},{\"type\":\"Word\",\"line\":1,\"column\":6,\"offset\":5,\"end_line\":1,\"end_column\":8,\"end_offset\":7,\"elements\":[{\"type\":\"Parameter\",\"name\":{\"type\":\"Variable\",\"name\":\"x\"},\"modifier\":null,\"words\":[]}]}],\"redirects\":[{\"type\":\"Output\",\"line\":1,\"column\":9,\"offset\":8,\"end_line\":1,\"end_column\":13,\"end_offset\":12,\"fd\":2,\"word\":{\"type\":\"Word\",\"line\":1,\"column\":12,\"offset\":11,\"end_line\":1,\"end_column\":13,\"end_offset\":12,\"elements\":[{\"type\":\"String\",\"string\":\"y\"}]},\"is_bar\":false}]}]},\"pairs\":[],\"is_in_background\":false}]}";
+    assert_eq!(String::from(expected_s), dump_test_str("echo $x 2> y\n"));
+}
+
+#[test]
+fn test_dump_str_dumps_logical_command_and_compound_command()
+{
+    let s = dump_test_str("! true && if false; then :; fi &\n");
+    assert!(s.contains("\"is_negative\":true"));
+    assert!(s.contains("\"pairs\":[{\"type\":\"LogicalPair\",\"op\":\"&&\","));
+    assert!(s.contains("\"type\":\"CompoundCommand\",\"line\":1,\"column\":11,\"offset\":10,\"end_line\":1,\"end_column\":31,\"end_offset\":30,\"command\":{\"type\":\"If\",\"cond_commands\":["));
+    assert!(s.contains("\"elif_pairs\":[],\"else_commands\":null}"));
+    assert!(s.ends_with("\"is_in_background\":true}]}"));
+}
+
+#[test]
+fn test_dump_str_dumps_quoted_words_and_arithmetic_expansion()
+{
+    let s = dump_test_str("echo 'a b' \"c$1\" $((x + 2))\n");
+    assert!(s.contains("{\"type\":\"SinglyQuoted\",\"string\":\"a b\"}"));
+    assert!(s.contains("{\"type\":\"DoublyQuoted\",\"elements\":[{\"type\":\"String\",\"string\":\"c\"},{\"type\":\"Parameter\",\"name\":{\"type\":\"Argument\",\"number\":1},\"modifier\":null,\"words\":[]}]}"));
+    assert!(s.contains("{\"type\":\"ArithmeticExpansion\",\"expression\":{\"type\":\"Binary\",\"line\":1,\"column\":21,\"offset\":20,\"end_line\":1,\"end_column\":26,\"end_offset\":25,\"left\":{\"type\":\"Parameter\",\"line\":1,\"column\":21,\"offset\":20,\"end_line\":1,\"end_column\":22,\"end_offset\":21,\"name\":{\"type\":\"Variable\",\"name\":\"x\"}},\"op\":\"+\",\"right\":{\"type\":\"Number\",\"line\":1,\"column\":25,\"offset\":24,\"end_line\":1,\"end_column\":26,\"end_offset\":25,\"value\":2}}}"));
+}
+
+#[test]
+fn test_dump_str_dumps_large_numbers_without_precision_loss()
+{
+    let s = dump_test_str("echo $((9007199254740993 + 9223372036854775807))\n");
+    assert!(s.contains("\"value\":9007199254740993}"));
+    assert!(s.contains("\"value\":9223372036854775807}"));
+}
+
+#[test]
+fn test_dump_str_dumps_function_definition_and_here_document()
+{
+    let s = dump_test_str("f() { cat <<-XXX; }\n\tabc\nXXX\n");
+    assert!(s.contains("\"type\":\"FunctionDefinition\""));
+    assert!(s.contains("\"body\":{\"type\":\"FunctionBody\""));
+    assert!(s.contains("\"command\":{\"type\":\"BraceGroup\",\"commands\":["));
+    assert!(s.contains("{\"type\":\"HereDocument\",\"line\":1,\"column\":11,\"offset\":10,\"end_line\":1,\"end_column\":17,\"end_offset\":16,\"fd\":null,\"delimiter\":\"XXX\",\"has_minus\":true,\"has_quoted\":false,\"elements\":[{\"type\":\"String\",\"string\":\"abc\\n\"}]}"));
+}
+
+#[test]
+fn test_dump_str_complains_on_syntax_error()
+{
+    let settings = Settings::new();
+    match dump_str("test.sh", "echo abc )\n", &settings) {
+        Err(err) => assert_eq!(String::from("test.sh: 1.10: unexpected token"), format!("{}", err)),
+        Ok(_) => assert!(false),
+    }
+}
@@ -15,11 +15,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::cell::*;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
+use std::ffi::OsString;
 use std::rc::*;
 use crate::builtins::*;
+use crate::completion::*;
+use crate::loadable::*;
 use crate::parser::*;
 use crate::settings::*;
 
@@ -29,8 +33,16 @@ pub struct Environment
     unexported_vars: HashMap<String, String>,
     read_only_var_attrs: HashSet<String>,
     builtin_funs: HashMap<String, BuiltinFunction>,
+    closure_builtin_funs: HashMap<String, Rc<RefCell<ClosureBuiltinFunction>>>,
+    disabled_builtin_funs: HashMap<String, BuiltinFunction>,
+    disabled_closure_builtin_funs: HashMap<String, Rc<RefCell<ClosureBuiltinFunction>>>,
+    fork_free_closure_builtin_fun_names: HashSet<String>,
+    shadowed_builtin_funs: HashMap<String, (BuiltinFunction, bool)>,
+    loadable_builtins: HashMap<String, LoadableBuiltin>,
     funs: HashMap<String, Rc<FunctionBody>>,
     aliases: HashMap<String, String>,
+    completion_specs: HashMap<String, CompletionSpec>,
+    dir_stack: Vec<String>,
 }
 
 impl Environment
@@ -41,8 +53,16 @@ impl Environment
             unexported_vars: HashMap::new(),
             read_only_var_attrs: HashSet::new(),
             builtin_funs: HashMap::new(),
+            closure_builtin_funs: HashMap::new(),
+            disabled_builtin_funs: HashMap::new(),
+            disabled_closure_builtin_funs: HashMap::new(),
+            fork_free_closure_builtin_fun_names: HashSet::new(),
+            shadowed_builtin_funs: HashMap::new(),
+            loadable_builtins: HashMap::new(),
             funs: HashMap::new(),
             aliases: HashMap::new(),
+            completion_specs: HashMap::new(),
+            dir_stack: Vec::new(),
         }
     }
 
@@ -102,6 +122,7 @@ impl Environment
     pub fn set_read_only_var_attr(&mut self, name: &str)
     { self.read_only_var_attrs.insert(String::from(name)); }
 
+    #[allow(dead_code)]
     pub fn unset_read_only_var_attr(&mut self, name: &str)
     { self.read_only_var_attrs.remove(&String::from(name)); }
     
@@ -115,7 +136,121 @@ impl Environment
     { self.builtin_funs.insert(String::from(name), builtin_fun); }
 
     pub fn unset_builtin_fun(&mut self, name: &str)
-    { self.builtin_funs.remove(&String::from(name)); }    
+    {
+        self.builtin_funs.remove(&String::from(name));
+        self.disabled_builtin_funs.remove(&String::from(name));
+    }
+
+    pub fn closure_builtin_fun(&self, name: &str) -> Option<Rc<RefCell<ClosureBuiltinFunction>>>
+    { self.closure_builtin_funs.get(&String::from(name)).map(|cbf| cbf.clone()) }
+
+    pub fn set_closure_builtin_fun(&mut self, name: &str, closure_builtin_fun: ClosureBuiltinFunction)
+    {
+        self.disabled_closure_builtin_funs.remove(&String::from(name));
+        self.fork_free_closure_builtin_fun_names.remove(&String::from(name));
+        self.closure_builtin_funs.insert(String::from(name), Rc::new(RefCell::new(closure_builtin_fun)));
+    }
+
+    pub fn unset_closure_builtin_fun(&mut self, name: &str)
+    {
+        self.closure_builtin_funs.remove(&String::from(name));
+        self.disabled_closure_builtin_funs.remove(&String::from(name));
+        self.fork_free_closure_builtin_fun_names.remove(&String::from(name));
+    }
+
+    pub fn fork_free_closure_builtin_fun_flag(&self, name: &str) -> bool
+    { self.fork_free_closure_builtin_fun_names.contains(&String::from(name)) }
+
+    pub fn set_fork_free_closure_builtin_fun_flag(&mut self, name: &str)
+    { self.fork_free_closure_builtin_fun_names.insert(String::from(name)); }
+
+    pub fn closure_builtin_funs(&self) -> &HashMap<String, Rc<RefCell<ClosureBuiltinFunction>>>
+    { &self.closure_builtin_funs }
+
+    pub fn has_builtin_fun(&self, name: &str) -> bool
+    { self.builtin_funs.contains_key(&String::from(name)) || self.closure_builtin_funs.contains_key(&String::from(name)) }
+
+    pub fn builtin_funs(&self) -> &HashMap<String, BuiltinFunction>
+    { &self.builtin_funs }
+
+    pub fn disabled_builtin_funs(&self) -> &HashMap<String, BuiltinFunction>
+    { &self.disabled_builtin_funs }
+
+    pub fn disabled_closure_builtin_funs(&self) -> &HashMap<String, Rc<RefCell<ClosureBuiltinFunction>>>
+    { &self.disabled_closure_builtin_funs }
+
+    pub fn disable_builtin_fun(&mut self, name: &str) -> bool
+    {
+        let is_closure_builtin_fun = match self.closure_builtin_funs.remove(&String::from(name)) {
+            Some(closure_builtin_fun) => {
+                self.disabled_closure_builtin_funs.insert(String::from(name), closure_builtin_fun);
+                true
+            },
+            None => self.disabled_closure_builtin_funs.contains_key(&String::from(name)),
+        };
+        match self.builtin_funs.remove(&String::from(name)) {
+            Some(builtin_fun) => {
+                self.disabled_builtin_funs.insert(String::from(name), builtin_fun);
+                true
+            },
+            None => is_closure_builtin_fun || self.disabled_builtin_funs.contains_key(&String::from(name)),
+        }
+    }
+
+    pub fn enable_builtin_fun(&mut self, name: &str) -> bool
+    {
+        let is_closure_builtin_fun = match self.disabled_closure_builtin_funs.remove(&String::from(name)) {
+            Some(closure_builtin_fun) => {
+                self.closure_builtin_funs.insert(String::from(name), closure_builtin_fun);
+                true
+            },
+            None => self.closure_builtin_funs.contains_key(&String::from(name)),
+        };
+        match self.disabled_builtin_funs.remove(&String::from(name)) {
+            Some(builtin_fun) => {
+                self.builtin_funs.insert(String::from(name), builtin_fun);
+                true
+            },
+            None => is_closure_builtin_fun || self.builtin_funs.contains_key(&String::from(name)),
+        }
+    }
+
+    pub fn shadow_builtin_fun(&mut self, name: &str)
+    {
+        match self.builtin_funs.remove(&String::from(name)) {
+            Some(builtin_fun) => {
+                self.shadowed_builtin_funs.insert(String::from(name), (builtin_fun, true));
+            },
+            None => {
+                if let Some(builtin_fun) = self.disabled_builtin_funs.remove(&String::from(name)) {
+                    self.shadowed_builtin_funs.insert(String::from(name), (builtin_fun, false));
+                }
+            },
+        }
+    }
+
+    pub fn restore_builtin_fun(&mut self, name: &str)
+    {
+        self.unset_builtin_fun(name);
+        match self.shadowed_builtin_funs.remove(&String::from(name)) {
+            Some((builtin_fun, true)) => {
+                self.builtin_funs.insert(String::from(name), builtin_fun);
+            },
+            Some((builtin_fun, false)) => {
+                self.disabled_builtin_funs.insert(String::from(name), builtin_fun);
+            },
+            None => (),
+        }
+    }
+
+    pub fn loadable_builtin(&self, name: &str) -> Option<LoadableBuiltin>
+    { self.loadable_builtins.get(&String::from(name)).map(|lb| lb.clone()) }
+
+    pub fn set_loadable_builtin(&mut self, name: &str, loadable_builtin: LoadableBuiltin)
+    { self.loadable_builtins.insert(String::from(name), loadable_builtin); }
+
+    pub fn unset_loadable_builtin(&mut self, name: &str)
+    { self.loadable_builtins.remove(&String::from(name)); }
     
     pub fn fun(&self, name: &str) -> Option<Rc<FunctionBody>>
     { self.funs.get(&String::from(name)).map(|fb| fb.clone()) }
@@ -126,6 +261,9 @@ impl Environment
     pub fn unset_fun(&mut self, name: &str)
     { self.funs.remove(&String::from(name)); }
 
+    pub fn funs(&self) -> &HashMap<String, Rc<FunctionBody>>
+    { &self.funs }
+
     pub fn alias(&self, name: &str) -> Option<String>
     { self.aliases.get(&String::from(name)).map(|v| v.clone()) }
 
@@ -140,4 +278,70 @@ impl Environment
     
     pub fn unset_all_aliases(&mut self)
     { self.aliases.clear(); }
+
+    pub fn completion_spec(&self, name: &str) -> Option<CompletionSpec>
+    { self.completion_specs.get(&String::from(name)).map(|s| s.clone()) }
+
+    pub fn set_completion_spec(&mut self, name: &str, spec: CompletionSpec)
+    { self.completion_specs.insert(String::from(name), spec); }
+
+    pub fn unset_completion_spec(&mut self, name: &str)
+    { self.completion_specs.remove(&String::from(name)); }
+
+    pub fn completion_specs(&self) -> &HashMap<String, CompletionSpec>
+    { &self.completion_specs }
+
+    pub fn unset_all_completion_specs(&mut self)
+    { self.completion_specs.clear(); }
+
+    pub fn dir_stack(&self) -> &[String]
+    { self.dir_stack.as_slice() }
+
+    pub fn set_dir_stack(&mut self, dir_stack: Vec<String>)
+    { self.dir_stack = dir_stack; }
+
+    pub fn snapshot(&self) -> EnvironmentSnapshot
+    {
+        EnvironmentSnapshot {
+            unexported_vars: self.unexported_vars.clone(),
+            exported_vars: env::vars_os().collect(),
+            read_only_var_attrs: self.read_only_var_attrs.clone(),
+            funs: self.funs.clone(),
+            aliases: self.aliases.clone(),
+            completion_specs: self.completion_specs.clone(),
+            dir_stack: self.dir_stack.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: EnvironmentSnapshot)
+    {
+        let exported_vars: HashMap<OsString, OsString> = snapshot.exported_vars.into_iter().collect();
+        for (name, _) in env::vars_os() {
+            if !exported_vars.contains_key(&name) {
+                env::remove_var(name);
+            }
+        }
+        for (name, value) in &exported_vars {
+            if env::var_os(name).as_ref() != Some(value) {
+                env::set_var(name, value);
+            }
+        }
+        self.unexported_vars = snapshot.unexported_vars;
+        self.read_only_var_attrs = snapshot.read_only_var_attrs;
+        self.funs = snapshot.funs;
+        self.aliases = snapshot.aliases;
+        self.completion_specs = snapshot.completion_specs;
+        self.dir_stack = snapshot.dir_stack;
+    }
+}
+
+pub struct EnvironmentSnapshot
+{
+    unexported_vars: HashMap<String, String>,
+    exported_vars: Vec<(OsString, OsString)>,
+    read_only_var_attrs: HashSet<String>,
+    funs: HashMap<String, Rc<FunctionBody>>,
+    aliases: HashMap<String, String>,
+    completion_specs: HashMap<String, CompletionSpec>,
+    dir_stack: Vec<String>,
 }
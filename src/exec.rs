@@ -31,6 +31,7 @@ use std::rc::*;
 use std::result;
 use libc;
 use crate::args::*;
+use crate::builtins::call_closure_builtin_fun;
 use crate::env::*;
 use crate::interp::*;
 use crate::settings::*;
@@ -61,6 +62,7 @@ pub struct Pipe
 
 impl Pipe
 {
+    #[cfg(test)]
     pub fn new(reading_file: Rc<RefCell<File>>, writing_file: Rc<RefCell<File>>) -> Pipe
     { Pipe { reading_file, writing_file, } }
     
@@ -94,6 +96,8 @@ pub struct Job
     pub pgid: i32,
     pub name: String,
     pub show_flag: bool,
+    pub nohup_flag: bool,
+    pub termios: Option<libc::termios>,
     prev_job_id: Option<u32>,
     next_job_id: Option<u32>,
 }
@@ -112,6 +116,8 @@ impl Job
             pgid: last_pid,
             name: String::from(name),
             show_flag: false,
+            nohup_flag: false,
+            termios: None,
             prev_job_id: None,
             next_job_id: None,
         }
@@ -130,6 +136,8 @@ impl Job
             pgid: pgid,
             name: String::from(name),
             show_flag: false,
+            nohup_flag: false,
+            termios: None,
             prev_job_id: None,
             next_job_id: None,
         }
@@ -162,6 +170,7 @@ pub struct Executor
     jobs: HashMap<u32, Job>,
     current_job_id: Option<u32>,
     has_foreground: bool,
+    shell_termios: Cell<Option<libc::termios>>,
 }
 
 impl Executor
@@ -178,6 +187,7 @@ impl Executor
             jobs: HashMap::new(),
             current_job_id: None,
             has_foreground: false,
+            shell_termios: Cell::new(None),
        }
     }
    
@@ -266,6 +276,7 @@ impl Executor
         }
     }
     
+    #[cfg(test)]
     pub fn clear_files(&mut self)
     { self.virtual_files.clear(); }
     
@@ -352,6 +363,22 @@ impl Executor
         }
     }
     
+    pub fn set_job_nohup_flag(&mut self, job_id: u32, nohup_flag: bool)
+    {
+        match self.jobs.get_mut(&job_id) {
+            Some(job) => job.nohup_flag = nohup_flag,
+            None => (),
+        }
+    }
+    
+    pub fn set_job_termios(&mut self, job_id: u32, termios: Option<libc::termios>)
+    {
+        match self.jobs.get_mut(&job_id) {
+            Some(job) => job.termios = termios,
+            None => (),
+        }
+    }
+    
     pub fn remove_job(&mut self, job_id: u32)
     {
         let mut prev_job_id: Option<u32> = None;
@@ -376,7 +403,29 @@ impl Executor
         }
         self.jobs.remove(&job_id);
     }
-    
+
+    pub fn hang_up_jobs(&self)
+    {
+        for job in self.jobs.values() {
+            if job.nohup_flag {
+                continue;
+            }
+            let pids_and_statuses = job.pids.iter().zip(job.statuses.iter()).chain(Some((&job.last_pid, &job.last_status)));
+            for (pid, status) in pids_and_statuses {
+                match status {
+                    WaitStatus::None => {
+                        let _res = kill(*pid, libc::SIGHUP);
+                    },
+                    WaitStatus::Stopped(_) => {
+                        let _res = kill(*pid, libc::SIGHUP);
+                        let _res = kill(*pid, libc::SIGCONT);
+                    },
+                    _ => (),
+                }
+            }
+        }
+    }
+
     pub fn set_foreground(&mut self)
     { self.has_foreground = true; }
     
@@ -389,6 +438,15 @@ impl Executor
         res
     }
     
+    pub fn interpret_as_last<T, F>(&mut self, f: F) -> T
+        where F: FnOnce(&mut Self) -> T
+    {
+        self.push_state(State::InNewProcess);
+        let res = f(self);
+        self.pop_state();
+        res
+    }
+    
     pub fn interpret_or<T, F>(&mut self, is_interp: bool, f: F) -> T
         where F: FnOnce(&mut Self) -> T
     {
@@ -496,7 +554,7 @@ impl Executor
                             if libc::WIFEXITED(status) {
                                 res = Ok(WaitStatus::Exited(libc::WEXITSTATUS(status)));
                                 if is_foregrond {
-                                    self.set_foreground_for_shell(settings);
+                                    self.set_foreground_for_shell_and_save_termios(settings);
                                 }
                                 break;
                             } else if libc::WIFSIGNALED(status) {
@@ -531,15 +589,60 @@ impl Executor
             }
         }
     }
+
+    pub fn set_foreground_for_job(&self, job: &Job, settings: &Settings)
+    {
+        if settings.monitor_flag {
+            if self.has_foreground {
+                let _res = tcsetpgrp(0, job.pgid); 
+                match &job.termios {
+                    Some(termios) => {
+                        let _res = tcsetattr(0, libc::TCSADRAIN, termios);
+                    },
+                    None => (),
+                }
+            }
+        }
+    }
     
     pub fn set_foreground_for_shell(&self, settings: &Settings)
     {
         if settings.monitor_flag {
             if self.has_foreground {
                 let _res = tcsetpgrp(0, self.shell_pid); 
+                match self.shell_termios.get() {
+                    Some(termios) => {
+                        let _res = tcsetattr(0, libc::TCSADRAIN, &termios);
+                    },
+                    None => (),
+                }
+            }
+        }
+    }
+
+    fn set_foreground_for_shell_and_save_termios(&self, settings: &Settings)
+    {
+        if settings.monitor_flag {
+            if self.has_foreground {
+                let _res = tcsetpgrp(0, self.shell_pid); 
+                if self.shell_termios.get().is_some() {
+                    self.save_shell_termios();
+                }
             }
         }
     }
+
+    pub fn save_shell_termios(&self)
+    { self.shell_termios.set(tcgetattr(0).ok()); }
+
+    pub fn terminal_termios(&self, settings: &Settings) -> Option<libc::termios>
+    {
+        if settings.monitor_flag && self.has_foreground {
+            tcgetattr(0).ok()
+        } else {
+            None
+        }
+    }
     
     pub fn set_process_group(&self, pid: i32, pgid: i32, settings: &Settings)
     {
@@ -608,6 +711,15 @@ impl Executor
     pub fn execute<F>(&mut self, interp: &mut Interpreter, vars: &[(String, String)], arg0: &str, args: &[String], is_untraced: bool, env: &mut Environment, settings: &mut Settings, mut stop_f: F) -> Result<(WaitStatus, Option<i32>)>
         where F: FnMut(i32) -> bool
     {
+        match env.closure_builtin_fun(arg0) {
+            Some(closure_builtin_fun) => {
+                let mut tmp_args = vec![String::from(arg0)];
+                tmp_args.extend_from_slice(args);
+                let status = call_closure_builtin_fun(&closure_builtin_fun, vars, tmp_args.as_slice(), interp, self, env, settings);
+                return Ok((WaitStatus::Exited(status), None));
+            },
+            None => (),
+        }
         match env.builtin_fun(arg0) {
             Some(builtin_fun) => {
                 let mut tmp_args = vec![String::from(arg0)];
@@ -627,7 +739,7 @@ impl Executor
                                     let mut tmp_args = Arguments::new();
                                     tmp_args.set_args(args.iter().map(|a| a.clone()).collect());
                                     settings.push_args(tmp_args);
-                                    let status = interp.interpret_fun_body(exec, &(*fun_body), env, settings);
+                                    let status = interp.interpret_fun_body(exec, arg0, &(*fun_body), env, settings);
                                     settings.pop_args();
                                     status
                             })?;
@@ -644,31 +756,33 @@ impl Executor
                             let mut tmp_args = Arguments::new();
                             tmp_args.set_args(args.iter().map(|a| a.clone()).collect());
                             settings.push_args(tmp_args);
-                            let status = interp.interpret_fun_body(self, &(*fun_body), env, settings);
+                            let status = interp.interpret_fun_body(self, arg0, &(*fun_body), env, settings);
                             settings.pop_args();
                             Ok((WaitStatus::Exited(status), None))
                         }
                     },
                     None => {
-                        let pid = self.create_process(false, settings, |exec, _| {
-                                for (name, value) in vars.iter() {
-                                    env.unset_unexported_var(name.as_str());
-                                    env.set_exported_var(name.as_str(), value.as_str());
-                                }
-                                match exec.close_and_move_files_for_execute() {
-                                    Ok(()) => {
-                                        set_signals_for_execute();
-                                        let mut cmd = Command::new(arg0);
-                                        cmd.args(args);
-                                        let err = cmd.exec();
-                                        eprintln!("{}: {}", arg0, err);
-                                        if err.kind() == ErrorKind::NotFound { 127 } else { 126 }
-                                    },
-                                    Err(err) => {
-                                        eprintln!("{}: {}", arg0, err);
-                                        126
-                                    },
-                                }
+                        let pid = self.interpret_or(interp.has_work_after_command(env, settings), |exec| {
+                                exec.create_process(false, settings, |exec, _| {
+                                        for (name, value) in vars.iter() {
+                                            env.unset_unexported_var(name.as_str());
+                                            env.set_exported_var(name.as_str(), value.as_str());
+                                        }
+                                        match exec.close_and_move_files_for_execute() {
+                                            Ok(()) => {
+                                                set_signals_for_execute();
+                                                let mut cmd = Command::new(arg0);
+                                                cmd.args(args);
+                                                let err = cmd.exec();
+                                                eprintln!("{}: {}", arg0, err);
+                                                if err.kind() == ErrorKind::NotFound { 127 } else { 126 }
+                                            },
+                                            Err(err) => {
+                                                eprintln!("{}: {}", arg0, err);
+                                                126
+                                            },
+                                        }
+                                })
                         })?;
                         let wait_status = loop {
                             match self.wait_for_process(pid, true, is_untraced, true, settings)? {
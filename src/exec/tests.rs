@@ -15,6 +15,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::os::unix::process::ExitStatusExt;
 use super::*;
 use crate::io::*;
 use crate::lexer::*;
@@ -2433,3 +2434,109 @@ fn test_executor_parse_job_id_complains_on_ambiguous_for_percent_ques_string()
         _ => assert!(false),
     }
 }
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_executor_parse_job_id_parses_job_id_with_question_mark()
+{
+    let mut exec = Executor::new();
+    exec.add_job(&Job::new(1234, "sleep 10"));
+    exec.add_job(&Job::new(2345, "cat file.txt"));
+    exec.add_job(&Job::new(3456, "less file2.txt"));
+    match exec.parse_job_id("%?at") {
+        Ok(job_id) => assert_eq!(2, job_id),
+        _ => assert!(false),
+    }
+    match exec.parse_job_id("%?file") {
+        Err(JobIdError::Ambiguous) => assert!(true),
+        _ => assert!(false),
+    }
+    match exec.parse_job_id("%?xxx") {
+        Err(JobIdError::NotFound) => assert!(true),
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_executor_hang_up_jobs_sends_sighup_to_jobs_without_nohup_flag()
+{
+    let mut exec = Executor::new();
+    let mut child = process::Command::new("sleep").arg("10").spawn().unwrap();
+    let mut child2 = process::Command::new("sleep").arg("10").spawn().unwrap();
+    exec.add_job(&Job::new(child.id() as i32, "sleep 10"));
+    exec.add_job(&Job::new(child2.id() as i32, "sleep 10"));
+    exec.set_job_nohup_flag(2, true);
+    exec.hang_up_jobs();
+    let status = child.wait().unwrap();
+    let is_running = child2.try_wait().unwrap().is_none();
+    let _res = child2.kill();
+    let _res = child2.wait();
+    assert_eq!(None, status.code());
+    assert_eq!(Some(libc::SIGHUP), status.signal());
+    assert_eq!(true, is_running);
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_executor_set_foreground_for_job_and_shell_sets_termios_for_pty()
+{
+    let status = create_process_and_wait_for_process(|| {
+            if unsafe { libc::setsid() } == -1 {
+                return 1;
+            }
+            let master_fd = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+            if master_fd == -1 || unsafe { libc::grantpt(master_fd) } == -1 || unsafe { libc::unlockpt(master_fd) } == -1 {
+                return 2;
+            }
+            let slave_name = unsafe { libc::ptsname(master_fd) };
+            if slave_name.is_null() {
+                return 2;
+            }
+            let slave_fd = unsafe { libc::open(slave_name, libc::O_RDWR) };
+            if slave_fd == -1 || unsafe { libc::dup2(slave_fd, 0) } == -1 {
+                return 2;
+            }
+            let mut exec = Executor::new();
+            let mut settings = Settings::new();
+            if exec.terminal_termios(&settings).is_some() {
+                return 3;
+            }
+            settings.monitor_flag = true;
+            exec.set_foreground();
+            exec.save_shell_termios();
+            let shell_termios = match exec.terminal_termios(&settings) {
+                Some(termios) => termios,
+                None => return 4,
+            };
+            if (shell_termios.c_lflag & libc::ECHO) == 0 {
+                return 4;
+            }
+            exec.add_job(&Job::new(exec.shell_pid, "vi"));
+            let mut job_termios = shell_termios;
+            job_termios.c_lflag &= !libc::ECHO;
+            exec.set_job_termios(1, Some(job_termios));
+            let job = match exec.jobs().get(&1) {
+                Some(job) => job.clone(),
+                None => return 5,
+            };
+            exec.set_foreground_for_job(&job, &settings);
+            match exec.terminal_termios(&settings) {
+                Some(termios) if (termios.c_lflag & libc::ECHO) == 0 => (),
+                _ => return 5,
+            }
+            exec.set_foreground_for_shell(&settings);
+            match exec.terminal_termios(&settings) {
+                Some(termios) if (termios.c_lflag & libc::ECHO) != 0 => (),
+                _ => return 6,
+            }
+            exec.set_foreground_for_job(&job, &settings);
+            exec.set_foreground_for_shell_and_save_termios(&settings);
+            match exec.shell_termios.get() {
+                Some(termios) if (termios.c_lflag & libc::ECHO) == 0 => (),
+                _ => return 7,
+            }
+            if unsafe { libc::tcgetpgrp(0) } != exec.shell_pid {
+                return 8;
+            }
+            0
+    });
+    assert_eq!(0, status);
+}
@@ -0,0 +1,910 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::cell::*;
+use std::cmp::max;
+use std::io::*;
+use std::rc::*;
+use crate::io::*;
+use crate::lexer::*;
+use crate::parser::*;
+use crate::settings::*;
+
+pub const DEFAULT_INDENT_WIDTH: usize = 4;
+
+fn pos_key(pos: &Position) -> (u64, u64)
+{ (pos.line, pos.column) }
+
+fn max_pos(pos: Position, pos2: Position) -> Position
+{
+    if pos_key(&pos2) > pos_key(&pos) {
+        pos2
+    } else {
+        pos
+    }
+}
+
+fn is_closing_token(token: &Token, closing_token: &Token) -> bool
+{
+    match (token, closing_token) {
+        (Token::Then, Token::Then) => true,
+        (Token::Do, Token::Do) => true,
+        (Token::Elif, Token::Elif) => true,
+        (Token::Else, Token::Else) => true,
+        (Token::Fi, Token::Fi) => true,
+        (Token::Done, Token::Done) => true,
+        (Token::Esac, Token::Esac) => true,
+        (Token::RBrace, Token::RBrace) => true,
+        (Token::RParen, Token::RParen) => true,
+        (Token::SemiSemi, Token::SemiSemi) => true,
+        _ => false,
+    }
+}
+
+pub fn indent_for_width(width: usize) -> String
+{
+    if width == 0 {
+        String::from("\t")
+    } else {
+        " ".repeat(width)
+    }
+}
+
+struct ScriptFormatter
+{
+    source: String,
+    indent: String,
+    level: usize,
+    out: String,
+    is_line_start: bool,
+    line_start_pos: Option<Position>,
+    line_end_pos: Option<Position>,
+    line_ends: Vec<(Position, Position, usize)>,
+    last_line: Option<u64>,
+    here_docs: Vec<Rc<RefCell<HereDocument>>>,
+    comments: Vec<(Position, String)>,
+    comment_idx: usize,
+    closing_tokens: Vec<(Token, Position)>,
+    source_ranges: Vec<(u64, u64)>,
+}
+
+impl ScriptFormatter
+{
+    fn new(source: &str, indent: &str, comments: &[(Position, String)], closing_tokens: &[(Token, Position)]) -> ScriptFormatter
+    {
+        ScriptFormatter {
+            source: String::from(source),
+            indent: String::from(indent),
+            level: 0,
+            out: String::new(),
+            is_line_start: true,
+            line_start_pos: None,
+            line_end_pos: None,
+            line_ends: Vec::new(),
+            last_line: None,
+            here_docs: Vec::new(),
+            comments: comments.to_vec(),
+            comment_idx: 0,
+            closing_tokens: closing_tokens.to_vec(),
+            source_ranges: Vec::new(),
+        }
+    }
+
+    fn find_closing_token(&self, closing_token: &Token, pos: &Position) -> Option<Position>
+    {
+        self.closing_tokens.iter().find(|(token, token_pos)| {
+                is_closing_token(token, closing_token) && pos_key(token_pos) >= pos_key(pos)
+        }).map(|(_, token_pos)| *token_pos)
+    }
+
+    fn word_end(&self, word: &Word) -> Position
+    { word.end_pos }
+
+    fn case_pair_end(&self, pair: &CasePair, pos: Position) -> Position
+    {
+        let pos2 = pair.pattern_words.iter().fold(pos, |pos3, word| max_pos(pos3, self.word_end(&(*word))));
+        self.logical_commands_end(pair.commands.as_slice()).unwrap_or(pos2)
+    }
+
+    fn command_end(&self, command: &Command) -> Position
+    { command.end_pos() }
+
+    fn logical_command_end(&self, command: &LogicalCommand) -> Position
+    { command.end_pos }
+
+    fn logical_commands_end(&self, commands: &[Rc<LogicalCommand>]) -> Option<Position>
+    { commands.last().map(|command| self.logical_command_end(&(*command))) }
+
+    fn last_line(&self, command: &LogicalCommand) -> u64
+    {
+        let mut line = self.logical_command_end(command).line;
+        let mut pipe_commands = vec![command.first_command.clone()];
+        pipe_commands.extend(command.pairs.iter().map(|pair| pair.command.clone()));
+        for pipe_command in &pipe_commands {
+            for command2 in &pipe_command.commands {
+                let redirects = match &(**command2) {
+                    Command::Simple(_, _, _, simple_command) => simple_command.redirects.as_slice(),
+                    Command::Compound(_, _, _, _, redirects) => redirects.as_slice(),
+                    Command::FunctionDefinition(_, _, _, _, fun_body) => fun_body.redirects.as_slice(),
+                };
+                for redirect in redirects {
+                    match &(**redirect) {
+                        Redirection::HereDocument(_, _, _, _, here_doc) => {
+                            let end_pos = here_doc.borrow().end_pos;
+                            if end_pos.column == 1 && end_pos.line > 1 {
+                                line = max(line, end_pos.line - 1);
+                            } else {
+                                line = max(line, end_pos.line);
+                            }
+                        },
+                        _ => (),
+                    }
+                }
+            }
+        }
+        line
+    }
+
+    fn write_str(&mut self, s: &str)
+    {
+        if self.is_line_start {
+            for _ in 0..self.level {
+                self.out.push_str(self.indent.as_str());
+            }
+            self.is_line_start = false;
+        }
+        self.out.push_str(s);
+    }
+
+    fn mark_pos(&mut self, pos: Position)
+    {
+        if self.line_start_pos.is_none() {
+            self.line_start_pos = Some(pos);
+        }
+        self.line_end_pos = Some(self.line_end_pos.map(|pos2| max_pos(pos2, pos)).unwrap_or(pos));
+    }
+
+    fn source_str(&self, pos: &Position, end_pos: &Position) -> Option<String>
+    {
+        if pos.offset < end_pos.offset {
+            self.source.get((pos.offset as usize)..(end_pos.offset as usize)).map(String::from)
+        } else {
+            None
+        }
+    }
+
+    fn write_source_str(&mut self, pos: &Position, end_pos: &Position) -> bool
+    {
+        match self.source_str(pos, end_pos) {
+            Some(s) => {
+                self.write_str(s.as_str());
+                self.source_ranges.push((pos.offset, end_pos.offset));
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn is_in_source_range(&self, pos: &Position) -> bool
+    { self.source_ranges.iter().any(|(offset, end_offset)| *offset <= pos.offset && pos.offset < *end_offset) }
+
+    fn write_blank_line_for_gap(&mut self, line: u64)
+    {
+        match self.last_line {
+            Some(last_line) if line > last_line + 1 => self.out.push('\n'),
+            _ => (),
+        }
+    }
+
+    fn end_line(&mut self)
+    {
+        if let (Some(start_pos), Some(end_pos)) = (self.line_start_pos, self.line_end_pos) {
+            self.line_ends.push((start_pos, end_pos, self.out.len()));
+        }
+        self.line_start_pos = None;
+        self.line_end_pos = None;
+        self.out.push('\n');
+        let here_docs = self.here_docs.clone();
+        for here_doc in &here_docs {
+            let here_doc = here_doc.borrow();
+            match self.source_str(&here_doc.pos, &here_doc.end_pos) {
+                Some(s) => {
+                    self.out.push_str(s.as_str());
+                    if !s.ends_with('\n') {
+                        self.out.push('\n');
+                    }
+                    self.source_ranges.push((here_doc.pos.offset, here_doc.end_pos.offset));
+                },
+                None => self.out.push_str(format!("{}", here_doc).as_str()),
+            }
+        }
+        self.here_docs.clear();
+        self.is_line_start = true;
+    }
+
+    fn insert_trailing_comment(&mut self, comment_pos: &Position, comment: &str) -> bool
+    {
+        let line_end = self.line_ends.iter().rposition(|(start_pos, end_pos, _)| {
+                start_pos.line <= comment_pos.line && comment_pos.line <= end_pos.line && pos_key(start_pos) < pos_key(comment_pos)
+        });
+        match line_end {
+            Some(i) => {
+                let idx = self.line_ends[i].2;
+                let s = format!(" #{}", comment.trim_end());
+                self.out.insert_str(idx, s.as_str());
+                for line_end2 in &mut self.line_ends[i..] {
+                    line_end2.2 += s.len();
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn write_comments_before(&mut self, pos: Option<&Position>)
+    {
+        while self.comment_idx < self.comments.len() && pos.map(|p| pos_key(&self.comments[self.comment_idx].0) < pos_key(p)).unwrap_or(true) {
+            let (comment_pos, comment) = self.comments[self.comment_idx].clone();
+            if self.is_in_source_range(&comment_pos) {
+                self.comment_idx += 1;
+                continue;
+            }
+            if self.insert_trailing_comment(&comment_pos, comment.as_str()) {
+                self.comment_idx += 1;
+                continue;
+            }
+            self.write_blank_line_for_gap(comment_pos.line);
+            self.write_str(format!("#{}", comment.trim_end()).as_str());
+            self.out.push('\n');
+            self.is_line_start = true;
+            self.last_line = Some(comment_pos.line);
+            self.comment_idx += 1;
+        }
+    }
+
+    fn fmt_block(&mut self, commands: &[Rc<LogicalCommand>], end_pos: Option<Position>)
+    {
+        self.end_line();
+        self.level += 1;
+        self.last_line = None;
+        self.fmt_logical_commands(commands);
+        match end_pos {
+            Some(end_pos) => self.write_comments_before(Some(&end_pos)),
+            None => (),
+        }
+        self.level -= 1;
+    }
+
+    fn fmt_cond(&mut self, commands: &[Rc<LogicalCommand>], closing_token: &Token, keyword: &str)
+    {
+        self.fmt_inline_logical_commands(commands, false);
+        if commands.last().map(|c| c.is_in_background).unwrap_or(false) {
+            self.write_str(format!(" {}", keyword).as_str());
+        } else {
+            self.write_str(format!("; {}", keyword).as_str());
+        }
+        let cond_end = self.logical_commands_end(commands);
+        if let Some(pos) = cond_end {
+            self.mark_pos(pos);
+        }
+        if let Some(pos) = cond_end.and_then(|pos| self.find_closing_token(closing_token, &pos)) {
+            self.mark_pos(pos);
+        }
+    }
+
+    fn fmt_compound_command(&mut self, command: &CompoundCommand, pos: &Position, end_pos: Position)
+    {
+        self.mark_pos(*pos);
+        if end_pos.line == pos.line {
+            self.fmt_inline_compound_command(command);
+            self.mark_pos(end_pos);
+            return;
+        }
+        match command {
+            CompoundCommand::BraceGroup(commands) => {
+                self.write_str("{");
+                self.fmt_block(commands.as_slice(), Some(end_pos));
+                self.write_str("}");
+            },
+            CompoundCommand::Subshell(commands) => {
+                self.write_str("(");
+                self.fmt_block(commands.as_slice(), Some(end_pos));
+                self.write_str(")");
+            },
+            CompoundCommand::For(name_word, words, commands) => {
+                self.write_str("for ");
+                self.fmt_word(name_word);
+                let mut words_end = name_word.pos;
+                match words {
+                    Some(words) => {
+                        self.write_str(" in");
+                        for word in words {
+                            self.write_str(" ");
+                            self.fmt_word(word);
+                            words_end = max_pos(words_end, self.word_end(&(*word)));
+                        }
+                    },
+                    None => (),
+                }
+                self.write_str("; do");
+                self.mark_pos(words_end);
+                match self.find_closing_token(&Token::Do, &words_end) {
+                    Some(do_pos) => self.mark_pos(do_pos),
+                    None => (),
+                }
+                self.fmt_block(commands.as_slice(), Some(end_pos));
+                self.write_str("done");
+            },
+            CompoundCommand::Case(word, pairs) => {
+                self.write_str("case ");
+                self.fmt_word(word);
+                self.write_str(" in");
+                self.mark_pos(self.word_end(&(*word)));
+                self.end_line();
+                self.level += 1;
+                self.last_line = None;
+                let mut pair_start = self.word_end(&(*word));
+                for pair in pairs {
+                    match pair.pattern_words.first() {
+                        Some(pattern_word) => self.write_comments_before(Some(&pattern_word.pos)),
+                        None => (),
+                    }
+                    let mut is_first = true;
+                    if let Some(pattern_word) = pair.pattern_words.first() {
+                        self.mark_pos(pattern_word.pos);
+                    }
+                    for pattern_word in &pair.pattern_words {
+                        if !is_first {
+                            self.write_str("|");
+                        }
+                        self.fmt_word(pattern_word);
+                        is_first = false;
+                    }
+                    self.write_str(")");
+                    let patterns_end = pair.pattern_words.iter().fold(pair_start, |pos2, word| max_pos(pos2, self.word_end(&(*word))));
+                    self.mark_pos(patterns_end);
+                    let pair_end = self.case_pair_end(pair, patterns_end);
+                    let semi_semi_pos = self.find_closing_token(&Token::SemiSemi, &pair_end).filter(|pos2| pos_key(pos2) < pos_key(&end_pos));
+                    self.fmt_block(pair.commands.as_slice(), Some(semi_semi_pos.unwrap_or(end_pos)));
+                    self.level += 1;
+                    self.write_str(";;");
+                    self.level -= 1;
+                    match semi_semi_pos {
+                        Some(semi_semi_pos) => {
+                            self.mark_pos(semi_semi_pos);
+                            pair_start = semi_semi_pos;
+                        },
+                        None => pair_start = pair_end,
+                    }
+                    self.end_line();
+                    self.last_line = Some(pair_start.line);
+                }
+                self.write_comments_before(Some(&end_pos));
+                self.level -= 1;
+                self.write_str("esac");
+            },
+            CompoundCommand::If(cond_commands, commands, pairs, else_commands) => {
+                self.write_str("if ");
+                self.fmt_cond(cond_commands.as_slice(), &Token::Then, "then");
+                let mut block_end = self.logical_commands_end(commands.as_slice()).unwrap_or(*pos);
+                let mut next_pos = if !pairs.is_empty() {
+                    self.find_closing_token(&Token::Elif, &block_end)
+                } else if else_commands.is_some() {
+                    self.find_closing_token(&Token::Else, &block_end)
+                } else {
+                    Some(end_pos)
+                };
+                self.fmt_block(commands.as_slice(), next_pos);
+                for (i, pair) in pairs.iter().enumerate() {
+                    match next_pos {
+                        Some(pos2) => self.mark_pos(pos2),
+                        None => (),
+                    }
+                    self.write_str("elif ");
+                    self.fmt_cond(pair.cond_commands.as_slice(), &Token::Then, "then");
+                    block_end = self.logical_commands_end(pair.commands.as_slice()).unwrap_or(block_end);
+                    next_pos = if i + 1 < pairs.len() {
+                        self.find_closing_token(&Token::Elif, &block_end)
+                    } else if else_commands.is_some() {
+                        self.find_closing_token(&Token::Else, &block_end)
+                    } else {
+                        Some(end_pos)
+                    };
+                    self.fmt_block(pair.commands.as_slice(), next_pos);
+                }
+                match else_commands {
+                    Some(else_commands) => {
+                        match next_pos {
+                            Some(pos2) => self.mark_pos(pos2),
+                            None => (),
+                        }
+                        self.write_str("else");
+                        self.fmt_block(else_commands.as_slice(), Some(end_pos));
+                    },
+                    None => (),
+                }
+                self.write_str("fi");
+            },
+            CompoundCommand::While(cond_commands, commands) => {
+                self.write_str("while ");
+                self.fmt_cond(cond_commands.as_slice(), &Token::Do, "do");
+                self.fmt_block(commands.as_slice(), Some(end_pos));
+                self.write_str("done");
+            },
+            CompoundCommand::Until(cond_commands, commands) => {
+                self.write_str("until ");
+                self.fmt_cond(cond_commands.as_slice(), &Token::Do, "do");
+                self.fmt_block(commands.as_slice(), Some(end_pos));
+                self.write_str("done");
+            },
+        }
+        self.mark_pos(end_pos);
+    }
+
+    fn fmt_word(&mut self, word: &Word)
+    {
+        if !self.write_source_str(&word.pos, &word.end_pos) {
+            self.write_str(format!("{}", word).as_str());
+        }
+    }
+
+    fn fmt_first_word(&mut self, word: &Word)
+    {
+        if !self.write_source_str(&word.pos, &word.end_pos) {
+            self.write_str(format!("{}", FirstWord(word)).as_str());
+        }
+    }
+
+    fn fmt_redirect_word(&mut self, n: &Option<i32>, op: &str, word: &Word)
+    {
+        match n {
+            Some(n) => self.write_str(format!("{}", n).as_str()),
+            None => (),
+        }
+        self.write_str(format!("{} ", op).as_str());
+        self.fmt_word(word);
+    }
+
+    fn fmt_redirect(&mut self, redirect: &Redirection)
+    {
+        match redirect {
+            Redirection::Input(_, _, _, n, word) => self.fmt_redirect_word(n, "<", &(*word)),
+            Redirection::Output(_, _, _, n, word, is_bar) => self.fmt_redirect_word(n, if *is_bar { ">|" } else { ">" }, &(*word)),
+            Redirection::InputAndOutput(_, _, _, n, word) => self.fmt_redirect_word(n, "<>", &(*word)),
+            Redirection::Appending(_, _, _, n, word) => self.fmt_redirect_word(n, ">>", &(*word)),
+            Redirection::InputDuplicating(_, _, _, n, word) => self.fmt_redirect_word(n, "<&", &(*word)),
+            Redirection::OutputDuplicating(_, _, _, n, word) => self.fmt_redirect_word(n, ">&", &(*word)),
+            Redirection::HereDocument(_, _, _, n, here_doc) => {
+                match n {
+                    Some(n) => self.write_str(format!("{}", n).as_str()),
+                    None => (),
+                }
+                let op = if here_doc.borrow().has_minus { "<<-" } else { "<<" };
+                let s = format!("{} {}", op, HereDocumentWordStr(here_doc.borrow().delim.as_str(), here_doc.borrow().has_quoted));
+                self.write_str(s.as_str());
+                self.here_docs.push(here_doc.clone());
+            },
+        }
+    }
+
+    fn fmt_redirects(&mut self, redirects: &[Rc<Redirection>])
+    {
+        for redirect in redirects {
+            self.write_str(" ");
+            self.fmt_redirect(&(*redirect));
+        }
+    }
+
+    fn fmt_simple_command(&mut self, command: &SimpleCommand)
+    {
+        let mut is_first = true;
+        for word in &command.words {
+            if is_first {
+                self.fmt_first_word(&(*word));
+            } else {
+                self.write_str(" ");
+                self.fmt_word(&(*word));
+            }
+            is_first = false;
+        }
+        if !command.words.is_empty() && !command.redirects.is_empty() {
+            self.write_str(" ");
+        }
+        is_first = true;
+        for redirect in &command.redirects {
+            if !is_first {
+                self.write_str(" ");
+            }
+            self.fmt_redirect(&(*redirect));
+            is_first = false;
+        }
+    }
+
+    fn fmt_inline_compound_command(&mut self, command: &CompoundCommand)
+    {
+        match command {
+            CompoundCommand::BraceGroup(commands) => {
+                self.write_str("{ ");
+                self.fmt_inline_logical_commands(commands.as_slice(), true);
+                self.write_str("}");
+            },
+            CompoundCommand::Subshell(commands) => {
+                self.write_str("(");
+                self.fmt_inline_logical_commands(commands.as_slice(), false);
+                self.write_str(")");
+            },
+            CompoundCommand::For(name_word, words, commands) => {
+                self.write_str("for ");
+                self.fmt_word(name_word);
+                match words {
+                    Some(words) => {
+                        self.write_str(" in");
+                        for word in words {
+                            self.write_str(" ");
+                            self.fmt_word(word);
+                        }
+                    },
+                    None => (),
+                }
+                self.write_str("; do ");
+                self.fmt_inline_logical_commands(commands.as_slice(), true);
+                self.write_str("done");
+            },
+            CompoundCommand::Case(word, pairs) => {
+                self.write_str("case ");
+                self.fmt_word(word);
+                self.write_str(" in ");
+                for pair in pairs {
+                    let mut is_first = true;
+                    for pattern_word in &pair.pattern_words {
+                        if !is_first {
+                            self.write_str("|");
+                        }
+                        self.fmt_word(pattern_word);
+                        is_first = false;
+                    }
+                    self.write_str(") ");
+                    self.fmt_inline_logical_commands(pair.commands.as_slice(), false);
+                    self.write_str(";; ");
+                }
+                self.write_str("esac");
+            },
+            CompoundCommand::If(cond_commands, commands, pairs, else_commands) => {
+                self.write_str("if ");
+                self.fmt_inline_logical_commands(cond_commands.as_slice(), true);
+                self.write_str("then ");
+                self.fmt_inline_logical_commands(commands.as_slice(), true);
+                for pair in pairs {
+                    self.write_str("elif ");
+                    self.fmt_inline_logical_commands(pair.cond_commands.as_slice(), true);
+                    self.write_str("then ");
+                    self.fmt_inline_logical_commands(pair.commands.as_slice(), true);
+                }
+                match else_commands {
+                    Some(else_commands) => {
+                        self.write_str("else ");
+                        self.fmt_inline_logical_commands(else_commands.as_slice(), true);
+                    },
+                    None => (),
+                }
+                self.write_str("fi");
+            },
+            CompoundCommand::While(cond_commands, commands) => {
+                self.write_str("while ");
+                self.fmt_inline_logical_commands(cond_commands.as_slice(), true);
+                self.write_str("do ");
+                self.fmt_inline_logical_commands(commands.as_slice(), true);
+                self.write_str("done");
+            },
+            CompoundCommand::Until(cond_commands, commands) => {
+                self.write_str("until ");
+                self.fmt_inline_logical_commands(cond_commands.as_slice(), true);
+                self.write_str("do ");
+                self.fmt_inline_logical_commands(commands.as_slice(), true);
+                self.write_str("done");
+            },
+        }
+    }
+
+    fn fmt_command(&mut self, command: &Command, is_inline: bool)
+    {
+        match command {
+            Command::Simple(_, _, _, simple_command) => {
+                self.fmt_simple_command(simple_command);
+                self.mark_pos(command.pos());
+                self.mark_pos(self.command_end(command));
+            },
+            Command::Compound(_, pos, end_pos, compound_command, redirects) => {
+                if is_inline {
+                    self.fmt_inline_compound_command(compound_command);
+                } else {
+                    self.fmt_compound_command(compound_command, pos, *end_pos);
+                }
+                self.fmt_redirects(redirects.as_slice());
+            },
+            Command::FunctionDefinition(_, _, _, name_word, fun_body) => {
+                self.write_str(format!("{}() ", name_word).as_str());
+                if is_inline {
+                    self.fmt_inline_compound_command(&fun_body.command);
+                } else {
+                    self.fmt_compound_command(&fun_body.command, &fun_body.pos, fun_body.end_pos);
+                }
+                self.fmt_redirects(fun_body.redirects.as_slice());
+            },
+        }
+    }
+
+    fn fmt_pipe_command(&mut self, command: &PipeCommand, is_inline: bool)
+    {
+        if command.is_negative {
+            self.write_str("! ");
+        }
+        let mut is_first = true;
+        for command2 in &command.commands {
+            if !is_first {
+                self.write_str(" | ");
+            }
+            self.fmt_command(&(*command2), is_inline);
+            is_first = false;
+        }
+    }
+
+    fn fmt_logical_command(&mut self, command: &LogicalCommand, is_inline: bool)
+    {
+        self.fmt_pipe_command(&(*command.first_command), is_inline);
+        for pair in &command.pairs {
+            self.write_str(format!(" {} ", pair.op).as_str());
+            self.fmt_pipe_command(&(*pair.command), is_inline);
+        }
+        if !is_inline && command.is_in_background {
+            self.write_str(" &");
+        }
+    }
+
+    fn fmt_inline_logical_commands(&mut self, commands: &[Rc<LogicalCommand>], has_last_semicolon: bool)
+    {
+        for (i, command) in commands.iter().enumerate() {
+            self.fmt_logical_command(&(*command), true);
+            if has_last_semicolon || i + 1 < commands.len() {
+                if command.is_in_background {
+                    self.write_str("& ");
+                } else {
+                    self.write_str("; ");
+                }
+            } else if command.is_in_background {
+                self.write_str("&");
+            }
+        }
+    }
+
+    fn fmt_logical_commands(&mut self, commands: &[Rc<LogicalCommand>])
+    {
+        for command in commands {
+            self.write_comments_before(Some(&command.pos));
+            self.write_blank_line_for_gap(command.pos.line);
+            self.mark_pos(command.pos);
+            self.fmt_logical_command(&(*command), false);
+            self.end_line();
+            self.last_line = Some(self.last_line(&(*command)));
+        }
+    }
+}
+
+pub fn format_logical_commands(commands: &[Rc<LogicalCommand>], source: &str, indent: &str, comments: &[(Position, String)], closing_tokens: &[(Token, Position)]) -> String
+{
+    let mut formatter = ScriptFormatter::new(source, indent, comments, closing_tokens);
+    formatter.fmt_logical_commands(commands);
+    formatter.write_comments_before(None);
+    formatter.out
+}
+
+pub fn format_str(path: &str, s: &str, indent: &str, settings: &Settings) -> ParserResult<String>
+{
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new(path, &Position::new(1, 1), &mut cr, 0, false);
+    lexer.set_kept_comments(true);
+    let mut parser = Parser::new();
+    parser.set_error_cont(false);
+    let commands = parser.parse_logical_commands(&mut lexer, settings)?;
+    Ok(format_logical_commands(commands.as_slice(), s, indent, lexer.comments(), lexer.closing_tokens()))
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum DiffOperation
+{
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+// Finds the middle snake of the shortest edit script between two ranges of lines by the
+// linear space variant of the Myers algorithm. The snake is returned as its start point and its
+// end point.
+fn find_middle_snake(old_lines: &[&str], new_lines: &[&str], start: (usize, usize), end: (usize, usize)) -> Option<((usize, usize), (usize, usize))>
+{
+    let (left, top) = (start.0 as isize, start.1 as isize);
+    let (right, bottom) = (end.0 as isize, end.1 as isize);
+    let width = right - left;
+    let height = bottom - top;
+    let size = width + height;
+    if size == 0 {
+        return None;
+    }
+    let max = (size + 1) / 2;
+    let delta = width - height;
+    let idx = |k: isize| -> usize { (k + max + 1) as usize };
+    let mut forward_xs = vec![0isize; (2 * max + 3) as usize];
+    let mut backward_ys = vec![0isize; (2 * max + 3) as usize];
+    forward_xs[idx(1)] = left;
+    backward_ys[idx(1)] = bottom;
+    for d in 0..=max {
+        let mut k = d;
+        while k >= -d {
+            let c = k - delta;
+            let (px, mut x) = if k == -d || (k != d && forward_xs[idx(k - 1)] < forward_xs[idx(k + 1)]) {
+                (forward_xs[idx(k + 1)], forward_xs[idx(k + 1)])
+            } else {
+                (forward_xs[idx(k - 1)], forward_xs[idx(k - 1)] + 1)
+            };
+            let mut y = top + (x - left) - k;
+            let py = if d == 0 || x != px { y } else { y - 1 };
+            while x < right && y < bottom && old_lines[x as usize] == new_lines[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward_xs[idx(k)] = x;
+            if delta % 2 != 0 && -d < c && c < d && y >= backward_ys[idx(c)] {
+                return Some(((px as usize, py as usize), (x as usize, y as usize)));
+            }
+            k -= 2;
+        }
+        let mut c = d;
+        while c >= -d {
+            let k = c + delta;
+            let (py, mut y) = if c == -d || (c != d && backward_ys[idx(c - 1)] > backward_ys[idx(c + 1)]) {
+                (backward_ys[idx(c + 1)], backward_ys[idx(c + 1)])
+            } else {
+                (backward_ys[idx(c - 1)], backward_ys[idx(c - 1)] - 1)
+            };
+            let mut x = left + (y - top) + k;
+            let px = if d == 0 || y != py { x } else { x + 1 };
+            while x > left && y > top && old_lines[(x - 1) as usize] == new_lines[(y - 1) as usize] {
+                x -= 1;
+                y -= 1;
+            }
+            backward_ys[idx(c)] = y;
+            if delta % 2 == 0 && -d <= k && k <= d && x <= forward_xs[idx(k)] {
+                return Some(((x as usize, y as usize), (px as usize, py as usize)));
+            }
+            c -= 2;
+        }
+    }
+    None
+}
+
+// Pushes the points of the shortest edit script between two ranges of lines without the start
+// point and the end point. Two adjacent points are joined by diagonals and at most one edit.
+fn find_diff_path(old_lines: &[&str], new_lines: &[&str], start: (usize, usize), end: (usize, usize), points: &mut Vec<(usize, usize)>)
+{
+    match find_middle_snake(old_lines, new_lines, start, end) {
+        Some((snake_start, snake_end)) => {
+            find_diff_path(old_lines, new_lines, start, snake_start, points);
+            points.push(snake_start);
+            points.push(snake_end);
+            find_diff_path(old_lines, new_lines, snake_end, end, points);
+        },
+        None => (),
+    }
+}
+
+fn push_diff_line(diff: &mut String, c: char, line: &str)
+{
+    diff.push(c);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+pub fn unified_diff(old_path: &str, new_path: &str, old_s: &str, new_s: &str) -> String
+{
+    let old_lines: Vec<&str> = old_s.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_s.split_inclusive('\n').collect();
+    let mut points: Vec<(usize, usize)> = vec![(0, 0)];
+    find_diff_path(old_lines.as_slice(), new_lines.as_slice(), (0, 0), (old_lines.len(), new_lines.len()), &mut points);
+    points.push((old_lines.len(), new_lines.len()));
+    let mut ops: Vec<DiffOperation> = Vec::new();
+    for pair in points.windows(2) {
+        let ((mut i, mut j), (i2, j2)) = (pair[0], pair[1]);
+        while i < i2 && j < j2 && old_lines[i] == new_lines[j] {
+            ops.push(DiffOperation::Equal(i, j));
+            i += 1;
+            j += 1;
+        }
+        if i2 - i > j2 - j {
+            ops.push(DiffOperation::Delete(i));
+            i += 1;
+        } else if j2 - j > i2 - i {
+            ops.push(DiffOperation::Insert(j));
+            j += 1;
+        }
+        while i < i2 && j < j2 {
+            ops.push(DiffOperation::Equal(i, j));
+            i += 1;
+            j += 1;
+        }
+    }
+    let context = 3;
+    let mut diff = String::new();
+    let mut k = 0;
+    while k < ops.len() {
+        match ops[k] {
+            DiffOperation::Equal(_, _) => {
+                k += 1;
+                continue;
+            },
+            _ => (),
+        }
+        let start = k.saturating_sub(context);
+        let mut end = k;
+        let mut last_change = k;
+        while end < ops.len() {
+            match ops[end] {
+                DiffOperation::Equal(_, _) => {
+                    if end - last_change > context * 2 {
+                        break;
+                    }
+                },
+                _ => last_change = end,
+            }
+            end += 1;
+        }
+        end = (last_change + 1 + context).min(ops.len());
+        if diff.is_empty() {
+            diff.push_str(format!("--- {}\n+++ {}\n", old_path, new_path).as_str());
+        }
+        let (mut old_start, mut new_start) = (0, 0);
+        for op in &ops[..start] {
+            match op {
+                DiffOperation::Equal(_, _) => {
+                    old_start += 1;
+                    new_start += 1;
+                },
+                DiffOperation::Delete(_) => old_start += 1,
+                DiffOperation::Insert(_) => new_start += 1,
+            }
+        }
+        let old_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOperation::Insert(_))).count();
+        let new_count = ops[start..end].iter().filter(|op| !matches!(op, DiffOperation::Delete(_))).count();
+        let old_line = if old_count > 0 { old_start + 1 } else { old_start };
+        let new_line = if new_count > 0 { new_start + 1 } else { new_start };
+        diff.push_str(format!("@@ -{},{} +{},{} @@\n", old_line, old_count, new_line, new_count).as_str());
+        for op in &ops[start..end] {
+            match op {
+                DiffOperation::Equal(i2, _) => push_diff_line(&mut diff, ' ', old_lines[*i2]),
+                DiffOperation::Delete(i2) => push_diff_line(&mut diff, '-', old_lines[*i2]),
+                DiffOperation::Insert(j2) => push_diff_line(&mut diff, '+', new_lines[*j2]),
+            }
+        }
+        k = end;
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,404 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+
+fn format_test_str(s: &str, indent_width: usize) -> String
+{
+    let settings = Settings::new();
+    match format_str("test.sh", s, indent_for_width(indent_width).as_str(), &settings) {
+        Ok(new_s) => new_s,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[test]
+fn test_format_str_formats_compound_commands()
+{
+    let s = "
+if true
+then echo abc; elif false; then
+echo def
+else echo ghi
+fi
+for i in 1 2
+do echo $i; done
+while read x; do
+echo \"$x\"; done
+";
+    let expected_s = "
+if true; then
+    echo abc
+elif false; then
+    echo def
+else
+    echo ghi
+fi
+for i in 1 2; do
+    echo $i
+done
+while read x; do
+    echo \"$x\"
+done
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_formats_case_command_and_function_definition()
+{
+    let s = "
+f() {
+  case $1 in
+    a|b) echo ab;;
+    *) echo other
+  esac
+}
+";
+    let expected_s = "
+f() {
+    case $1 in
+        a|b)
+            echo ab
+            ;;
+        *)
+            echo other
+            ;;
+    esac
+}
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_leaves_single_line_compound_commands()
+{
+    let s = "
+[ -f x ] && { echo abc; exit 1; }
+for i in 1 2; do echo $i; done
+";
+    let expected_s = "
+[ -f x ] && { echo abc; exit 1; }
+for i in 1 2; do echo $i; done
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_preserves_comments_and_blank_lines()
+{
+    let s = "
+# first comment
+echo abc # trailing comment
+
+
+if true; then
+  # inner comment
+  echo def
+  # last inner comment
+fi # after fi
+# last comment
+";
+    let expected_s = "
+# first comment
+echo abc # trailing comment
+
+if true; then
+    # inner comment
+    echo def
+    # last inner comment
+fi # after fi
+# last comment
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_places_trailing_comments_after_case_arms()
+{
+    let s = "
+case $i in
+1) echo one;; # one
+2) echo two
+;; # two
+*) echo other ;; # other
+esac
+";
+    let expected_s = "
+case $i in
+    1)
+        echo one
+        ;; # one
+    2)
+        echo two
+        ;; # two
+    *)
+        echo other
+        ;; # other
+esac
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_formats_case_arms_without_space_before_semicolons()
+{
+    let s = "
+case $i in
+1) echo one;;
+*) echo other;; # other
+esac
+";
+    let expected_s = "
+case $i in
+    1)
+        echo one
+        ;;
+    *)
+        echo other
+        ;; # other
+esac
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_places_trailing_comments_after_last_tokens_before_comments()
+{
+    let s = "
+if true # condition
+then echo a # trailing
+else # else
+echo b
+fi
+";
+    let expected_s = "
+if true; then # condition
+    echo a # trailing
+else # else
+    echo b
+fi
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_formats_compound_commands_with_redirections()
+{
+    let s = "
+while read x; do echo $x; done < f
+{ echo abc; } > f
+while read x
+do echo $x; done < f
+{ echo abc
+} > f
+";
+    let expected_s = "
+while read x; do echo $x; done < f
+{ echo abc; } > f
+while read x; do
+    echo $x
+done < f
+{
+    echo abc
+} > f
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_places_here_documents_after_lines()
+{
+    let s = "
+if true; then
+cat <<EOF; echo abc
+line $x
+EOF
+fi
+";
+    let expected_s = "
+if true; then
+    cat << EOF
+line $x
+EOF
+    echo abc
+fi
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_uses_indentation_width()
+{
+    let s = "
+{
+echo abc
+}
+";
+    assert_eq!(String::from("{\n  echo abc\n}\n"), format_test_str(&s[1..], 2));
+    assert_eq!(String::from("{\n\techo abc\n}\n"), format_test_str(&s[1..], 0));
+}
+
+#[test]
+fn test_format_str_is_idempotent()
+{
+    let s = "
+# comment
+f() {
+  if true; then echo a
+  fi
+  case x in y) ;; esac
+}
+";
+    let new_s = format_test_str(&s[1..], 4);
+    assert_eq!(new_s, format_test_str(new_s.as_str(), 4));
+}
+
+#[test]
+fn test_format_str_complains_on_syntax_error()
+{
+    let settings = Settings::new();
+    match format_str("test.sh", "if true\n", "    ", &settings) {
+        Err(err) => assert_eq!(String::from("test.sh: 2.1: unterminated `if` started at 1.1"), format!("{}", err)),
+        Ok(_) => assert!(false),
+    }
+}
+
+#[test]
+fn test_format_str_keeps_here_document_operators_and_bodies()
+{
+    let s = "
+if true; then
+cat <<-EOF
+\tline $x
+\t\tline ${y}
+\tEOF
+fi
+";
+    let expected_s = "
+if true; then
+    cat <<- EOF
+\tline $x
+\t\tline ${y}
+\tEOF
+fi
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_format_str_keeps_comments_in_command_substitutions()
+{
+    let s = "
+x=$(
+  # inner comment
+  echo abc
+) # trailing comment
+echo \"$x\" ${y}
+";
+    let expected_s = "
+x=$(
+  # inner comment
+  echo abc
+) # trailing comment
+echo \"$x\" ${y}
+";
+    assert_eq!(String::from(&expected_s[1..]), format_test_str(&s[1..], 4));
+}
+
+#[test]
+fn test_unified_diff_returns_diff()
+{
+    let old_s = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new_s = "a\nB\nc\nd\ne\nf\ng\nh\ni\nJ\n";
+    let expected_diff = "
+--- old.sh
++++ new.sh
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -7,4 +7,4 @@
+ g
+ h
+ i
+-j
++J
+";
+    assert_eq!(String::from(&expected_diff[1..]), unified_diff("old.sh", "new.sh", old_s, new_s));
+}
+
+#[test]
+fn test_unified_diff_returns_empty_string_for_same_strings()
+{ assert_eq!(String::new(), unified_diff("old.sh", "new.sh", "abc\n", "abc\n")); }
+
+#[test]
+fn test_unified_diff_returns_diff_for_no_newline_at_end_of_file()
+{
+    let expected_diff = "
+--- old.sh
++++ new.sh
+@@ -1,1 +1,1 @@
+-abc
+\\ No newline at end of file
++abc
+";
+    assert_eq!(String::from(&expected_diff[1..]), unified_diff("old.sh", "new.sh", "abc", "abc\n"));
+}
+
+#[test]
+fn test_unified_diff_returns_diff_for_many_lines()
+{
+    let old_lines: Vec<String> = (0..10000).map(|i| format!("{}\n", i)).collect();
+    let mut new_lines = old_lines.clone();
+    new_lines[5000] = String::from("x\n");
+    new_lines.remove(9000);
+    new_lines.insert(100, String::from("y\n"));
+    let expected_diff = "
+--- old.sh
++++ new.sh
+@@ -98,6 +98,7 @@
+ 97
+ 98
+ 99
++y
+ 100
+ 101
+ 102
+@@ -4998,7 +4999,7 @@
+ 4997
+ 4998
+ 4999
+-5000
++x
+ 5001
+ 5002
+ 5003
+@@ -8998,7 +8999,6 @@
+ 8997
+ 8998
+ 8999
+-9000
+ 9001
+ 9002
+ 9003
+";
+    assert_eq!(String::from(&expected_diff[1..]), unified_diff("old.sh", "new.sh", old_lines.concat().as_str(), new_lines.concat().as_str()));
+}
@@ -0,0 +1,688 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::collections::HashSet;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::*;
+use std::os::unix::io::AsRawFd;
+use std::path::*;
+use std::result;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use libc;
+use crate::env::*;
+use crate::utils::*;
+
+const OLD_FILE_HEADER: &'static str = "#V2";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryEntry
+{
+    pub line: String,
+    pub time: u64,
+    pub session_id: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryControl
+{
+    pub ignore_space_flag: bool,
+    pub ignore_dups_flag: bool,
+    pub erase_dups_flag: bool,
+    pub ignore_patterns: Vec<String>,
+}
+
+impl HistoryControl
+{
+    pub fn new() -> HistoryControl
+    {
+        HistoryControl {
+            ignore_space_flag: false,
+            ignore_dups_flag: false,
+            erase_dups_flag: false,
+            ignore_patterns: Vec::new(),
+        }
+    }
+
+    pub fn from_env(env: &Environment) -> HistoryControl
+    {
+        let mut control = HistoryControl::new();
+        match env.var("HISTCONTROL") {
+            Some(histcontrol) => {
+                for value in histcontrol.split(':') {
+                    match value {
+                        "ignorespace" => control.ignore_space_flag = true,
+                        "ignoredups" => control.ignore_dups_flag = true,
+                        "ignoreboth" => {
+                            control.ignore_space_flag = true;
+                            control.ignore_dups_flag = true;
+                        },
+                        "erasedups" => control.erase_dups_flag = true,
+                        _ => (),
+                    }
+                }
+            },
+            None => (),
+        }
+        match env.var("HISTIGNORE") {
+            Some(histignore) => control.ignore_patterns = histignore.split(':').filter(|s| !s.is_empty()).map(String::from).collect(),
+            None => (),
+        }
+        control
+    }
+
+    pub fn is_ignored(&self, line: &str, last_line: Option<&str>) -> bool
+    {
+        if self.ignore_space_flag && line.starts_with([' ', '\t']) {
+            return true;
+        }
+        if self.ignore_dups_flag && last_line == Some(line) {
+            return true;
+        }
+        self.ignore_patterns.iter().any(|pattern| fnmatch(pattern, line, 0))
+    }
+}
+
+pub fn current_time() -> u64
+{
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+fn escape_history_line(line: &str) -> String
+{
+    let s = line.replace('\\', "\\\\").replace('\n', "\\n");
+    if s.starts_with('#') {
+        format!("\\{}", s)
+    } else {
+        s
+    }
+}
+
+fn unescape_history_line(s: &str) -> String
+{
+    let mut line = String::new();
+    let mut chars = s.chars();
+    loop {
+        match chars.next() {
+            Some('\\') => {
+                match chars.next() {
+                    Some('n') => line.push('\n'),
+                    Some(c) => line.push(c),
+                    None => line.push('\\'),
+                }
+            },
+            Some(c) => line.push(c),
+            None => break,
+        }
+    }
+    line
+}
+
+fn parse_time_line(s: &str) -> Option<(u64, String)>
+{
+    let t = s.strip_prefix('#')?;
+    let (time_s, session_id) = match t.split_once(' ') {
+        Some((time_s, session_id)) => (time_s, session_id),
+        None => (t, ""),
+    };
+    if time_s.is_empty() || !time_s.chars().all(|c| c.is_ascii_digit()) || session_id.contains(' ') {
+        return None;
+    }
+    Some((time_s.parse::<u64>().ok()?, String::from(session_id)))
+}
+
+pub fn parse_history_str(s: &str) -> Vec<HistoryEntry>
+{
+    let mut entries: Vec<HistoryEntry> = Vec::new();
+    let mut time_and_session_id: Option<(u64, String)> = None;
+    for (i, line) in s.lines().enumerate() {
+        if line.is_empty() || (i == 0 && line == OLD_FILE_HEADER) {
+            continue;
+        }
+        match parse_time_line(line) {
+            Some(tmp_time_and_session_id) => time_and_session_id = Some(tmp_time_and_session_id),
+            None => {
+                let (time, session_id) = time_and_session_id.take().unwrap_or((0, String::new()));
+                entries.push(HistoryEntry {
+                        line: unescape_history_line(line),
+                        time,
+                        session_id,
+                });
+            },
+        }
+    }
+    entries
+}
+
+pub fn history_entry_to_string(entry: &HistoryEntry) -> String
+{
+    if entry.session_id.is_empty() {
+        format!("#{}\n{}\n", entry.time, escape_history_line(entry.line.as_str()))
+    } else {
+        format!("#{} {}\n{}\n", entry.time, entry.session_id, escape_history_line(entry.line.as_str()))
+    }
+}
+
+pub fn append_history_entry<P: AsRef<Path>>(path: P, entry: &HistoryEntry) -> Result<()>
+{
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    flock(file.as_raw_fd(), libc::LOCK_EX)?;
+    file.write_all(history_entry_to_string(entry).as_bytes())
+}
+
+pub fn erase_dup_history_entries(entries: &mut Vec<HistoryEntry>)
+{
+    let mut lines: HashSet<String> = HashSet::new();
+    let mut new_entries: Vec<HistoryEntry> = Vec::new();
+    for entry in entries.drain(..).rev() {
+        if lines.insert(entry.line.clone()) {
+            new_entries.push(entry);
+        }
+    }
+    new_entries.reverse();
+    *entries = new_entries;
+}
+
+pub fn merge_history_file<P: AsRef<Path>>(path: P, file_size: usize, is_erased_dups: bool) -> Result<()>
+{
+    let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+    flock(file.as_raw_fd(), libc::LOCK_EX)?;
+    let mut s = String::new();
+    file.read_to_string(&mut s)?;
+    let mut entries = parse_history_str(s.as_str());
+    entries.sort_by_key(|entry| entry.time);
+    if is_erased_dups {
+        erase_dup_history_entries(&mut entries);
+    }
+    let start = entries.len().saturating_sub(file_size);
+    let mut new_s = String::new();
+    for entry in &entries[start..] {
+        new_s.push_str(history_entry_to_string(entry).as_str());
+    }
+    file.seek(SeekFrom::Start(0))?;
+    file.set_len(0)?;
+    file.write_all(new_s.as_bytes())
+}
+
+fn split_history_words(line: &str) -> Vec<String>
+{
+    let mut words: Vec<String> = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        match chars.peek() {
+            Some(c) if c.is_whitespace() => {
+                chars.next();
+            },
+            Some(c) if ";&|<>()".contains(*c) => {
+                let c = chars.next().unwrap();
+                let mut word = String::from(c);
+                match chars.peek() {
+                    Some(c2) if (*c2 == c && c != '(' && c != ')') || (c == '>' && *c2 == '|') || ((c == '<' || c == '>') && *c2 == '&') => {
+                        word.push(*c2);
+                        chars.next();
+                    },
+                    _ => (),
+                }
+                words.push(word);
+            },
+            Some(_) => {
+                let mut word = String::new();
+                let mut quote: Option<char> = None;
+                loop {
+                    match (chars.peek().copied(), quote) {
+                        (None, _) => break,
+                        (Some(c), None) if c.is_whitespace() || ";&|<>()".contains(c) => break,
+                        (Some('\\'), q) if q != Some('\'') => {
+                            word.push(chars.next().unwrap());
+                            match chars.next() {
+                                Some(c) => word.push(c),
+                                None => (),
+                            }
+                        },
+                        (Some(c @ ('\'' | '"')), None) => {
+                            word.push(c);
+                            chars.next();
+                            quote = Some(c);
+                        },
+                        (Some(c), Some(q)) if c == q => {
+                            word.push(c);
+                            chars.next();
+                            quote = None;
+                        },
+                        (Some(c), _) => {
+                            word.push(c);
+                            chars.next();
+                        },
+                    }
+                }
+                words.push(word);
+            },
+            None => break,
+        }
+    }
+    words
+}
+
+fn parse_history_number(chars: &[char], i: &mut usize) -> Option<usize>
+{
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    if *i > start {
+        chars[start..*i].iter().collect::<String>().parse::<usize>().ok()
+    } else {
+        None
+    }
+}
+
+fn parse_word_designator(chars: &[char], i: &mut usize, word_count: usize) -> Option<(usize, Option<usize>)>
+{
+    let last = word_count.saturating_sub(1);
+    match chars.get(*i) {
+        Some('^') => {
+            *i += 1;
+            Some((1, Some(1)))
+        },
+        Some('$') => {
+            *i += 1;
+            Some((last, Some(last)))
+        },
+        Some('*') => {
+            *i += 1;
+            Some((1, None))
+        },
+        Some('-') => {
+            *i += 1;
+            match chars.get(*i) {
+                Some('$') => {
+                    *i += 1;
+                    Some((0, Some(last)))
+                },
+                _ => Some((0, Some(parse_history_number(chars, i)?))),
+            }
+        },
+        Some(c) if c.is_ascii_digit() => {
+            let x = parse_history_number(chars, i)?;
+            match chars.get(*i) {
+                Some('*') => {
+                    *i += 1;
+                    Some((x, None))
+                },
+                Some('-') => {
+                    *i += 1;
+                    match chars.get(*i) {
+                        Some('$') => {
+                            *i += 1;
+                            Some((x, Some(last)))
+                        },
+                        Some(c) if c.is_ascii_digit() => Some((x, Some(parse_history_number(chars, i)?))),
+                        _ => Some((x, Some(last.saturating_sub(1)))),
+                    }
+                },
+                _ => Some((x, Some(x))),
+            }
+        },
+        _ => None,
+    }
+}
+
+fn parse_substitution(chars: &[char], i: &mut usize) -> (String, String)
+{
+    let delim = chars[*i];
+    *i += 1;
+    let mut strs: Vec<String> = Vec::new();
+    for _ in 0..2 {
+        let mut t = String::new();
+        while *i < chars.len() && chars[*i] != delim && chars[*i] != '\n' {
+            if chars[*i] == '\\' && *i + 1 < chars.len() && chars[*i + 1] == delim {
+                *i += 1;
+            }
+            t.push(chars[*i]);
+            *i += 1;
+        }
+        if *i < chars.len() && chars[*i] == delim {
+            *i += 1;
+        }
+        strs.push(t);
+    }
+    (strs[0].clone(), strs[1].clone())
+}
+
+fn substitute(s: &str, old: &str, new: &str, is_global: bool) -> Option<String>
+{
+    if old.is_empty() || !s.contains(old) {
+        return None;
+    }
+    let new = new.replace('&', old);
+    if is_global {
+        Some(s.replace(old, new.as_str()))
+    } else {
+        Some(s.replacen(old, new.as_str(), 1))
+    }
+}
+
+fn apply_history_modifiers(chars: &[char], i: &mut usize, mut text: String, last_subst: &mut Option<(String, String)>, is_printed_only: &mut bool) -> result::Result<String, String>
+{
+    while *i + 1 < chars.len() && chars[*i] == ':' {
+        let mut j = *i + 1;
+        let mut is_global = false;
+        if chars[j] == 'g' && j + 1 < chars.len() && (chars[j + 1] == 's' || chars[j + 1] == '&') {
+            is_global = true;
+            j += 1;
+        }
+        match chars[j] {
+            'h' => {
+                match text.rfind('/') {
+                    Some(k) if k > 0 => text.truncate(k),
+                    Some(_) => text.truncate(1),
+                    None => (),
+                }
+                j += 1;
+            },
+            't' => {
+                match text.rfind('/') {
+                    Some(k) => text = String::from(&text[(k + 1)..]),
+                    None => (),
+                }
+                j += 1;
+            },
+            'r' => {
+                match text.rfind('.') {
+                    Some(k) if !text[k..].contains('/') => text.truncate(k),
+                    _ => (),
+                }
+                j += 1;
+            },
+            'e' => {
+                match text.rfind('.') {
+                    Some(k) if !text[k..].contains('/') => text = String::from(&text[k..]),
+                    _ => text = String::new(),
+                }
+                j += 1;
+            },
+            'p' => {
+                *is_printed_only = true;
+                j += 1;
+            },
+            's' if j + 1 < chars.len() => {
+                j += 1;
+                let (mut old, new) = parse_substitution(chars, &mut j);
+                if old.is_empty() {
+                    old = last_subst.as_ref().map(|p| p.0.clone()).unwrap_or_default();
+                }
+                text = match substitute(text.as_str(), old.as_str(), new.as_str(), is_global) {
+                    Some(tmp_text) => tmp_text,
+                    None => return Err(format!(":s{}{}: substitution failed", old, new)),
+                };
+                *last_subst = Some((old, new));
+            },
+            '&' => {
+                let (old, new) = match last_subst {
+                    Some(subst) => subst.clone(),
+                    None => return Err(String::from(":&: no previous substitution")),
+                };
+                text = match substitute(text.as_str(), old.as_str(), new.as_str(), is_global) {
+                    Some(tmp_text) => tmp_text,
+                    None => return Err(format!(":s{}{}: substitution failed", old, new)),
+                };
+                j += 1;
+            },
+            _ => break,
+        }
+        *i = j;
+    }
+    Ok(text)
+}
+
+fn find_history_event(chars: &[char], i: &mut usize, entries: &[HistoryEntry], current: &str) -> result::Result<Option<String>, String>
+{
+    let start = *i;
+    let event = match chars.get(*i) {
+        Some('!') => {
+            *i += 1;
+            entries.last().map(|entry| entry.line.clone())
+        },
+        Some('#') => {
+            *i += 1;
+            Some(String::from(current))
+        },
+        Some(c) if c.is_ascii_digit() => {
+            let n = parse_history_number(chars, i).unwrap_or(0);
+            n.checked_sub(1).and_then(|k| entries.get(k)).map(|entry| entry.line.clone())
+        },
+        Some('-') if chars.get(*i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false) => {
+            *i += 1;
+            let n = parse_history_number(chars, i).unwrap_or(0);
+            entries.len().checked_sub(n).filter(|_| n > 0).and_then(|k| entries.get(k)).map(|entry| entry.line.clone())
+        },
+        Some('^' | '$' | '*' | ':') => entries.last().map(|entry| entry.line.clone()),
+        Some('?') => {
+            *i += 1;
+            let mut t = String::new();
+            while *i < chars.len() && chars[*i] != '?' && chars[*i] != '\n' {
+                t.push(chars[*i]);
+                *i += 1;
+            }
+            if *i < chars.len() && chars[*i] == '?' {
+                *i += 1;
+            }
+            entries.iter().rev().find(|entry| entry.line.contains(t.as_str())).map(|entry| entry.line.clone())
+        },
+        _ => {
+            let mut t = String::new();
+            while *i < chars.len() && !chars[*i].is_whitespace() && !":;&|<>()\"'".contains(chars[*i]) {
+                t.push(chars[*i]);
+                *i += 1;
+            }
+            if t.is_empty() {
+                return Ok(None);
+            }
+            entries.iter().rev().find(|entry| entry.line.starts_with(t.as_str())).map(|entry| entry.line.clone())
+        },
+    };
+    match event {
+        Some(event) => Ok(Some(event)),
+        None => {
+            let spec: String = chars[start..*i].iter().collect();
+            Err(format!("!{}: event not found", spec))
+        },
+    }
+}
+
+pub fn expand_history(line: &str, entries: &[HistoryEntry]) -> result::Result<(String, bool, bool), String>
+{
+    let chars: Vec<char> = line.chars().collect();
+    let mut new_line = String::new();
+    let mut is_expanded = false;
+    let mut is_printed_only = false;
+    let mut last_subst: Option<(String, String)> = None;
+    let mut i: usize = 0;
+    if chars.first() == Some(&'^') {
+        let (old, new) = parse_substitution(chars.as_slice(), &mut i);
+        let event = match entries.last() {
+            Some(entry) => entry.line.clone(),
+            None => return Err(String::from("!!: event not found")),
+        };
+        let mut text = match substitute(event.as_str(), old.as_str(), new.as_str(), false) {
+            Some(tmp_text) => tmp_text,
+            None => return Err(format!(":s^{}^{}: substitution failed", old, new)),
+        };
+        last_subst = Some((old, new));
+        text = apply_history_modifiers(chars.as_slice(), &mut i, text, &mut last_subst, &mut is_printed_only)?;
+        new_line.push_str(text.as_str());
+        is_expanded = true;
+    }
+    let mut quote: Option<char> = None;
+    while i < chars.len() {
+        let c = chars[i];
+        match (c, quote) {
+            ('\\', q) if q != Some('\'') => {
+                new_line.push(c);
+                i += 1;
+                if i < chars.len() {
+                    new_line.push(chars[i]);
+                    i += 1;
+                }
+            },
+            ('\'', None) => {
+                quote = Some('\'');
+                new_line.push(c);
+                i += 1;
+            },
+            ('\'', Some('\'')) => {
+                quote = None;
+                new_line.push(c);
+                i += 1;
+            },
+            ('"', None) => {
+                quote = Some('"');
+                new_line.push(c);
+                i += 1;
+            },
+            ('"', Some('"')) => {
+                quote = None;
+                new_line.push(c);
+                i += 1;
+            },
+            ('!', q) if q != Some('\'') => {
+                match chars.get(i + 1) {
+                    None | Some(' ' | '\t' | '\n' | '=' | '(') => {
+                        new_line.push(c);
+                        i += 1;
+                        continue;
+                    },
+                    Some('"') if q == Some('"') => {
+                        new_line.push(c);
+                        i += 1;
+                        continue;
+                    },
+                    _ => (),
+                }
+                let mut j = i + 1;
+                let event = match find_history_event(chars.as_slice(), &mut j, entries, new_line.as_str())? {
+                    Some(event) => event,
+                    None => {
+                        new_line.push(c);
+                        i += 1;
+                        continue;
+                    },
+                };
+                let mut text = event.clone();
+                let k = j;
+                if j < chars.len() && (chars[j] == ':' || "^$*".contains(chars[j])) {
+                    if chars[j] == ':' {
+                        j += 1;
+                    }
+                    let words = split_history_words(event.as_str());
+                    match parse_word_designator(chars.as_slice(), &mut j, words.len()) {
+                        Some((x, y)) => {
+                            let y = y.unwrap_or(words.len().saturating_sub(1));
+                            if x > y {
+                                if x != 1 || chars[j - 1] != '*' {
+                                    return Err(format!("{}: bad word specifier", chars[i..j].iter().collect::<String>()));
+                                }
+                                text = String::new();
+                            } else if y >= words.len() {
+                                return Err(format!("{}: bad word specifier", chars[i..j].iter().collect::<String>()));
+                            } else {
+                                text = words[x..=y].join(" ");
+                            }
+                        },
+                        None => j = k,
+                    }
+                }
+                text = apply_history_modifiers(chars.as_slice(), &mut j, text, &mut last_subst, &mut is_printed_only)?;
+                new_line.push_str(text.as_str());
+                is_expanded = true;
+                i = j;
+            },
+            (_, _) => {
+                new_line.push(c);
+                i += 1;
+            },
+        }
+    }
+    Ok((new_line, is_expanded, is_printed_only))
+}
+
+pub struct History
+{
+    entries: Vec<HistoryEntry>,
+    max_len: usize,
+    session_id: String,
+}
+
+impl History
+{
+    pub fn new(max_len: usize, session_id: &str) -> History
+    {
+        History {
+            entries: Vec::new(),
+            max_len,
+            session_id: String::from(session_id),
+        }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry]
+    { self.entries.as_slice() }
+
+    pub fn max_len(&self) -> usize
+    { self.max_len }
+
+    fn truncate(&mut self)
+    {
+        let start = self.entries.len().saturating_sub(self.max_len);
+        self.entries.drain(..start);
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<()>
+    {
+        let s = fs::read_to_string(path)?;
+        self.entries.extend(parse_history_str(s.as_str()));
+        self.truncate();
+        Ok(())
+    }
+
+    pub fn add(&mut self, line: &str, time: u64, control: &HistoryControl) -> Option<HistoryEntry>
+    {
+        if line.is_empty() || self.max_len == 0 {
+            return None;
+        }
+        if control.is_ignored(line, self.entries.last().map(|entry| entry.line.as_str())) {
+            return None;
+        }
+        if control.erase_dups_flag {
+            self.entries.retain(|entry| entry.line != line);
+        }
+        let entry = HistoryEntry {
+            line: String::from(line),
+            time,
+            session_id: self.session_id.clone(),
+        };
+        self.entries.push(entry.clone());
+        self.truncate();
+        Some(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,221 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+use crate::settings::*;
+use crate::test_helpers::*;
+use sealed_test::prelude::*;
+
+#[test]
+fn test_parse_history_str_parses_entries_with_timestamps_and_session_ids()
+{
+    let s = "
+#1700000000 123
+echo abc
+#1700000001
+ls -l
+cat\\nfile \\\\ abc
+";
+    let entries = parse_history_str(&s[1..]);
+    assert_eq!(3, entries.len());
+    assert_eq!(HistoryEntry { line: String::from("echo abc"), time: 1700000000, session_id: String::from("123"), }, entries[0]);
+    assert_eq!(HistoryEntry { line: String::from("ls -l"), time: 1700000001, session_id: String::new(), }, entries[1]);
+    assert_eq!(HistoryEntry { line: String::from("cat\nfile \\ abc"), time: 0, session_id: String::new(), }, entries[2]);
+}
+
+#[test]
+fn test_parse_history_str_parses_old_history_file()
+{
+    let s = "
+#V2
+echo abc
+# comment
+";
+    let entries = parse_history_str(&s[1..]);
+    assert_eq!(2, entries.len());
+    assert_eq!(String::from("echo abc"), entries[0].line);
+    assert_eq!(String::from("# comment"), entries[1].line);
+}
+
+#[test]
+fn test_history_entry_to_string_converts_entry_to_string()
+{
+    let entry = HistoryEntry { line: String::from("echo \\\nabc"), time: 1700000000, session_id: String::from("123"), };
+    assert_eq!(String::from("#1700000000 123\necho \\\\\\nabc\n"), history_entry_to_string(&entry));
+    assert_eq!(vec![entry.clone()], parse_history_str(history_entry_to_string(&entry).as_str()));
+}
+
+#[test]
+fn test_history_entry_to_string_escapes_line_with_number_sign()
+{
+    let entry = HistoryEntry { line: String::from("#123"), time: 1700000000, session_id: String::new(), };
+    assert_eq!(String::from("#1700000000\n\\#123\n"), history_entry_to_string(&entry));
+    assert_eq!(vec![entry.clone()], parse_history_str(history_entry_to_string(&entry).as_str()));
+    let entry2 = HistoryEntry { line: String::from("\\#123"), time: 1700000001, session_id: String::new(), };
+    assert_eq!(vec![entry2.clone()], parse_history_str(history_entry_to_string(&entry2).as_str()));
+}
+
+#[test]
+fn test_erase_dup_history_entries_keeps_last_entries()
+{
+    let mut entries: Vec<HistoryEntry> = ["echo abc", "echo def", "echo abc", "ls", "echo def"].iter().enumerate().map(|(i, line)| {
+            HistoryEntry { line: String::from(*line), time: i as u64, session_id: String::new(), }
+    }).collect();
+    erase_dup_history_entries(&mut entries);
+    let lines_and_times: Vec<(&str, u64)> = entries.iter().map(|entry| (entry.line.as_str(), entry.time)).collect();
+    assert_eq!(vec![("echo abc", 2), ("ls", 3), ("echo def", 4)], lines_and_times);
+}
+
+#[test]
+fn test_history_add_adds_entries_for_history_control()
+{
+    let mut control = HistoryControl::new();
+    control.ignore_space_flag = true;
+    control.ignore_dups_flag = true;
+    control.ignore_patterns = vec![String::from("ls"), String::from("cd *")];
+    let mut history = History::new(3, "123");
+    assert!(history.add("echo abc", 1, &control).is_some());
+    assert!(history.add("echo abc", 2, &control).is_none());
+    assert!(history.add(" echo def", 3, &control).is_none());
+    assert!(history.add("ls", 4, &control).is_none());
+    assert!(history.add("cd /tmp", 5, &control).is_none());
+    assert!(history.add("ls -l", 6, &control).is_some());
+    assert!(history.add("echo def", 7, &control).is_some());
+    assert!(history.add("echo ghi", 8, &control).is_some());
+    let lines: Vec<&str> = history.entries().iter().map(|entry| entry.line.as_str()).collect();
+    assert_eq!(vec!["ls -l", "echo def", "echo ghi"], lines);
+    assert_eq!(String::from("123"), history.entries()[0].session_id);
+    assert_eq!(6, history.entries()[0].time);
+}
+
+#[test]
+fn test_history_add_erases_duplicates()
+{
+    let mut control = HistoryControl::new();
+    control.erase_dups_flag = true;
+    let mut history = History::new(10, "123");
+    history.add("echo abc", 1, &control);
+    history.add("echo def", 2, &control);
+    history.add("echo abc", 3, &control);
+    let lines: Vec<&str> = history.entries().iter().map(|entry| entry.line.as_str()).collect();
+    assert_eq!(vec!["echo def", "echo abc"], lines);
+}
+
+#[test]
+fn test_history_control_from_env_reads_variables()
+{
+    let settings = Settings::new();
+    let mut env = Environment::new();
+    env.set_var("HISTCONTROL", "ignoreboth:erasedups", &settings);
+    env.set_var("HISTIGNORE", "ls:exit", &settings);
+    let control = HistoryControl::from_env(&env);
+    assert_eq!(true, control.ignore_space_flag);
+    assert_eq!(true, control.ignore_dups_flag);
+    assert_eq!(true, control.erase_dups_flag);
+    assert_eq!(vec![String::from("ls"), String::from("exit")], control.ignore_patterns);
+}
+
+#[sealed_test]
+fn test_merge_history_file_merges_entries_from_sessions()
+{
+    let entry1 = HistoryEntry { line: String::from("echo abc"), time: 1, session_id: String::from("1"), };
+    let entry2 = HistoryEntry { line: String::from("echo def"), time: 3, session_id: String::from("2"), };
+    let entry3 = HistoryEntry { line: String::from("echo abc"), time: 4, session_id: String::from("2"), };
+    let entry4 = HistoryEntry { line: String::from("echo ghi"), time: 2, session_id: String::from("1"), };
+    write_file("history", "#V2\necho old\n");
+    append_history_entry("history", &entry1).unwrap();
+    append_history_entry("history", &entry2).unwrap();
+    append_history_entry("history", &entry3).unwrap();
+    append_history_entry("history", &entry4).unwrap();
+    merge_history_file("history", 3, true).unwrap();
+    let expected_content = "
+#2 1
+echo ghi
+#3 2
+echo def
+#4 2
+echo abc
+";
+    assert_eq!(String::from(&expected_content[1..]), read_file("history"));
+    let mut history = History::new(2, "3");
+    history.load("history").unwrap();
+    assert_eq!(vec![entry2, entry3], history.entries().to_vec());
+}
+
+fn history_entries(lines: &[&str]) -> Vec<HistoryEntry>
+{ lines.iter().map(|line| HistoryEntry { line: String::from(*line), time: 0, session_id: String::new(), }).collect() }
+
+#[test]
+fn test_expand_history_expands_event_designators()
+{
+    let entries = history_entries(&["ls -l /tmp", "echo abc def", "cat file.txt"]);
+    assert_eq!(Ok((String::from("sudo cat file.txt"), true, false)), expand_history("sudo !!", entries.as_slice()));
+    assert_eq!(Ok((String::from("ls -l /tmp"), true, false)), expand_history("!1", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo abc def"), true, false)), expand_history("!-2", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo abc def"), true, false)), expand_history("!ec", entries.as_slice()));
+    assert_eq!(Ok((String::from("ls -l /tmp"), true, false)), expand_history("!?-l?", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo a echo a "), true, false)), expand_history("echo a !#", entries.as_slice()));
+    assert_eq!(Err(String::from("!xyz: event not found")), expand_history("!xyz", entries.as_slice()));
+    assert_eq!(Err(String::from("!9: event not found")), expand_history("!9", entries.as_slice()));
+}
+
+#[test]
+fn test_expand_history_expands_word_designators()
+{
+    let entries = history_entries(&["cp 'a b.txt' dir/c.txt && echo ok"]);
+    assert_eq!(Ok((String::from("echo ok"), true, false)), expand_history("echo !$", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo cp"), true, false)), expand_history("echo !!:0", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo 'a b.txt'"), true, false)), expand_history("echo !^", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo 'a b.txt' dir/c.txt && echo ok"), true, false)), expand_history("echo !*", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo 'a b.txt' dir/c.txt"), true, false)), expand_history("echo !!:1-2", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo dir/c.txt && echo"), true, false)), expand_history("echo !!:2-", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo cp 'a b.txt'"), true, false)), expand_history("echo !!:-1", entries.as_slice()));
+    assert_eq!(Err(String::from("!!:9: bad word specifier")), expand_history("echo !!:9", entries.as_slice()));
+}
+
+#[test]
+fn test_expand_history_expands_modifiers()
+{
+    let entries = history_entries(&["tar xf /tmp/dir/archive.tar.gz"]);
+    assert_eq!(Ok((String::from("cd /tmp/dir"), true, false)), expand_history("cd !$:h", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo archive.tar.gz"), true, false)), expand_history("echo !$:t", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo /tmp/dir/archive.tar"), true, false)), expand_history("echo !$:r", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo .gz"), true, false)), expand_history("echo !$:e", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo archive"), true, false)), expand_history("echo !$:t:r:r", entries.as_slice()));
+    assert_eq!(Ok((String::from("tar tf /tmp/dir/archive.tar.gz"), true, false)), expand_history("!!:s/xf/tf/", entries.as_slice()));
+    assert_eq!(Ok((String::from("tar xf /var/dir/archive.var.gz"), true, false)), expand_history("!!:gs/tmp/var/:s/tar./var./", entries.as_slice()));
+    assert_eq!(Ok((String::from("tar xf /tmp/dir/archive.tar.gz"), true, true)), expand_history("!!:p", entries.as_slice()));
+    assert_eq!(Err(String::from(":sxyzabc: substitution failed")), expand_history("!!:s/xyz/abc/", entries.as_slice()));
+}
+
+#[test]
+fn test_expand_history_expands_quick_substitution()
+{
+    let entries = history_entries(&["ehco abc", "gti status"]);
+    assert_eq!(Ok((String::from("git status"), true, false)), expand_history("^gti^git", entries.as_slice()));
+    assert_eq!(Ok((String::from("git status -s"), true, false)), expand_history("^gti^git^ -s", entries.as_slice()));
+    assert_eq!(Err(String::from(":s^xyz^abc: substitution failed")), expand_history("^xyz^abc", entries.as_slice()));
+}
+
+#[test]
+fn test_expand_history_does_not_expand_quoted_exclamation_marks()
+{
+    let entries = history_entries(&["echo abc"]);
+    assert_eq!(Ok((String::from("echo 'abc!!' \\!! abc! ! a!=b"), false, false)), expand_history("echo 'abc!!' \\!! abc! ! a!=b", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo \"echo abc\""), true, false)), expand_history("echo \"!!\"", entries.as_slice()));
+    assert_eq!(Ok((String::from("echo \"abc!\""), false, false)), expand_history("echo \"abc!\"", entries.as_slice()));
+}
@@ -23,24 +23,48 @@ use std::io::*;
 use std::path;
 use std::rc::*;
 use std::slice;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use libc;
 use crate::env::*;
 use crate::exec::*;
 use crate::io::*;
+use crate::json::*;
 use crate::lexer::*;
 use crate::parser::*;
 use crate::settings::*;
 use crate::signals::*;
 use crate::utils::*;
 use crate::xcfprintln;
-use crate::xsfprint;
 use crate::xsfprintln;
 
 pub const DEFAULT_IFS: &'static str = " \t\n";
 pub const DEFAULT_PS4: &'static str = "+ ";
 
+pub const ERR_SIG: i32 = -1;
+pub const DEBUG_SIG: i32 = -2;
+pub const RETURN_SIG: i32 = -3;
+
 const MAX_PARAM_EXPR_COUNT: u32 = 16;
 
+const FORK_FREE_BUILTIN_FUN_NAMES: &[&str] = &[
+    ":",
+    "alias",
+    "caller",
+    "compgen",
+    "complete",
+    "dirs",
+    "export",
+    "getopts",
+    "readonly",
+    "set",
+    "shift",
+    "times",
+    "unalias",
+    "unset",
+];
+
 #[derive(Clone, Debug)]
 pub enum Value
 {
@@ -73,6 +97,15 @@ enum ReturnState
     Exit(bool),
 }
 
+#[derive(Clone)]
+pub struct CallFrame
+{
+    pub name: String,
+    pub source_path: String,
+    pub call_path: String,
+    pub call_pos: Position,
+}
+
 #[derive(Clone)]
 enum InterpreterRedirection
 {
@@ -93,6 +126,9 @@ pub struct Interpreter
     loop_count_stack: Vec<usize>,
     current_loop_count: usize,
     fun_count: usize,
+    call_stack: Vec<CallFrame>,
+    current_path: String,
+    current_pos: Position,
     last_job_pid: Option<i32>,
     signal_names: HashMap<i32, String>,
     special_builtin_fun_names: HashSet<String>,
@@ -120,41 +156,191 @@ fn set_vars(exec: &Executor, vars: &[(String, String)], env: &mut Environment, s
     0
 }
 
+fn xtrace_fd(env: &Environment) -> i32
+{
+    match env.var("RSUSH_XTRACEFD") {
+        Some(s) => s.parse::<i32>().ok().filter(|fd| *fd >= 0).unwrap_or(2),
+        None => 2,
+    }
+}
+
+fn print_xtrace_line(exec: &Executor, line: &str, env: &Environment)
+{
+    let fd = xtrace_fd(env);
+    if exec.saved_file(fd).is_some() {
+        xsfprintln!(exec, fd, "{}", line);
+    } else {
+        xcfprintln!(exec, fd, "{}", line);
+    }
+}
+
 fn print_command_for_xtrace_or_extxtrace(exec: &Executor, path_and_pos: Option<(&str, &Position)>, vars: &[(String, String)], args: &[String], env: &Environment)
 {
-    let ps4 = env.var("PS4").unwrap_or(String::from(DEFAULT_PS4));
-    xsfprint!(exec, 2, "{}", ps4);
+    let mut line = env.var("PS4").unwrap_or(String::from(DEFAULT_PS4));
     match path_and_pos {
-        Some((path, pos)) => xsfprint!(exec, 2, "{}: {}: ", path, pos),
+        Some((path, pos)) => line.push_str(format!("{}: {}: ", path, pos).as_str()),
         None => (),
     }
     let mut is_first = true;
     for (name, value) in vars.iter() {
-        if is_first {
-            xsfprint!(exec, 2, "{}={}", name, value);
-        } else {
-            xsfprint!(exec, 2, " {}={}", name, value);
+        if !is_first {
+            line.push(' ');
         }
+        line.push_str(format!("{}={}", name, value).as_str());
         is_first = false;
     }
     for arg in args.iter() {
-        if is_first {
-            xsfprint!(exec, 2, "{}", arg);
-        } else  {
-            xsfprint!(exec, 2, " {}", arg);
+        if !is_first {
+            line.push(' ');
         }
+        line.push_str(arg.as_str());
         is_first = false;
     }
-    xsfprintln!(exec, 2, "");
+    print_xtrace_line(exec, line.as_str(), env);
+}
+
+struct CommandTrace
+{
+    path: String,
+    pos: Position,
+    vars: Vec<(String, String)>,
+    args: Vec<String>,
+    time: SystemTime,
+    instant: Instant,
+}
+
+fn is_json_xtrace(env: &Environment, settings: &Settings) -> bool
+{ (settings.xtrace_flag || settings.extxtrace_flag) && env.var("RSUSH_XTRACEFORMAT") == Some(String::from("json")) }
+
+fn trace_command(exec: &Executor, path: &str, pos: &Position, vars: &[(String, String)], args: &[String], env: &Environment, settings: &Settings) -> Option<CommandTrace>
+{
+    if settings.xtrace_flag || settings.extxtrace_flag {
+        if is_json_xtrace(env, settings) {
+            return Some(CommandTrace {
+                    path: String::from(path),
+                    pos: *pos,
+                    vars: vars.to_vec(),
+                    args: args.to_vec(),
+                    time: SystemTime::now(),
+                    instant: Instant::now(),
+            });
+        } else if settings.xtrace_flag {
+            print_command_for_xtrace_or_extxtrace(exec, None, vars, args, env);
+        } else {
+            print_command_for_xtrace_or_extxtrace(exec, Some((path, pos)), vars, args, env);
+        }
+    }
+    None
+}
+
+fn print_command_trace_for_json(exec: &Executor, trace: &CommandTrace, status: i32, env: &Environment)
+{
+    let timestamp = trace.time.duration_since(UNIX_EPOCH).map(|d| d.as_secs_f64()).unwrap_or(0.0);
+    let duration = trace.instant.elapsed().as_secs_f64();
+    let assignments: Vec<(String, JsonValue)> = trace.vars.iter().map(|(name, value)| (name.clone(), JsonValue::from_str(value.as_str()))).collect();
+    let value = JsonValue::Object(vec![
+            (String::from("timestamp"), JsonValue::Number(timestamp)),
+            (String::from("path"), JsonValue::from_str(trace.path.as_str())),
+            (String::from("line"), JsonValue::Integer(trace.pos.line as i64)),
+            (String::from("column"), JsonValue::Integer(trace.pos.column as i64)),
+            (String::from("argv"), JsonValue::from_strs(trace.args.as_slice())),
+            (String::from("assignments"), JsonValue::Object(assignments)),
+            (String::from("status"), JsonValue::Integer(status as i64)),
+            (String::from("duration"), JsonValue::Number(duration))
+    ]);
+    print_xtrace_line(exec, format!("{}", value).as_str(), env);
+}
+
+fn is_fork_free_simple_command(command: &SimpleCommand, env: &Environment, fun_names: &mut HashSet<String>) -> bool
+{
+    let mut word_iter = command.words.iter().skip_while(|word| assignment_name(word).is_some());
+    let name = match word_iter.next() {
+        Some(word) => {
+            match literal_word_str(word) {
+                Some(tmp_name) => tmp_name,
+                None => return false,
+            }
+        },
+        None => return true,
+    };
+    if env.alias(name.as_str()).is_some() {
+        false
+    } else if env.closure_builtin_fun(name.as_str()).is_some() {
+        env.fork_free_closure_builtin_fun_flag(name.as_str())
+    } else if env.builtin_fun(name.as_str()).is_some() {
+        if name == "command" {
+            match word_iter.next().and_then(|word| literal_word_str(word)) {
+                Some(opt) => opt == "-v" || opt == "-V",
+                None => false,
+            }
+        } else {
+            FORK_FREE_BUILTIN_FUN_NAMES.contains(&name.as_str())
+        }
+    } else {
+        match env.fun(name.as_str()) {
+            Some(fun_body) => {
+                if fun_names.contains(&name) {
+                    return true;
+                }
+                fun_names.insert(name);
+                is_fork_free_compound_command(&fun_body.command, env, fun_names)
+            },
+            None => false,
+        }
+    }
+}
+
+fn is_fork_free_compound_command(command: &CompoundCommand, env: &Environment, fun_names: &mut HashSet<String>) -> bool
+{
+    match command {
+        CompoundCommand::BraceGroup(commands) => is_fork_free_logical_commands(commands.as_slice(), env, fun_names),
+        CompoundCommand::Subshell(_) => false,
+        CompoundCommand::For(_, _, commands) => is_fork_free_logical_commands(commands.as_slice(), env, fun_names),
+        CompoundCommand::Case(_, pairs) => pairs.iter().all(|pair| is_fork_free_logical_commands(pair.commands.as_slice(), env, fun_names)),
+        CompoundCommand::If(cond_commands, commands, elif_pairs, else_commands) => {
+            is_fork_free_logical_commands(cond_commands.as_slice(), env, fun_names) &&
+            is_fork_free_logical_commands(commands.as_slice(), env, fun_names) &&
+            elif_pairs.iter().all(|pair| is_fork_free_logical_commands(pair.cond_commands.as_slice(), env, fun_names) && is_fork_free_logical_commands(pair.commands.as_slice(), env, fun_names)) &&
+            else_commands.as_ref().map(|tmp_commands| is_fork_free_logical_commands(tmp_commands.as_slice(), env, fun_names)).unwrap_or(true)
+        },
+        CompoundCommand::While(cond_commands, commands) | CompoundCommand::Until(cond_commands, commands) => {
+            is_fork_free_logical_commands(cond_commands.as_slice(), env, fun_names) &&
+            is_fork_free_logical_commands(commands.as_slice(), env, fun_names)
+        },
+    }
+}
+
+fn is_fork_free_command(command: &Command, env: &Environment, fun_names: &mut HashSet<String>) -> bool
+{
+    match command {
+        Command::Simple(_, _, _, simple_command) => is_fork_free_simple_command(simple_command, env, fun_names),
+        Command::Compound(_, _, _, compound_command, _) => is_fork_free_compound_command(compound_command, env, fun_names),
+        Command::FunctionDefinition(_, _, _, _, _) => false,
+    }
 }
 
-fn add_job_for_sigtstp<F>(exec: &mut Executor, last_pid: i32, name_f: F) -> bool
+fn is_fork_free_logical_commands(commands: &[Rc<LogicalCommand>], env: &Environment, fun_names: &mut HashSet<String>) -> bool
+{
+    commands.iter().all(|command| {
+            if command.is_in_background {
+                return false;
+            }
+            let mut pipe_commands = vec![&command.first_command];
+            pipe_commands.extend(command.pairs.iter().map(|pair| &pair.command));
+            pipe_commands.iter().all(|pipe_command| {
+                    pipe_command.commands.len() == 1 && is_fork_free_command(&pipe_command.commands[0], env, fun_names)
+            })
+    })
+}
+
+fn add_job_for_sigtstp<F>(exec: &mut Executor, last_pid: i32, termios: Option<libc::termios>, name_f: F) -> bool
     where F: FnOnce() -> String
 {
     match exec.add_job(&Job::new(last_pid, name_f().as_str())) {
         Some(job_id) => {
             exec.set_job_last_status(job_id, WaitStatus::Stopped(libc::SIGTSTP));
             exec.set_job_show_flag(job_id, true);
+            exec.set_job_termios(job_id, termios);
             true
         },
         None => {
@@ -164,7 +350,7 @@ fn add_job_for_sigtstp<F>(exec: &mut Executor, last_pid: i32, name_f: F) -> bool
     }
 }
 
-fn add_job_with_pids_for_sigtstp<F>(exec: &mut Executor, pids: &[i32], last_pid: i32, pgid: i32, name_f: F) -> bool
+fn add_job_with_pids_for_sigtstp<F>(exec: &mut Executor, pids: &[i32], last_pid: i32, pgid: i32, termios: Option<libc::termios>, name_f: F) -> bool
     where F: FnOnce(usize) -> (Vec<String>, String, String)
 {
     let mut tmp_pids: Vec<i32> = Vec::new();
@@ -175,6 +361,7 @@ fn add_job_with_pids_for_sigtstp<F>(exec: &mut Executor, pids: &[i32], last_pid:
             exec.set_job_statuses(job_id, vec![WaitStatus::Stopped(libc::SIGTSTP); pids.len()]);
             exec.set_job_last_status(job_id, WaitStatus::Stopped(libc::SIGTSTP));
             exec.set_job_show_flag(job_id, true);
+            exec.set_job_termios(job_id, termios);
             true
         },
         None => {
@@ -244,6 +431,7 @@ impl Interpreter
         special_builtin_fun_names.insert(String::from("readonly"));
         special_builtin_fun_names.insert(String::from("return"));
         special_builtin_fun_names.insert(String::from("set"));
+        special_builtin_fun_names.insert(String::from("source"));
         special_builtin_fun_names.insert(String::from("times"));
         special_builtin_fun_names.insert(String::from("trap"));
         special_builtin_fun_names.insert(String::from("unset"));
@@ -255,6 +443,9 @@ impl Interpreter
             loop_count_stack: Vec::new(),
             current_loop_count: 0,
             fun_count: 0,
+            call_stack: Vec::new(),
+            current_path: String::new(),
+            current_pos: Position::new(0, 0),
             last_job_pid: None,
             signal_names: sig_names,
             special_builtin_fun_names,
@@ -272,15 +463,19 @@ impl Interpreter
     pub fn has_none(&self) -> bool
     { self.return_state == ReturnState::None }
 
+    #[cfg(test)]
     pub fn has_break_with(&self, n: usize) -> bool
     { self.return_state == ReturnState::Break(n) }
 
+    #[cfg(test)]
     pub fn has_continue_with(&self, n: usize) -> bool
     { self.return_state == ReturnState::Continue(n) }
     
+    #[cfg(test)]
     pub fn has_return(&self) -> bool
     { self.return_state == ReturnState::Return }
     
+    #[cfg(test)]
     pub fn has_exit_with(&self, is_interactive: bool) -> bool
     { self.return_state == ReturnState::Exit(is_interactive) }
 
@@ -364,12 +559,15 @@ impl Interpreter
         0
     }
 
+    #[allow(dead_code)]
     pub fn set_break(&mut self, n: usize)
     { self.return_state = ReturnState::Break(n); }
 
+    #[allow(dead_code)]
     pub fn set_continue(&mut self, n: usize)
     { self.return_state = ReturnState::Continue(n); }
     
+    #[allow(dead_code)]
     pub fn set_return(&mut self)
     { self.return_state = ReturnState::Return; }
     
@@ -388,6 +586,7 @@ impl Interpreter
         }
     }
     
+    #[cfg(test)]
     pub fn exec_redirect_flag(&self) -> bool
     { self.exec_redirect_flag }
     
@@ -397,21 +596,49 @@ impl Interpreter
     pub fn is_in_loop(&self) -> bool
     { self.current_loop_count > 0 }
     
+    #[cfg(test)]
     pub fn increase_current_loop_count(&mut self) 
     { self.current_loop_count += 1; }
 
+    #[cfg(test)]
     pub fn decrease_current_loop_count(&mut self) 
     { self.current_loop_count -= 1; }
     
     pub fn is_in_fun(&self) -> bool
     { self.fun_count > 0 }
     
+    #[cfg(test)]
     pub fn increase_fun_count(&mut self) 
     { self.fun_count += 1; }
 
+    #[cfg(test)]
     pub fn decrease_fun_count(&mut self) 
     { self.fun_count -= 1; }
 
+    pub fn call_stack(&self) -> &[CallFrame]
+    { self.call_stack.as_slice() }
+
+    pub fn push_call_frame(&mut self, name: &str, source_path: &str)
+    {
+        let frame = CallFrame {
+            name: String::from(name),
+            source_path: String::from(source_path),
+            call_path: self.current_path.clone(),
+            call_pos: self.current_pos,
+        };
+        self.call_stack.push(frame);
+    }
+
+    pub fn pop_call_frame(&mut self)
+    { self.call_stack.pop(); }
+
+    fn print_call_stack(&self, exec: &Executor)
+    {
+        for frame in self.call_stack.iter().rev() {
+            xsfprintln!(exec, 2, "    at {} ({}) called from {}: {}", frame.name, frame.source_path, frame.call_path, frame.call_pos);
+        }
+    }
+
     fn push_loop_count(&mut self, count: usize)
     {
         self.loop_count_stack.push(self.current_loop_count);
@@ -440,7 +667,13 @@ impl Interpreter
     }
 
     pub fn has_special_builtin_fun(&self, name: &str, env: &Environment) -> bool
-    { self.special_builtin_fun_names.contains(&String::from(name)) && env.builtin_fun(name).is_some() }
+    { self.special_builtin_fun_names.contains(&String::from(name)) && env.has_builtin_fun(name) }
+
+    pub fn add_special_builtin_fun_name(&mut self, name: &str)
+    { self.special_builtin_fun_names.insert(String::from(name)); }
+
+    pub fn remove_special_builtin_fun_name(&mut self, name: &str)
+    { self.special_builtin_fun_names.remove(&String::from(name)); }
     
     pub fn set_action_flag(&mut self)
     { self.action_flag = true; }
@@ -448,6 +681,9 @@ impl Interpreter
     pub fn actions(&self) -> &HashMap<i32, String>
     { &self.actions }
     
+    pub fn has_work_after_command(&self, env: &Environment, settings: &Settings) -> bool
+    { !self.actions.is_empty() || is_json_xtrace(env, settings) || (settings.errexit_flag && settings.stacktrace_flag) }
+    
     pub fn set_action(&mut self, sig: i32, action: String)
     { self.actions.insert(sig, action); }
 
@@ -458,6 +694,7 @@ impl Interpreter
         where F: FnOnce() -> String
     {
         let mut job_pid: Option<i32> = None;
+        let mut job_termios: Option<libc::termios> = None;
         let res = match exec.execute(self, vars, arg0, args, true, env, settings, |sig| sig == libc::SIGTSTP) {
             Ok((WaitStatus::None, _)) => panic!("wait status is none"),
             Ok((WaitStatus::Exited(status), _)) => Some(status),
@@ -470,6 +707,7 @@ impl Interpreter
                 Some(sig + 128)
             },
             Ok((WaitStatus::Stopped(sig @ libc::SIGTSTP), pid)) => {
+                job_termios = exec.terminal_termios(settings);
                 exec.set_foreground_for_shell(settings);
                 job_pid = pid;
                 if is_exit_for_err {
@@ -487,7 +725,7 @@ impl Interpreter
         };
         match job_pid {
             Some(job_pid) => {
-                add_job_for_sigtstp(exec, job_pid, name_f);
+                add_job_for_sigtstp(exec, job_pid, job_termios, name_f);
             },
             None => (),
         }
@@ -499,6 +737,7 @@ impl Interpreter
               G: FnOnce(&mut Executor, WaitStatus)
     {
         let mut job_pid: Option<i32> = None;
+        let mut job_termios: Option<libc::termios> = None;
         let res = loop {
             match exec.wait_for_process(pid, true, is_untraced, true, settings) {
                 Ok(WaitStatus::None) => panic!("wait status is none"),
@@ -516,6 +755,7 @@ impl Interpreter
                     break Some(sig + 128);
                 },
                 Ok(wait_status @ WaitStatus::Stopped(sig @ libc::SIGTSTP)) => {
+                    job_termios = exec.terminal_termios(settings);
                     exec.set_foreground_for_shell(settings);
                     job_pid = pid;
                     g(exec, wait_status);
@@ -535,7 +775,7 @@ impl Interpreter
         };
         match job_pid {
             Some(job_pid) => {
-                add_job_for_sigtstp(exec, job_pid, name_f);
+                add_job_for_sigtstp(exec, job_pid, job_termios, name_f);
             },
             None => (),
         }
@@ -546,6 +786,7 @@ impl Interpreter
         where F: FnOnce(usize) -> (Vec<String>, String, String)
     {
         let mut job_pids: Vec<i32> = Vec::new();
+        let mut job_termios: Option<libc::termios> = None;
         let mut res: Option<i32> = None;
         let mut is_success_for_first_processes = true;
         for (i, pid) in pids.iter().enumerate() {
@@ -563,6 +804,7 @@ impl Interpreter
                     },
                     Ok(WaitStatus::Stopped(sig @ libc::SIGTSTP)) => {
                         if i == pids.len() - 1 {
+                            job_termios = exec.terminal_termios(settings);
                             exec.set_foreground_for_shell(settings);
                         }
                         match pid {
@@ -594,7 +836,7 @@ impl Interpreter
             Some(last_job_pid) => {
                 match pgid {
                     Some(pgid) => {
-                        add_job_with_pids_for_sigtstp(exec, &job_pids[..(job_pids.len() - 1)], *last_job_pid, pgid, name_f);
+                        add_job_with_pids_for_sigtstp(exec, &job_pids[..(job_pids.len() - 1)], *last_job_pid, pgid, job_termios, name_f);
                     },
                     None => (),
                 }
@@ -946,8 +1188,59 @@ impl Interpreter
         }
     }
     
+    fn interpret_in_process<F>(&mut self, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, f: F) -> i32
+        where F: FnOnce(&mut Self, &mut Executor, &mut Environment, &mut Settings) -> i32
+    {
+        let env_snapshot = env.snapshot();
+        let saved_settings = settings.clone();
+        let saved_path = self.current_path.clone();
+        let saved_pos = self.current_pos;
+        let status = exec.interpret(|exec| f(self, exec, env, settings));
+        if self.has_break_or_continue_or_return_or_exit() {
+            self.clear_return_state();
+        }
+        self.current_path = saved_path;
+        self.current_pos = saved_pos;
+        *settings = saved_settings;
+        env.restore(env_snapshot);
+        status
+    }
+
+    fn substitute_command_in_process(&mut self, exec: &mut Executor, commands: &[Rc<LogicalCommand>], env: &mut Environment, settings: &mut Settings) -> Option<String>
+    {
+        let file = match create_capture_file() {
+            Ok(tmp_file) => Rc::new(RefCell::new(tmp_file)),
+            Err(err) => {
+                xsfprintln!(exec, 2, "{}", err);
+                self.set_exit(false);
+                return None;
+            },
+        };
+        exec.push_file(1, file.clone());
+        self.push_loop_count(0);
+        let status = self.interpret_in_process(exec, env, settings, |interp, exec, env, settings| {
+                interp.interpret_logical_commands(exec, commands, env, settings)
+        });
+        self.pop_loop_count();
+        exec.pop_file(1);
+        self.last_status = status;
+        let mut s = String::new();
+        let mut file_r = file.borrow_mut();
+        match file_r.seek(SeekFrom::Start(0)).and_then(|_| file_r.read_to_string(&mut s)) {
+            Ok(_) => Some(String::from(s.trim_end_matches('\n'))),
+            Err(err) => {
+                xsfprintln!(exec, 2, "{}", err);
+                self.set_exit(false);
+                None
+            },
+        }
+    }
+
     fn substitute_command(&mut self, exec: &mut Executor, commands: &[Rc<LogicalCommand>], env: &mut Environment, settings: &mut Settings) -> Option<String>
     {
+        if is_fork_free_logical_commands(commands, env, &mut HashSet::new()) {
+            return self.substitute_command_in_process(exec, commands, env, settings);
+        }
         exec.interpret(|exec| {
                 let mut pipes: Vec<Pipe> = Vec::new();
                 let mut is_success = true;
@@ -966,9 +1259,7 @@ impl Interpreter
                             exec.push_file(1, exec.pipes()[0].writing_file.clone());
                             exec.clear_pipes();
                             self.push_loop_count(0);
-                            let status = exec.interpret_or(commands.len() > 1, |exec| {
-                                    self.interpret_logical_commands(exec, commands, env, settings)
-                            });
+                            let status = self.interpret_logical_commands(exec, commands, env, settings);
                             self.pop_loop_count();
                             status
                     });
@@ -1011,7 +1302,7 @@ impl Interpreter
     fn assign_to_arith_expr(&mut self, exec: &Executor, expr: &ArithmeticExpression, x: i64, env: &mut Environment, settings: &Settings) -> Option<i64>
     {
         match expr {
-            ArithmeticExpression::Parameter(_, _, param_name) => {
+            ArithmeticExpression::Parameter(_, _, _, param_name) => {
                 if set_param(param_name, format!("{}", x).as_str(), env, settings) {
                     Some(x)
                 } else {
@@ -1035,8 +1326,8 @@ impl Interpreter
     fn evaluate_arith_expr(&mut self, exec: &Executor, expr: &ArithmeticExpression, param_expr_count: u32, env: &mut Environment, settings: &Settings) -> Option<i64>
     {
         match expr {
-            ArithmeticExpression::Number(_, _, x) => Some(*x),
-            ArithmeticExpression::Parameter(_, _, param_name) => {
+            ArithmeticExpression::Number(_, _, _, x) => Some(*x),
+            ArithmeticExpression::Parameter(_, _, _, param_name) => {
                 match self.param_to_string(exec, param_name, env, settings)? {
                     Some(s) => {
                         if !s.is_empty() {
@@ -1092,7 +1383,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Unary(_, _, UnaryOperator::Negate, expr1) => {
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::Negate, expr1) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 match x.checked_neg() {
                     Some(y) => Some(y),
@@ -1103,11 +1394,11 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Unary(_, _, UnaryOperator::Not, expr1) => {
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::Not, expr1) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 Some(!x)
             },
-            ArithmeticExpression::Unary(_, _, UnaryOperator::LogicalNot, expr1) => {
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::LogicalNot, expr1) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 if x != 0 {
                     Some(0)
@@ -1115,7 +1406,7 @@ impl Interpreter
                     Some(1)
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Multiply, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Multiply, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_mul(y) {
@@ -1127,7 +1418,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Divide, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Divide, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y != 0 {
@@ -1145,7 +1436,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Module, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Module, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y != 0 {
@@ -1163,7 +1454,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Add, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Add, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_add(y) {
@@ -1175,7 +1466,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Subtract, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Subtract, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_sub(y) {
@@ -1187,7 +1478,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ShiftLeft, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ShiftLeft, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y <= u32::MAX as i64 && y >= 0 {
@@ -1205,7 +1496,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ShiftRight, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ShiftRight, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y <= u32::MAX as i64 && y >= 0 {
@@ -1223,52 +1514,52 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::LessThan, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::LessThan, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x < y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::GreaterEqual, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::GreaterEqual, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x >= y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::GreaterThan, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::GreaterThan, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x > y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::LessEqual, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::LessEqual, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x <= y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Equal, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Equal, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x == y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::NotEqual, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::NotEqual, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(if x != y { 1 } else { 0 })
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::And, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::And, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(x & y)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ExclusiveOr, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ExclusiveOr, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(x ^ y)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Or, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Or, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 Some(x | y)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::LogicalAnd, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::LogicalAnd, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 if x != 0 {
                     let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
@@ -1277,7 +1568,7 @@ impl Interpreter
                     Some(x)
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::LogicalOr, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::LogicalOr, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 if x == 0 {
                     let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
@@ -1286,11 +1577,11 @@ impl Interpreter
                     Some(x)
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::Assign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::Assign, expr2) => {
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 self.assign_to_arith_expr(exec, &(*expr1), y, env, settings)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::MultiplyAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::MultiplyAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_mul(y) {
@@ -1302,7 +1593,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::DivideAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::DivideAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y != 0 {
@@ -1320,7 +1611,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ModuleAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ModuleAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y != 0 {
@@ -1338,7 +1629,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::AddAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::AddAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_add(y) {
@@ -1350,7 +1641,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::SubtractAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::SubtractAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 match x.checked_sub(y) {
@@ -1362,7 +1653,7 @@ impl Interpreter
                     },
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ShiftLeftAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ShiftLeftAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y <= u32::MAX as i64 && y >= 0 {
@@ -1380,7 +1671,7 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ShiftRightAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ShiftRightAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 if y <= u32::MAX as i64 && y >= 0 {
@@ -1398,22 +1689,22 @@ impl Interpreter
                     None
                 }
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::AndAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::AndAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 self.assign_to_arith_expr(exec, &(*expr1), x & y, env, settings)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::ExclusiveOrAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::ExclusiveOrAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 self.assign_to_arith_expr(exec, &(*expr1), x ^ y, env, settings)
             },
-            ArithmeticExpression::Binary(_, _, expr1, BinaryOperator::OrAssign, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, BinaryOperator::OrAssign, expr2) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 let y = self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)?;
                 self.assign_to_arith_expr(exec, &(*expr1), x | y, env, settings)
             },
-            ArithmeticExpression::Conditional(_, _, expr1, expr2, expr3) => {
+            ArithmeticExpression::Conditional(_, _, _, expr1, expr2, expr3) => {
                 let x = self.evaluate_arith_expr(exec, &(*expr1), param_expr_count, env, settings)?;
                 if x != 0 {
                     self.evaluate_arith_expr(exec, &(*expr2), param_expr_count, env, settings)
@@ -1850,15 +2141,14 @@ impl Interpreter
         Some(ss.join(""))
     }
     
-    fn interpret_redirects<F, G>(&mut self, exec: &mut Executor, redirects: &[Rc<Redirection>], is_special_builtin_fun: bool, env: &mut Environment, settings: &mut Settings, f: F, name_f: G) -> i32
-        where F: FnOnce(&mut Self, &mut Executor, &mut Environment, &mut Settings) -> i32,
-              G: FnOnce(usize) -> (Vec<String>, String, String)
+    fn interpret_redirects<F>(&mut self, exec: &mut Executor, redirects: &[Rc<Redirection>], is_special_builtin_fun: bool, env: &mut Environment, settings: &mut Settings, f: F) -> i32
+        where F: FnOnce(&mut Self, &mut Executor, &mut Environment, &mut Settings) -> i32
     {
         let mut is_success = true;
         let mut interp_redirects: Vec<InterpreterRedirection> = Vec::new();
         for redirect in redirects.iter() {
             match &(**redirect) {
-                Redirection::Input(_, _, n, word) => {
+                Redirection::Input(_, _, _, n, word) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(path) => interp_redirects.push(InterpreterRedirection::Input(n.unwrap_or(0), path)),
                         None => {
@@ -1867,7 +2157,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::Output(_, _, n, word, is_bar) => {
+                Redirection::Output(_, _, _, n, word, is_bar) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(path) => interp_redirects.push(InterpreterRedirection::Output(n.unwrap_or(1), path, *is_bar)),
                         None => {
@@ -1876,7 +2166,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::InputAndOutput(_, _, n, word) => {
+                Redirection::InputAndOutput(_, _, _, n, word) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(path) => interp_redirects.push(InterpreterRedirection::InputAndOutput(n.unwrap_or(0), path)),
                         None => {
@@ -1885,7 +2175,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::Appending(_, _, n, word) => {
+                Redirection::Appending(_, _, _, n, word) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(path) => interp_redirects.push(InterpreterRedirection::Appending(n.unwrap_or(1), path)),
                         None => {
@@ -1894,7 +2184,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::InputDuplicating(path, pos, n, word) => {
+                Redirection::InputDuplicating(path, pos, _, n, word) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(fd_s) => {
                             if is_io_number_str(fd_s.as_str()) {
@@ -1924,7 +2214,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::OutputDuplicating(path, pos, n, word) => {
+                Redirection::OutputDuplicating(path, pos, _, n, word) => {
                     match self.perform_word_expansion_as_string(exec, &(*word), env, settings) {
                         Some(fd_s) => {
                             if is_io_number_str(fd_s.as_str()) {
@@ -1954,7 +2244,7 @@ impl Interpreter
                         },
                     }
                 },
-                Redirection::HereDocument(_, _, n, here_doc) => {
+                Redirection::HereDocument(_, _, _, n, here_doc) => {
                     match self.perform_here_doc_expansion(exec, &here_doc.borrow(), env, settings) {
                         Some(s) => interp_redirects.push(InterpreterRedirection::HereDocument(n.unwrap_or(0), s)),
                         None => {
@@ -1973,7 +2263,6 @@ impl Interpreter
             }
         }
         let mut is_success_for_interp_redirects = true;
-        let mut pipes: Vec<Pipe> = Vec::new();
         let mut i = 0;
         for interp_redirect in &interp_redirects {
             match interp_redirect {
@@ -2066,127 +2355,27 @@ impl Interpreter
                         },
                     }
                 },
-                InterpreterRedirection::HereDocument(_, _) => {
-                    match pipe_with_cloexec() {
-                        Ok(pipe_fds) => pipes.push(unsafe { Pipe::from_pipe_fds(&pipe_fds) }),
+                InterpreterRedirection::HereDocument(vfd, s) => {
+                    match create_here_doc_file(s.as_str()) {
+                        Ok(file) => exec.push_file(*vfd, Rc::new(RefCell::new(file))),
                         Err(err) => {
-                            xsfprintln!(exec, 2, "{}", err);
+                            if is_special_builtin_fun {
+                                xsfprintln!(exec, 2, "{}", err);
+                            } else {
+                                xcfprintln!(exec, 2, "{}", err);
+                            }
                             is_success = false;
                             is_success_for_interp_redirects = false;
                             break;
-                        }
+                        },
                     }
                 },
             }
             i += 1;
         }
-        exec.set_pipes(pipes);
         let mut status = 1;
         if is_success {
-            if exec.pipes().is_empty() {
-                status = f(self, exec, env, settings);
-            } else {
-                status = exec.interpret(|exec| {
-                        let mut is_fun_process = false;
-                        let mut j = 0;
-                        let mut pids: Vec<Option<i32>> = Vec::new();
-                        for interp_redirect in &interp_redirects {
-                            match interp_redirect {
-                                InterpreterRedirection::HereDocument(_, s) => {
-                                    let res = exec.create_process(false, settings, |exec, _| {
-                                            let file = exec.pipes()[j].writing_file.clone();
-                                            exec.clear_pipes();
-                                            let mut file_r = file.borrow_mut();
-                                            match file_r.write_all(s.as_bytes()) {
-                                                Ok(()) => 0,
-                                                Err(err) => {
-                                                    if is_special_builtin_fun {
-                                                        xsfprintln!(exec, 2, "{}", err);
-                                                    } else {
-                                                        xcfprintln!(exec, 2, "{}", err);
-                                                    }
-                                                    1
-                                                },
-                                            }
-                                    });
-                                    match res {
-                                        Ok(pid) => pids.push(pid),
-                                        Err(err) => {
-                                            if is_special_builtin_fun {
-                                                xsfprintln!(exec, 2, "{}", err);
-                                            } else {
-                                                xcfprintln!(exec, 2, "{}", err);
-                                            }
-                                            is_success = false;
-                                            is_success_for_interp_redirects = false;
-                                            break;
-                                        },
-                                    }
-                                    j += 1;
-                                },
-                                _ => (),
-                            }
-                        }
-                        if is_success {
-                            let res = exec.create_process(false, settings, |exec, settings| {
-                                    let mut k = 0;
-                                    for interp_redirect in &interp_redirects {
-                                        match interp_redirect {
-                                            InterpreterRedirection::HereDocument(vfd, _) => {
-                                                exec.push_file(*vfd, exec.pipes()[k].reading_file.clone());
-                                                k += 1;
-                                            },
-                                            _ => (),
-                                        }
-                                    }
-                                    exec.clear_pipes();
-                                    let status = f(self, exec, env, settings);
-                                    interp_redirects.reverse();
-                                    for interp_redirect in &interp_redirects {
-                                        match interp_redirect {
-                                            InterpreterRedirection::HereDocument(vfd, _) => {
-                                                k -= 1;
-                                                exec.pop_file(*vfd);
-                                            },
-                                            _ => (),
-                                        }
-                                    }
-                                    status
-                            });
-                            match res {
-                                Ok(pid) => {
-                                    is_fun_process = true;
-                                    pids.push(pid);
-                                },
-                                Err(err) => {
-                                    xcfprintln!(exec, 2, "{}", err);
-                                    is_success = false;
-                                },
-                            }
-                        }
-                        j += 1;
-                        exec.clear_pipes();
-                        let pgid = pids.last().map(|pid| *pid).unwrap_or(None);
-                        set_process_group_and_foreground_for_processes(exec, pids.as_slice(), pgid, settings);
-                        match self.wait_for_processes(exec, pids.as_slice(), pgid, j, true, is_special_builtin_fun, settings, name_f) {
-                            (Some(tmp_status), tmp_is_success_for_interp_redirects) => {
-                                is_success_for_interp_redirects &= tmp_is_success_for_interp_redirects;
-                                if is_fun_process {
-                                    tmp_status
-                                } else {
-                                    1
-                                }
-                            },
-                            (None, tmp_is_success_for_interp_redirects) => {
-                                is_success_for_interp_redirects &= tmp_is_success_for_interp_redirects;
-                                is_success = false;
-                                1
-                            },
-                        }
-                });
-            }
-        } else {
-            exec.clear_pipes();
+            status = f(self, exec, env, settings);
         }
         if is_success && self.exec_redirect_flag {
             for interp_redirect in &interp_redirects {
@@ -2196,7 +2385,7 @@ impl Interpreter
                     InterpreterRedirection::InputAndOutput(vfd, _) => exec.pop_penultimate_file(*vfd),
                     InterpreterRedirection::Appending(vfd, _) => exec.pop_penultimate_file(*vfd),
                     InterpreterRedirection::Duplicating(vfd, _) => exec.pop_penultimate_file(*vfd),
-                    InterpreterRedirection::HereDocument(_, _) => (),
+                    InterpreterRedirection::HereDocument(vfd, _) => exec.pop_penultimate_file(*vfd),
                 }
             }
             self.exec_redirect_flag = false;
@@ -2209,7 +2398,7 @@ impl Interpreter
                     InterpreterRedirection::InputAndOutput(vfd, _) => exec.pop_file(*vfd),
                     InterpreterRedirection::Appending(vfd, _) => exec.pop_file(*vfd),
                     InterpreterRedirection::Duplicating(vfd, _) => exec.pop_file(*vfd),
-                    InterpreterRedirection::HereDocument(_, _) => (),
+                    InterpreterRedirection::HereDocument(vfd, _) => exec.pop_file(*vfd),
                 }
             }
         }
@@ -2243,7 +2432,8 @@ impl Interpreter
                                 word_elems.extend_from_slice(&word.word_elems[1..]);
                                 let new_word = Word {
                                     path: word.path.clone(),
-                                    pos: Position { line: word.pos.line, column: word.pos.column + name.len() as u64, }, 
+                                    pos: Position { line: word.pos.line, column: word.pos.column + name.len() as u64, offset: word.pos.offset + name.len() as u64, }, 
+                                    end_pos: word.end_pos,
                                     word_elems,
                                 };
                                 match self.perform_var_word_expansion_as_string(exec, &new_word, env, settings) {
@@ -2267,8 +2457,16 @@ impl Interpreter
         if settings.noexec_flag {
             return self.last_status;
         }
+        if self.action_flag && self.actions.contains_key(&DEBUG_SIG) {
+            env.set_var("RSUSH_COMMAND", format!("{}", command).as_str(), settings);
+            match self.do_action(exec, DEBUG_SIG, env, settings) {
+                Some(action_status) => return action_status,
+                None => (),
+            }
+        }
         let mut vars: Vec<(String, String)> = Vec::new();
         let mut word_iter = command.words.iter();
+        let mut command_trace: Option<CommandTrace> = None;
         let status = match self.add_vars(exec, &mut word_iter, &mut vars, env, settings) {
             Some(Some(prog_word)) => {
                 match self.perform_word_expansion(exec, &(*prog_word), env, settings) {
@@ -2353,36 +2551,18 @@ impl Interpreter
                         if is_success {
                             match args.first() {
                                 Some(arg0) => {
-                                    if settings.xtrace_flag {
-                                        print_command_for_xtrace_or_extxtrace(exec, None, vars.as_slice(), args.as_slice(), env);
-                                    } else if settings.extxtrace_flag {
-                                        print_command_for_xtrace_or_extxtrace(exec, Some((path, pos)), vars.as_slice(), args.as_slice(), env);
-                                    }
-                                    let name0 = singly_quote_str(settings.arg0.as_str());
-                                    let name_f = |count: usize| {
-                                        let process_names = vec![name0; count];
-                                        let name = format!("{}", command);
-                                        (process_names, name.clone(), name)
-                                    };
+                                    command_trace = trace_command(exec, path, pos, vars.as_slice(), args.as_slice(), env, settings);
                                     self.interpret_redirects(exec, redirects.as_slice(), self.has_special_builtin_fun(arg0.as_str(), env), env, settings, |interp, exec, env, settings| {
+                                            interp.current_path = String::from(path);
+                                            interp.current_pos = *pos;
                                             interp.execute(exec, vars.as_slice(), arg0.as_str(), &args[1..], false, env, settings, || format!("{}", command)).unwrap_or(1)
-                                    }, name_f)
+                                    })
                                 },
                                 None => {
-                                    if settings.xtrace_flag {
-                                        print_command_for_xtrace_or_extxtrace(exec, None, vars.as_slice(), &[], env);
-                                    } else if settings.extxtrace_flag {
-                                        print_command_for_xtrace_or_extxtrace(exec, Some((path, pos)), vars.as_slice(), &[], env);
-                                    }
-                                    let name0 = singly_quote_str(settings.arg0.as_str());
-                                    let name_f = |count: usize| {
-                                        let process_names = vec![name0; count];
-                                        let name = format!("{}", command);
-                                        (process_names, name.clone(), name)
-                                    };
+                                    command_trace = trace_command(exec, path, pos, vars.as_slice(), &[], env, settings);
                                     self.interpret_redirects(exec, redirects.as_slice(), false, env, settings, |_, exec, env, settings| {
                                             set_vars(exec, vars.as_slice(), env, settings)
-                                    }, name_f)
+                                    })
                                 },
                             }
                         } else {
@@ -2393,50 +2573,57 @@ impl Interpreter
                 }
             },
             Some(None) => {
-                if settings.xtrace_flag {
-                    print_command_for_xtrace_or_extxtrace(exec, None, vars.as_slice(), &[], env);
-                } else if settings.extxtrace_flag {
-                    print_command_for_xtrace_or_extxtrace(exec, Some((path, pos)), vars.as_slice(), &[], env);
-                }
-                let name0 = singly_quote_str(settings.arg0.as_str());
-                let name_f = |count: usize| {
-                    let process_names = vec![name0; count];
-                    let name = format!("{}", command);
-                    (process_names, name.clone(), name)
-                };
+                command_trace = trace_command(exec, path, pos, vars.as_slice(), &[], env, settings);
                 self.interpret_redirects(exec, command.redirects.as_slice(), false, env, settings, |_, exec, env, settings| {
                         set_vars(exec, vars.as_slice(), env, settings)
-                }, name_f)
+                })
             },
             None => 1,
         };
+        match command_trace {
+            Some(command_trace) => print_command_trace_for_json(exec, &command_trace, status, env),
+            None => (),
+        }
         self.last_status = status;
-        if status != 0 && settings.errexit_flag && self.non_simple_command_count == 0 {
-            self.exit(status, true)
-        } else {
-            status
+        if status != 0 && self.non_simple_command_count == 0 {
+            if self.has_none() {
+                match self.do_action(exec, ERR_SIG, env, settings) {
+                    Some(action_status) => return action_status,
+                    None => (),
+                }
+            }
+            if settings.errexit_flag {
+                if settings.stacktrace_flag && self.has_none() {
+                    xsfprintln!(exec, 2, "{}: {}: Exited with status {}", path, pos, status);
+                    self.print_call_stack(exec);
+                }
+                return self.exit(status, true);
+            }
         }
+        status
     }
 
-    fn interpret_compound_command<F, G>(&mut self, exec: &mut Executor, command: &CompoundCommand, redirects: &[Rc<Redirection>], env: &mut Environment, settings: &mut Settings, name_f: F, name_g: G) -> i32
-        where F: FnOnce() -> String,
-              G: FnOnce(usize) -> (Vec<String>, String, String)
+    fn interpret_compound_command<F>(&mut self, exec: &mut Executor, command: &CompoundCommand, redirects: &[Rc<Redirection>], env: &mut Environment, settings: &mut Settings, name_f: F) -> i32
+        where F: FnOnce() -> String
     {
         self.interpret_redirects(exec, redirects, false, env, settings, |interp, exec, env, settings| {
                 match command {
                     CompoundCommand::BraceGroup(commands) => {
-                        exec.interpret_or(commands.len() > 1, |exec| {
-                                interp.interpret_logical_commands(exec, commands.as_slice(), env, settings)
-                        })
+                        interp.interpret_logical_commands(exec, commands.as_slice(), env, settings)
                     },
                     CompoundCommand::Subshell(commands) => {
                         if settings.noexec_flag {
                             return interp.last_status;
                         }
+                        if is_fork_free_logical_commands(commands.as_slice(), env, &mut HashSet::new()) {
+                            let status = interp.interpret_in_process(exec, env, settings, |interp, exec, env, settings| {
+                                    interp.interpret_logical_commands(exec, commands.as_slice(), env, settings)
+                            });
+                            interp.last_status = status;
+                            return status;
+                        }
                         let res = exec.create_process(false, settings, |exec, settings| {
-                                exec.interpret_or(commands.len() > 1, |exec| {
-                                        interp.interpret_logical_commands(exec, commands.as_slice(), env, settings)
-                                })
+                                interp.interpret_logical_commands(exec, commands.as_slice(), env, settings)
                         });
                         match res {
                             Ok(pid) => {
@@ -2669,7 +2856,7 @@ impl Interpreter
                         })
                     },
                 }
-        }, name_g)
+        })
     }
 
     fn interpret_fun_def(&mut self, exec: &mut Executor, name_word: &Word, fun_body: &Rc<FunctionBody>, env: &mut Environment, settings: &mut Settings) -> i32
@@ -2693,18 +2880,14 @@ impl Interpreter
     fn interpret_command(&mut self, exec: &mut Executor, command: &Command, env: &mut Environment, settings: &mut Settings) -> i32
     {
         env.set_var("LINENO", format!("{}", command.pos().line).as_str(), settings);
+        self.current_path = command.path();
+        self.current_pos = command.pos();
         match command {
-            Command::Simple(path, pos, simple_command) => self.interpret_simple_command(exec, path.as_str(), pos, &(*simple_command), env, settings),
-            Command::Compound(_, _, compound_command, redirects) => {
-                let name0 = singly_quote_str(settings.arg0.as_str());
-                let name_g = |count: usize| {
-                    let process_names = vec![name0; count];
-                    let name = format!("{}", command);
-                    (process_names, name.clone(), name)
-                };
-                self.interpret_compound_command(exec, &(*compound_command), redirects.as_slice(), env, settings, || format!("{}", command), name_g)
+            Command::Simple(path, pos, _, simple_command) => self.interpret_simple_command(exec, path.as_str(), pos, &(*simple_command), env, settings),
+            Command::Compound(_, _, _, compound_command, redirects) => {
+                self.interpret_compound_command(exec, &(*compound_command), redirects.as_slice(), env, settings, || format!("{}", command))
             },
-            Command::FunctionDefinition(_, _, name_word, fun_body) => self.interpret_fun_def(exec, &(*name_word), fun_body, env, settings),
+            Command::FunctionDefinition(_, _, _, name_word, fun_body) => self.interpret_fun_def(exec, &(*name_word), fun_body, env, settings),
         }
     }
 
@@ -2719,7 +2902,9 @@ impl Interpreter
                 self.non_simple_command_count += 1;
             }
             if !command.commands.is_empty() {
-                status = self.interpret_command(exec, &(*command.commands[0]), env, settings);
+                status = exec.interpret_or(command.is_negative, |exec| {
+                        self.interpret_command(exec, &(*command.commands[0]), env, settings)
+                });
             }
             if command.is_negative {
                 self.non_simple_command_count -= 1;
@@ -2883,36 +3068,45 @@ impl Interpreter
     
     pub fn interpret_logical_commands(&mut self, exec: &mut Executor, commands: &[Rc<LogicalCommand>], env: &mut Environment, settings: &mut Settings) -> i32
     {
-        exec.interpret_or(commands.len() > 1, |exec| {
-                let mut status = self.last_status;
-                for command in commands.iter() {
-                    if settings.noexec_flag { break; }
-                    status = self.interpret_logical_command(exec, &(**command), env, settings);
-                    if self.has_break_or_continue_or_return_or_exit() { break; }
-                }
-                status
-        })
+        let mut status = self.last_status;
+        for (i, command) in commands.iter().enumerate() {
+            if settings.noexec_flag { break; }
+            status = exec.interpret_or(i + 1 < commands.len(), |exec| {
+                    self.interpret_logical_command(exec, &(**command), env, settings)
+            });
+            if self.has_break_or_continue_or_return_or_exit() { break; }
+        }
+        status
     }
 
-    pub fn interpret_fun_body(&mut self, exec: &mut Executor, fun_body: &FunctionBody, env: &mut Environment, settings: &mut Settings) -> i32
+    pub fn interpret_fun_body(&mut self, exec: &mut Executor, name: &str, fun_body: &FunctionBody, env: &mut Environment, settings: &mut Settings) -> i32
     {
+        self.push_call_frame(name, fun_body.path.as_str());
         self.fun_count += 1;
         self.push_loop_count(0);
-        let name0 = singly_quote_str(settings.arg0.as_str());
-        let name_g = |count: usize| {
-            let process_names = vec![name0; count];
-            let name = format!("{}", fun_body);
-            (process_names, name.clone(), name)
-        };
-        let status = self.interpret_compound_command(exec, &fun_body.command, fun_body.redirects.as_slice(), env, settings, || format!("{}", fun_body), name_g);
+        let status = self.interpret_compound_command(exec, &fun_body.command, fun_body.redirects.as_slice(), env, settings, || format!("{}", fun_body));
         self.pop_loop_count();
         self.fun_count -= 1;
         if self.has_break_or_continue_or_return() {
             self.clear_return_state();
         }
+        let res = self.do_action(exec, RETURN_SIG, env, settings);
+        self.pop_call_frame();
+        res.unwrap_or(status)
+    }
+
+    pub fn interpret_fun_body_for_completion(&mut self, exec: &mut Executor, name: &str, fun_body: &FunctionBody, env: &mut Environment, settings: &mut Settings) -> i32
+    {
+        // A completion function doesn't exit the shell and doesn't change the exit status.
+        let saved_last_status = self.last_status;
+        let saved_return_state = self.return_state;
+        self.return_state = ReturnState::None;
+        let status = self.interpret_fun_body(exec, name, fun_body, env, settings);
+        self.return_state = saved_return_state;
+        self.last_status = saved_last_status;
         status
     }
-    
+
     pub fn do_action(&mut self, exec: &mut Executor, sig: i32, env: &mut Environment, settings: &mut Settings) -> Option<i32>
     {
         if self.action_flag {
@@ -2926,7 +3120,9 @@ impl Interpreter
                     self.non_simple_command_count = 0;
                     self.fun_count = 0;
                     self.push_loop_count(0);
-                    self.last_status = 0;
+                    if sig >= 0 {
+                        self.last_status = 0;
+                    }
                     self.return_state = ReturnState::None;
                     let mut cursor = Cursor::new(action.as_bytes());
                     let mut cr = CharReader::new(&mut cursor);
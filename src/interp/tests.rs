@@ -6336,6 +6336,103 @@ abc def abc def
     }
 }
 
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_here_document_redirection_for_read_builtin_function()
+{
+    let s = "
+read VAR1 VAR2 << EOT
+abc def
+EOT
+echo $VAR1 $VAR2
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc def
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_here_document_redirections_for_long_here_documents()
+{
+    let s = format!("
+: << EOT
+{}
+EOT
+read VAR << EOT
+{}
+EOT
+echo ${{#VAR}}
+", "x".repeat(100000), "y".repeat(8192));
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+8192
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
 #[sealed_test(before=setup(), after=teardown())]
 fn test_interpreter_interpret_logical_commands_changes_redirection_files()
 {
@@ -7732,19 +7829,13 @@ ghi jkl
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_subshell_for_variable_changing()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_executing_last_command()
 {
     let s = "
-unset VAR1
-VAR1=abc
-unset VAR2
-VAR2=def
 (
-    echo $VAR1 $VAR2
-    VAR2=ghi
-    echo $VAR1 $VAR2
+    ./rsush_test exit 0
+    ./rsush_test ppid
 )
-echo $VAR1 $VAR2
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -7773,12 +7864,7 @@ echo $VAR1 $VAR2
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc def
-abc ghi
-abc def
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(format!("{}\n", process::id()), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -7787,14 +7873,14 @@ abc def
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_subshell_for_redirections()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_executing_last_command_and_trap()
 {
     let s = "
 (
-    echo abc
-    echo def > test2.txt
-    echo ghi
-) > test1.txt
+    trap \"\" USR1
+    ./rsush_test exit 0
+    ./rsush_test ppid
+)
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -7823,30 +7909,24 @@ fn test_interpreter_interpret_logical_commands_interprets_subshell_for_redirecti
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            assert_ne!(format!("{}\n", process::id()), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
-            let expected_test1_txt_content = "
-abc
-ghi
-";
-            assert_eq!(String::from(&expected_test1_txt_content[1..]), read_file("test1.txt"));
-            let expected_test2_txt_content = "
-def
-";
-            assert_eq!(String::from(&expected_test2_txt_content[1..]), read_file("test2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_for_loop()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_executing_last_command_and_json_xtrace()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    echo $i
-done
+RSUSH_XTRACEFORMAT=json
+set -x
+(
+    ./rsush_test exit 0
+    ./rsush_test ppid
+)
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -7875,15 +7955,8 @@ done
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-1
-2
-3
-4
-5
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_ne!(format!("{}\n", process::id()), read_file("stdout.txt"));
+            assert!(read_file("stderr.txt").contains("\"argv\":[\"./rsush_test\",\"ppid\"]"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -7891,15 +7964,14 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_for_loop_with_break()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_executing_last_command_and_stacktrace()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    if [ \"$i\" -eq 4 ]; then
-        break
-    fi
-    echo $i
-done
+set -e -o stacktrace
+(
+    ./rsush_test exit 0
+    ./rsush_test ppid
+)
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -7928,12 +8000,7 @@ done
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-1
-2
-3
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_ne!(format!("{}\n", process::id()), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -7942,15 +8009,10 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_for_loop_with_continue()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_executing_last_negative_command()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    if [ \"$i\" -eq 4 ]; then
-        continue
-    fi
-    echo $i
-done
+(! ./rsush_test exit 0)
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -7975,17 +8037,11 @@ done
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-1
-2
-3
-5
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -7994,18 +8050,19 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_for_loop_with_double_break()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_variable_changing()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    for j in 1 2 3 4 5; do
-        if [ \"$i\" -eq \"$j\" ]; then
-            echo \"$i = $j\"
-            break 2
-        fi
-        echo \"$i =/= $j\"
-    done
-done
+unset VAR1
+VAR1=abc
+unset VAR2
+VAR2=def
+(
+    echo $VAR1 $VAR2
+    VAR2=ghi
+    echo $VAR1 $VAR2
+)
+echo $VAR1 $VAR2
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8035,7 +8092,9 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1 = 1
+abc def
+abc ghi
+abc def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8046,18 +8105,20 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_for_loop_with_double_continue()
+fn test_interpreter_interpret_logical_commands_interprets_fork_free_command_substitution_and_subshell()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    for j in 1 2 3 4 5; do
-        if [ \"$i\" -eq \"$j\" ]; then
-            echo \"$i = $j\"
-            continue 2
-        fi
-        echo \"$i =/= $j\"
-    done
-done
+unset VAR1 VAR2
+VAR1=abc
+f() {
+    VAR1=def
+    export VAR2=ghi
+    set -- x y
+    compgen -W \"$1 $2\" -- \"\"
+}
+VAR3=\"$(f)\"
+(f > /dev/null; unset VAR1)
+echo $VAR1 ${VAR2-unset} $# $VAR3
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8087,38 +8148,24 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1 = 1
-2 =/= 1
-2 = 2
-3 =/= 1
-3 =/= 2
-3 = 3
-4 =/= 1
-4 =/= 2
-4 =/= 3
-4 = 4
-5 =/= 1
-5 =/= 2
-5 =/= 3
-5 =/= 4
-5 = 5
+abc unset 0 x y
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(None, env.var("VAR2"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_for_loop_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_command_substitution_with_function_definition_in_new_process()
 {
     let s = "
-for i in 1 2 3 4 5; do
-    echo $i
-    false
-done
+f() { :; }
+VAR1=\"$(f() { cd /; }; f)\"
+f
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8132,7 +8179,6 @@ done
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -8142,16 +8188,15 @@ done
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
             exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let current_dir = std::env::current_dir().unwrap();
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-1
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(current_dir, std::env::current_dir().unwrap());
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -8160,13 +8205,12 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_first_match()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_with_function_definition_in_new_process()
 {
     let s = "
-case abcdef in
-    abc*) echo abc;;
-    def*) echo def;;
-esac
+f() { :; }
+(f() { cd /; }; f)
+f
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8189,16 +8233,15 @@ esac
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
             exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let current_dir = std::env::current_dir().unwrap();
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
             assert_eq!(0, status);
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(current_dir, std::env::current_dir().unwrap());
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -8207,14 +8250,15 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_second_match()
+fn test_interpreter_interpret_logical_commands_interprets_subshell_for_redirections()
 {
     let s = "
-case defabc in
-    abc*) echo abc;;
-    def*) echo def;;
-esac
-";
+(
+    echo abc
+    echo def > test2.txt
+    echo ghi
+) > test1.txt
+";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
     let mut cr = CharReader::new(&mut cursor);
@@ -8242,25 +8286,30 @@ esac
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-def
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
+            let expected_test1_txt_content = "
+abc
+ghi
+";
+            assert_eq!(String::from(&expected_test1_txt_content[1..]), read_file("test1.txt"));
+            let expected_test2_txt_content = "
+def
+";
+            assert_eq!(String::from(&expected_test2_txt_content[1..]), read_file("test2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_no_match()
+fn test_interpreter_interpret_logical_commands_interprets_for_loop()
 {
     let s = "
-case xxxdef in
-    abc*) echo abc;;
-    def*) echo def;;
-esac
+for i in 1 2 3 4 5; do
+    echo $i
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8290,6 +8339,11 @@ esac
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
+1
+2
+3
+4
+5
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8300,13 +8354,15 @@ esac
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_first_match_and_patterns()
+fn test_interpreter_interpret_logical_commands_interprets_for_loop_with_break()
 {
     let s = "
-case 123def in
-    abc*|123*) echo abc;;
-    def*|456*) echo def;;
-esac
+for i in 1 2 3 4 5; do
+    if [ \"$i\" -eq 4 ]; then
+        break
+    fi
+    echo $i
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8336,7 +8392,9 @@ esac
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc
+1
+2
+3
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8347,13 +8405,15 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_second_match_and_patterns()
+fn test_interpreter_interpret_logical_commands_interprets_for_loop_with_continue()
 {
     let s = "
-case 456abc in
-    abc*|123*) echo abc;;
-    def*|456*) echo def;;
-esac
+for i in 1 2 3 4 5; do
+    if [ \"$i\" -eq 4 ]; then
+        continue
+    fi
+    echo $i
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8383,7 +8443,10 @@ esac
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-def
+1
+2
+3
+5
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8394,13 +8457,18 @@ def
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_no_match_and_patterns()
+fn test_interpreter_interpret_logical_commands_interprets_nested_for_loop_with_double_break()
 {
     let s = "
-case xxxdef in
-    abc*|123*) echo abc;;
-    def*|456*) echo def;;
-esac
+for i in 1 2 3 4 5; do
+    for j in 1 2 3 4 5; do
+        if [ \"$i\" -eq \"$j\" ]; then
+            echo \"$i = $j\"
+            break 2
+        fi
+        echo \"$i =/= $j\"
+    done
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8430,6 +8498,7 @@ esac
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
+1 = 1
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8440,16 +8509,18 @@ esac
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_nested_for_loop_with_double_continue()
 {
     let s = "
-case abcdef in
-    abc*)
-        echo abc
-        false
-        echo def
-        ;;
-esac
+for i in 1 2 3 4 5; do
+    for j in 1 2 3 4 5; do
+        if [ \"$i\" -eq \"$j\" ]; then
+            echo \"$i = $j\"
+            continue 2
+        fi
+        echo \"$i =/= $j\"
+    done
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8463,7 +8534,6 @@ esac
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -8475,12 +8545,26 @@ esac
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc
+1 = 1
+2 =/= 1
+2 = 2
+3 =/= 1
+3 =/= 2
+3 = 3
+4 =/= 1
+4 =/= 2
+4 =/= 3
+4 = 4
+5 =/= 1
+5 =/= 2
+5 =/= 3
+5 =/= 4
+5 = 5
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8491,12 +8575,13 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_true_condition()
+fn test_interpreter_interpret_logical_commands_interprets_for_loop_for_errexit_that_is_set()
 {
     let s = "
-if true; then
-    echo abc
-fi
+for i in 1 2 3 4 5; do
+    echo $i
+    false
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8510,6 +8595,7 @@ fi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -8521,12 +8607,12 @@ fi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc
+1
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8537,12 +8623,13 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_false_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_first_match()
 {
     let s = "
-if false; then
-    echo abc
-fi
+case abcdef in
+    abc*) echo abc;;
+    def*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8567,11 +8654,12 @@ fi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8582,14 +8670,13 @@ fi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_true_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_second_match()
 {
     let s = "
-if true; then
-    echo abc
-else
-    echo def
-fi
+case defabc in
+    abc*) echo abc;;
+    def*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8619,7 +8706,7 @@ fi
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8630,14 +8717,13 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_false_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_no_match()
 {
     let s = "
-if false; then
-    echo abc
-else
-    echo def
-fi
+case xxxdef in
+    abc*) echo abc;;
+    def*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8667,7 +8753,6 @@ fi
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8678,16 +8763,13 @@ def
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_first_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_first_match_and_patterns()
 {
     let s = "
-if true; then
-    echo abc
-elif true; then
-    echo def
-elif true; then
-    echo ghi
-fi
+case 123def in
+    abc*|123*) echo abc;;
+    def*|456*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8728,16 +8810,13 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_second_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_second_match_and_patterns()
 {
     let s = "
-if false; then
-    echo abc
-elif true; then
-    echo def
-elif true; then
-    echo ghi
-fi
+case 456abc in
+    abc*|123*) echo abc;;
+    def*|456*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8778,16 +8857,13 @@ def
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_third_condition()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_no_match_and_patterns()
 {
     let s = "
-if false; then
-    echo abc
-elif false; then
-    echo def
-elif true; then
-    echo ghi
-fi
+case xxxdef in
+    abc*|123*) echo abc;;
+    def*|456*) echo def;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8817,7 +8893,6 @@ fi
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-ghi
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8828,16 +8903,16 @@ ghi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_all_false_conditions()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_errexit_that_is_set()
 {
     let s = "
-if false; then
-    echo abc
-elif false; then
-    echo def
-elif false; then
-    echo ghi
-fi
+case abcdef in
+    abc*)
+        echo abc
+        false
+        echo def
+        ;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -8851,6 +8926,7 @@ fi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -8864,9 +8940,10 @@ fi
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8877,17 +8954,11 @@ fi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_and_else_for_all_false_conditions()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_true_condition()
 {
     let s = "
-if false; then
+if true; then
     echo abc
-elif false; then
-    echo def
-elif false; then
-    echo ghi
-else
-    echo jkl
 fi
 ";
     let s2 = &s[1..];
@@ -8918,7 +8989,7 @@ fi
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-jkl
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8929,13 +9000,11 @@ jkl
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_false_condition()
 {
     let s = "
-if true; then
+if false; then
     echo abc
-    false
-    echo def
 fi
 ";
     let s2 = &s[1..];
@@ -8950,7 +9019,6 @@ fi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -8964,10 +9032,9 @@ fi
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -8978,15 +9045,13 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_true_condition()
 {
     let s = "
-if false; then
+if true; then
     echo abc
 else
     echo def
-    false
-    echo ghi
 fi
 ";
     let s2 = &s[1..];
@@ -9001,7 +9066,6 @@ fi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9013,12 +9077,12 @@ fi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-def
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9029,17 +9093,13 @@ def
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_false_condition()
 {
     let s = "
 if false; then
     echo abc
-elif false; then
+else
     echo def
-elif true; then
-    echo ghi
-    false
-    echo jkl
 fi
 ";
     let s2 = &s[1..];
@@ -9054,7 +9114,6 @@ fi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9068,10 +9127,10 @@ fi
             exec.clear_files();
             assert_eq!(0, status);
             assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-ghi
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9082,14 +9141,16 @@ ghi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_while_loop()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_first_condition()
 {
     let s = "
-i=1
-while [ \"$i\" -le 5 ]; do
-    echo $i
-    i=\"`expr \"$i\" + 1`\"
-done
+if true; then
+    echo abc
+elif true; then
+    echo def
+elif true; then
+    echo ghi
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9119,11 +9180,7 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
-2
-3
-4
-5
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9134,17 +9191,16 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_while_loop_with_break()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_second_condition()
 {
     let s = "
-i=1
-while [ \"$i\" -le 5 ]; do
-    if [ \"$i\" -eq 4 ]; then
-        break
-    fi
-    echo $i
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+elif true; then
+    echo def
+elif true; then
+    echo ghi
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9174,9 +9230,7 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
-2
-3
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9187,18 +9241,16 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_while_loop_with_continue()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_true_third_condition()
 {
     let s = "
-i=1
-while [ \"$i\" -le 5 ]; do
-    if [ \"$i\" -eq 4 ]; then
-        i=\"`expr \"$i\" + 1`\"
-        continue
-    fi
-    echo $i
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+elif false; then
+    echo def
+elif true; then
+    echo ghi
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9228,10 +9280,7 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
-2
-3
-5
+ghi
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9242,22 +9291,16 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_while_loop_with_double_break()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_all_false_conditions()
 {
     let s = "
-i=1
-while [ \"$i\" -le 5 ]; do
-    j=1
-    while [ \"$j\" -le 5 ]; do
-        if [ \"$i\" -eq \"$j\" ]; then
-            echo \"$i = $j\"
-            break 2
-        fi
-        echo \"$i =/= $j\"
-        j=\"`expr \"$j\" + 1`\"
-    done
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+elif false; then
+    echo def
+elif false; then
+    echo ghi
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9282,12 +9325,11 @@ done
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1 = 1
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9298,23 +9340,18 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_while_loop_with_double_continue()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_and_else_for_all_false_conditions()
 {
     let s = "
-i=1
-while [ \"$i\" -le 5 ]; do
-    j=1
-    while [ \"$j\" -le 5 ]; do
-        if [ \"$i\" -eq \"$j\" ]; then
-            echo \"$i = $j\"
-            i=\"`expr \"$i\" + 1`\"
-            continue 2
-        fi
-        echo \"$i =/= $j\"
-        j=\"`expr \"$j\" + 1`\"
-    done
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+elif false; then
+    echo def
+elif false; then
+    echo ghi
+else
+    echo jkl
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9344,21 +9381,7 @@ done
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1 = 1
-2 =/= 1
-2 = 2
-3 =/= 1
-3 =/= 2
-3 = 3
-4 =/= 1
-4 =/= 2
-4 =/= 3
-4 = 4
-5 =/= 1
-5 =/= 2
-5 =/= 3
-5 =/= 4
-5 = 5
+jkl
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9369,16 +9392,14 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_while_loop_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_errexit_that_is_set()
 {
     let s = "
-i=1
-while false
-    [ \"$i\" -le 5 ]; do
-    echo $i
+if true; then
+    echo abc
     false
-    i=\"`expr \"$i\" + 1`\"
-done
+    echo def
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9409,7 +9430,7 @@ done
             assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9420,14 +9441,16 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_until_loop()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_else_for_errexit_that_is_set()
 {
     let s = "
-i=1
-until [ \"$i\" -gt 5 ]; do
-    echo $i
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+else
+    echo def
+    false
+    echo ghi
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9441,6 +9464,7 @@ done
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9452,16 +9476,12 @@ done
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
-2
-3
-4
-5
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9472,17 +9492,18 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_until_loop_with_break()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_with_three_conditions_for_errexit_that_is_set()
 {
     let s = "
-i=1
-until [ \"$i\" -gt 5 ]; do
-    if [ \"$i\" -eq 4 ]; then
-        break
-    fi
-    echo $i
-    i=\"`expr \"$i\" + 1`\"
-done
+if false; then
+    echo abc
+elif false; then
+    echo def
+elif true; then
+    echo ghi
+    false
+    echo jkl
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9496,6 +9517,7 @@ done
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9509,12 +9531,10 @@ done
             exec.clear_files();
             assert_eq!(0, status);
             assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-1
-2
-3
+ghi
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9525,15 +9545,11 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_until_loop_with_continue()
+fn test_interpreter_interpret_logical_commands_interprets_while_loop()
 {
     let s = "
 i=1
-until [ \"$i\" -gt 5 ]; do
-    if [ \"$i\" -eq 4 ]; then
-        i=\"`expr \"$i\" + 1`\"
-        continue
-    fi
+while [ \"$i\" -le 5 ]; do
     echo $i
     i=\"`expr \"$i\" + 1`\"
 done
@@ -9569,6 +9585,7 @@ done
 1
 2
 3
+4
 5
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
@@ -9580,21 +9597,129 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_until_loop_with_double_break()
+fn test_interpreter_interpret_logical_commands_interprets_while_loop_with_break()
 {
     let s = "
 i=1
-until [ \"$i\" -gt 5 ]; do
-    j=1
-    until [ \"$j\" -gt 5 ]; do
-        if [ \"$i\" -eq \"$j\" ]; then
-            echo \"$i = $j\"
-            break 2
-        fi
-        echo \"$i =/= $j\"
-        j=\"`expr \"$j\" + 1`\"
-    done
-    i=\"`expr \"$i\" + 1`\"
+while [ \"$i\" -le 5 ]; do
+    if [ \"$i\" -eq 4 ]; then
+        break
+    fi
+    echo $i
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+2
+3
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_while_loop_with_continue()
+{
+    let s = "
+i=1
+while [ \"$i\" -le 5 ]; do
+    if [ \"$i\" -eq 4 ]; then
+        i=\"`expr \"$i\" + 1`\"
+        continue
+    fi
+    echo $i
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+2
+3
+5
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_nested_while_loop_with_double_break()
+{
+    let s = "
+i=1
+while [ \"$i\" -le 5 ]; do
+    j=1
+    while [ \"$j\" -le 5 ]; do
+        if [ \"$i\" -eq \"$j\" ]; then
+            echo \"$i = $j\"
+            break 2
+        fi
+        echo \"$i =/= $j\"
+        j=\"`expr \"$j\" + 1`\"
+    done
+    i=\"`expr \"$i\" + 1`\"
 done
 ";
     let s2 = &s[1..];
@@ -9636,13 +9761,13 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_until_loop_with_double_continue()
+fn test_interpreter_interpret_logical_commands_interprets_nested_while_loop_with_double_continue()
 {
     let s = "
 i=1
-until [ \"$i\" -gt 5 ]; do
+while [ \"$i\" -le 5 ]; do
     j=1
-    until [ \"$j\" -gt 5 ]; do
+    while [ \"$j\" -le 5 ]; do
         if [ \"$i\" -eq \"$j\" ]; then
             echo \"$i = $j\"
             i=\"`expr \"$i\" + 1`\"
@@ -9707,11 +9832,12 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_until_loop_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_interprets_while_loop_for_errexit_that_is_set()
 {
     let s = "
 i=1
-until [ \"$i\" -gt 5 ]; do
+while false
+    [ \"$i\" -le 5 ]; do
     echo $i
     false
     i=\"`expr \"$i\" + 1`\"
@@ -9757,14 +9883,746 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation()
+fn test_interpreter_interpret_logical_commands_interprets_until_loop()
 {
     let s = "
-f() {
-    echo abc $*
-    echo jkl
+i=1
+until [ \"$i\" -gt 5 ]; do
+    echo $i
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+2
+3
+4
+5
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_until_loop_with_break()
+{
+    let s = "
+i=1
+until [ \"$i\" -gt 5 ]; do
+    if [ \"$i\" -eq 4 ]; then
+        break
+    fi
+    echo $i
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+2
+3
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_until_loop_with_continue()
+{
+    let s = "
+i=1
+until [ \"$i\" -gt 5 ]; do
+    if [ \"$i\" -eq 4 ]; then
+        i=\"`expr \"$i\" + 1`\"
+        continue
+    fi
+    echo $i
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+2
+3
+5
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_nested_until_loop_with_double_break()
+{
+    let s = "
+i=1
+until [ \"$i\" -gt 5 ]; do
+    j=1
+    until [ \"$j\" -gt 5 ]; do
+        if [ \"$i\" -eq \"$j\" ]; then
+            echo \"$i = $j\"
+            break 2
+        fi
+        echo \"$i =/= $j\"
+        j=\"`expr \"$j\" + 1`\"
+    done
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1 = 1
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_nested_until_loop_with_double_continue()
+{
+    let s = "
+i=1
+until [ \"$i\" -gt 5 ]; do
+    j=1
+    until [ \"$j\" -gt 5 ]; do
+        if [ \"$i\" -eq \"$j\" ]; then
+            echo \"$i = $j\"
+            i=\"`expr \"$i\" + 1`\"
+            continue 2
+        fi
+        echo \"$i =/= $j\"
+        j=\"`expr \"$j\" + 1`\"
+    done
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1 = 1
+2 =/= 1
+2 = 2
+3 =/= 1
+3 =/= 2
+3 = 3
+4 =/= 1
+4 =/= 2
+4 =/= 3
+4 = 4
+5 =/= 1
+5 =/= 2
+5 =/= 3
+5 =/= 4
+5 = 5
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_until_loop_for_errexit_that_is_set()
+{
+    let s = "
+i=1
+until [ \"$i\" -gt 5 ]; do
+    echo $i
+    false
+    i=\"`expr \"$i\" + 1`\"
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.errexit_flag = true;
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+1
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation()
+{
+    let s = "
+f() {
+    echo abc $*
+    echo jkl
+}
+f def ghi
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc def ghi
+jkl
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_settings_of_variables()
+{
+    let s = "
+f() {
+    echo abc $VAR1 $VAR2
+    echo jkl
+}
+VAR1=def VAR2=ghi f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc def ghi
+jkl
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_redirection()
+{
+    let s = "
+f() {
+    echo abc def
+} > test.txt
+f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            assert_eq!(String::new(), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+            let expected_test_txt_content = "
+abc def
+";
+            assert_eq!(String::from(&expected_test_txt_content[1..]), read_file("test.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_with_last_status_zero_as_return_value()
+{
+    let s = "
+f() {
+    echo abc
+    true
+}
+f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_with_last_status_one_as_return_value()
+{
+    let s = "
+f() {
+    echo abc
+    false
+}
+f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_with_return_and_return_value_zero()
+{
+    let s = "
+f() {
+    echo abc
+    return 0
+    echo def
+}
+f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_with_return_and_return_value_one()
+{
+    let s = "
+f() {
+    echo abc
+    return 1
+    echo def
+}
+f
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_nested_function_invocation()
+{
+    let s = "
+f() {
+    echo def $*
+}
+g() {
+    echo abc $1
+    f 123 456
+    echo ghi $2
+}
+g xxx yyy
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            let mut settings = Settings::new();
+            settings.arg0 = String::from("rsush");
+            initialize_builtin_funs(&mut env);
+            initialize_test_builtin_funs(&mut env);
+            initialize_vars(&mut env);
+            write_file("stdin.txt", "Some line\nSecond line\n");
+            exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
+            exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
+            exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(create_file("stderr.txt"))));
+            exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
+            let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            exec.clear_files();
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(false, interp.exec_redirect_flag);
+            let expected_stdout_content = "
+abc xxx
+def 123 456
+ghi yyy
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[sealed_test(before=setup(), after=teardown())]
+fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_errexit_that_is_set()
+{
+    let s = "
+f() {
+    echo abc
+    false
+    echo def
 }
-f def ghi
+f
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9778,6 +10636,7 @@ f def ghi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9789,13 +10648,12 @@ f def ghi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc def ghi
-jkl
+abc
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -9806,14 +10664,11 @@ jkl
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_settings_of_variables()
+fn test_interpreter_interpret_logical_commands_exports_variables()
 {
     let s = "
-f() {
-    echo abc $VAR1 $VAR2
-    echo jkl
-}
-VAR1=def VAR2=ghi f
+export VAR1=abc VAR2=def VAR3=ghi
+./rsush_test env
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9827,6 +10682,7 @@ VAR1=def VAR2=ghi f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9842,11 +10698,10 @@ VAR1=def VAR2=ghi f
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc def ghi
-jkl
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            let stdout_content = read_file("stdout.txt");
+            assert!(stdout_content.contains("VAR1=abc\n"));
+            assert!(stdout_content.contains("VAR2=def\n"));
+            assert!(stdout_content.contains("VAR3=ghi\n"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -9855,13 +10710,13 @@ jkl
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_redirection()
+fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute()
 {
     let s = "
-f() {
-    echo abc def
-} > test.txt
-f
+unset VAR
+VAR=abc
+readonly VAR
+VAR=def
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9875,6 +10730,7 @@ f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9886,31 +10742,25 @@ f
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
-            let expected_test_txt_content = "
-abc def
-";
-            assert_eq!(String::from(&expected_test_txt_content[1..]), read_file("test.txt"));
+            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_with_last_status_zero_as_return_value()
+fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_colon_equal_parameter_expansion()
 {
     let s = "
-f() {
-    echo abc
-    true
-}
-f
+unset VAR
+readonly VAR
+./rsush_test args ${VAR:=abc}
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9924,6 +10774,7 @@ f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9935,15 +10786,12 @@ f
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(false), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
+            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -9951,14 +10799,12 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_with_last_status_one_as_return_value()
+fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_equal_parameter_expansion()
 {
     let s = "
-f() {
-    echo abc
-    false
-}
-f
+unset VAR
+readonly VAR
+./rsush_test args ${VAR=abc}
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -9972,6 +10818,7 @@ f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -9985,13 +10832,10 @@ f
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(ReturnState::Exit(false), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
+            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -9999,15 +10843,12 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_with_return_and_return_value_zero()
+fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_arithmetic_expansion()
 {
     let s = "
-f() {
-    echo abc
-    return 0
-    echo def
-}
-f
+unset VAR
+readonly VAR
+./rsush_test args $((VAR=1))
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10021,6 +10862,7 @@ f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
+            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10032,15 +10874,12 @@ f
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
+            assert_eq!(ReturnState::Exit(false), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
+            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -10048,15 +10887,10 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_with_return_and_return_value_one()
+fn test_interpreter_interpret_logical_commands_complains_on_non_existent_file_for_input_redirection()
 {
     let s = "
-f() {
-    echo abc
-    return 1
-    echo def
-}
-f
+./rsush_test read_fd 0 7 < test.txt
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10085,30 +10919,20 @@ f
             assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            let stderr2_content = read_file("stderr2.txt");
+            assert!(stderr2_content.starts_with("test.txt: "));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_nested_function_invocation()
+fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_for_input_duplicating_redirection()
 {
     let s = "
-f() {
-    echo def $*
-}
-g() {
-    echo abc $1
-    f 123 456
-    echo ghi $2
-}
-g xxx yyy
+./rsush_test read_fd 3 22 3<& xxx
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10133,33 +10957,23 @@ g xxx yyy
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc xxx
-def 123 456
-ghi yyy
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(String::from("test.sh: 1.27: invalid I/O number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_function_definition_and_function_invocation_for_errexit_that_is_set()
+fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number_for_input_duplicating_redirection()
 {
     let s = "
-f() {
-    echo abc
-    false
-    echo def
-}
-f
+./rsush_test read_fd 3 22 3<& 2147483648
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10173,7 +10987,6 @@ f
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.errexit_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10187,25 +11000,21 @@ f
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abc
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(String::from("test.sh: 1.27: too large I/O number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_exports_variables()
+fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_input_duplicating_redirection()
 {
     let s = "
-export VAR1=abc VAR2=def VAR3=ghi
-./rsush_test env
+./rsush_test read_fd 3 22 3<& 4
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10219,7 +11028,6 @@ export VAR1=abc VAR2=def VAR3=ghi
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10231,29 +11039,23 @@ export VAR1=abc VAR2=def VAR3=ghi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(0, status);
-            assert_eq!(0, interp.last_status);
+            assert_eq!(1, status);
+            assert_eq!(1, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let stdout_content = read_file("stdout.txt");
-            assert!(stdout_content.contains("VAR1=abc\n"));
-            assert!(stdout_content.contains("VAR2=def\n"));
-            assert!(stdout_content.contains("VAR3=ghi\n"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(String::from("4: Bad fd number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute()
+fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_for_output_duplicating_redirection()
 {
     let s = "
-unset VAR
-VAR=abc
-readonly VAR
-VAR=def
+./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& xxx
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10267,7 +11069,6 @@ VAR=def
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10285,19 +11086,17 @@ VAR=def
             assert_eq!(false, interp.exec_redirect_flag);
             assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr2.txt"));
+            assert_eq!(String::from("test.sh: 1.47: invalid I/O number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_colon_equal_parameter_expansion()
+fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number_for_output_duplicating_redirection()
 {
     let s = "
-unset VAR
-readonly VAR
-./rsush_test args ${VAR:=abc}
+./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& 2147483648
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10311,7 +11110,6 @@ readonly VAR
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10325,23 +11123,21 @@ readonly VAR
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(false), interp.return_state);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             assert_eq!(String::new(), read_file("stdout.txt"));
-            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::from("test.sh: 1.47: too large I/O number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_equal_parameter_expansion()
+fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_output_duplicating_redirection()
 {
     let s = "
-unset VAR
-readonly VAR
-./rsush_test args ${VAR=abc}
+./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& 3
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10355,7 +11151,6 @@ readonly VAR
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10369,23 +11164,22 @@ readonly VAR
             exec.clear_files();
             assert_eq!(1, status);
             assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(false), interp.return_state);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             assert_eq!(String::new(), read_file("stdout.txt"));
-            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
-            assert_eq!(String::new(), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::from("3: Bad fd number\n"), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_does_not_set_varaible_that_has_readonly_attribute_for_arithmetic_expansion()
+fn test_interpreter_interpret_logical_commands_interprets_commands_for_bug_of_variable_and_pattern_expansion_in_first_case()
 {
     let s = "
-unset VAR
-readonly VAR
-./rsush_test args $((VAR=1))
+VAR='\\\\\\'
+echo $VAR
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10399,7 +11193,6 @@ readonly VAR
             let mut interp = Interpreter::new();
             let mut env = Environment::new();
             let mut settings = Settings::new();
-            settings.nounset_flag = true;
             settings.arg0 = String::from("rsush");
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
@@ -10411,12 +11204,15 @@ readonly VAR
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::Exit(false), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
-            assert_eq!(String::from("VAR: Is read only\n"), read_file("stderr.txt"));
+            let expected_stdout_content = "
+\\\\\\
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -10424,10 +11220,13 @@ readonly VAR
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_non_existent_file_for_input_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_commands_for_bug_of_variable_and_pattern_expansion_in_second_case()
 {
     let s = "
-./rsush_test read_fd 0 7 < test.txt
+VAR1='   '
+echo \"abc${VAR1}def\"
+VAR2=$VAR1
+echo \"ghi${VAR2}jkl\"
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10452,24 +11251,29 @@ fn test_interpreter_interpret_logical_commands_complains_on_non_existent_file_fo
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+abc   def
+ghi   jkl
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            let stderr2_content = read_file("stderr2.txt");
-            assert!(stderr2_content.starts_with("test.txt: "));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_for_input_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_bug_of_case_pattern_expasion()
 {
     let s = "
-./rsush_test read_fd 3 22 3<& xxx
+case abc in
+    *) echo abc;;
+esac
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10494,23 +11298,28 @@ fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_f
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+abc
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("test.sh: 1.27: invalid I/O number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number_for_input_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_for_loop_for_bug_of_for_default_words()
 {
     let s = "
-./rsush_test read_fd 3 22 3<& 2147483648
+for i; do
+    echo $i
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10525,6 +11334,8 @@ fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number
             let mut env = Environment::new();
             let mut settings = Settings::new();
             settings.arg0 = String::from("rsush");
+            let args = vec![String::from("abc"), String::from("def"), String::from("ghi")];
+            settings.current_args_mut().set_args(args);
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
@@ -10535,23 +11346,30 @@ fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+abc
+def
+ghi
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("test.sh: 1.27: too large I/O number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_input_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_first_condition_and_bug_of_returns_of_clause_conditions()
 {
     let s = "
-./rsush_test read_fd 3 22 3<& 4
+if exit 11; then
+    echo abc
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10576,23 +11394,29 @@ fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_in
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(11, status);
+            assert_eq!(11, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("4: Bad fd number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_for_output_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_second_condition_and_bug_of_returns_of_clause_conditions()
 {
     let s = "
-./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& xxx
+if false; then
+    echo abc
+elif exit 11; then
+    echo def
+fi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10617,23 +11441,27 @@ fn test_interpreter_interpret_logical_commands_complains_on_invalid_i_o_number_f
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(11, status);
+            assert_eq!(11, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("test.sh: 1.47: invalid I/O number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number_for_output_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_while_loop_for_bug_of_returns_of_clause_conditions()
 {
     let s = "
-./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& 2147483648
+while exit 11; do
+    echo abc
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10658,23 +11486,27 @@ fn test_interpreter_interpret_logical_commands_complains_on_too_large_i_o_number
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(11, status);
+            assert_eq!(11, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("test.sh: 1.47: too large I/O number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_output_duplicating_redirection()
+fn test_interpreter_interpret_logical_commands_interprets_until_loop_for_bug_of_returns_of_clause_conditions()
 {
     let s = "
-./rsush_test write_2fds 1 2 2 abc def ghi jkl 2>& 3
+until exit 11; do
+    echo abc
+done
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10699,24 +11531,27 @@ fn test_interpreter_interpret_logical_commands_complains_on_bad_fd_number_for_ou
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(1, status);
-            assert_eq!(1, interp.last_status);
-            assert_eq!(ReturnState::None, interp.return_state);
+            assert_eq!(11, status);
+            assert_eq!(11, interp.last_status);
+            assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stdout_content = "
+";
+            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
-            assert_eq!(String::from("3: Bad fd number\n"), read_file("stderr2.txt"));
+            assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_commands_for_bug_of_variable_and_pattern_expansion_in_first_case()
+fn test_interpreter_interpret_logical_commands_performs_expansions_for_bug_of_last_expansion_character()
 {
     let s = "
-VAR='\\\\\\'
-echo $VAR
+unset VAR
+VAR='abc:'
+./rsush_test args abc${VAR}def
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10734,6 +11569,8 @@ echo $VAR
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
+            env.unset_var("IFS");
+            env.set_unexported_var("IFS", ":");
             write_file("stdin.txt", "Some line\nSecond line\n");
             exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
@@ -10746,7 +11583,8 @@ echo $VAR
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-\\\\\\
+abcabc
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -10757,13 +11595,14 @@ echo $VAR
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_commands_for_bug_of_variable_and_pattern_expansion_in_second_case()
+fn test_interpreter_interpret_logical_commands_interprets_err_action()
 {
     let s = "
-VAR1='   '
-echo \"abc${VAR1}def\"
-VAR2=$VAR1
-echo \"ghi${VAR2}jkl\"
+trap './rsush_test args err $?' ERR
+./rsush_test args abc
+./rsush_test exit 2
+./rsush_test exit 3 || ./rsush_test args def
+./rsush_test args ghi
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10781,6 +11620,7 @@ echo \"ghi${VAR2}jkl\"
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
+            interp.set_action_flag();
             write_file("stdin.txt", "Some line\nSecond line\n");
             exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
@@ -10793,8 +11633,11 @@ echo \"ghi${VAR2}jkl\"
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
-abc   def
-ghi   jkl
+abc
+err
+2
+def
+ghi
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -10805,12 +11648,12 @@ ghi   jkl
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_case_clause_for_bug_of_case_pattern_expasion()
+fn test_interpreter_interpret_logical_commands_interprets_debug_action()
 {
     let s = "
-case abc in
-    *) echo abc;;
-esac
+trap './rsush_test args \"$RSUSH_COMMAND\"' DEBUG
+./rsush_test args abc
+./rsush_test args def
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10828,6 +11671,7 @@ esac
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
+            interp.set_action_flag();
             write_file("stdin.txt", "Some line\nSecond line\n");
             exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
@@ -10840,7 +11684,10 @@ esac
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
+./rsush_test args abc
 abc
+./rsush_test args def
+def
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -10851,12 +11698,16 @@ abc
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_for_loop_for_bug_of_for_default_words()
+fn test_interpreter_interpret_logical_commands_interprets_return_action()
 {
     let s = "
-for i; do
-    echo $i
-done
+trap './rsush_test args return' RETURN
+f() {
+    ./rsush_test args abc
+    return 2
+}
+f
+./rsush_test args $?
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10871,11 +11722,10 @@ done
             let mut env = Environment::new();
             let mut settings = Settings::new();
             settings.arg0 = String::from("rsush");
-            let args = vec![String::from("abc"), String::from("def"), String::from("ghi")];
-            settings.current_args_mut().set_args(args);
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
+            interp.set_action_flag();
             write_file("stdin.txt", "Some line\nSecond line\n");
             exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
@@ -10889,8 +11739,8 @@ done
             assert_eq!(false, interp.exec_redirect_flag);
             let expected_stdout_content = "
 abc
-def
-ghi
+return
+2
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -10901,12 +11751,19 @@ ghi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_first_condition_and_bug_of_returns_of_clause_conditions()
+fn test_interpreter_interpret_logical_commands_interprets_caller_builtin()
 {
     let s = "
-if exit 11; then
-    echo abc
-fi
+f() {
+    g
+}
+g() {
+    caller
+    caller 0
+    caller 1
+    caller 2 || ./rsush_test args $?
+}
+f
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10931,11 +11788,16 @@ fi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(11, status);
-            assert_eq!(11, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
+            assert!(interp.call_stack().is_empty());
             let expected_stdout_content = "
+2 test.sh
+2 g test.sh
+10 f test.sh
+1
 ";
             assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
@@ -10946,14 +11808,23 @@ fi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_if_clause_for_second_condition_and_bug_of_returns_of_clause_conditions()
+fn test_interpreter_interpret_logical_commands_interprets_caller_builtin_for_command_substitutions()
 {
     let s = "
-if false; then
-    echo abc
-elif exit 11; then
-    echo def
-fi
+f() {
+    caller 0
+}
+g() {
+    :
+}
+f \"$(
+g
+)\" \"$(
+./rsush_test args abc
+)\" > \"$(
+./rsush_test args out.txt
+)\"
+f
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -10978,13 +11849,13 @@ fi
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(11, status);
-            assert_eq!(11, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert!(interp.call_stack().is_empty());
+            assert_eq!(String::from("7 f test.sh\n"), read_file("out.txt"));
+            assert_eq!(String::from("14 f test.sh\n"), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
@@ -10993,12 +11864,18 @@ fi
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_while_loop_for_bug_of_returns_of_clause_conditions()
+fn test_interpreter_interpret_logical_commands_prints_stack_trace_for_errexit_and_stacktrace()
 {
     let s = "
-while exit 11; do
-    echo abc
-done
+set -e -o stacktrace
+f() {
+    g
+}
+g() {
+    ./rsush_test exit 2
+}
+f
+./rsush_test args abc
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -11023,14 +11900,18 @@ done
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(11, status);
-            assert_eq!(11, interp.last_status);
+            assert_eq!(2, status);
+            assert_eq!(2, interp.last_status);
             assert_eq!(ReturnState::Exit(true), interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
+            assert!(interp.call_stack().is_empty());
+            assert_eq!(String::new(), read_file("stdout.txt"));
+            let expected_stderr_content = "
+test.sh: 6.5: Exited with status 2
+    at g (test.sh) called from test.sh: 3.5
+    at f (test.sh) called from test.sh: 8.1
 ";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::from(&expected_stderr_content[1..]), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
         },
         _ => assert!(false),
@@ -11038,12 +11919,14 @@ done
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_interprets_until_loop_for_bug_of_returns_of_clause_conditions()
+fn test_interpreter_interpret_logical_commands_writes_xtrace_to_xtrace_fd()
 {
     let s = "
-until exit 11; do
-    echo abc
-done
+exec 3> trace.txt
+RSUSH_XTRACEFD=3
+set -x
+./rsush_test args abc def
+set +x
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -11068,27 +11951,34 @@ done
             exec.push_file(2, Rc::new(RefCell::new(create_file("stderr2.txt"))));
             let status = interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
             exec.clear_files();
-            assert_eq!(11, status);
-            assert_eq!(11, interp.last_status);
-            assert_eq!(ReturnState::Exit(true), interp.return_state);
+            assert_eq!(0, status);
+            assert_eq!(0, interp.last_status);
+            assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
+            assert_eq!(String::from("abc\ndef\n"), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
+            let expected_trace_content = "
++ ./rsush_test args abc def
++ set +x
+";
+            assert_eq!(String::from(&expected_trace_content[1..]), read_file("trace.txt"));
         },
         _ => assert!(false),
     }
 }
 
 #[sealed_test(before=setup(), after=teardown())]
-fn test_interpreter_interpret_logical_commands_performs_expansions_for_bug_of_last_expansion_character()
+fn test_interpreter_interpret_logical_commands_writes_xtrace_in_json_format()
 {
     let s = "
-unset VAR
-VAR='abc:'
-./rsush_test args abc${VAR}def
+RSUSH_XTRACEFORMAT=json
+f() {
+    ./rsush_test exit 2
+}
+set -x
+X=abc f
+Y=def
 ";
     let s2 = &s[1..];
     let mut cursor = Cursor::new(s2.as_bytes());
@@ -11106,8 +11996,6 @@ VAR='abc:'
             initialize_builtin_funs(&mut env);
             initialize_test_builtin_funs(&mut env);
             initialize_vars(&mut env);
-            env.unset_var("IFS");
-            env.set_unexported_var("IFS", ":");
             write_file("stdin.txt", "Some line\nSecond line\n");
             exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(open_file("stdin.txt"))));
             exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(create_file("stdout.txt"))));
@@ -11119,13 +12007,20 @@ VAR='abc:'
             assert_eq!(0, interp.last_status);
             assert_eq!(ReturnState::None, interp.return_state);
             assert_eq!(false, interp.exec_redirect_flag);
-            let expected_stdout_content = "
-abcabc
-def
-";
-            assert_eq!(String::from(&expected_stdout_content[1..]), read_file("stdout.txt"));
-            assert_eq!(String::new(), read_file("stderr.txt"));
+            assert_eq!(String::new(), read_file("stdout.txt"));
             assert_eq!(String::new(), read_file("stderr2.txt"));
+            let stderr_content = read_file("stderr.txt");
+            let lines: Vec<&str> = stderr_content.lines().collect();
+            assert_eq!(3, lines.len());
+            assert!(lines[0].starts_with("{\"timestamp\":"));
+            assert!(lines[0].contains(",\"path\":\"test.sh\",\"line\":3,\"column\":5,\"argv\":[\"./rsush_test\",\"exit\",\"2\"],\"assignments\":{},\"status\":2,\"duration\":"));
+            assert!(lines[0].ends_with("}"));
+            assert!(lines[1].starts_with("{\"timestamp\":"));
+            assert!(lines[1].contains(",\"path\":\"test.sh\",\"line\":6,\"column\":1,\"argv\":[\"f\"],\"assignments\":{\"X\":\"abc\"},\"status\":2,\"duration\":"));
+            assert!(lines[1].ends_with("}"));
+            assert!(lines[2].starts_with("{\"timestamp\":"));
+            assert!(lines[2].contains(",\"path\":\"test.sh\",\"line\":7,\"column\":1,\"argv\":[],\"assignments\":{\"Y\":\"def\"},\"status\":0,\"duration\":"));
+            assert!(lines[2].ends_with("}"));
         },
         _ => assert!(false),
     }
@@ -0,0 +1,297 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue
+{
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue
+{
+    pub fn from_str(s: &str) -> JsonValue
+    { JsonValue::String(String::from(s)) }
+
+    pub fn from_strs(ss: &[String]) -> JsonValue
+    { JsonValue::Array(ss.iter().map(|s| JsonValue::String(s.clone())).collect()) }
+
+    pub fn parse(s: &str) -> Option<JsonValue>
+    {
+        let mut char_iter = s.chars().peekable();
+        let value = parse_json_value(&mut char_iter)?;
+        skip_json_spaces(&mut char_iter);
+        match char_iter.next() {
+            None => Some(value),
+            Some(_) => None,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&JsonValue>
+    {
+        match self {
+            JsonValue::Object(fields) => fields.iter().find(|p| p.0 == name).map(|p| &p.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool>
+    {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64>
+    {
+        match self {
+            JsonValue::Integer(n) => Some(*n as f64),
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str>
+    {
+        match self {
+            JsonValue::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]>
+    {
+        match self {
+            JsonValue::Array(elems) => Some(elems.as_slice()),
+            _ => None,
+        }
+    }
+}
+
+fn skip_json_spaces(char_iter: &mut Peekable<Chars<'_>>)
+{
+    while let Some(' ' | '\t' | '\n' | '\r') = char_iter.peek() {
+        char_iter.next();
+    }
+}
+
+fn parse_json_keyword(char_iter: &mut Peekable<Chars<'_>>, keyword: &str, value: JsonValue) -> Option<JsonValue>
+{
+    for c in keyword.chars() {
+        if char_iter.next() != Some(c) {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+fn parse_json_hex4(char_iter: &mut Peekable<Chars<'_>>) -> Option<u32>
+{
+    let mut n = 0u32;
+    for _ in 0..4 {
+        n = n * 16 + char_iter.next()?.to_digit(16)?;
+    }
+    Some(n)
+}
+
+fn parse_json_str(char_iter: &mut Peekable<Chars<'_>>) -> Option<String>
+{
+    match char_iter.next() {
+        Some('"') => (),
+        _ => return None,
+    }
+    let mut s = String::new();
+    loop {
+        match char_iter.next()? {
+            '"' => break,
+            '\\' => {
+                match char_iter.next()? {
+                    '"' => s.push('"'),
+                    '\\' => s.push('\\'),
+                    '/' => s.push('/'),
+                    'b' => s.push('\x08'),
+                    'f' => s.push('\x0c'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'u' => {
+                        let mut n = parse_json_hex4(char_iter)?;
+                        if (0xd800..0xdc00).contains(&n) {
+                            if char_iter.next() != Some('\\') || char_iter.next() != Some('u') {
+                                return None;
+                            }
+                            let n2 = parse_json_hex4(char_iter)?;
+                            if !(0xdc00..0xe000).contains(&n2) {
+                                return None;
+                            }
+                            n = 0x10000 + ((n - 0xd800) << 10) + (n2 - 0xdc00);
+                        }
+                        s.push(char::from_u32(n)?);
+                    },
+                    _ => return None,
+                }
+            },
+            c if (c as u32) < 0x20 => return None,
+            c => s.push(c),
+        }
+    }
+    Some(s)
+}
+
+fn parse_json_number(char_iter: &mut Peekable<Chars<'_>>) -> Option<JsonValue>
+{
+    let mut s = String::new();
+    while let Some(c @ ('0'..='9' | '-' | '+' | '.' | 'e' | 'E')) = char_iter.peek() {
+        s.push(*c);
+        char_iter.next();
+    }
+    s.parse::<f64>().ok().map(JsonValue::Number)
+}
+
+fn parse_json_value(char_iter: &mut Peekable<Chars<'_>>) -> Option<JsonValue>
+{
+    skip_json_spaces(char_iter);
+    match char_iter.peek()? {
+        'n' => parse_json_keyword(char_iter, "null", JsonValue::Null),
+        't' => parse_json_keyword(char_iter, "true", JsonValue::Bool(true)),
+        'f' => parse_json_keyword(char_iter, "false", JsonValue::Bool(false)),
+        '"' => parse_json_str(char_iter).map(JsonValue::String),
+        '[' => {
+            char_iter.next();
+            let mut elems: Vec<JsonValue> = Vec::new();
+            skip_json_spaces(char_iter);
+            if char_iter.peek() == Some(&']') {
+                char_iter.next();
+                return Some(JsonValue::Array(elems));
+            }
+            loop {
+                elems.push(parse_json_value(char_iter)?);
+                skip_json_spaces(char_iter);
+                match char_iter.next()? {
+                    ',' => (),
+                    ']' => break,
+                    _ => return None,
+                }
+            }
+            Some(JsonValue::Array(elems))
+        },
+        '{' => {
+            char_iter.next();
+            let mut fields: Vec<(String, JsonValue)> = Vec::new();
+            skip_json_spaces(char_iter);
+            if char_iter.peek() == Some(&'}') {
+                char_iter.next();
+                return Some(JsonValue::Object(fields));
+            }
+            loop {
+                skip_json_spaces(char_iter);
+                let name = parse_json_str(char_iter)?;
+                skip_json_spaces(char_iter);
+                if char_iter.next()? != ':' {
+                    return None;
+                }
+                let value = parse_json_value(char_iter)?;
+                fields.push((name, value));
+                skip_json_spaces(char_iter);
+                match char_iter.next()? {
+                    ',' => (),
+                    '}' => break,
+                    _ => return None,
+                }
+            }
+            Some(JsonValue::Object(fields))
+        },
+        '-' | '0'..='9' => parse_json_number(char_iter),
+        _ => None,
+    }
+}
+
+pub fn escape_json_str(s: &str) -> String
+{
+    let mut new_s = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => new_s.push_str("\\\""),
+            '\\' => new_s.push_str("\\\\"),
+            '\n' => new_s.push_str("\\n"),
+            '\r' => new_s.push_str("\\r"),
+            '\t' => new_s.push_str("\\t"),
+            '\x08' => new_s.push_str("\\b"),
+            '\x0c' => new_s.push_str("\\f"),
+            c if (c as u32) < 0x20 => new_s.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => new_s.push(c),
+        }
+    }
+    new_s
+}
+
+impl fmt::Display for JsonValue
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Integer(n) => write!(f, "{}", n),
+            JsonValue::Number(n) => {
+                if n.is_finite() {
+                    write!(f, "{}", n)
+                } else {
+                    write!(f, "null")
+                }
+            },
+            JsonValue::String(s) => write!(f, "\"{}\"", escape_json_str(s.as_str())),
+            JsonValue::Array(elems) => {
+                write!(f, "[")?;
+                let mut is_first = true;
+                for elem in elems.iter() {
+                    if !is_first {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", elem)?;
+                    is_first = false;
+                }
+                write!(f, "]")
+            },
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+                let mut is_first = true;
+                for (name, value) in fields.iter() {
+                    if !is_first {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", escape_json_str(name.as_str()), value)?;
+                    is_first = false;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,75 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+
+#[test]
+fn test_escape_json_str_escapes_special_characters()
+{
+    assert_eq!(String::from("abc\\\"def\\\\ghi\\n\\t\\u0001"), escape_json_str("abc\"def\\ghi\n\t\x01"));
+}
+
+#[test]
+fn test_json_value_fmt_formats_json_values()
+{
+    let value = JsonValue::Object(vec![
+        (String::from("null"), JsonValue::Null),
+        (String::from("bool"), JsonValue::Bool(true)),
+        (String::from("int"), JsonValue::Number(12.0)),
+        (String::from("large_int"), JsonValue::Integer(9007199254740993)),
+        (String::from("float"), JsonValue::Number(1.5)),
+        (String::from("str"), JsonValue::from_str("a\"b")),
+        (String::from("array"), JsonValue::from_strs(&[String::from("abc"), String::from("def")])),
+        (String::from("object"), JsonValue::Object(Vec::new()))
+    ]);
+    let expected_s = "{\"null\":null,\"bool\":true,\"int\":12,\"large_int\":9007199254740993,\"float\":1.5,\"str\":\"a\\\"b\",\"array\":[\"abc\",\"def\"],\"object\":{}}";
+    assert_eq!(String::from(expected_s), format!("{}", value));
+}
+
+#[test]
+fn test_json_value_parse_parses_json_values()
+{
+    let s = " {\"null\": null, \"bool\": false, \"num\": -12.5e1, \"str\": \"a\\\"b\\u0041\\ud83d\\ude00\", \"array\": [1, \"x\", []], \"object\": {}} ";
+    let expected_value = JsonValue::Object(vec![
+        (String::from("null"), JsonValue::Null),
+        (String::from("bool"), JsonValue::Bool(false)),
+        (String::from("num"), JsonValue::Number(-125.0)),
+        (String::from("str"), JsonValue::from_str("a\"bA\u{1f600}")),
+        (String::from("array"), JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::from_str("x"), JsonValue::Array(Vec::new())])),
+        (String::from("object"), JsonValue::Object(Vec::new()))
+    ]);
+    assert_eq!(Some(expected_value), JsonValue::parse(s));
+}
+
+#[test]
+fn test_json_value_parse_complains_on_invalid_json()
+{
+    assert_eq!(None, JsonValue::parse("{\"abc\": }"));
+    assert_eq!(None, JsonValue::parse("[1, 2"));
+    assert_eq!(None, JsonValue::parse("\"abc"));
+    assert_eq!(None, JsonValue::parse("nul"));
+    assert_eq!(None, JsonValue::parse("1 2"));
+}
+
+#[test]
+fn test_json_value_get_returns_field_value()
+{
+    let value = JsonValue::parse("{\"id\": 1, \"method\": \"initialize\"}").unwrap();
+    assert_eq!(Some(1.0), value.get("id").and_then(|v| v.as_f64()));
+    assert_eq!(Some("initialize"), value.get("method").and_then(|v| v.as_str()));
+    assert_eq!(None, value.get("params"));
+}
@@ -17,6 +17,7 @@
 //
 use std::collections::HashMap;
 use std::fmt;
+use std::mem::take;
 use std::io::*;
 use std::rc::*;
 use crate::io::*;
@@ -361,8 +362,9 @@ pub struct Lexer<'a>
 {
     reader: &'a mut dyn CharRead,
     pushed_chars: Vec<(char, Position)>,
-    pushed_tokens: Vec<(Token, Position)>,
-    pushed_arith_tokens: Vec<(ArithmeticToken, Position)>,
+    pushed_tokens: Vec<(Token, Position, Position)>,
+    pushed_arith_tokens: Vec<(ArithmeticToken, Position, Position)>,
+    token_end_poses: Vec<Position>,
     state_stack: Vec<State>,
     current_state: State,
     path: String,
@@ -370,6 +372,9 @@ pub struct Lexer<'a>
     backquote_column_inc: u64,
     content_for_verbose: String,
     has_ignored_eof: bool,
+    has_kept_comments: bool,
+    comments: Vec<(Position, String)>,
+    closing_tokens: Vec<(Token, Position)>,
     first_keywords: HashMap<String, Token>,
     second_keywords: HashMap<String, Token>,
 }
@@ -402,6 +407,7 @@ impl<'a> Lexer<'a>
             pushed_chars: Vec::new(),
             pushed_tokens: Vec::new(),
             pushed_arith_tokens: Vec::new(),
+            token_end_poses: Vec::new(),
             state_stack: Vec::new(),
             current_state: State::Initial,
             path: String::from(path),
@@ -409,6 +415,9 @@ impl<'a> Lexer<'a>
             backquote_column_inc,
             content_for_verbose: String::new(),
             has_ignored_eof: is_ignored_eof,
+            has_kept_comments: false,
+            comments: Vec::new(),
+            closing_tokens: Vec::new(),
             first_keywords,
             second_keywords,
         }
@@ -419,6 +428,22 @@ impl<'a> Lexer<'a>
 
     pub fn pos(&self) -> Position
     { self.pos }
+
+    pub fn token_end_pos(&self) -> Position
+    {
+        match self.token_end_poses.last() {
+            Some(pos) => *pos,
+            None => self.pos,
+        }
+    }
+
+    fn pop_token_end_pos(&mut self) -> Position
+    {
+        match self.token_end_poses.pop() {
+            Some(pos) => pos,
+            None => self.pos,
+        }
+    }
     
     pub fn content_for_verbose(&self) -> String
     { self.content_for_verbose.clone() }
@@ -426,6 +451,15 @@ impl<'a> Lexer<'a>
     pub fn clear_content_for_verbose(&mut self)
     { self.content_for_verbose.clear(); }
 
+    pub fn set_kept_comments(&mut self, b: bool)
+    { self.has_kept_comments = b; }
+
+    pub fn comments(&self) -> &[(Position, String)]
+    { self.comments.as_slice() }
+
+    pub fn closing_tokens(&self) -> &[(Token, Position)]
+    { self.closing_tokens.as_slice() }
+
     pub fn push_initial(&mut self)
     { self.push_state(State::Initial); }
 
@@ -463,6 +497,25 @@ impl<'a> Lexer<'a>
             None        => (),
         }
     }
+
+    pub fn reset_states(&mut self)
+    {
+        self.state_stack.clear();
+        self.current_state = State::Initial;
+    }
+
+    pub fn skip_line(&mut self, settings: &Settings) -> ParserResult<bool>
+    {
+        self.pushed_tokens.clear();
+        self.pushed_arith_tokens.clear();
+        loop {
+            match self.get_char(settings)? {
+                (None, _) => break Ok(false),
+                (Some('\n'), _) => break Ok(true),
+                (Some(_), _) => (),
+            }
+        }
+    }
     
     fn get_char(&mut self, settings: &Settings) -> ParserResult<(Option<char>, Position)>
     {
@@ -489,6 +542,7 @@ impl<'a> Lexer<'a>
         };
         match res {
             Ok((Some(c), pos)) => {
+                self.pos.offset += c.len_utf8() as u64;
                 if c == '\n' {
                     self.pos.line += 1;
                     self.pos.column = 1;
@@ -516,8 +570,9 @@ impl<'a> Lexer<'a>
         }
     }
     
-    fn skip_comment(&mut self, is_arith_expr: bool, settings: &Settings) -> ParserResult<()>
+    fn skip_comment(&mut self, comment_pos: &Position, is_arith_expr: bool, settings: &Settings) -> ParserResult<()>
     {
+        let mut comment = String::new();
         loop {
             match self.get_char(settings)? {
                 (None, _) => break,
@@ -527,7 +582,13 @@ impl<'a> Lexer<'a>
                     }
                     break;
                 },
-                (Some(_), _) => (),
+                (Some(c), _) => comment.push(c),
+            }
+        }
+        if self.has_kept_comments {
+            match self.comments.last() {
+                Some((last_pos, _)) if last_pos.line >= comment_pos.line => (),
+                _ => self.comments.push((*comment_pos, comment)),
             }
         }
         Ok(())
@@ -549,7 +610,7 @@ impl<'a> Lexer<'a>
                         },
                     }
                 },
-                (Some('#'), _) => self.skip_comment(is_arith_expr, settings)?,
+                (Some('#'), pos) => self.skip_comment(&pos, is_arith_expr, settings)?,
                 (Some(c @ '\n'), pos) if !is_arith_expr => {
                     self.unget_char(c, &pos, settings);
                     break;
@@ -1213,9 +1274,31 @@ impl<'a> Lexer<'a>
     }
     
     pub fn next_token(&mut self, settings: &Settings) -> ParserResult<(Token, Position)>
+    {
+        let is_pushed_token = !self.pushed_tokens.is_empty();
+        let pushed_end_pos = self.pushed_tokens.last().map(|t| t.2);
+        let saved_token_end_poses = take(&mut self.token_end_poses);
+        let res = self.read_token(settings);
+        self.token_end_poses = saved_token_end_poses;
+        match &res {
+            Ok(_) => self.token_end_poses.push(pushed_end_pos.unwrap_or(self.pos)),
+            Err(_) => (),
+        }
+        if self.has_kept_comments && !is_pushed_token {
+            match &res {
+                Ok((token @ (Token::Then | Token::Do | Token::Elif | Token::Else | Token::Fi | Token::Done | Token::Esac | Token::RBrace | Token::RParen | Token::SemiSemi), pos)) => {
+                    self.closing_tokens.push((token.clone(), *pos));
+                },
+                _ => (),
+            }
+        }
+        res
+    }
+
+    fn read_token(&mut self, settings: &Settings) -> ParserResult<(Token, Position)>
     {
         match self.pushed_tokens.pop() {
-            Some((token, pos)) => Ok((token, pos)),
+            Some((token, pos, _)) => Ok((token, pos)),
             None => {
                 match &self.current_state {
                     State::InArithmeticExpression => {
@@ -1390,12 +1473,28 @@ impl<'a> Lexer<'a>
     }
 
     pub fn undo_token(&mut self, token: &Token, pos: &Position)
-    { self.pushed_tokens.push((token.clone(), *pos)); }
+    {
+        let end_pos = self.pop_token_end_pos();
+        self.pushed_tokens.push((token.clone(), *pos, end_pos));
+    }
     
     pub fn next_arith_token(&mut self, settings: &Settings) -> ParserResult<(ArithmeticToken, Position)>
+    {
+        let pushed_end_pos = self.pushed_arith_tokens.last().map(|t| t.2);
+        let saved_token_end_poses = take(&mut self.token_end_poses);
+        let res = self.read_arith_token(settings);
+        self.token_end_poses = saved_token_end_poses;
+        match &res {
+            Ok(_) => self.token_end_poses.push(pushed_end_pos.unwrap_or(self.pos)),
+            Err(_) => (),
+        }
+        res
+    }
+
+    fn read_arith_token(&mut self, settings: &Settings) -> ParserResult<(ArithmeticToken, Position)>
     {
         match self.pushed_arith_tokens.pop() {
-            Some((arith_token, pos)) => Ok((arith_token, pos)),
+            Some((arith_token, pos, _)) => Ok((arith_token, pos)),
             None => {
                 match &self.current_state {
                     State::InArithmeticExpression | State::InArithmeticExpressionAndParentheses | State::InArithmeticExpressionAndParameter => {
@@ -1656,7 +1755,10 @@ impl<'a> Lexer<'a>
     }
 
     pub fn undo_arith_token(&mut self, arith_token: &ArithmeticToken, pos: &Position)
-    { self.pushed_arith_tokens.push((arith_token.clone(), *pos)); }
+    {
+        let end_pos = self.pop_token_end_pos();
+        self.pushed_arith_tokens.push((arith_token.clone(), *pos, end_pos));
+    }
 }
 
 #[cfg(test)]
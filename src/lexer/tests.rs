@@ -235,6 +235,78 @@ fn test_lexer_get_char_reads_newline()
     assert_eq!(String::new(), lexer.content_for_verbose);
 }
 
+#[test]
+fn test_lexer_get_char_counts_byte_offsets()
+{
+    let s = "ąb\nc";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let settings = Settings::new();
+    let mut offsets: Vec<u64> = Vec::new();
+    loop {
+        match lexer.get_char(&settings) {
+            Ok((Some(_), pos)) => offsets.push(pos.offset),
+            Ok((None, pos)) => {
+                offsets.push(pos.offset);
+                break;
+            },
+            Err(_) => assert!(false),
+        }
+    }
+    assert_eq!(vec![0, 2, 3, 4, 5], offsets);
+}
+
+#[test]
+fn test_lexer_token_end_pos_returns_end_positions_for_undone_tokens()
+{
+    let s = "abc  def;";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let settings = Settings::new();
+    let _res = lexer.next_token(&settings);
+    assert_eq!(4, lexer.token_end_pos().column);
+    match lexer.next_token(&settings) {
+        Ok((token, pos)) => {
+            assert_eq!(9, lexer.token_end_pos().column);
+            assert_eq!(8, lexer.token_end_pos().offset);
+            lexer.undo_token(&token, &pos);
+        },
+        Err(_) => assert!(false),
+    }
+    assert_eq!(4, lexer.token_end_pos().column);
+    let _res = lexer.next_token(&settings);
+    assert_eq!(9, lexer.token_end_pos().column);
+    let _res = lexer.next_token(&settings);
+    assert_eq!(10, lexer.token_end_pos().column);
+}
+
+#[test]
+fn test_lexer_token_end_pos_returns_end_positions_for_many_undone_tokens()
+{
+    let s = "a b c d e f g h i j k l m n o p q r s t u v w x y z";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let settings = Settings::new();
+    let mut tokens: Vec<(Token, Position)> = Vec::new();
+    for _ in 0..26 {
+        match lexer.next_token(&settings) {
+            Ok(pair) => tokens.push(pair),
+            Err(_) => assert!(false),
+        }
+    }
+    for (i, (token, pos)) in tokens.iter().enumerate().rev() {
+        assert_eq!((i as u64) * 2 + 2, lexer.token_end_pos().column);
+        lexer.undo_token(token, pos);
+    }
+    for i in 0..26 {
+        let _res = lexer.next_token(&settings);
+        assert_eq!((i as u64) * 2 + 2, lexer.token_end_pos().column);
+    }
+}
+
 #[test]
 fn test_lexer_next_token_returns_token()
 {
@@ -436,6 +508,28 @@ fn test_lexer_next_token_skips_comment()
     assert_eq!(String::new(), lexer.content_for_verbose);
 }
 
+#[test]
+fn test_lexer_next_token_keeps_comment()
+{
+    let s = "# comment\n";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    lexer.set_kept_comments(true);
+    let settings = Settings::new();
+    match lexer.next_token(&settings) {
+        Ok((Token::Newline, pos)) => {
+            assert_eq!(1, pos.line);
+            assert_eq!(10, pos.column);
+        },
+        _ => assert!(false),
+    }
+    assert_eq!(1, lexer.comments().len());
+    assert_eq!(1, lexer.comments()[0].0.line);
+    assert_eq!(1, lexer.comments()[0].0.column);
+    assert_eq!(String::from(" comment"), lexer.comments()[0].1);
+}
+
 #[test]
 fn test_lexer_next_token_skips_comment_for_eof()
 {
@@ -2505,7 +2599,7 @@ fn test_lexer_next_token_returns_word_with_command()
                     assert_eq!(false, logical_commands[0].first_command.is_negative);
                     assert_eq!(1, logical_commands[0].first_command.commands.len());
                     match &(*logical_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(3, pos.column);
@@ -2580,7 +2674,7 @@ fn test_lexer_next_token_returns_word_with_nested_command()
                     assert_eq!(false, logical_commands[0].first_command.is_negative);
                     assert_eq!(1, logical_commands[0].first_command.commands.len());
                     match &(*logical_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(3, pos.column);
@@ -2623,7 +2717,7 @@ fn test_lexer_next_token_returns_word_with_nested_command()
                                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                                     match &(*logical_commands2[0].first_command.commands[0]) {
-                                        Command::Simple(path, pos, simple_command2) => {
+                                        Command::Simple(path, pos, _, simple_command2) => {
                                             assert_eq!(&String::from("test.sh"), path);
                                             assert_eq!(1, pos.line);
                                             assert_eq!(14, pos.column);
@@ -2694,7 +2788,7 @@ fn test_lexer_next_token_returns_word_with_command_for_backquote()
                     assert_eq!(2, logical_commands[0].first_command.pos.column);
                     assert_eq!(1, logical_commands[0].first_command.commands.len());
                     match &(*logical_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(2, pos.column);
@@ -2770,7 +2864,7 @@ fn test_lexer_next_token_returns_word_with_nested_command_for_backquote()
                     assert_eq!(false, logical_commands[0].first_command.is_negative);
                     assert_eq!(1, logical_commands[0].first_command.commands.len());
                     match &(*logical_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(2, pos.column);
@@ -2813,7 +2907,7 @@ fn test_lexer_next_token_returns_word_with_nested_command_for_backquote()
                                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                                     match &(*logical_commands2[0].first_command.commands[0]) {
-                                        Command::Simple(path, pos, simple_command2) => {
+                                        Command::Simple(path, pos, _, simple_command2) => {
                                             assert_eq!(&String::from("test.sh"), path);
                                             assert_eq!(1, pos.line);
                                             assert_eq!(13, pos.column);
@@ -2872,13 +2966,13 @@ fn test_lexer_next_token_returns_word_with_arithmetic_expression()
             assert_eq!(1, pos.column);
             assert_eq!(1, word_elems.len());
             match &word_elems[0] {
-                WordElement::Simple(SimpleWordElement::ArithmeticExpression(ArithmeticExpression::Binary(expr_path, expr_pos, expr1, op, expr2))) => {
+                WordElement::Simple(SimpleWordElement::ArithmeticExpression(ArithmeticExpression::Binary(expr_path, expr_pos, _, expr1, op, expr2))) => {
                     assert_eq!(&String::from("test.sh"), expr_path);
                     assert_eq!(1, expr_pos.line);
                     assert_eq!(4, expr_pos.column);
                     assert_eq!(BinaryOperator::Add, *op);
                     match &(**expr1) {
-                        ArithmeticExpression::Number(path1, pos1, n1) => {
+                        ArithmeticExpression::Number(path1, pos1, _, n1) => {
                             assert_eq!(&String::from("test.sh"), path1);
                             assert_eq!(1, pos1.line);
                             assert_eq!(4, pos1.column);
@@ -2887,7 +2981,7 @@ fn test_lexer_next_token_returns_word_with_arithmetic_expression()
                         _ => assert!(false),
                     }
                     match &(**expr2) {
-                        ArithmeticExpression::Number(path2, pos2, n2) => {
+                        ArithmeticExpression::Number(path2, pos2, _, n2) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(8, pos2.column);
@@ -2934,7 +3028,7 @@ fn test_lexer_next_token_returns_word_with_command_in_doubly_quoted_string()
                             assert_eq!(false, logical_commands[0].first_command.is_negative);
                             assert_eq!(1, logical_commands[0].first_command.commands.len());
                             match &(*logical_commands[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(1, pos.line);
                                     assert_eq!(4, pos.column);
@@ -3015,7 +3109,7 @@ fn test_lexer_next_token_returns_word_with_command_in_doubly_quoted_string_for_b
                             assert_eq!(false, logical_commands[0].first_command.is_negative);
                             assert_eq!(1, logical_commands[0].first_command.commands.len());
                             match &(*logical_commands[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(1, pos.line);
                                     assert_eq!(3, pos.column);
@@ -3083,13 +3177,13 @@ fn test_lexer_next_token_returns_word_with_arithmetic_expression_in_doubly_quote
                 WordElement::DoublyQuoted(simple_word_elems) => {
                     assert_eq!(1, simple_word_elems.len());
                     match &simple_word_elems[0] {
-                        SimpleWordElement::ArithmeticExpression(ArithmeticExpression::Binary(expr_path, expr_pos, expr1, op, expr2)) => {
+                        SimpleWordElement::ArithmeticExpression(ArithmeticExpression::Binary(expr_path, expr_pos, _, expr1, op, expr2)) => {
                             assert_eq!(&String::from("test.sh"), expr_path);
                             assert_eq!(1, expr_pos.line);
                             assert_eq!(5, expr_pos.column);
                             assert_eq!(BinaryOperator::Add, *op);
                             match &(**expr1) {
-                                ArithmeticExpression::Number(path1, pos1, n1) => {
+                                ArithmeticExpression::Number(path1, pos1, _, n1) => {
                                     assert_eq!(&String::from("test.sh"), path1);
                                     assert_eq!(1, pos1.line);
                                     assert_eq!(5, pos1.column);
@@ -3098,7 +3192,7 @@ fn test_lexer_next_token_returns_word_with_arithmetic_expression_in_doubly_quote
                                 _ => assert!(false),
                             }
                             match &(**expr2) {
-                                ArithmeticExpression::Number(path2, pos2, n2) => {
+                                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                                     assert_eq!(&String::from("test.sh"), path2);
                                     assert_eq!(1, pos2.line);
                                     assert_eq!(9, pos2.column);
@@ -3422,7 +3516,7 @@ fn test_lexer_next_token_complains_on_unexpected_token_for_command_in_backquote(
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(12, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `{` started at 1.2"), msg);
             assert_eq!(false, is_cont);
         },
         _ => assert!(false),
@@ -0,0 +1,49 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+mod args;
+mod builtins;
+pub mod dump;
+mod completion;
+mod env;
+mod exec;
+pub mod formatter;
+mod history;
+mod interp;
+mod io;
+mod iter;
+mod json;
+mod lexer;
+mod line_editor;
+pub mod lint;
+mod loadable;
+pub mod lsp;
+mod macros;
+pub mod parser;
+pub mod settings;
+mod shell;
+mod signals;
+mod utils;
+mod vars;
+
+#[cfg(test)]
+mod test_builtins;
+#[cfg(test)]
+mod test_helpers;
+
+pub use builtins::Context;
+pub use shell::Shell;
@@ -0,0 +1,586 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::borrow::Cow;
+use std::cell::*;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::io::*;
+use std::path;
+use std::path::*;
+use std::rc::*;
+use rustyline;
+use rustyline::completion::Completer;
+use rustyline::completion::FilenameCompleter;
+use rustyline::completion::Pair;
+use rustyline::Context;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::ValidationContext;
+use rustyline::validate::ValidationResult;
+use rustyline::validate::Validator;
+use rustyline::Helper;
+use crate::completion::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::io::*;
+use crate::lexer::*;
+use crate::parser::*;
+use crate::settings::*;
+use crate::utils::*;
+
+const KEYWORD_COLOR: &'static str = "\x1b[1;34m";
+const STRING_COLOR: &'static str = "\x1b[33m";
+const PARAMETER_COLOR: &'static str = "\x1b[36m";
+const OPERATOR_COLOR: &'static str = "\x1b[35m";
+const COMMENT_COLOR: &'static str = "\x1b[90m";
+const UNKNOWN_COMMAND_COLOR: &'static str = "\x1b[31m";
+const RESET_COLOR: &'static str = "\x1b[0m";
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum ExpectedWord
+{
+    Command,
+    Argument,
+    Name,
+    ThirdWord,
+    Pattern,
+}
+
+fn char_len_at(s: &str, i: usize) -> usize
+{ s[i..].chars().next().map(|c| c.len_utf8()).unwrap_or(1) }
+
+fn find_closing_byte(s: &str, i: usize, end: usize, opening: u8, closing: u8) -> usize
+{
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut j = i;
+    while j < end {
+        match bytes[j] {
+            b'\\' => j += 1,
+            b if b == opening => depth += 1,
+            b if b == closing => {
+                depth -= 1;
+                if depth == 0 {
+                    return j + 1;
+                }
+            },
+            _ => (),
+        }
+        j += char_len_at(s, j.min(end - 1));
+    }
+    end
+}
+
+fn add_word_spans(s: &str, start: usize, end: usize, spans: &mut Vec<(usize, usize, &'static str)>)
+{
+    let bytes = s.as_bytes();
+    let mut i = start;
+    while i < end {
+        match bytes[i] {
+            b'\\' => {
+                i += 1;
+                if i < end {
+                    i += char_len_at(s, i);
+                }
+            },
+            b'\'' => {
+                let j = match s[(i + 1)..end].find('\'') {
+                    Some(k) => i + 1 + k + 1,
+                    None => end,
+                };
+                spans.push((i, j, STRING_COLOR));
+                i = j;
+            },
+            b'"' => {
+                let mut j = i + 1;
+                while j < end && bytes[j] != b'"' {
+                    if bytes[j] == b'\\' {
+                        j += 1;
+                    }
+                    j += 1;
+                }
+                let j = (j + 1).min(end);
+                spans.push((i, j, STRING_COLOR));
+                i = j;
+            },
+            b'`' => {
+                let j = match s[(i + 1)..end].find('`') {
+                    Some(k) => i + 1 + k + 1,
+                    None => end,
+                };
+                spans.push((i, j, PARAMETER_COLOR));
+                i = j;
+            },
+            b'$' => {
+                let j = match bytes.get(i + 1) {
+                    Some(b'{') if i + 1 < end => find_closing_byte(s, i + 1, end, b'{', b'}'),
+                    Some(b'(') if i + 1 < end => find_closing_byte(s, i + 1, end, b'(', b')'),
+                    Some(b'0'..=b'9' | b'@' | b'*' | b'#' | b'?' | b'$' | b'!' | b'-') if i + 1 < end => i + 2,
+                    Some(b'A'..=b'Z' | b'a'..=b'z' | b'_') if i + 1 < end => {
+                        let mut k = i + 1;
+                        while k < end && (bytes[k].is_ascii_alphanumeric() || bytes[k] == b'_') {
+                            k += 1;
+                        }
+                        k
+                    },
+                    _ => i + 1,
+                };
+                if j > i + 1 {
+                    spans.push((i, j, PARAMETER_COLOR));
+                }
+                i = j;
+            },
+            _ => i += char_len_at(s, i),
+        }
+    }
+}
+
+fn is_literal_word_str(s: &str) -> bool
+{ !s.contains(['$', '`', '\'', '"', '\\']) }
+
+fn is_assignment_str(s: &str) -> bool
+{
+    match s.split_once('=') {
+        Some((name, _)) => is_name_str(name),
+        None => false,
+    }
+}
+
+struct LineScan
+{
+    spans: Vec<(usize, usize, &'static str)>,
+    expected_word: ExpectedWord,
+    is_redirect_target: bool,
+    command_words: Vec<String>,
+    has_error: bool,
+}
+
+fn scan_line(s: &str, settings: &Settings, is_known_command: &dyn Fn(&str) -> bool) -> LineScan
+{
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("(standard input)", &Position::new(1, 1), &mut cr, 0, false);
+    lexer.set_kept_comments(true);
+    let mut spans: Vec<(usize, usize, &'static str)> = Vec::new();
+    let mut expected_word = ExpectedWord::Command;
+    let mut is_case = false;
+    let mut is_redirect_target = false;
+    let mut is_here_doc_word = false;
+    let mut pending_command: Option<(usize, usize, bool)> = None;
+    let mut command_words: Vec<String> = Vec::new();
+    let mut has_error = false;
+    let mut last_end: usize = 0;
+    loop {
+        let is_pushed = if is_here_doc_word {
+            lexer.push_here_doc_word();
+            true
+        } else if is_redirect_target {
+            false
+        } else {
+            match expected_word {
+                ExpectedWord::Command | ExpectedWord::Pattern => {
+                    lexer.push_first_word();
+                    true
+                },
+                ExpectedWord::ThirdWord => {
+                    lexer.push_third_word();
+                    true
+                },
+                _ => false,
+            }
+        };
+        let res = lexer.next_token(settings);
+        if is_pushed {
+            lexer.pop_state();
+        }
+        let (token, pos) = match res {
+            Ok(tmp_res) => tmp_res,
+            Err(_) => {
+                match pending_command.take() {
+                    Some((start, end, false)) => spans.push((start, end, UNKNOWN_COMMAND_COLOR)),
+                    _ => (),
+                }
+                add_word_spans(s, last_end.min(s.len()), s.len(), &mut spans);
+                has_error = true;
+                break;
+            },
+        };
+        let start = (pos.offset as usize).min(s.len());
+        let end = (lexer.token_end_pos().offset as usize).min(s.len());
+        match (pending_command.take(), &token) {
+            (Some(_), Token::LParen) => (),
+            (Some((command_start, command_end, false)), _) => spans.push((command_start, command_end, UNKNOWN_COMMAND_COLOR)),
+            (_, _) => (),
+        }
+        match token {
+            Token::EOF => break,
+            Token::For | Token::Case => {
+                spans.push((start, end, KEYWORD_COLOR));
+                command_words.clear();
+                is_case = match token {
+                    Token::Case => true,
+                    _ => false,
+                };
+                expected_word = ExpectedWord::Name;
+            },
+            Token::In => {
+                spans.push((start, end, KEYWORD_COLOR));
+                command_words.clear();
+                expected_word = if is_case { ExpectedWord::Pattern } else { ExpectedWord::Argument };
+            },
+            Token::Fi | Token::Done | Token::Esac | Token::RBrace => {
+                spans.push((start, end, KEYWORD_COLOR));
+                command_words.clear();
+                expected_word = ExpectedWord::Argument;
+            },
+            Token::If | Token::Then | Token::Else | Token::Elif | Token::Do | Token::While | Token::Until | Token::LBrace | Token::Excl => {
+                spans.push((start, end, KEYWORD_COLOR));
+                command_words.clear();
+                expected_word = ExpectedWord::Command;
+            },
+            Token::Word(_) => {
+                let word_s = &s[start..end];
+                if is_redirect_target {
+                    add_word_spans(s, start, end, &mut spans);
+                    is_redirect_target = false;
+                } else {
+                    match expected_word {
+                        ExpectedWord::Command => {
+                            if is_assignment_str(word_s) {
+                                add_word_spans(s, start, end, &mut spans);
+                            } else if is_literal_word_str(word_s) {
+                                pending_command = Some((start, end, is_known_command(word_s)));
+                                command_words = vec![String::from(word_s)];
+                                expected_word = ExpectedWord::Argument;
+                            } else {
+                                add_word_spans(s, start, end, &mut spans);
+                                command_words = vec![String::from(word_s)];
+                                expected_word = ExpectedWord::Argument;
+                            }
+                        },
+                        ExpectedWord::Argument => {
+                            add_word_spans(s, start, end, &mut spans);
+                            command_words.push(String::from(word_s));
+                        },
+                        ExpectedWord::Pattern => add_word_spans(s, start, end, &mut spans),
+                        ExpectedWord::Name => {
+                            add_word_spans(s, start, end, &mut spans);
+                            expected_word = ExpectedWord::ThirdWord;
+                        },
+                        ExpectedWord::ThirdWord => {
+                            add_word_spans(s, start, end, &mut spans);
+                            expected_word = ExpectedWord::Argument;
+                        },
+                    }
+                }
+            },
+            Token::Newline => {
+                command_words.clear();
+                if expected_word != ExpectedWord::Pattern {
+                    expected_word = ExpectedWord::Command;
+                }
+            },
+            Token::Semi | Token::Amp | Token::Bar | Token::AmpAmp | Token::BarBar | Token::LParen => {
+                spans.push((start, end, OPERATOR_COLOR));
+                command_words.clear();
+                if expected_word != ExpectedWord::Pattern {
+                    expected_word = ExpectedWord::Command;
+                }
+            },
+            Token::RParen => {
+                spans.push((start, end, OPERATOR_COLOR));
+                command_words.clear();
+                expected_word = ExpectedWord::Command;
+            },
+            Token::SemiSemi => {
+                spans.push((start, end, OPERATOR_COLOR));
+                command_words.clear();
+                expected_word = ExpectedWord::Pattern;
+            },
+            Token::LessLess(_) | Token::LessLessMinus(_) => {
+                spans.push((start, end, OPERATOR_COLOR));
+                is_here_doc_word = true;
+            },
+            Token::HereDocWord(_, _) => {
+                spans.push((start, end, STRING_COLOR));
+                is_here_doc_word = false;
+            },
+            Token::HereDoc(_, _, _) => (),
+            _ => {
+                spans.push((start, end, OPERATOR_COLOR));
+                is_redirect_target = true;
+            },
+        }
+        last_end = end;
+    }
+    for (pos, _) in lexer.comments() {
+        let start = (pos.offset as usize).min(s.len());
+        let end = match s[start..].find('\n') {
+            Some(i) => start + i,
+            None => s.len(),
+        };
+        spans.push((start, end, COMMENT_COLOR));
+    }
+    spans.sort_by_key(|span| span.0);
+    LineScan {
+        spans,
+        expected_word,
+        is_redirect_target,
+        command_words,
+        has_error,
+    }
+}
+
+pub fn highlight_spans(s: &str, settings: &Settings, is_known_command: &dyn Fn(&str) -> bool) -> Vec<(usize, usize, &'static str)>
+{ scan_line(s, settings, is_known_command).spans }
+
+pub fn highlight_str(s: &str, spans: &[(usize, usize, &'static str)]) -> String
+{
+    let mut new_s = String::new();
+    let mut i: usize = 0;
+    for (start, end, color) in spans {
+        if *start < i || *end <= *start || !s.is_char_boundary(*start) || !s.is_char_boundary(*end) {
+            continue;
+        }
+        new_s.push_str(&s[i..*start]);
+        new_s.push_str(color);
+        new_s.push_str(&s[*start..*end]);
+        new_s.push_str(RESET_COLOR);
+        i = *end;
+    }
+    new_s.push_str(&s[i..]);
+    new_s
+}
+
+pub fn is_incomplete_input(s: &str, settings: &Settings) -> bool
+{
+    let mut input = String::from(s);
+    input.push('\n');
+    let mut cursor = Cursor::new(input.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("(standard input)", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    match parser.parse_logical_commands(&mut lexer, settings) {
+        Err(ParserError::Syntax(_, _, _, true)) => true,
+        _ => false,
+    }
+}
+
+fn find_word_start(line: &str, pos: usize) -> usize
+{
+    match line[..pos].rfind([' ', '\t', '\n', ';', '|', '&', '(', ')', '<', '>']) {
+        Some(i) => i + 1,
+        None => 0,
+    }
+}
+
+fn pairs_for_strs<'b, I>(ss: I, prefix: &str, replacement_prefix: &str) -> Vec<Pair>
+    where I: Iterator<Item = &'b String>
+{
+    let mut names: Vec<&String> = ss.filter(|s| s.starts_with(prefix)).collect();
+    names.sort();
+    names.dedup();
+    names.iter().map(|name| Pair { display: (*name).clone(), replacement: format!("{}{}", replacement_prefix, name) }).collect()
+}
+
+pub struct CompletionShell
+{
+    pub interp: Interpreter,
+    pub exec: Executor,
+    pub env: Environment,
+    pub settings: Settings,
+}
+
+pub struct LineEditorHelper
+{
+    command_names: HashSet<String>,
+    var_names: Vec<String>,
+    job_ids: Vec<u32>,
+    path: String,
+    prog_cache: RefCell<HashMap<String, bool>>,
+    filename_completer: FilenameCompleter,
+    shell: Option<Rc<RefCell<CompletionShell>>>,
+    settings: Settings,
+}
+
+impl LineEditorHelper
+{
+    pub fn new(settings: &Settings) -> LineEditorHelper
+    {
+        LineEditorHelper {
+            command_names: HashSet::new(),
+            var_names: Vec::new(),
+            job_ids: Vec::new(),
+            path: String::from("/bin:/usr/bin"),
+            prog_cache: RefCell::new(HashMap::new()),
+            filename_completer: FilenameCompleter::new(),
+            shell: None,
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn refresh(&mut self, env: &Environment, exec: &Executor, settings: &Settings)
+    {
+        self.command_names.clear();
+        self.command_names.extend(env.builtin_funs().keys().map(|name| name.clone()));
+        self.command_names.extend(env.closure_builtin_funs().keys().map(|name| name.clone()));
+        self.command_names.extend(env.funs().keys().map(|name| name.clone()));
+        self.command_names.extend(env.aliases().keys().map(|name| name.clone()));
+        self.var_names = env.unexported_vars().keys().map(|name| name.clone()).collect();
+        self.var_names.extend(env::vars().map(|(name, _)| name));
+        self.job_ids = exec.jobs().keys().map(|id| *id).collect();
+        self.job_ids.sort();
+        self.path = env.var("PATH").unwrap_or(String::from("/bin:/usr/bin"));
+        self.prog_cache.borrow_mut().clear();
+        self.settings = settings.clone();
+    }
+
+    pub fn set_shell(&mut self, shell: Rc<RefCell<CompletionShell>>)
+    { self.shell = Some(shell); }
+
+    pub fn unset_shell(&mut self)
+    { self.shell = None; }
+
+    pub fn is_known_command(&self, name: &str) -> bool
+    {
+        if self.command_names.contains(name) {
+            return true;
+        }
+        match self.prog_cache.borrow().get(name) {
+            Some(b) => return *b,
+            None => (),
+        }
+        let b = if name.contains(path::MAIN_SEPARATOR) {
+            is_executable_file(name)
+        } else {
+            self.path.split(':').any(|dir_path| {
+                    let mut prog_path_buf = PathBuf::from(dir_path);
+                    prog_path_buf.push(name);
+                    is_executable_file(prog_path_buf.as_path())
+            })
+        };
+        self.prog_cache.borrow_mut().insert(String::from(name), b);
+        b
+    }
+
+    fn command_name_pairs(&self, prefix: &str) -> Vec<Pair>
+    {
+        let mut names: Vec<String> = self.command_names.iter().filter(|name| name.starts_with(prefix)).map(|name| name.clone()).collect();
+        names.extend(path_command_names(self.path.as_str(), prefix));
+        pairs_for_strs(names.iter(), prefix, "")
+    }
+
+    fn complete_with_spec(&self, command_words: &[String], word: &str, line: &str, pos: usize) -> Option<Vec<Pair>>
+    {
+        let mut shell_r = self.shell.as_ref()?.try_borrow_mut().ok()?;
+        let shell = &mut *shell_r;
+        let spec = shell.env.completion_spec(command_words.first()?.as_str())?;
+        let mut comp_words = command_words.to_vec();
+        comp_words.push(String::from(word));
+        let names = complete_with_spec(&spec, comp_words.as_slice(), comp_words.len() - 1, line, pos, &mut shell.interp, &mut shell.exec, &mut shell.env, &mut shell.settings);
+        Some(names.iter().map(|name| Pair { display: name.clone(), replacement: name.clone() }).collect())
+    }
+
+    pub fn complete_line(&self, line: &str, pos: usize) -> (usize, Vec<Pair>)
+    {
+        let start = find_word_start(line, pos);
+        let word = &line[start..pos];
+        if let Some(prefix) = word.strip_prefix("${") {
+            return (start + 2, pairs_for_strs(self.var_names.iter(), prefix, ""));
+        }
+        if let Some(prefix) = word.strip_prefix('$') {
+            return (start + 1, pairs_for_strs(self.var_names.iter(), prefix, ""));
+        }
+        if word.starts_with('%') {
+            let job_specs: Vec<String> = self.job_ids.iter().map(|id| format!("%{}", id)).collect();
+            return (start, job_specs.iter().filter(|job_spec| job_spec.starts_with(word)).map(|job_spec| Pair { display: job_spec.clone(), replacement: job_spec.clone() }).collect());
+        }
+        let scan = scan_line(&line[..start], &self.settings, &|_| true);
+        if !scan.has_error && !scan.is_redirect_target {
+            if scan.expected_word == ExpectedWord::Command && !word.contains(path::MAIN_SEPARATOR) {
+                return (start, self.command_name_pairs(word));
+            }
+            if scan.expected_word == ExpectedWord::Argument {
+                match self.complete_with_spec(scan.command_words.as_slice(), word, line, pos) {
+                    Some(pairs) => return (start, pairs),
+                    None => (),
+                }
+            }
+            match (scan.command_words.first().map(|s| s.as_str()), scan.command_words.last().map(|s| s.as_str())) {
+                (Some("set"), Some("-o" | "+o")) if scan.command_words.len() >= 2 => {
+                    let option_names: Vec<String> = OPTION_NAMES.iter().map(|name| String::from(*name)).collect();
+                    return (start, pairs_for_strs(option_names.iter(), word, ""));
+                },
+                _ => (),
+            }
+        }
+        match self.filename_completer.complete_path(line, pos) {
+            Ok(res) => res,
+            Err(_) => (pos, Vec::new()),
+        }
+    }
+}
+
+impl Completer for LineEditorHelper
+{
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)>
+    { Ok(self.complete_line(line, pos)) }
+}
+
+impl Hinter for LineEditorHelper
+{
+    type Hint = String;
+}
+
+impl Highlighter for LineEditorHelper
+{
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str>
+    {
+        let spans = highlight_spans(line, &self.settings, &|name| self.is_known_command(name));
+        if spans.is_empty() {
+            Cow::Borrowed(line)
+        } else {
+            Cow::Owned(highlight_str(line, spans.as_slice()))
+        }
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool
+    { true }
+}
+
+impl Validator for LineEditorHelper
+{
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult>
+    {
+        if is_incomplete_input(ctx.input(), &self.settings) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Helper for LineEditorHelper
+{}
+
+#[cfg(test)]
+mod tests;
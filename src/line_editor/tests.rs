@@ -0,0 +1,315 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use crate::builtins::*;
+use crate::test_helpers::*;
+use sealed_test::prelude::*;
+
+fn span_strs<'a>(s: &'a str, spans: &[(usize, usize, &'static str)]) -> Vec<(&'a str, &'static str)>
+{ spans.iter().map(|(start, end, color)| (&s[*start..*end], *color)).collect() }
+
+#[test]
+fn test_highlight_spans_returns_spans_for_tokens()
+{
+    let settings = Settings::new();
+    let s = "if true; then echo \"a $x\" 'b' ${y} >&2; fi # abc";
+    let spans = highlight_spans(s, &settings, &|_| true);
+    let expected_spans = vec![
+        ("if", KEYWORD_COLOR),
+        (";", OPERATOR_COLOR),
+        ("then", KEYWORD_COLOR),
+        ("\"a $x\"", STRING_COLOR),
+        ("'b'", STRING_COLOR),
+        ("${y}", PARAMETER_COLOR),
+        (">&", OPERATOR_COLOR),
+        (";", OPERATOR_COLOR),
+        ("fi", KEYWORD_COLOR),
+        ("# abc", COMMENT_COLOR)
+    ];
+    assert_eq!(expected_spans, span_strs(s, spans.as_slice()));
+}
+
+#[test]
+fn test_highlight_spans_returns_spans_for_unknown_commands()
+{
+    let settings = Settings::new();
+    let s = "X=1 foo abc | echo def && $cmd; f() { bar; }";
+    let spans = highlight_spans(s, &settings, &|name| name == "echo");
+    let expected_spans = vec![
+        ("foo", UNKNOWN_COMMAND_COLOR),
+        ("|", OPERATOR_COLOR),
+        ("&&", OPERATOR_COLOR),
+        ("$cmd", PARAMETER_COLOR),
+        (";", OPERATOR_COLOR),
+        ("(", OPERATOR_COLOR),
+        (")", OPERATOR_COLOR),
+        ("{", KEYWORD_COLOR),
+        ("bar", UNKNOWN_COMMAND_COLOR),
+        (";", OPERATOR_COLOR),
+        ("}", KEYWORD_COLOR)
+    ];
+    assert_eq!(expected_spans, span_strs(s, spans.as_slice()));
+}
+
+#[test]
+fn test_highlight_spans_returns_spans_for_case_and_for_clauses()
+{
+    let settings = Settings::new();
+    let s = "case $x in\na|b) echo;;\nesac; for i in a b; do :; done";
+    let spans = highlight_spans(s, &settings, &|_| true);
+    let expected_spans = vec![
+        ("case", KEYWORD_COLOR),
+        ("$x", PARAMETER_COLOR),
+        ("in", KEYWORD_COLOR),
+        ("|", OPERATOR_COLOR),
+        (")", OPERATOR_COLOR),
+        (";;", OPERATOR_COLOR),
+        ("esac", KEYWORD_COLOR),
+        (";", OPERATOR_COLOR),
+        ("for", KEYWORD_COLOR),
+        ("in", KEYWORD_COLOR),
+        (";", OPERATOR_COLOR),
+        ("do", KEYWORD_COLOR),
+        (";", OPERATOR_COLOR),
+        ("done", KEYWORD_COLOR)
+    ];
+    assert_eq!(expected_spans, span_strs(s, spans.as_slice()));
+}
+
+#[test]
+fn test_highlight_spans_returns_spans_for_unterminated_string()
+{
+    let settings = Settings::new();
+    let s = "echo \"abc $x";
+    let spans = highlight_spans(s, &settings, &|_| true);
+    assert_eq!(vec![("\"abc $x", STRING_COLOR)], span_strs(s, spans.as_slice()));
+}
+
+#[test]
+fn test_highlight_str_inserts_colors()
+{
+    let s = "if x; then";
+    let spans = vec![(0, 2, KEYWORD_COLOR), (4, 5, OPERATOR_COLOR)];
+    assert_eq!(String::from("\x1b[1;34mif\x1b[0m x\x1b[35m;\x1b[0m then"), highlight_str(s, spans.as_slice()));
+}
+
+#[test]
+fn test_is_incomplete_input_checks_whether_input_is_incomplete()
+{
+    let settings = Settings::new();
+    assert_eq!(true, is_incomplete_input("if true; then", &settings));
+    assert_eq!(true, is_incomplete_input("echo \"abc", &settings));
+    assert_eq!(true, is_incomplete_input("cat <<EOT\nabc", &settings));
+    assert_eq!(false, is_incomplete_input("echo abc", &settings));
+    assert_eq!(false, is_incomplete_input("echo abc )", &settings));
+}
+
+#[sealed_test]
+fn test_line_editor_helper_is_known_command_checks_commands()
+{
+    let settings = Settings::new();
+    let mut env = Environment::new();
+    initialize_builtin_funs(&mut env);
+    env.set_alias("ll", "ls -l");
+    make_dir("bin");
+    write_file("bin/prog", "#!/bin/sh\n");
+    fs::set_permissions("bin/prog", fs::Permissions::from_mode(0o755)).unwrap();
+    write_file("bin/data", "abc\n");
+    env.set_var("PATH", "bin", &settings);
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &Executor::new(), &settings);
+    assert_eq!(true, helper.is_known_command("cd"));
+    assert_eq!(true, helper.is_known_command("ll"));
+    assert_eq!(true, helper.is_known_command("prog"));
+    assert_eq!(true, helper.is_known_command("bin/prog"));
+    assert_eq!(false, helper.is_known_command("data"));
+    assert_eq!(false, helper.is_known_command("xxx"));
+}
+
+fn replacements(pairs: &[Pair]) -> Vec<String>
+{ pairs.iter().map(|pair| pair.replacement.clone()).collect() }
+
+#[sealed_test]
+fn test_line_editor_helper_complete_line_completes_commands()
+{
+    let settings = Settings::new();
+    let mut env = Environment::new();
+    initialize_builtin_funs(&mut env);
+    env.set_alias("rsalias", "ls -l");
+    make_dir("bin");
+    write_file("bin/rsprog", "#!/bin/sh\n");
+    fs::set_permissions("bin/rsprog", fs::Permissions::from_mode(0o755)).unwrap();
+    write_file("bin/rsdata", "abc\n");
+    env.set_var("PATH", "bin", &settings);
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &Executor::new(), &settings);
+    let (start, pairs) = helper.complete_line("echo abc; rs", 12);
+    assert_eq!(10, start);
+    assert_eq!(vec![String::from("rsalias"), String::from("rsprog")], replacements(pairs.as_slice()));
+    let (start, pairs) = helper.complete_line("unal", 4);
+    assert_eq!(0, start);
+    assert_eq!(vec![String::from("unalias")], replacements(pairs.as_slice()));
+}
+
+#[sealed_test]
+fn test_line_editor_helper_complete_line_completes_paths()
+{
+    let settings = Settings::new();
+    let env = Environment::new();
+    make_dir("dir");
+    write_file("dir/file1.txt", "abc\n");
+    write_file("dir/file2.txt", "def\n");
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &Executor::new(), &settings);
+    let (start, pairs) = helper.complete_line("cat dir/fi", 10);
+    assert_eq!(4, start);
+    let mut names = replacements(pairs.as_slice());
+    names.sort();
+    assert_eq!(vec![String::from("dir/file1.txt"), String::from("dir/file2.txt")], names);
+    let (start, pairs) = helper.complete_line("echo abc > dir/file1", 20);
+    assert_eq!(11, start);
+    assert_eq!(vec![String::from("dir/file1.txt")], replacements(pairs.as_slice()));
+}
+
+#[test]
+fn test_line_editor_helper_complete_line_completes_variables()
+{
+    let settings = Settings::new();
+    let mut env = Environment::new();
+    env.set_var("RSUSH_VAR1", "abc", &settings);
+    env.set_var("RSUSH_VAR2", "def", &settings);
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &Executor::new(), &settings);
+    let (start, pairs) = helper.complete_line("echo $RSUSH_V", 13);
+    assert_eq!(6, start);
+    assert_eq!(vec![String::from("RSUSH_VAR1"), String::from("RSUSH_VAR2")], replacements(pairs.as_slice()));
+    let (start, pairs) = helper.complete_line("echo ${RSUSH_VAR2", 17);
+    assert_eq!(7, start);
+    assert_eq!(vec![String::from("RSUSH_VAR2")], replacements(pairs.as_slice()));
+}
+
+#[test]
+fn test_line_editor_helper_complete_line_completes_job_specs()
+{
+    let settings = Settings::new();
+    let env = Environment::new();
+    let mut exec = Executor::new();
+    exec.add_job(&Job::new(1234, "sleep 10"));
+    exec.add_job(&Job::new(1235, "sleep 20"));
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &exec, &settings);
+    let (start, pairs) = helper.complete_line("fg %", 4);
+    assert_eq!(3, start);
+    assert_eq!(vec![String::from("%1"), String::from("%2")], replacements(pairs.as_slice()));
+}
+
+#[test]
+fn test_line_editor_helper_complete_line_completes_option_names()
+{
+    let settings = Settings::new();
+    let env = Environment::new();
+    let mut helper = LineEditorHelper::new(&settings);
+    helper.refresh(&env, &Executor::new(), &settings);
+    let (start, pairs) = helper.complete_line("set -o no", 9);
+    assert_eq!(7, start);
+    assert_eq!(vec![String::from("noclobber"), String::from("noexec"), String::from("noglob"), String::from("nolog"), String::from("notify"), String::from("nounset")], replacements(pairs.as_slice()));
+    let (start, pairs) = helper.complete_line("set +o x", 8);
+    assert_eq!(7, start);
+    assert_eq!(vec![String::from("xtrace")], replacements(pairs.as_slice()));
+}
+
+#[test]
+fn test_line_editor_helper_complete_line_completes_words_for_completion_specs()
+{
+    let s = "
+_deploy() {
+    COMPREPLY=\"$(compgen -W 'staging production' -- \"$2\")\"
+    COMPREPLY=\"$COMPREPLY
+$1:$3:$COMP_CWORD\"
+}
+complete -F _deploy deploy
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let mut settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            initialize_builtin_funs(&mut env);
+            interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            let mut helper = LineEditorHelper::new(&settings);
+            helper.refresh(&env, &exec, &settings);
+            let shell = Rc::new(RefCell::new(CompletionShell { interp, exec, env, settings }));
+            helper.set_shell(shell.clone());
+            let (start, pairs) = helper.complete_line("deploy -v st", 12);
+            helper.unset_shell();
+            assert_eq!(10, start);
+            assert_eq!(vec![String::from("staging"), String::from("deploy:-v:2")], replacements(pairs.as_slice()));
+            assert_eq!(None, shell.borrow().env.var("COMPREPLY"));
+            assert_eq!(None, shell.borrow().env.var("COMP_WORDS"));
+        },
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn test_line_editor_helper_complete_line_does_not_exit_for_completion_function_with_exit()
+{
+    let s = "
+_deploy() {
+    COMPREPLY=staging
+    false
+    exit 2
+}
+complete -F _deploy deploy
+true
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let mut settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            let mut exec = Executor::new();
+            let mut interp = Interpreter::new();
+            let mut env = Environment::new();
+            initialize_builtin_funs(&mut env);
+            interp.interpret_logical_commands(&mut exec, logical_commands.as_slice(), &mut env, &mut settings);
+            let mut helper = LineEditorHelper::new(&settings);
+            helper.refresh(&env, &exec, &settings);
+            let shell = Rc::new(RefCell::new(CompletionShell { interp, exec, env, settings }));
+            helper.set_shell(shell.clone());
+            let (start, pairs) = helper.complete_line("deploy st", 9);
+            helper.unset_shell();
+            assert_eq!(7, start);
+            assert_eq!(vec![String::from("staging")], replacements(pairs.as_slice()));
+            assert!(!shell.borrow().interp.has_exit());
+            assert_eq!(0, shell.borrow().interp.last_status());
+        },
+        _ => assert!(false),
+    }
+}
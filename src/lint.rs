@@ -0,0 +1,403 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io::*;
+use std::rc::*;
+use crate::io::*;
+use crate::lexer::*;
+use crate::parser::*;
+use crate::settings::*;
+use crate::utils::*;
+
+#[derive(Clone)]
+pub struct LintFinding
+{
+    pub path: String,
+    pub pos: Position,
+    pub rule: &'static str,
+    pub msg: String,
+}
+
+impl fmt::Display for LintFinding
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    { write!(f, "{}: {}: {} [{}]", self.path, self.pos, self.msg, self.rule) }
+}
+
+pub const UNQUOTED_AT_RULE: &'static str = "unquoted-at";
+pub const UNCHECKED_CD_RULE: &'static str = "unchecked-cd";
+pub const UNQUOTED_TEST_VAR_RULE: &'static str = "unquoted-test-var";
+pub const UNUSED_FUNCTION_RULE: &'static str = "unused-function";
+pub const READONLY_ASSIGNMENT_RULE: &'static str = "readonly-assignment";
+
+struct FunctionDefinitionInfo
+{
+    name: String,
+    path: String,
+    pos: Position,
+}
+
+struct Linter
+{
+    findings: Vec<LintFinding>,
+    fun_defs: Vec<FunctionDefinitionInfo>,
+    used_names: HashSet<String>,
+    read_only_names: HashSet<String>,
+    has_errexit: bool,
+}
+
+fn has_unquoted_expansion(word: &Word) -> bool
+{
+    word.word_elems.iter().any(|word_elem| {
+            match word_elem {
+                WordElement::Simple(SimpleWordElement::Parameter(ParameterName::Special(SpecialParameterName::At), _)) => false,
+                WordElement::Simple(SimpleWordElement::Parameter(ParameterName::Special(SpecialParameterName::Hash), None)) => false,
+                WordElement::Simple(SimpleWordElement::Parameter(ParameterName::Special(SpecialParameterName::Ques), None)) => false,
+                WordElement::Simple(SimpleWordElement::Parameter(ParameterName::Special(SpecialParameterName::Dolar), None)) => false,
+                WordElement::Simple(SimpleWordElement::Parameter(ParameterName::Special(SpecialParameterName::Excl), None)) => false,
+                WordElement::Simple(SimpleWordElement::Parameter(_, _)) => true,
+                WordElement::Simple(SimpleWordElement::Command(_)) => true,
+                _ => false,
+            }
+    })
+}
+
+impl Linter
+{
+    fn new() -> Linter
+    {
+        Linter {
+            findings: Vec::new(),
+            fun_defs: Vec::new(),
+            used_names: HashSet::new(),
+            read_only_names: HashSet::new(),
+            has_errexit: false,
+        }
+    }
+
+    fn add_finding(&mut self, path: &str, pos: Position, rule: &'static str, msg: String)
+    {
+        let finding = LintFinding {
+            path: String::from(path),
+            pos,
+            rule,
+            msg,
+        };
+        self.findings.push(finding);
+    }
+
+    fn add_used_names(&mut self, s: &str)
+    {
+        for name in s.split(|c: char| !c.is_alphanumeric() && c != '_') {
+            if !name.is_empty() {
+                self.used_names.insert(String::from(name));
+            }
+        }
+    }
+
+    fn lint_simple_word_elem(&mut self, word: &Word, simple_word_elem: &SimpleWordElement, is_quoted: bool)
+    {
+        match simple_word_elem {
+            SimpleWordElement::String(s) => self.add_used_names(s.as_str()),
+            SimpleWordElement::Parameter(param_name, modifier_and_words) => {
+                match param_name {
+                    ParameterName::Special(SpecialParameterName::At) if !is_quoted => {
+                        self.add_finding(word.path.as_str(), word.pos, UNQUOTED_AT_RULE, String::from("Unquoted $@ is split into fields"));
+                    },
+                    _ => (),
+                }
+                match modifier_and_words {
+                    Some((_, words)) => {
+                        for word in words {
+                            self.lint_word_for_quoting(&(*word), is_quoted);
+                        }
+                    },
+                    None => (),
+                }
+            },
+            SimpleWordElement::ParameterLength(_) => (),
+            SimpleWordElement::Command(commands) => self.lint_logical_commands(commands.as_slice(), false),
+            SimpleWordElement::ArithmeticExpression(_) => (),
+        }
+    }
+
+    fn lint_word_for_quoting(&mut self, word: &Word, is_quoted: bool)
+    {
+        for word_elem in &word.word_elems {
+            match word_elem {
+                WordElement::Simple(simple_word_elem) => self.lint_simple_word_elem(word, simple_word_elem, is_quoted),
+                WordElement::SinglyQuoted(s) => self.add_used_names(s.as_str()),
+                WordElement::DoublyQuoted(simple_word_elems) => {
+                    for simple_word_elem in simple_word_elems {
+                        self.lint_simple_word_elem(word, simple_word_elem, true);
+                    }
+                },
+            }
+        }
+    }
+
+    fn lint_word(&mut self, word: &Word)
+    { self.lint_word_for_quoting(word, false); }
+
+    fn lint_redirects(&mut self, redirects: &[Rc<Redirection>])
+    {
+        for redirect in redirects {
+            match &(**redirect) {
+                Redirection::Input(_, _, _, _, word) => self.lint_word(&(*word)),
+                Redirection::Output(_, _, _, _, word, _) => self.lint_word(&(*word)),
+                Redirection::InputAndOutput(_, _, _, _, word) => self.lint_word(&(*word)),
+                Redirection::Appending(_, _, _, _, word) => self.lint_word(&(*word)),
+                Redirection::InputDuplicating(_, _, _, _, word) => self.lint_word(&(*word)),
+                Redirection::OutputDuplicating(_, _, _, _, word) => self.lint_word(&(*word)),
+                Redirection::HereDocument(_, _, _, _, _) => (),
+            }
+        }
+    }
+
+    fn lint_simple_command(&mut self, path: &str, pos: Position, command: &SimpleCommand, is_checked: bool)
+    {
+        let mut word_iter = command.words.iter();
+        let mut prog_word: Option<Rc<Word>> = None;
+        loop {
+            match word_iter.next() {
+                Some(word) => {
+                    match assignment_name(&(*word)) {
+                        Some(name) => {
+                            if name == String::from("PPID") || self.read_only_names.contains(&name) {
+                                self.add_finding(word.path.as_str(), word.pos, READONLY_ASSIGNMENT_RULE, format!("Assignment to read only variable {}", name));
+                            }
+                            self.lint_word(&(*word));
+                        },
+                        None => {
+                            prog_word = Some(word.clone());
+                            break;
+                        },
+                    }
+                },
+                None => break,
+            }
+        }
+        let args: Vec<Rc<Word>> = word_iter.map(|w| w.clone()).collect();
+        match prog_word {
+            Some(prog_word) => {
+                self.lint_word(&(*prog_word));
+                for arg in &args {
+                    self.lint_word(&(*arg));
+                }
+                match literal_word_str(&(*prog_word)) {
+                    Some(name) => {
+                        if name == String::from("cd") {
+                            if !is_checked && !self.has_errexit {
+                                self.add_finding(path, pos, UNCHECKED_CD_RULE, String::from("cd without error check"));
+                            }
+                        } else if name == String::from("[") || name == String::from("test") {
+                            for arg in &args {
+                                if has_unquoted_expansion(&(*arg)) {
+                                    self.add_finding(arg.path.as_str(), arg.pos, UNQUOTED_TEST_VAR_RULE, String::from("Unquoted expansion in test command"));
+                                }
+                            }
+                        } else if name == String::from("readonly") {
+                            for arg in &args {
+                                match literal_word_str(&(*arg)) {
+                                    Some(s) => {
+                                        let name = match s.split_once('=') {
+                                            Some((name, _)) => String::from(name),
+                                            None => s,
+                                        };
+                                        if is_name_str(name.as_str()) {
+                                            self.read_only_names.insert(name);
+                                        }
+                                    },
+                                    None => (),
+                                }
+                            }
+                        } else if name == String::from("set") {
+                            let arg_strs: Vec<Option<String>> = args.iter().map(|a| literal_word_str(&(*a))).collect();
+                            let mut i = 0;
+                            while i < arg_strs.len() {
+                                match &arg_strs[i] {
+                                    Some(s) if (s.starts_with('-') || s.starts_with('+')) && s.len() > 1 && s[1..].chars().all(|c| c.is_ascii_alphabetic()) => {
+                                        let is_set = s.starts_with('-');
+                                        if s[1..].contains('e') {
+                                            self.has_errexit = is_set;
+                                        }
+                                        if s[1..].contains('o') {
+                                            match arg_strs.get(i + 1) {
+                                                Some(Some(s2)) if s2 == &String::from("errexit") => self.has_errexit = is_set,
+                                                _ => (),
+                                            }
+                                            i += 1;
+                                        }
+                                    },
+                                    _ => break,
+                                }
+                                i += 1;
+                            }
+                        }
+                    },
+                    None => (),
+                }
+            },
+            None => (),
+        }
+        self.lint_redirects(command.redirects.as_slice());
+    }
+
+    fn lint_compound_command(&mut self, command: &CompoundCommand, is_checked: bool)
+    {
+        match command {
+            CompoundCommand::BraceGroup(commands) => self.lint_logical_commands(commands.as_slice(), is_checked),
+            CompoundCommand::Subshell(commands) => self.lint_logical_commands(commands.as_slice(), is_checked),
+            CompoundCommand::For(_, words, commands) => {
+                match words {
+                    Some(words) => {
+                        for word in words {
+                            self.lint_word(&(*word));
+                        }
+                    },
+                    None => (),
+                }
+                self.lint_logical_commands(commands.as_slice(), false);
+            },
+            CompoundCommand::Case(word, pairs) => {
+                self.lint_word(&(*word));
+                for pair in pairs {
+                    for pattern_word in &pair.pattern_words {
+                        self.lint_word(&(*pattern_word));
+                    }
+                    self.lint_logical_commands(pair.commands.as_slice(), false);
+                }
+            },
+            CompoundCommand::If(cond_commands, commands, pairs, else_commands) => {
+                self.lint_logical_commands(cond_commands.as_slice(), true);
+                self.lint_logical_commands(commands.as_slice(), false);
+                for pair in pairs {
+                    self.lint_logical_commands(pair.cond_commands.as_slice(), true);
+                    self.lint_logical_commands(pair.commands.as_slice(), false);
+                }
+                match else_commands {
+                    Some(else_commands) => self.lint_logical_commands(else_commands.as_slice(), false),
+                    None => (),
+                }
+            },
+            CompoundCommand::While(cond_commands, commands) | CompoundCommand::Until(cond_commands, commands) => {
+                self.lint_logical_commands(cond_commands.as_slice(), true);
+                self.lint_logical_commands(commands.as_slice(), false);
+            },
+        }
+    }
+
+    fn lint_command(&mut self, command: &Command, is_checked: bool)
+    {
+        match command {
+            Command::Simple(path, pos, _, simple_command) => self.lint_simple_command(path.as_str(), *pos, simple_command, is_checked),
+            Command::Compound(_, _, _, compound_command, redirects) => {
+                self.lint_compound_command(compound_command, is_checked);
+                self.lint_redirects(redirects.as_slice());
+            },
+            Command::FunctionDefinition(path, pos, _, name_word, fun_body) => {
+                match literal_word_str(&(*name_word)) {
+                    Some(name) => {
+                        let fun_def = FunctionDefinitionInfo {
+                            name,
+                            path: path.clone(),
+                            pos: *pos,
+                        };
+                        self.fun_defs.push(fun_def);
+                    },
+                    None => (),
+                }
+                self.lint_compound_command(&fun_body.command, false);
+                self.lint_redirects(fun_body.redirects.as_slice());
+            },
+        }
+    }
+
+    fn lint_pipe_command(&mut self, command: &PipeCommand, is_checked: bool)
+    {
+        let is_checked2 = is_checked || command.is_negative || command.commands.len() > 1;
+        for command2 in &command.commands {
+            self.lint_command(&(*command2), is_checked2);
+        }
+    }
+
+    fn lint_logical_command(&mut self, command: &LogicalCommand, is_checked: bool)
+    {
+        let is_checked2 = is_checked || !command.pairs.is_empty();
+        self.lint_pipe_command(&(*command.first_command), is_checked2);
+        for pair in &command.pairs {
+            self.lint_pipe_command(&(*pair.command), is_checked2);
+        }
+    }
+
+    fn lint_logical_commands(&mut self, commands: &[Rc<LogicalCommand>], is_checked: bool)
+    {
+        for command in commands {
+            self.lint_logical_command(&(*command), is_checked);
+        }
+    }
+
+    fn add_findings_for_unused_funs(&mut self)
+    {
+        let mut findings: Vec<LintFinding> = Vec::new();
+        for fun_def in &self.fun_defs {
+            if !self.used_names.contains(&fun_def.name) {
+                let finding = LintFinding {
+                    path: fun_def.path.clone(),
+                    pos: fun_def.pos,
+                    rule: UNUSED_FUNCTION_RULE,
+                    msg: format!("Function {} is never used", fun_def.name),
+                };
+                findings.push(finding);
+            }
+        }
+        self.findings.extend(findings);
+    }
+}
+
+pub fn lint_logical_commands(commands: &[Rc<LogicalCommand>]) -> Vec<LintFinding>
+{
+    let mut linter = Linter::new();
+    linter.lint_logical_commands(commands, false);
+    linter.add_findings_for_unused_funs();
+    let mut findings = linter.findings;
+    findings.sort_by_key(|finding| (finding.pos.line, finding.pos.column));
+    findings
+}
+
+pub fn lint_str(path: &str, s: &str, settings: &Settings) -> (Vec<ParserError>, Vec<LintFinding>)
+{
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new(path, &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    parser.set_error_cont(false);
+    let (commands, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, settings);
+    (errs, lint_logical_commands(commands.as_slice()))
+}
+
+pub fn lint_file(path: &str, settings: &Settings) -> Result<(Vec<ParserError>, Vec<LintFinding>)>
+{
+    let s = fs::read_to_string(path)?;
+    Ok(lint_str(path, s.as_str(), settings))
+}
+
+#[cfg(test)]
+mod tests;
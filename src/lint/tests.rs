@@ -0,0 +1,191 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+
+fn lint_test_str(s: &str) -> (Vec<String>, Vec<String>)
+{
+    let settings = Settings::new();
+    let (errs, findings) = lint_str("test.sh", s, &settings);
+    (errs.iter().map(|e| format!("{}", e)).collect(), findings.iter().map(|f| format!("{}", f)).collect())
+}
+
+#[test]
+fn test_lint_str_finds_unquoted_at()
+{
+    let s = "
+echo $@
+echo \"$@\"
+echo ${@}x
+echo \"${x:-$@}\" ${x:-\"$@\"}
+echo ${x:-$@}
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 1.6: Unquoted $@ is split into fields [unquoted-at]"),
+        String::from("test.sh: 3.6: Unquoted $@ is split into fields [unquoted-at]"),
+        String::from("test.sh: 5.11: Unquoted $@ is split into fields [unquoted-at]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_finds_unchecked_cd()
+{
+    let s = "
+cd /tmp
+cd /tmp || exit 1
+if cd /tmp; then
+    cd /usr
+fi
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 1.1: cd without error check [unchecked-cd]"),
+        String::from("test.sh: 4.5: cd without error check [unchecked-cd]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_does_not_find_unchecked_cd_for_errexit()
+{
+    let s = "
+set -e
+cd /tmp
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert!(findings.is_empty());
+}
+
+#[test]
+fn test_lint_str_finds_unchecked_cd_for_non_errexit_set_arguments()
+{
+    let s = "
+set -- -e
+cd /tmp
+set -x -o pipefail
+cd /tmp
+set -xe
+cd /tmp
+set +e
+cd /tmp
+set -o errexit
+cd /tmp
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 2.1: cd without error check [unchecked-cd]"),
+        String::from("test.sh: 4.1: cd without error check [unchecked-cd]"),
+        String::from("test.sh: 8.1: cd without error check [unchecked-cd]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_finds_unquoted_test_var()
+{
+    let s = "
+[ $x = y ]
+[ \"$x\" = y ]
+test $# -eq 0
+test $(cat file) = abc
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 1.3: Unquoted expansion in test command [unquoted-test-var]"),
+        String::from("test.sh: 4.6: Unquoted expansion in test command [unquoted-test-var]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_finds_unused_functions()
+{
+    let s = "
+f() { g; }
+g() { :; }
+h() { :; }
+i() { :; }
+trap 'i' EXIT
+f
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 3.1: Function h is never used [unused-function]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_finds_readonly_assignments()
+{
+    let s = "
+X=1
+readonly X Y=2
+X=3
+Y=4 Z=5 echo abc
+PPID=6
+LINENO=7
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert!(errs.is_empty());
+    assert_eq!(vec![
+        String::from("test.sh: 3.1: Assignment to read only variable X [readonly-assignment]"),
+        String::from("test.sh: 4.1: Assignment to read only variable Y [readonly-assignment]"),
+        String::from("test.sh: 5.1: Assignment to read only variable PPID [readonly-assignment]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_reports_syntax_errors_and_continues()
+{
+    let s = "
+echo abc )
+cd /tmp
+echo def )
+echo $@
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert_eq!(vec![
+        String::from("test.sh: 1.10: unexpected token"),
+        String::from("test.sh: 3.10: unexpected token")
+    ], errs);
+    assert_eq!(vec![
+        String::from("test.sh: 2.1: cd without error check [unchecked-cd]"),
+        String::from("test.sh: 4.6: Unquoted $@ is split into fields [unquoted-at]")
+    ], findings);
+}
+
+#[test]
+fn test_lint_str_reports_syntax_errors_in_if_commands_and_continues()
+{
+    let s = "
+if true; then
+    echo abc )
+fi
+echo $@
+";
+    let (errs, findings) = lint_test_str(&s[1..]);
+    assert_eq!(vec![
+        String::from("test.sh: 2.14: unexpected token")
+    ], errs);
+    assert_eq!(vec![
+        String::from("test.sh: 4.6: Unquoted $@ is split into fields [unquoted-at]")
+    ], findings);
+}
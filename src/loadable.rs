@@ -0,0 +1,222 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::ffi::*;
+use std::io::*;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::os::raw::c_void;
+use std::os::unix::ffi::OsStrExt;
+use std::path::*;
+use std::ptr::null;
+use std::rc::*;
+use std::slice;
+use libc;
+use crate::env::*;
+use crate::exec::*;
+use crate::interp::*;
+use crate::settings::*;
+use crate::xcfprintln;
+
+pub const LOADABLE_BUILTIN_SYMBOL_PREFIX: &'static str = "rsush_builtin_";
+
+#[repr(C)]
+pub struct RawContext
+{
+    pub data: *mut c_void,
+    pub get_var: extern "C" fn(*mut c_void, *const c_char) -> *const c_char,
+    pub set_var: extern "C" fn(*mut c_void, *const c_char, *const c_char) -> c_int,
+    pub write: extern "C" fn(*mut c_void, c_int, *const c_char, usize) -> isize,
+}
+
+pub type RawBuiltinMain = extern "C" fn(c_int, *const *const c_char, *mut RawContext) -> c_int;
+
+#[repr(C)]
+pub struct RawBuiltin
+{
+    pub name: *const c_char,
+    pub main: RawBuiltinMain,
+}
+
+fn dlerror_to_error() -> Error
+{
+    let msg = unsafe { libc::dlerror() };
+    if !msg.is_null() {
+        Error::new(ErrorKind::Other, unsafe { CStr::from_ptr(msg) }.to_string_lossy().into_owned())
+    } else {
+        Error::new(ErrorKind::Other, "Unknown dynamic linking error")
+    }
+}
+
+pub struct Library
+{
+    handle: *mut c_void,
+}
+
+impl Library
+{
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Library>
+    {
+        let path_cstring = match CString::new(path.as_ref().as_os_str().as_bytes()) {
+            Ok(tmp_path_cstring) => tmp_path_cstring,
+            Err(_) => return Err(Error::new(ErrorKind::InvalidInput, "Path contains nul byte")),
+        };
+        let handle = unsafe { libc::dlopen(path_cstring.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if !handle.is_null() {
+            Ok(Library { handle, })
+        } else {
+            Err(dlerror_to_error())
+        }
+    }
+
+    pub fn symbol(&self, name: &str) -> Result<*mut c_void>
+    {
+        let name_cstring = match CString::new(name) {
+            Ok(tmp_name_cstring) => tmp_name_cstring,
+            Err(_) => return Err(Error::new(ErrorKind::InvalidInput, "Name contains nul byte")),
+        };
+        unsafe { libc::dlerror() };
+        let sym = unsafe { libc::dlsym(self.handle, name_cstring.as_ptr()) };
+        if !sym.is_null() {
+            Ok(sym)
+        } else {
+            Err(dlerror_to_error())
+        }
+    }
+}
+
+impl Drop for Library
+{
+    fn drop(&mut self)
+    { unsafe { libc::dlclose(self.handle) }; }
+}
+
+#[derive(Clone)]
+pub struct LoadableBuiltin
+{
+    _library: Rc<Library>,
+    main: RawBuiltinMain,
+}
+
+impl LoadableBuiltin
+{
+    pub fn load(library: &Rc<Library>, name: &str) -> Result<LoadableBuiltin>
+    {
+        let sym = library.symbol(format!("{}{}", LOADABLE_BUILTIN_SYMBOL_PREFIX, name).as_str())?;
+        let raw_builtin = unsafe { &*(sym as *const RawBuiltin) };
+        if raw_builtin.name.is_null() || unsafe { CStr::from_ptr(raw_builtin.name) }.to_bytes() != name.as_bytes() {
+            return Err(Error::new(ErrorKind::InvalidData, "Invalid built-in command name"));
+        }
+        Ok(LoadableBuiltin {
+                _library: library.clone(),
+                main: raw_builtin.main,
+        })
+    }
+}
+
+struct ContextData<'a>
+{
+    exec: &'a mut Executor,
+    env: &'a mut Environment,
+    settings: &'a mut Settings,
+    var_values: Vec<CString>,
+}
+
+extern "C" fn context_get_var(data: *mut c_void, name: *const c_char) -> *const c_char
+{
+    let data = unsafe { &mut *(data as *mut ContextData<'_>) };
+    if name.is_null() {
+        return null();
+    }
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned();
+    match data.env.var(name.as_str()) {
+        Some(value) => {
+            match CString::new(value) {
+                Ok(value_cstring) => {
+                    data.var_values.push(value_cstring);
+                    data.var_values.last().map(|v| v.as_ptr()).unwrap_or(null())
+                },
+                Err(_) => null(),
+            }
+        },
+        None => null(),
+    }
+}
+
+extern "C" fn context_set_var(data: *mut c_void, name: *const c_char, value: *const c_char) -> c_int
+{
+    let data = unsafe { &mut *(data as *mut ContextData<'_>) };
+    if name.is_null() || value.is_null() {
+        return -1;
+    }
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned();
+    let value = unsafe { CStr::from_ptr(value) }.to_string_lossy().into_owned();
+    if data.env.read_only_var_attr(name.as_str()) {
+        return -1;
+    }
+    data.env.set_var(name.as_str(), value.as_str(), data.settings);
+    0
+}
+
+extern "C" fn context_write(data: *mut c_void, fd: c_int, buf: *const c_char, len: usize) -> isize
+{
+    let data = unsafe { &mut *(data as *mut ContextData<'_>) };
+    if buf.is_null() && len > 0 {
+        return -1;
+    }
+    let bytes = if len > 0 {
+        unsafe { slice::from_raw_parts(buf as *const u8, len) }
+    } else {
+        &[]
+    };
+    match data.exec.current_file(fd) {
+        Some(file) => {
+            match file.borrow_mut().write_all(bytes) {
+                Ok(()) => len as isize,
+                Err(_) => -1,
+            }
+        },
+        None => -1,
+    }
+}
+
+pub fn main(_vars: &[(String, String)], args: &[String], _interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    let loadable_builtin = match env.loadable_builtin(args[0].as_str()) {
+        Some(tmp_loadable_builtin) => tmp_loadable_builtin,
+        None => {
+            xcfprintln!(exec, 2, "{}: Not dynamically loaded", args[0]);
+            return 1;
+        },
+    };
+    let arg_cstrings: Vec<CString> = args.iter().map(|a| CString::new(a.as_str()).unwrap_or(CString::default())).collect();
+    let mut arg_ptrs: Vec<*const c_char> = arg_cstrings.iter().map(|a| a.as_ptr()).collect();
+    arg_ptrs.push(null());
+    let mut data = ContextData {
+        exec,
+        env,
+        settings,
+        var_values: Vec::new(),
+    };
+    let mut context = RawContext {
+        data: &mut data as *mut ContextData<'_> as *mut c_void,
+        get_var: context_get_var,
+        set_var: context_set_var,
+        write: context_write,
+    };
+    (loadable_builtin.main)(arg_cstrings.len() as c_int, arg_ptrs.as_ptr(), &mut context as *mut RawContext)
+}
@@ -0,0 +1,743 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::collections::HashMap;
+use std::io::*;
+use std::path::*;
+use std::rc::*;
+use crate::io::*;
+use crate::json::*;
+use crate::lexer::*;
+use crate::parser::*;
+use crate::settings::*;
+use crate::utils::*;
+
+const BUILTIN_USAGES: &[(&str, &str)] = &[
+    (".", ". file [argument...]"),
+    (":", ": [argument...]"),
+    ("alias", "alias [name[=value]...]"),
+    ("bg", "bg [job_id...]"),
+    ("break", "break [n]"),
+    ("caller", "caller [n]"),
+    ("cd", "cd [-L | -P] [directory]\ncd -"),
+    ("command", "command [-p] [-v | -V] command_name [argument...]"),
+    ("compgen", "compgen [-cdfv] [-W wordlist] [word]"),
+    ("complete", "complete [-cdfv] [-W wordlist] [-F function] name...\ncomplete -p [name...]\ncomplete -r [name...]"),
+    ("continue", "continue [n]"),
+    ("dirs", "dirs [-clpv] [+n | -n]"),
+    ("disown", "disown [-h] [job_id...]\ndisown [-h] -a"),
+    ("enable", "enable [-a] [-n] [name...]\nenable -f file name...\nenable -d name..."),
+    ("eval", "eval [argument...]"),
+    ("exec", "exec [command [argument...]]"),
+    ("exit", "exit [n]"),
+    ("export", "export name[=word]...\nexport -p"),
+    ("fg", "fg [job_id]"),
+    ("getopts", "getopts optstring name [arg...]"),
+    ("jobs", "jobs [-l | -p] [job_id...]"),
+    ("popd", "popd [-L | -P] [+n | -n]"),
+    ("pushd", "pushd [-L | -P] [directory | +n | -n]"),
+    ("read", "read [-r] var..."),
+    ("readonly", "readonly name[=word]...\nreadonly -p"),
+    ("return", "return [n]"),
+    ("set", "set [-abCefhmnuvx] [-o option] [argument...]\nset [+abCefhmnuvx] [+o option] [argument...]\nset -- [argument...]\nset -o\nset +o"),
+    ("shift", "shift [n]"),
+    ("source", "source file [argument...]"),
+    ("suspend", "suspend [-f]"),
+    ("times", "times"),
+    ("trap", "trap n [condition...]\ntrap [action condition...]\ntrap -p [condition...]"),
+    ("umask", "umask [-S] [mask]"),
+    ("unalias", "unalias alias_name...\nunalias -a"),
+    ("unset", "unset [-fv] name..."),
+    ("wait", "wait [pid...]\nwait [-n] [-p var] [-t seconds] [job_id...]"),
+];
+
+const LSP_ERROR_PARSE: i32 = -32700;
+const LSP_ERROR_INVALID_REQUEST: i32 = -32600;
+const LSP_ERROR_METHOD_NOT_FOUND: i32 = -32601;
+
+const LSP_SEVERITY_ERROR: i32 = 1;
+const LSP_SYMBOL_KIND_FUNCTION: i32 = 12;
+const LSP_COMPLETION_ITEM_KIND_VARIABLE: i32 = 6;
+
+struct FunctionDefinitionSymbol
+{
+    name: String,
+    pos: Position,
+    end_pos: Position,
+    name_word: Rc<Word>,
+}
+
+struct Symbols
+{
+    fun_defs: Vec<FunctionDefinitionSymbol>,
+    command_name_words: Vec<(String, Rc<Word>)>,
+    sourced_file_words: Vec<(String, Rc<Word>)>,
+    var_names: Vec<String>,
+}
+
+impl Symbols
+{
+    fn new() -> Symbols
+    {
+        Symbols {
+            fun_defs: Vec::new(),
+            command_name_words: Vec::new(),
+            sourced_file_words: Vec::new(),
+            var_names: Vec::new(),
+        }
+    }
+
+    fn add_var_name(&mut self, name: String)
+    {
+        if !self.var_names.contains(&name) {
+            self.var_names.push(name);
+        }
+    }
+
+    fn collect_simple_word_elems(&mut self, simple_word_elems: &[SimpleWordElement])
+    {
+        for simple_word_elem in simple_word_elems {
+            match simple_word_elem {
+                SimpleWordElement::Command(commands) => self.collect_logical_commands(commands.as_slice()),
+                _ => (),
+            }
+        }
+    }
+
+    fn collect_word(&mut self, word: &Word)
+    {
+        for word_elem in &word.word_elems {
+            match word_elem {
+                WordElement::Simple(simple_word_elem) => self.collect_simple_word_elems(std::slice::from_ref(simple_word_elem)),
+                WordElement::DoublyQuoted(simple_word_elems) => self.collect_simple_word_elems(simple_word_elems.as_slice()),
+                _ => (),
+            }
+        }
+    }
+
+    fn collect_simple_command(&mut self, command: &SimpleCommand)
+    {
+        let mut word_iter = command.words.iter();
+        let mut prog_word: Option<Rc<Word>> = None;
+        loop {
+            match word_iter.next() {
+                Some(word) => {
+                    self.collect_word(&(*word));
+                    match assignment_name(&(*word)) {
+                        Some(name) => self.add_var_name(name),
+                        None => {
+                            prog_word = Some(word.clone());
+                            break;
+                        },
+                    }
+                },
+                None => break,
+            }
+        }
+        let args: Vec<Rc<Word>> = word_iter.map(|w| w.clone()).collect();
+        for arg in &args {
+            self.collect_word(&(*arg));
+        }
+        match prog_word.as_ref().and_then(|w| literal_word_str(&(*w)).map(|name| (name, w.clone()))) {
+            Some((name, prog_word)) => {
+                match name.as_str() {
+                    "." | "source" => {
+                        match args.first() {
+                            Some(arg) => {
+                                match literal_word_str(&(*arg)) {
+                                    Some(file) => self.sourced_file_words.push((file, arg.clone())),
+                                    None => (),
+                                }
+                            },
+                            None => (),
+                        }
+                    },
+                    "export" | "readonly" | "read" => {
+                        for arg in &args {
+                            match literal_word_str(&(*arg)) {
+                                Some(s) if !s.starts_with('-') => {
+                                    let name = match s.split_once('=') {
+                                        Some((name, _)) => String::from(name),
+                                        None => s,
+                                    };
+                                    if is_name_str(name.as_str()) {
+                                        self.add_var_name(name);
+                                    }
+                                },
+                                _ => (),
+                            }
+                        }
+                    },
+                    "getopts" => {
+                        match args.get(1).and_then(|arg| literal_word_str(&(*arg))) {
+                            Some(name) if is_name_str(name.as_str()) => self.add_var_name(name),
+                            _ => (),
+                        }
+                    },
+                    _ => (),
+                }
+                self.command_name_words.push((name, prog_word));
+            },
+            None => (),
+        }
+    }
+
+    fn collect_compound_command(&mut self, command: &CompoundCommand)
+    {
+        match command {
+            CompoundCommand::BraceGroup(commands) => self.collect_logical_commands(commands.as_slice()),
+            CompoundCommand::Subshell(commands) => self.collect_logical_commands(commands.as_slice()),
+            CompoundCommand::For(var_word, words, commands) => {
+                match literal_word_str(&(*var_word)) {
+                    Some(name) => self.add_var_name(name),
+                    None => (),
+                }
+                match words {
+                    Some(words) => {
+                        for word in words {
+                            self.collect_word(&(*word));
+                        }
+                    },
+                    None => (),
+                }
+                self.collect_logical_commands(commands.as_slice());
+            },
+            CompoundCommand::Case(word, pairs) => {
+                self.collect_word(&(*word));
+                for pair in pairs {
+                    self.collect_logical_commands(pair.commands.as_slice());
+                }
+            },
+            CompoundCommand::If(cond_commands, commands, pairs, else_commands) => {
+                self.collect_logical_commands(cond_commands.as_slice());
+                self.collect_logical_commands(commands.as_slice());
+                for pair in pairs {
+                    self.collect_logical_commands(pair.cond_commands.as_slice());
+                    self.collect_logical_commands(pair.commands.as_slice());
+                }
+                match else_commands {
+                    Some(else_commands) => self.collect_logical_commands(else_commands.as_slice()),
+                    None => (),
+                }
+            },
+            CompoundCommand::While(cond_commands, commands) | CompoundCommand::Until(cond_commands, commands) => {
+                self.collect_logical_commands(cond_commands.as_slice());
+                self.collect_logical_commands(commands.as_slice());
+            },
+        }
+    }
+
+    fn collect_command(&mut self, command: &Command)
+    {
+        match command {
+            Command::Simple(_, _, _, command) => self.collect_simple_command(command),
+            Command::Compound(_, _, _, command, _) => self.collect_compound_command(command),
+            Command::FunctionDefinition(_, pos, end_pos, name_word, fun_body) => {
+                match literal_word_str(&(*name_word)) {
+                    Some(name) => {
+                        let fun_def = FunctionDefinitionSymbol {
+                            name,
+                            pos: *pos,
+                            end_pos: *end_pos,
+                            name_word: name_word.clone(),
+                        };
+                        self.fun_defs.push(fun_def);
+                    },
+                    None => (),
+                }
+                self.collect_compound_command(&fun_body.command);
+            },
+        }
+    }
+
+    fn collect_pipe_command(&mut self, command: &PipeCommand)
+    {
+        for command2 in &command.commands {
+            self.collect_command(&(*command2));
+        }
+    }
+
+    fn collect_logical_commands(&mut self, commands: &[Rc<LogicalCommand>])
+    {
+        for command in commands {
+            self.collect_pipe_command(&(*command.first_command));
+            for pair in &command.pairs {
+                self.collect_pipe_command(&(*pair.command));
+            }
+        }
+    }
+}
+
+struct Document
+{
+    path: String,
+    text: String,
+    errs: Vec<ParserError>,
+    symbols: Symbols,
+}
+
+impl Document
+{
+    fn new(path: &str, text: &str, settings: &Settings) -> Document
+    {
+        let mut cursor = Cursor::new(text.as_bytes());
+        let mut cr = CharReader::new(&mut cursor);
+        let mut lexer = Lexer::new(path, &Position::new(1, 1), &mut cr, 0, false);
+        let mut parser = Parser::new();
+        parser.set_error_cont(false);
+        let (commands, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, settings);
+        let mut symbols = Symbols::new();
+        symbols.collect_logical_commands(commands.as_slice());
+        Document {
+            path: String::from(path),
+            text: String::from(text),
+            errs,
+            symbols,
+        }
+    }
+
+    fn line(&self, line: u64) -> &str
+    {
+        self.text.split('\n').nth((line as usize).saturating_sub(1)).unwrap_or_default()
+    }
+
+    fn lsp_position(&self, pos: &Position) -> JsonValue
+    {
+        let character: usize = self.line(pos.line).chars().take((pos.column as usize).saturating_sub(1)).map(|c| c.len_utf16()).sum();
+        JsonValue::Object(vec![
+            (String::from("line"), JsonValue::Integer(pos.line.saturating_sub(1) as i64)),
+            (String::from("character"), JsonValue::Integer(character as i64))
+        ])
+    }
+
+    fn lsp_range(&self, pos: &Position, end_pos: &Position) -> JsonValue
+    {
+        JsonValue::Object(vec![
+            (String::from("start"), self.lsp_position(pos)),
+            (String::from("end"), self.lsp_position(end_pos))
+        ])
+    }
+
+    fn position_from_lsp(&self, value: &JsonValue) -> Option<Position>
+    {
+        let line = value.get("line")?.as_f64()? as u64 + 1;
+        let character = value.get("character")?.as_f64()? as usize;
+        let mut n = 0;
+        let mut column = 1;
+        for c in self.line(line).chars() {
+            if n + c.len_utf16() > character {
+                break;
+            }
+            n += c.len_utf16();
+            column += 1;
+        }
+        Some(Position::new(line, column))
+    }
+
+    fn diagnostics(&self) -> JsonValue
+    {
+        let mut diagnostics: Vec<JsonValue> = Vec::new();
+        for err in &self.errs {
+            match err {
+                ParserError::Syntax(_, pos, msg, _) => {
+                    let end_pos = Position::new(pos.line, pos.column + 1);
+                    diagnostics.push(JsonValue::Object(vec![
+                        (String::from("range"), self.lsp_range(pos, &end_pos)),
+                        (String::from("severity"), JsonValue::Integer(LSP_SEVERITY_ERROR as i64)),
+                        (String::from("source"), JsonValue::from_str("rsush")),
+                        (String::from("message"), JsonValue::from_str(msg.as_str()))
+                    ]));
+                },
+                ParserError::IO(_, _) => (),
+            }
+        }
+        JsonValue::Array(diagnostics)
+    }
+
+    fn document_symbols(&self) -> JsonValue
+    {
+        let symbols: Vec<JsonValue> = self.symbols.fun_defs.iter().map(|fun_def| {
+                JsonValue::Object(vec![
+                    (String::from("name"), JsonValue::from_str(fun_def.name.as_str())),
+                    (String::from("kind"), JsonValue::Integer(LSP_SYMBOL_KIND_FUNCTION as i64)),
+                    (String::from("range"), self.lsp_range(&fun_def.pos, &fun_def.end_pos)),
+                    (String::from("selectionRange"), self.lsp_range(&fun_def.name_word.pos, &fun_def.name_word.end_pos))
+                ])
+        }).collect();
+        JsonValue::Array(symbols)
+    }
+
+    fn word_at<'b>(words: &'b [(String, Rc<Word>)], pos: &Position) -> Option<&'b (String, Rc<Word>)>
+    {
+        words.iter().find(|(_, word)| {
+                (word.pos.line, word.pos.column) <= (pos.line, pos.column) && (pos.line, pos.column) < (word.end_pos.line, word.end_pos.column)
+        })
+    }
+
+    fn definition(&self, uri: &str, pos: &Position) -> JsonValue
+    {
+        match Document::word_at(self.symbols.command_name_words.as_slice(), pos) {
+            Some((name, _)) => {
+                let locations: Vec<JsonValue> = self.symbols.fun_defs.iter().filter(|fun_def| &fun_def.name == name).map(|fun_def| {
+                        lsp_location(uri, self.lsp_range(&fun_def.name_word.pos, &fun_def.name_word.end_pos))
+                }).collect();
+                return JsonValue::Array(locations);
+            },
+            None => (),
+        }
+        match Document::word_at(self.symbols.sourced_file_words.as_slice(), pos) {
+            Some((file, _)) => {
+                let file_path = Path::new(file.as_str());
+                let path_buf = if file_path.is_absolute() {
+                    file_path.to_path_buf()
+                } else {
+                    match Path::new(self.path.as_str()).parent() {
+                        Some(dir_path) => dir_path.join(file_path),
+                        None => file_path.to_path_buf(),
+                    }
+                };
+                if path_buf.is_file() {
+                    let zero_pos = JsonValue::Object(vec![
+                        (String::from("line"), JsonValue::Integer(0)),
+                        (String::from("character"), JsonValue::Integer(0))
+                    ]);
+                    let range = JsonValue::Object(vec![
+                        (String::from("start"), zero_pos.clone()),
+                        (String::from("end"), zero_pos)
+                    ]);
+                    JsonValue::Array(vec![lsp_location(path_to_uri(path_buf.to_string_lossy().as_ref()).as_str(), range)])
+                } else {
+                    JsonValue::Null
+                }
+            },
+            None => JsonValue::Null,
+        }
+    }
+
+    fn hover(&self, pos: &Position) -> JsonValue
+    {
+        match Document::word_at(self.symbols.command_name_words.as_slice(), pos) {
+            Some((name, word)) => {
+                match BUILTIN_USAGES.iter().find(|p| p.0 == name.as_str()) {
+                    Some((_, usage)) => {
+                        JsonValue::Object(vec![
+                            (String::from("contents"), JsonValue::Object(vec![
+                                    (String::from("kind"), JsonValue::from_str("markdown")),
+                                    (String::from("value"), JsonValue::String(format!("```sh\n{}\n```", usage)))
+                            ])),
+                            (String::from("range"), self.lsp_range(&word.pos, &word.end_pos))
+                        ])
+                    },
+                    None => JsonValue::Null,
+                }
+            },
+            None => JsonValue::Null,
+        }
+    }
+
+    fn completion(&self) -> JsonValue
+    {
+        let mut var_names = self.symbols.var_names.clone();
+        var_names.sort();
+        let items: Vec<JsonValue> = var_names.iter().map(|name| {
+                JsonValue::Object(vec![
+                    (String::from("label"), JsonValue::from_str(name.as_str())),
+                    (String::from("kind"), JsonValue::Integer(LSP_COMPLETION_ITEM_KIND_VARIABLE as i64))
+                ])
+        }).collect();
+        JsonValue::Array(items)
+    }
+}
+
+fn lsp_location(uri: &str, range: JsonValue) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (String::from("uri"), JsonValue::from_str(uri)),
+        (String::from("range"), range)
+    ])
+}
+
+pub fn uri_to_path(uri: &str) -> String
+{
+    let s = uri.strip_prefix("file://").unwrap_or(uri);
+    let bytes = s.as_bytes();
+    let mut path_bytes: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            match std::str::from_utf8(&bytes[(i + 1)..(i + 3)]).ok().and_then(|t| u8::from_str_radix(t, 16).ok()) {
+                Some(b) => {
+                    path_bytes.push(b);
+                    i += 3;
+                    continue;
+                },
+                None => (),
+            }
+        }
+        path_bytes.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(path_bytes.as_slice()).into_owned()
+}
+
+pub fn path_to_uri(path: &str) -> String
+{
+    let mut uri = String::from("file://");
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(b as char),
+            _ => uri.push_str(format!("%{:02X}", b).as_str()),
+        }
+    }
+    uri
+}
+
+fn lsp_response(id: &JsonValue, result: JsonValue) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), JsonValue::from_str("2.0")),
+        (String::from("id"), id.clone()),
+        (String::from("result"), result)
+    ])
+}
+
+fn lsp_error_response(id: &JsonValue, code: i32, msg: &str) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), JsonValue::from_str("2.0")),
+        (String::from("id"), id.clone()),
+        (String::from("error"), JsonValue::Object(vec![
+                (String::from("code"), JsonValue::Integer(code as i64)),
+                (String::from("message"), JsonValue::from_str(msg))
+        ]))
+    ])
+}
+
+fn lsp_notification(method: &str, params: JsonValue) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), JsonValue::from_str("2.0")),
+        (String::from("method"), JsonValue::from_str(method)),
+        (String::from("params"), params)
+    ])
+}
+
+pub struct LspServer
+{
+    docs: HashMap<String, Document>,
+    has_shutdown: bool,
+    settings: Settings,
+}
+
+impl LspServer
+{
+    pub fn new(settings: &Settings) -> LspServer
+    {
+        LspServer {
+            docs: HashMap::new(),
+            has_shutdown: false,
+            settings: settings.clone(),
+        }
+    }
+
+    fn update_doc(&mut self, uri: &str, text: &str) -> JsonValue
+    {
+        let doc = Document::new(uri_to_path(uri).as_str(), text, &self.settings);
+        let diagnostics = doc.diagnostics();
+        self.docs.insert(String::from(uri), doc);
+        lsp_notification("textDocument/publishDiagnostics", JsonValue::Object(vec![
+                (String::from("uri"), JsonValue::from_str(uri)),
+                (String::from("diagnostics"), diagnostics)
+        ]))
+    }
+
+    fn doc_and_pos(&self, params: &JsonValue) -> Option<(String, &Document, Option<Position>)>
+    {
+        let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+        let doc = self.docs.get(uri)?;
+        let pos = params.get("position").and_then(|value| doc.position_from_lsp(value));
+        Some((String::from(uri), doc, pos))
+    }
+
+    fn handle_request(&mut self, method: &str, params: &JsonValue) -> Option<JsonValue>
+    {
+        match method {
+            "initialize" => {
+                Some(JsonValue::Object(vec![
+                    (String::from("capabilities"), JsonValue::Object(vec![
+                            (String::from("textDocumentSync"), JsonValue::Integer(1)),
+                            (String::from("documentSymbolProvider"), JsonValue::Bool(true)),
+                            (String::from("definitionProvider"), JsonValue::Bool(true)),
+                            (String::from("hoverProvider"), JsonValue::Bool(true)),
+                            (String::from("completionProvider"), JsonValue::Object(vec![
+                                    (String::from("triggerCharacters"), JsonValue::from_strs(&[String::from("$"), String::from("{")]))
+                            ]))
+                    ])),
+                    (String::from("serverInfo"), JsonValue::Object(vec![
+                            (String::from("name"), JsonValue::from_str("rsush")),
+                            (String::from("version"), JsonValue::from_str(env!("CARGO_PKG_VERSION")))
+                    ]))
+                ]))
+            },
+            "shutdown" => {
+                self.has_shutdown = true;
+                Some(JsonValue::Null)
+            },
+            "textDocument/documentSymbol" => {
+                Some(self.doc_and_pos(params).map(|(_, doc, _)| doc.document_symbols()).unwrap_or(JsonValue::Null))
+            },
+            "textDocument/definition" => {
+                Some(match self.doc_and_pos(params) {
+                    Some((uri, doc, Some(pos))) => doc.definition(uri.as_str(), &pos),
+                    _ => JsonValue::Null,
+                })
+            },
+            "textDocument/hover" => {
+                Some(match self.doc_and_pos(params) {
+                    Some((_, doc, Some(pos))) => doc.hover(&pos),
+                    _ => JsonValue::Null,
+                })
+            },
+            "textDocument/completion" => {
+                Some(self.doc_and_pos(params).map(|(_, doc, _)| doc.completion()).unwrap_or(JsonValue::Null))
+            },
+            _ => None,
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &JsonValue) -> Option<JsonValue>
+    {
+        match method {
+            "textDocument/didOpen" => {
+                let doc_value = params.get("textDocument")?;
+                let uri = doc_value.get("uri")?.as_str()?;
+                let text = doc_value.get("text")?.as_str()?;
+                Some(self.update_doc(uri, text))
+            },
+            "textDocument/didChange" => {
+                let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+                let text = params.get("contentChanges")?.as_array()?.last()?.get("text")?.as_str()?;
+                Some(self.update_doc(uri, text))
+            },
+            "textDocument/didClose" => {
+                let uri = params.get("textDocument")?.get("uri")?.as_str()?;
+                self.docs.remove(uri);
+                Some(lsp_notification("textDocument/publishDiagnostics", JsonValue::Object(vec![
+                        (String::from("uri"), JsonValue::from_str(uri)),
+                        (String::from("diagnostics"), JsonValue::Array(Vec::new()))
+                ])))
+            },
+            _ => None,
+        }
+    }
+
+    pub fn handle_message(&mut self, msg: &JsonValue) -> (Vec<JsonValue>, Option<i32>)
+    {
+        let method = msg.get("method").and_then(|value| value.as_str());
+        let params = msg.get("params").unwrap_or(&JsonValue::Null);
+        match (msg.get("id"), method) {
+            (Some(id), Some(method)) => {
+                match self.handle_request(method, params) {
+                    Some(result) => (vec![lsp_response(id, result)], None),
+                    None => (vec![lsp_error_response(id, LSP_ERROR_METHOD_NOT_FOUND, "Method not found")], None),
+                }
+            },
+            (Some(_), None) => (Vec::new(), None),
+            (None, Some("exit")) => (Vec::new(), Some(if self.has_shutdown { 0 } else { 1 })),
+            (None, Some(method)) => (self.handle_notification(method, params).into_iter().collect(), None),
+            (None, None) => (vec![lsp_error_response(&JsonValue::Null, LSP_ERROR_INVALID_REQUEST, "Invalid request")], None),
+        }
+    }
+}
+
+fn read_lsp_message(r: &mut dyn BufRead) -> Result<Option<String>>
+{
+    let mut content_len: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if r.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            break;
+        }
+        match line.split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("Content-Length") => {
+                match value.trim().parse::<usize>() {
+                    Ok(n) => content_len = Some(n),
+                    Err(_) => return Err(Error::new(ErrorKind::InvalidData, "Invalid content length")),
+                }
+            },
+            _ => (),
+        }
+    }
+    match content_len {
+        Some(content_len) => {
+            let mut buf = vec![0u8; content_len];
+            r.read_exact(buf.as_mut_slice())?;
+            Ok(Some(String::from_utf8_lossy(buf.as_slice()).into_owned()))
+        },
+        None => Err(Error::new(ErrorKind::InvalidData, "No content length")),
+    }
+}
+
+fn write_lsp_message(w: &mut dyn Write, msg: &JsonValue) -> Result<()>
+{
+    let s = format!("{}", msg);
+    write!(w, "Content-Length: {}\r\n\r\n{}", s.len(), s)?;
+    w.flush()
+}
+
+pub fn run_lsp_server(settings: &Settings) -> i32
+{
+    let mut server = LspServer::new(settings);
+    let stdin_r = stdin();
+    let mut stdin_l = stdin_r.lock();
+    let stdout_r = stdout();
+    let mut stdout_l = stdout_r.lock();
+    loop {
+        let s = match read_lsp_message(&mut stdin_l) {
+            Ok(Some(s)) => s,
+            Ok(None) => return 1,
+            Err(err) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+        };
+        let (msgs, status) = match JsonValue::parse(s.as_str()) {
+            Some(msg) => server.handle_message(&msg),
+            None => (vec![lsp_error_response(&JsonValue::Null, LSP_ERROR_PARSE, "Parse error")], None),
+        };
+        for msg in &msgs {
+            match write_lsp_message(&mut stdout_l, msg) {
+                Ok(()) => (),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    return 1;
+                },
+            }
+        }
+        match status {
+            Some(status) => return status,
+            None => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,178 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use super::*;
+use crate::test_helpers::*;
+use sealed_test::prelude::*;
+
+fn did_open_msg(uri: &str, text: &str) -> JsonValue
+{
+    JsonValue::Object(vec![
+        (String::from("jsonrpc"), JsonValue::from_str("2.0")),
+        (String::from("method"), JsonValue::from_str("textDocument/didOpen")),
+        (String::from("params"), JsonValue::Object(vec![
+                (String::from("textDocument"), JsonValue::Object(vec![
+                        (String::from("uri"), JsonValue::from_str(uri)),
+                        (String::from("languageId"), JsonValue::from_str("sh")),
+                        (String::from("version"), JsonValue::Number(1.0)),
+                        (String::from("text"), JsonValue::from_str(text))
+                ]))
+        ]))
+    ])
+}
+
+fn request_msg(id: u32, method: &str, uri: &str, line: u32, character: u32) -> JsonValue
+{
+    let s = format!("{{\"jsonrpc\":\"2.0\",\"id\":{},\"method\":\"{}\",\"params\":{{\"textDocument\":{{\"uri\":\"{}\"}},\"position\":{{\"line\":{},\"character\":{}}}}}}}", id, method, uri, line, character);
+    JsonValue::parse(s.as_str()).unwrap()
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_initialize_request()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    let msg = JsonValue::parse("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{}}").unwrap();
+    let (msgs, status) = server.handle_message(&msg);
+    assert_eq!(None, status);
+    assert_eq!(1, msgs.len());
+    assert_eq!(Some(1.0), msgs[0].get("id").and_then(|v| v.as_f64()));
+    let capabilities = msgs[0].get("result").and_then(|v| v.get("capabilities")).unwrap();
+    assert_eq!(Some(true), capabilities.get("definitionProvider").and_then(|v| v.as_bool()));
+    assert_eq!(Some(true), capabilities.get("hoverProvider").and_then(|v| v.as_bool()));
+    assert_eq!(Some(true), capabilities.get("documentSymbolProvider").and_then(|v| v.as_bool()));
+}
+
+#[test]
+fn test_lsp_server_handle_message_publishes_diagnostics_for_syntax_errors()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    let (msgs, _) = server.handle_message(&did_open_msg("file:///tmp/test.sh", "echo abc )\nwhile true; do\n"));
+    assert_eq!(1, msgs.len());
+    let expected_s = "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///tmp/test.sh\",\"diagnostics\":[{\"range\":{\"start\":{\"line\":0,\"character\":9},\"end\":{\"line\":0,\"character\":10}},\"severity\":1,\"source\":\"rsush\",\"message\":\"unexpected token\"},{\"range\":{\"start\":{\"line\":2,\"character\":0},\"end\":{\"line\":2,\"character\":0}},\"severity\":1,\"source\":\"rsush\",\"message\":\"unterminated `while` started at 2.1\"}]}}";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0]));
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_document_symbol_request()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg("file:///tmp/test.sh", "f() {\n    echo abc\n}\ng() (\n    echo def\n)\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/documentSymbol", "file:///tmp/test.sh", 0, 0));
+    let expected_s = "[{\"name\":\"f\",\"kind\":12,\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":2,\"character\":1}},\"selectionRange\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":1}}},{\"name\":\"g\",\"kind\":12,\"range\":{\"start\":{\"line\":3,\"character\":0},\"end\":{\"line\":5,\"character\":1}},\"selectionRange\":{\"start\":{\"line\":3,\"character\":0},\"end\":{\"line\":3,\"character\":1}}}]";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0].get("result").unwrap()));
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_definition_request_for_function()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg("file:///tmp/test.sh", "echo abc\n  fun() {\n    echo def\n}\nx=$(fun)\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/definition", "file:///tmp/test.sh", 4, 5));
+    let expected_s = "[{\"uri\":\"file:///tmp/test.sh\",\"range\":{\"start\":{\"line\":1,\"character\":2},\"end\":{\"line\":1,\"character\":5}}}]";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0].get("result").unwrap()));
+}
+
+#[sealed_test]
+fn test_lsp_server_handle_message_responds_to_definition_request_for_sourced_file()
+{
+    write_file("lib.sh", "echo abc\n");
+    let dir_path = std::env::current_dir().unwrap();
+    let uri = path_to_uri(dir_path.join("test.sh").to_string_lossy().as_ref());
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg(uri.as_str(), ". lib.sh\nsource none.sh\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/definition", uri.as_str(), 0, 3));
+    let lib_uri = path_to_uri(dir_path.join("lib.sh").to_string_lossy().as_ref());
+    let expected_s = format!("[{{\"uri\":\"{}\",\"range\":{{\"start\":{{\"line\":0,\"character\":0}},\"end\":{{\"line\":0,\"character\":0}}}}}}]", lib_uri);
+    assert_eq!(expected_s, format!("{}", msgs[0].get("result").unwrap()));
+    let (msgs, _) = server.handle_message(&request_msg(3, "textDocument/definition", uri.as_str(), 1, 8));
+    assert_eq!(Some(&JsonValue::Null), msgs[0].get("result"));
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_hover_request_for_builtin()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg("file:///tmp/test.sh", "cd /tmp\nfoo\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/hover", "file:///tmp/test.sh", 0, 1));
+    let expected_s = "{\"contents\":{\"kind\":\"markdown\",\"value\":\"```sh\\ncd [-L | -P] [directory]\\ncd -\\n```\"},\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":2}}}";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0].get("result").unwrap()));
+    let (msgs, _) = server.handle_message(&request_msg(3, "textDocument/hover", "file:///tmp/test.sh", 1, 1));
+    assert_eq!(Some(&JsonValue::Null), msgs[0].get("result"));
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_hover_request_for_source_and_trap_builtins()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg("file:///tmp/test.sh", "source lib.sh a b\ntrap -p INT\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/hover", "file:///tmp/test.sh", 0, 1));
+    let expected_s = "{\"contents\":{\"kind\":\"markdown\",\"value\":\"```sh\\nsource file [argument...]\\n```\"},\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":6}}}";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0].get("result").unwrap()));
+    let (msgs, _) = server.handle_message(&request_msg(3, "textDocument/hover", "file:///tmp/test.sh", 1, 1));
+    let expected_s = "{\"contents\":{\"kind\":\"markdown\",\"value\":\"```sh\\ntrap n [condition...]\\ntrap [action condition...]\\ntrap -p [condition...]\\n```\"},\"range\":{\"start\":{\"line\":1,\"character\":0},\"end\":{\"line\":1,\"character\":4}}}";
+    assert_eq!(String::from(expected_s), format!("{}", msgs[0].get("result").unwrap()));
+}
+
+#[test]
+fn test_lsp_server_handle_message_responds_to_completion_request()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    server.handle_message(&did_open_msg("file:///tmp/test.sh", "X=1 Y=2\nfor i in a b; do read -r line; done\nexport Z=3\necho $\n"));
+    let (msgs, _) = server.handle_message(&request_msg(2, "textDocument/completion", "file:///tmp/test.sh", 3, 6));
+    let items = msgs[0].get("result").and_then(|v| v.as_array()).unwrap();
+    let labels: Vec<&str> = items.iter().map(|item| item.get("label").and_then(|v| v.as_str()).unwrap()).collect();
+    assert_eq!(vec!["X", "Y", "Z", "i", "line"], labels);
+}
+
+#[test]
+fn test_lsp_server_handle_message_exits_after_shutdown()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    let exit_msg = JsonValue::parse("{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}").unwrap();
+    let (_, status) = server.handle_message(&exit_msg);
+    assert_eq!(Some(1), status);
+    let (msgs, status) = server.handle_message(&JsonValue::parse("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"shutdown\"}").unwrap());
+    assert_eq!(None, status);
+    assert_eq!(Some(&JsonValue::Null), msgs[0].get("result"));
+    let (_, status) = server.handle_message(&exit_msg);
+    assert_eq!(Some(0), status);
+}
+
+#[test]
+fn test_lsp_server_handle_message_complains_on_unknown_method()
+{
+    let settings = Settings::new();
+    let mut server = LspServer::new(&settings);
+    let (msgs, _) = server.handle_message(&JsonValue::parse("{\"jsonrpc\":\"2.0\",\"id\":\"x\",\"method\":\"abc\"}").unwrap());
+    assert_eq!(String::from("{\"jsonrpc\":\"2.0\",\"id\":\"x\",\"error\":{\"code\":-32601,\"message\":\"Method not found\"}}"), format!("{}", msgs[0]));
+}
+
+#[test]
+fn test_uri_to_path_decodes_uri()
+{
+    assert_eq!(String::from("/tmp/a b/ż.sh"), uri_to_path("file:///tmp/a%20b/%C5%BC.sh"));
+    assert_eq!(String::from("file:///tmp/a%20b/%C5%BC.sh"), path_to_uri("/tmp/a b/ż.sh"));
+}
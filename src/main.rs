@@ -15,72 +15,16 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
-use std::cell::*;
-use std::fs::*;
+use std::fs;
 use std::io::*;
-use std::os::unix::io::FromRawFd;
-use std::path::*;
 use std::process::exit;
-use std::rc::*;
-use rustyline;
-use rustyline::config::EditMode;
-use rustyline::error::ReadlineError;
-use rustyline::Config;
-use rustyline::Editor;
-
-#[allow(dead_code)]
-mod args;
-#[allow(dead_code)]
-mod builtins;
-#[allow(dead_code)]
-mod env;
-#[allow(dead_code)]
-mod exec;
-#[allow(dead_code)]
-mod interp;
-#[allow(dead_code)]
-mod io;
-#[allow(dead_code)]
-mod iter;
-#[allow(dead_code)]
-mod lexer;
-#[allow(dead_code)]
-mod macros;
-#[allow(dead_code)]
-mod parser;
-#[allow(dead_code)]
-mod settings;
-#[allow(dead_code)]
-mod signals;
-#[allow(dead_code)]
-mod utils;
-#[allow(dead_code)]
-mod vars;
-
-#[allow(dead_code)]
-#[cfg(test)]
-mod test_builtins;
-#[allow(dead_code)]
-#[cfg(test)]
-mod test_helpers;
-
-use builtins::initialize_builtin_funs;
-use env::*;
-use exec::*;
-use interp::*;
-use io::*;
-use lexer::*;
-use parser::*;
-use settings::*;
-use signals::set_signal_flag;
-use signals::get_sigaction_for_interrupt;
-use signals::set_sigaction_for_interrupt;
-use signals::initialize_signals;
-use utils::*;
-use vars::initialize_vars;
-
-const DEFAULT_PS2: &'static str = "> ";
-const DEFAULT_HISTORY_SIZE: usize = 500;
+use rsush::Shell;
+use rsush::dump::dump_str;
+use rsush::formatter::*;
+use rsush::lint::lint_file;
+use rsush::lsp::run_lsp_server;
+use rsush::parser::*;
+use rsush::settings::*;
 
 enum CommandFlag
 {
@@ -101,458 +45,211 @@ enum ShellCommands
     FromFile(Option<String>),
 }
 
-struct EditModeFlags
+fn interpret(shell_commands: ShellCommands, shell: &mut Shell, opts: &Options) -> i32
 {
-    vi_flag: bool,
-    emacs_flag: bool,
+    let status = match shell_commands {
+        ShellCommands::FromString(s) => shell.run_str_as_last(s.as_str()),
+        ShellCommands::FromFile(None) => shell.run_stdin(opts.interactive_flag),
+        ShellCommands::FromFile(Some(path)) => {
+            match shell.run_file(path.as_str()) {
+                Ok(tmp_status) => tmp_status,
+                Err(err) => {
+                    eprintln!("{}: {}", path, err);
+                    1
+                }
+            }
+        },
+    };
+    shell.do_exit_action(status)
 }
 
-impl EditModeFlags
+fn eprint_parser_error(err: &ParserError, s: &str, settings: &Settings)
 {
-    fn from_settings(settings: &Settings) -> EditModeFlags
-    {
-        EditModeFlags {
-            vi_flag: settings.vi_flag,
-            emacs_flag: settings.emacs_flag,
-        }
+    eprintln!("{}", err);
+    match err.pos().map(|pos| caret_lines(s, &pos, settings)).flatten() {
+        Some(lines) => eprintln!("{}", lines),
+        None => (),
     }
 }
 
-fn default_ps1() -> &'static str
+fn lint(paths: &[String], settings: &Settings) -> i32
 {
-    if getuid() == 0 {
-        "# "
-    } else {
-        "$ "
+    if paths.is_empty() {
+        eprintln!("No files");
+        return 1;
     }
-}
-
-fn update_jobs(interp: &mut Interpreter, exec: &mut Executor, settings: &Settings)
-{
-    let jobs: Vec<(u32, Job)> = exec.jobs().iter().map(|p| (*(p.0), p.1.clone())).collect();
-    for (job_id, job) in &jobs {
-        let mut is_show = false;
-        for (i, (pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
-            match status {
-                WaitStatus::None | WaitStatus::Stopped(_) => {
-                    match exec.wait_for_process(Some(*pid), false, true, false, settings) {
-                        Ok(tmp_wait_status) => {
-                            match tmp_wait_status {
-                                WaitStatus::None => (),
-                                _ => is_show = true,
-                            }
-                            match tmp_wait_status {
-                                WaitStatus::None => (),
-                                _ => exec.set_job_status(*job_id, i, tmp_wait_status),
-                            }
-                        },
-                        Err(err) => xsfprint!(exec, 2, "{}", err),
-                    }
-                },
-                _ => (),
-            }
-        }
-        let mut wait_status = WaitStatus::None;
-        match job.last_status {
-            WaitStatus::None | WaitStatus::Stopped(_) => {
-                match exec.wait_for_process(Some(job.last_pid), false, true, false, settings) {
-                    Ok(tmp_wait_status) => {
-                        wait_status = if is_show || job.show_flag {
-                            match tmp_wait_status {
-                                WaitStatus::None => job.last_status,
-                                _ => tmp_wait_status,
-                            }
-                        } else {
-                            tmp_wait_status
-                        };
-                        match tmp_wait_status {
-                            WaitStatus::None => (),
-                            _ => exec.set_job_last_status(*job_id, tmp_wait_status),
-                        }
-                    },
-                    Err(err) => xsfprint!(exec, 2, "{}", err),
+    let mut status = 0;
+    for path in paths {
+        match lint_file(path.as_str(), settings) {
+            Ok((errs, findings)) => {
+                for err in &errs {
+                    println!("{}", err);
+                    status = 1;
                 }
-            },
-            _ => {
-                if is_show || job.show_flag {
-                    wait_status = job.last_status;
+                for finding in &findings {
+                    println!("{}", finding);
+                    status = 1;
                 }
             },
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                status = 1;
+            },
         }
-        exec.set_job_show_flag(*job_id, false);
-        if settings.notify_flag {
-            match wait_status {
-                WaitStatus::None => (),
-                _ => xsfprintln!(exec, 2, "{}", interp.job_to_string(exec, *job_id, job, Some(wait_status), JobFormatFlag::None)),
-            }
-        }
-    }
-    let jobs: Vec<(u32, Job)> = exec.jobs().iter().map(|p| (*(p.0), p.1.clone())).collect();
-    for (job_id, job) in &jobs {
-        if job.is_done() {
-            exec.remove_job(*job_id);
-        }
-    }
-}
-
-fn intepret_str(s: &str, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
-{
-    initialize_signals(false);
-    interp.set_action_flag();
-    let mut cursor = Cursor::new(s.as_bytes());
-    let mut cr = CharReader::new(&mut cursor);
-    let mut lexer = Lexer::new("(command string)", &Position::new(1, 1), &mut cr, 0, false);
-    let mut parser = Parser::new();
-    parser.set_error_cont(false);
-    match parser.parse_logical_commands(&mut lexer, settings) {
-        Ok(commands) => interp.interpret_logical_commands(exec, commands.as_slice(), env, settings),
-        Err(err) => {
-            xsfprintln!(exec, 2, "{}", err);
-            1
-        },
     }
+    status
 }
 
-fn interpret_stream(path: &str, cr: &mut dyn CharRead, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, is_ignored_eof: bool, is_signal_initializing: bool) -> (i32, bool)
+fn format(args: &[String], settings: &Settings) -> i32
 {
-    if is_signal_initializing {
-        initialize_signals(false);
-    }
-    interp.set_action_flag();
-    let mut lexer = Lexer::new(path, &Position::new(1, 1), cr, 0, is_ignored_eof);
-    let mut parser = Parser::new();
+    let mut indent_width = DEFAULT_INDENT_WIDTH;
+    let mut is_diff = false;
+    let mut is_write = false;
+    let mut paths: Vec<String> = Vec::new();
+    let mut arg_iter = args.iter();
     loop {
-        match parser.parse_logical_commands_for_line(&mut lexer, settings) {
-            Ok(None) => {
-                let status = interp.last_status();
-                break (status, false);
+        match arg_iter.next() {
+            Some(arg) if arg == &String::from("--") => {
+                paths.extend(arg_iter.map(|a| a.clone()));
+                break;
             },
-            Ok(Some(commands)) => {
-                if settings.verbose_flag {
-                    xsfprint!(exec, 2, "{}", lexer.content_for_verbose());
-                    lexer.clear_content_for_verbose();
-                }
-                let status = interp.interpret_logical_commands(exec, commands.as_slice(), env, settings);
-                if interp.has_break_or_continue_or_return_or_exit() {
-                    if interp.has_exit() {
-                        break (status, true);
-                    }
-                    interp.clear_return_state();
+            Some(arg) if arg == &String::from("-i") || arg == &String::from("--indent") => {
+                match arg_iter.next().map(|a| a.parse::<usize>()) {
+                    Some(Ok(tmp_indent_width)) => indent_width = tmp_indent_width,
+                    Some(Err(_)) => {
+                        eprintln!("Invalid indentation");
+                        return 1;
+                    },
+                    None => {
+                        eprintln!("option requires an argument -- '{}'", arg);
+                        return 1;
+                    },
                 }
-                update_jobs(interp, exec, settings);
-            }
-            Err(err) => {
-                xsfprintln!(exec, 2, "{}", err);
-                break (1, false);
-            }
-        }
-    }
-}
-
-fn interpret_file(path: &str, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, is_ignored_eof: bool, is_signal_initializing: bool) -> Result<(i32, bool)>
-{
-    let mut file = File::open(path)?;
-    let mut br = BufReader::new(&mut file);
-    let mut cr = CharReader::new(&mut br);
-    Ok(interpret_stream(path, &mut cr, interp, exec, env, settings, is_ignored_eof, is_signal_initializing))
-}
-
-fn new_rustyline_editor(settings: &Settings) -> rustyline::Result<Editor<()>>
-{
-    let mut config_builder = Config::builder();
-    config_builder = config_builder.auto_add_history(false);
-    if settings.vi_flag {
-        config_builder = config_builder.edit_mode(EditMode::Vi);
-    } else if settings.emacs_flag {
-        config_builder = config_builder.edit_mode(EditMode::Emacs);
-    }
-    let config = config_builder.build();
-    Editor::<()>::with_config(config)
-}
-
-fn update_rustyline_edit_mode(editor: Editor<()>, old_edit_mode_flags: &EditModeFlags, history_size: usize, settings: &Settings) -> rustyline::Result<Editor<()>>
-{
-    if old_edit_mode_flags.vi_flag != settings.vi_flag || old_edit_mode_flags.emacs_flag != settings.emacs_flag {
-        let history: Vec<String> = editor.history().iter().map(|s| s.clone()).collect();
-        let mut new_editor = new_rustyline_editor(settings)?;
-        new_editor.history_mut().set_max_len(history_size);
-        for entry in history {
-            new_editor.add_history_entry(entry);
+            },
+            Some(arg) if arg == &String::from("--diff") => is_diff = true,
+            Some(arg) if arg == &String::from("--write") => is_write = true,
+            Some(arg) if arg.starts_with('-') && arg.len() > 1 => {
+                eprintln!("unknown option -- '{}'", arg);
+                return 1;
+            },
+            Some(arg) => paths.push(arg.clone()),
+            None => break,
         }
-        Ok(new_editor)
-    } else {
-        Ok(editor)
     }
-}
-
-fn parse_stdin_str(s: &str, line: u64, settings: &Settings) -> ParserResult<Option<Vec<Rc<LogicalCommand>>>>
-{
-    let mut cursor = Cursor::new(s.as_bytes());
-    let mut cr = CharReader::new(&mut cursor);
-    let mut lexer = Lexer::new("(standard input)", &Position::new(line, 1), &mut cr, 0, false);
-    let mut parser = Parser::new();
-    parser.parse_logical_commands_for_line(&mut lexer, settings)
-}
-
-fn load_history<P: AsRef<Path>>(editor: &mut Editor<()>, path: &P, exec: &mut Executor)
-{
-    match editor.load_history(path.as_ref()) {
-        Ok(()) => (),
-        Err(ReadlineError::Io(io_err)) if io_err.kind() == ErrorKind::NotFound => (),
-        Err(err) => xsfprintln!(exec, 2, "{}: {}", path.as_ref().to_string_lossy(), err),
-    }
-}
-
-fn save_history<P: AsRef<Path>>(editor: &mut Editor<()>, path: &P, exec: &mut Executor)
-{
-    match editor.save_history(path.as_ref()) {
-        Ok(()) => (),
-        Err(err) => xsfprintln!(exec, 2, "{}: {}", path.as_ref().to_string_lossy(), err),
-    }
-}
-
-fn interactively_interpret(interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
-{
-    settings.interactive_flag = true;
-    initialize_signals(true);
-    interp.set_action_flag();
-    exec.set_foreground();
-    let _res = setpgid(exec.shell_pid(), exec.shell_pid());
-    exec.set_foreground_for_shell(settings);
-    match interpret_file("/etc/rsushrc", interp, exec, env, settings, false, false) {
-        Ok((status, is_exit)) => {
-            if is_exit { return status; }
+    let indent = indent_for_width(indent_width);
+    if paths.is_empty() {
+        if is_write {
+            eprintln!("No files");
+            return 1;
         }
-        Err(err) if err.kind() == ErrorKind::NotFound => (),
-        Err(err) => xsfprintln!(exec, 2, "/etc/rsushrc: {}", err),
-    }
-    let home = env.var("HOME").unwrap_or(String::from("/"));
-    let path = format!("{}/.rsushrc", home);
-    match interpret_file(path.as_str(), interp, exec, env, settings, false, false) {
-        Ok((status, is_exit)) => {
-            if is_exit { return status; }
+        let mut s = String::new();
+        match stdin().read_to_string(&mut s) {
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                return 1;
+            },
         }
-        Err(err) if err.kind() == ErrorKind::NotFound => (),
-        Err(err) => xsfprintln!(exec, 2, "{}: {}", path, err),
-    }
-    let mut saved_shell_sigaction = get_sigaction_for_interrupt();
-    let mut editor = match new_rustyline_editor(settings) {
-        Ok(tmp_editor) => tmp_editor,
-        Err(err) => {
-            xsfprintln!(exec, 2, "{}", err);
-            return 1;
-        },
-    };
-    let history_size = match env.var("RSUSH_HISTSIZE") {
-        Some(rsush_histsize) => {
-            match rsush_histsize.parse::<usize>() {
-                Ok(tmp_history_size) => tmp_history_size,
-                Err(_) => {
-                    xsfprintln!(exec, 2, "Invalid history size");
-                    DEFAULT_HISTORY_SIZE
-                },
-            }
-        },
-        None => DEFAULT_HISTORY_SIZE,
-    };
-    let history_path = format!("{}/.rsush_history", home);
-    editor.history_mut().set_max_len(history_size);
-    load_history(&mut editor, &history_path, exec);
-    let mut line: u64 = 1;
-    loop {
-        let ps1 = env.var("PS1").unwrap_or(String::from(default_ps1()));
-        match editor.readline(ps1.as_str()) {
-            Ok(buf) => {
-                let mut saved_editor_sigaction = get_sigaction_for_interrupt();
-                set_sigaction_for_interrupt(&saved_shell_sigaction);
-                if !settings.nolog_flag {
-                    editor.add_history_entry(&buf);
-                }
-                let mut new_line = line;
-                let mut lines = buf.clone();
-                lines.push('\n');
-                new_line += 1;
-                let commands = match parse_stdin_str(lines.as_str(), line, settings) {
-                    Ok(None) => break interp.last_status(),
-                    Ok(Some(tmp_commands)) => Some(tmp_commands),
-                    Err(mut err @ ParserError::Syntax(_, _, _, true)) => {
-                        saved_shell_sigaction = get_sigaction_for_interrupt();
-                        set_sigaction_for_interrupt(&saved_editor_sigaction);
-                        loop {
-                            let ps2 = env.var("PS2").unwrap_or(String::from(DEFAULT_PS2));
-                            match editor.readline(ps2.as_str()) {
-                                Ok(buf2) => {
-                                    saved_editor_sigaction = get_sigaction_for_interrupt();
-                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
-                                    if !settings.nolog_flag {
-                                        editor.add_history_entry(&buf2);
-                                    }
-                                    lines.push_str(buf2.as_str());
-                                    lines.push('\n');
-                                    new_line += 1;
-                                    match parse_stdin_str(lines.as_str(), line, settings) { 
-                                        Ok(None) => break None,
-                                        Ok(Some(tmp_commands)) => break Some(tmp_commands),
-                                        Err(err2 @ ParserError::Syntax(_, _, _, true)) => err = err2,
-                                        Err(err2 @ ParserError::Syntax(_, _, _, false)) => {
-                                            xsfprintln!(exec, 2, "{}", err2);
-                                            break None;
-                                        },
-                                        Err(err2) => {
-                                            xsfprintln!(exec, 2, "{}", err2);
-                                            save_history(&mut editor, &history_path, exec);
-                                            return 1;
-                                        },
-                                    }
-                                    saved_shell_sigaction = get_sigaction_for_interrupt();
-                                    set_sigaction_for_interrupt(&saved_editor_sigaction);
-                                },
-                                Err(ReadlineError::Interrupted) => {
-                                    saved_editor_sigaction = get_sigaction_for_interrupt();
-                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
-                                    set_signal_flag(libc::SIGINT);
-                                    let saved_last_status = interp.last_status();
-                                    match interp.do_actions(exec, env, settings) {
-                                        Some(action_status) => {
-                                            if interp.has_exit_with_interactive() {
-                                                return action_status
-                                            }
-                                            interp.clear_return_state();
-                                            interp.set_last_status(saved_last_status);
-                                        },
-                                        None => (),
-                                    }
-                                    xsfprintln!(exec, 2, "{}", err);
-                                    break None
-                                },
-                                Err(ReadlineError::Eof) => {
-                                    saved_editor_sigaction = get_sigaction_for_interrupt();
-                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
-                                    xsfprintln!(exec, 2, "{}", err);
-                                    break None
-                                },
-                                Err(err2) => {
-                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
-                                    xsfprintln!(exec, 2, "{}", err2);
-                                    save_history(&mut editor, &history_path, exec);
-                                    return 1;
-                                },
-                            }
-                        }
-                    },
-                    Err(err @ ParserError::Syntax(_, _, _, false)) => {
-                        xsfprintln!(exec, 2, "{}", err);
-                        None
-                    },
-                    Err(err) => {
-                        xsfprintln!(exec, 2, "{}", err);
-                        save_history(&mut editor, &history_path, exec);
-                        return 1;
-                    },
-                };
-                if settings.verbose_flag {
-                    xsfprint!(exec, 2, "{}", lines);
+        return match format_str("(standard input)", s.as_str(), indent.as_str(), settings) {
+            Ok(new_s) => {
+                if is_diff {
+                    if new_s != s {
+                        print!("{}", unified_diff("(standard input)", "(standard input)", s.as_str(), new_s.as_str()));
+                        1
+                    } else {
+                        0
+                    }
+                } else {
+                    print!("{}", new_s);
+                    0
                 }
-                line = new_line;
-                match commands {
-                    Some(commands) => {
-                        let old_edit_mode_flags = EditModeFlags::from_settings(settings);
-                        let status = interp.interpret_logical_commands(exec, commands.as_slice(), env, settings);
-                        saved_shell_sigaction = get_sigaction_for_interrupt();
-                        set_sigaction_for_interrupt(&saved_editor_sigaction);
-                        match update_rustyline_edit_mode(editor, &old_edit_mode_flags, history_size, settings) {
-                            Ok(tmp_editor) => editor = tmp_editor,
+            },
+            Err(err) => {
+                eprint_parser_error(&err, s.as_str(), settings);
+                1
+            },
+        };
+    }
+    let mut status = 0;
+    for path in &paths {
+        let s = match fs::read_to_string(path) {
+            Ok(tmp_s) => tmp_s,
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                status = 1;
+                continue;
+            },
+        };
+        match format_str(path.as_str(), s.as_str(), indent.as_str(), settings) {
+            Ok(new_s) => {
+                if is_write {
+                    if new_s != s {
+                        match fs::write(path, new_s.as_bytes()) {
+                            Ok(()) => (),
                             Err(err) => {
-                                set_sigaction_for_interrupt(&saved_shell_sigaction);
-                                xsfprintln!(exec, 2, "{}", err);
-                                return 1;
-                            }
+                                eprintln!("{}: {}", path, err);
+                                status = 1;
+                            },
                         }
-                        saved_editor_sigaction = get_sigaction_for_interrupt();
-                        set_sigaction_for_interrupt(&saved_shell_sigaction);
-                        if interp.has_break_or_continue_or_return_or_exit() {
-                            if interp.has_exit_with_interactive() {
-                                save_history(&mut editor, &history_path, exec);
-                                break status;
-                            }
-                            interp.clear_return_state();
-                        }
-                    },
-                    None => (),
-                }
-                saved_shell_sigaction = get_sigaction_for_interrupt();
-                set_sigaction_for_interrupt(&saved_editor_sigaction);
-                update_jobs(interp, exec, settings);
-            }
-            Err(ReadlineError::Interrupted) => {
-                let saved_editor_sigaction = get_sigaction_for_interrupt();
-                set_sigaction_for_interrupt(&saved_shell_sigaction);
-                set_signal_flag(libc::SIGINT);
-                let saved_last_status = interp.last_status();
-                match interp.do_actions(exec, env, settings) {
-                    Some(action_status) => {
-                        if interp.has_exit_with_interactive() {
-                            return action_status
-                        }
-                        interp.clear_return_state();
-                        interp.set_last_status(saved_last_status);
-                    },
-                    None => (),
-                }
-                saved_shell_sigaction = get_sigaction_for_interrupt();
-                set_sigaction_for_interrupt(&saved_editor_sigaction);
-                update_jobs(interp, exec, settings);
-            },
-            Err(ReadlineError::Eof) => {
-                if !settings.ignoreeof_flag {
-                    set_sigaction_for_interrupt(&saved_shell_sigaction);
-                    save_history(&mut editor, &history_path, exec);
-                    break interp.last_status();
+                    }
+                } else if is_diff {
+                    if new_s != s {
+                        print!("{}", unified_diff(path.as_str(), path.as_str(), s.as_str(), new_s.as_str()));
+                        status = 1;
+                    }
                 } else {
-                    update_jobs(interp, exec, settings);
+                    print!("{}", new_s);
                 }
             },
             Err(err) => {
-                set_sigaction_for_interrupt(&saved_shell_sigaction);
-                xsfprintln!(exec, 2, "{}", err);
-                save_history(&mut editor, &history_path, exec);
-                return 1;
+                eprint_parser_error(&err, s.as_str(), settings);
+                status = 1;
             },
         }
     }
+    status
 }
 
-fn interpret(shell_commands: ShellCommands, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, opts: &Options) -> i32
+fn dump_ast(paths: &[String], settings: &Settings) -> i32
 {
-    exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(unsafe { File::from_raw_fd(0) })));
-    exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(unsafe { File::from_raw_fd(1) })));
-    exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(unsafe { File::from_raw_fd(2) })));
-    let mut status = match shell_commands {
-        ShellCommands::FromString(s) => intepret_str(s.as_str(), interp, exec, env, settings),
-        ShellCommands::FromFile(None) => {
-            if opts.interactive_flag.unwrap_or(isatty(0).unwrap_or(false)) {
-                interactively_interpret(interp, exec, env, settings)
-            } else {
-                let mut br = BufReader::new(stdin());
-                let mut cr = CharReader::new(&mut br);
-                interpret_stream("(standard input)", &mut cr, interp, exec, env, settings, true, true).0
-            }
-        },
-        ShellCommands::FromFile(Some(path)) => {
-            match interpret_file(path.as_str(), interp, exec, env, settings, true, true) {
-                Ok((tmp_status, _)) => tmp_status,
-                Err(err) => {
-                    xsfprintln!(exec, 2, "{}: {}", path, err);
-                    1
-                }
-            }
-        },
-    };
-    match interp.do_action(exec, 0, env, settings) {
-        Some(action_status) => status = action_status,
-        None => (),
+    if paths.is_empty() {
+        let mut s = String::new();
+        match stdin().read_to_string(&mut s) {
+            Ok(_) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                return 1;
+            },
+        }
+        return match dump_str("(standard input)", s.as_str(), settings) {
+            Ok(value) => {
+                println!("{}", value);
+                0
+            },
+            Err(err) => {
+                eprint_parser_error(&err, s.as_str(), settings);
+                1
+            },
+        };
+    }
+    let mut status = 0;
+    for path in paths {
+        let s = match fs::read_to_string(path) {
+            Ok(tmp_s) => tmp_s,
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                status = 1;
+                continue;
+            },
+        };
+        match dump_str(path.as_str(), s.as_str(), settings) {
+            Ok(value) => println!("{}", value),
+            Err(err) => {
+                eprint_parser_error(&err, s.as_str(), settings);
+                status = 1;
+            },
+        }
     }
-    exec.clear_files();
     status
 }
 
@@ -563,20 +260,23 @@ fn main()
         command_flag: CommandFlag::None,
         interactive_flag: None,
     };
-    let mut exec = Executor::new();
-    let mut interp = Interpreter::new();
-    let mut env = Environment::new();
-    let mut settings = Settings::new();
-    initialize_builtin_funs(&mut env);
-    initialize_vars(&mut env);
+    let mut shell = Shell::new();
     match shell_args.get(0) {
-        Some(arg0) => settings.arg0 = arg0.clone(),
+        Some(arg0) => shell.settings_mut().arg0 = arg0.clone(),
         None => {
             eprintln!("No shell name");
             exit(1);
         },
     }
-    let res = settings.parse_options(shell_args.as_slice(), |opt_type, c, _| {
+    match shell_args.get(1).map(|s| s.as_str()) {
+        Some("--lint") => exit(lint(&shell_args[2..], shell.settings())),
+        Some("--fmt") => exit(format(&shell_args[2..], shell.settings())),
+        Some("--dump-ast") => exit(dump_ast(&shell_args[2..], shell.settings())),
+        Some("--lsp") => exit(run_lsp_server(shell.settings())),
+        _ => (),
+    }
+    shell.initialize_signals();
+    let res = shell.settings_mut().parse_options(shell_args.as_slice(), |opt_type, c, _| {
             match (opt_type, c) {
                 (OptionType::Minus, 'c') => {
                     opts.command_flag = CommandFlag::String;
@@ -607,25 +307,25 @@ fn main()
                         args.extend_from_slice(&shell_args[(i + 1)..]);
                     }
                     match &file {
-                        Some(file) => settings.arg0 = file.clone(),
+                        Some(file) => shell.settings_mut().arg0 = file.clone(),
                         None => (),
                     }
-                    settings.current_args_mut().set_args(args);
-                    let status = interpret(ShellCommands::FromFile(file), &mut interp, &mut exec, &mut env, &mut settings, &opts);
+                    shell.settings_mut().current_args_mut().set_args(args);
+                    let status = interpret(ShellCommands::FromFile(file), &mut shell, &opts);
                     exit(status);
                 },
                 CommandFlag::String => {
                     match shell_args.get(i) {
                         Some(s) => {
                             let mut args = Vec::new();
-                            if shell_args.len() >= i + 1 {
-                                settings.arg0 = shell_args[i + 1].clone();
+                            if shell_args.len() > i + 1 {
+                                shell.settings_mut().arg0 = shell_args[i + 1].clone();
                             }
                             if shell_args.len() >= i + 2 {
                                 args.extend_from_slice(&shell_args[(i + 2)..]);
                             }
-                            settings.current_args_mut().set_args(args);
-                            let status = interpret(ShellCommands::FromString(s.clone()), &mut interp, &mut exec, &mut env, &mut settings, &opts);
+                            shell.settings_mut().current_args_mut().set_args(args);
+                            let status = interpret(ShellCommands::FromString(s.clone()), &mut shell, &opts);
                             exit(status);
                         },
                         None => {
@@ -639,8 +339,8 @@ fn main()
                     if shell_args.len() >= i {
                         args.extend_from_slice(&shell_args[i..]);
                     }
-                    settings.current_args_mut().set_args(args);
-                    let status = interpret(ShellCommands::FromFile(None), &mut interp, &mut exec, &mut env, &mut settings, &opts);
+                    shell.settings_mut().current_args_mut().set_args(args);
+                    let status = interpret(ShellCommands::FromFile(None), &mut shell, &opts);
                     exit(status);
                 },
             }
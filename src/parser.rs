@@ -16,19 +16,23 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
 use std::cell::*;
+use std::cmp::max;
 use std::collections::HashSet;
 use std::fmt;
 use std::io::*;
 use std::rc::*;
 use std::result;
+use crate::io::*;
 use crate::lexer::*;
 use crate::settings::*;
+use crate::utils::*;
 
 #[derive(Clone)]
 pub struct Word
 {
     pub path: String,
     pub pos: Position,
+    pub end_pos: Position,
     pub word_elems: Vec<WordElement>,
 }
 
@@ -43,6 +47,40 @@ impl fmt::Display for Word
     }
 }
 
+pub fn literal_word_str(word: &Word) -> Option<String>
+{
+    let mut s = String::new();
+    for word_elem in &word.word_elems {
+        match word_elem {
+            WordElement::Simple(SimpleWordElement::String(s2)) => s.push_str(s2.as_str()),
+            WordElement::SinglyQuoted(s2) => s.push_str(s2.as_str()),
+            WordElement::DoublyQuoted(simple_word_elems) => {
+                for simple_word_elem in simple_word_elems {
+                    match simple_word_elem {
+                        SimpleWordElement::String(s2) => s.push_str(s2.as_str()),
+                        _ => return None,
+                    }
+                }
+            },
+            _ => return None,
+        }
+    }
+    Some(s)
+}
+
+pub fn assignment_name(word: &Word) -> Option<String>
+{
+    match word.word_elems.first() {
+        Some(WordElement::Simple(SimpleWordElement::String(s))) => {
+            match s.split_once('=') {
+                Some((name, _)) if is_name_str(name) => Some(String::from(name)),
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct FirstWord<'a>(pub &'a Word);
 
@@ -81,9 +119,11 @@ impl<'a> fmt::Display for FirstWord<'a>
 pub struct HereDocument
 {
     pub delim: String,
+    pub pos: Position,
     pub has_minus: bool,
     pub has_quoted: bool, 
     pub simple_word_elems: Vec<SimpleWordElement>,
+    pub end_pos: Position,
 }
 
 impl fmt::Display for HereDocument
@@ -100,13 +140,13 @@ impl fmt::Display for HereDocument
 #[derive(Clone)]
 pub enum Redirection
 {
-    Input(String, Position, Option<i32>, Rc<Word>),
-    Output(String, Position, Option<i32>, Rc<Word>, bool),
-    InputAndOutput(String, Position, Option<i32>, Rc<Word>),
-    Appending(String, Position, Option<i32>, Rc<Word>),
-    InputDuplicating(String, Position, Option<i32>, Rc<Word>),
-    OutputDuplicating(String, Position, Option<i32>, Rc<Word>),
-    HereDocument(String, Position, Option<i32>, Rc<RefCell<HereDocument>>),
+    Input(String, Position, Position, Option<i32>, Rc<Word>),
+    Output(String, Position, Position, Option<i32>, Rc<Word>, bool),
+    InputAndOutput(String, Position, Position, Option<i32>, Rc<Word>),
+    Appending(String, Position, Position, Option<i32>, Rc<Word>),
+    InputDuplicating(String, Position, Position, Option<i32>, Rc<Word>),
+    OutputDuplicating(String, Position, Position, Option<i32>, Rc<Word>),
+    HereDocument(String, Position, Position, Option<i32>, Rc<RefCell<HereDocument>>),
 }
 
 impl Redirection
@@ -114,40 +154,53 @@ impl Redirection
     pub fn path(&self) -> String
     {
         match self {
-            Redirection::Input(path, _, _, _) => path.clone(),
-            Redirection::Output(path, _, _, _, _) => path.clone(),
-            Redirection::InputAndOutput(path, _, _, _) => path.clone(),
-            Redirection::Appending(path, _, _, _) => path.clone(),
-            Redirection::InputDuplicating(path, _, _, _) => path.clone(),
-            Redirection::OutputDuplicating(path, _, _, _) => path.clone(),
-            Redirection::HereDocument(path, _, _, _) => path.clone(),
+            Redirection::Input(path, _, _, _, _) => path.clone(),
+            Redirection::Output(path, _, _, _, _, _) => path.clone(),
+            Redirection::InputAndOutput(path, _, _, _, _) => path.clone(),
+            Redirection::Appending(path, _, _, _, _) => path.clone(),
+            Redirection::InputDuplicating(path, _, _, _, _) => path.clone(),
+            Redirection::OutputDuplicating(path, _, _, _, _) => path.clone(),
+            Redirection::HereDocument(path, _, _, _, _) => path.clone(),
         }
     }
 
     pub fn pos(&self) -> Position
     {
         match self {
-            Redirection::Input(_, pos, _, _) => *pos,
-            Redirection::Output(_, pos, _, _, _) => *pos,
-            Redirection::InputAndOutput(_, pos, _, _) => *pos,
-            Redirection::Appending(_, pos, _, _) => *pos,
-            Redirection::InputDuplicating(_, pos, _, _) => *pos,
-            Redirection::OutputDuplicating(_, pos, _, _) => *pos,
-            Redirection::HereDocument(_, pos, _, _) => *pos,
+            Redirection::Input(_, pos, _, _, _) => *pos,
+            Redirection::Output(_, pos, _, _, _, _) => *pos,
+            Redirection::InputAndOutput(_, pos, _, _, _) => *pos,
+            Redirection::Appending(_, pos, _, _, _) => *pos,
+            Redirection::InputDuplicating(_, pos, _, _, _) => *pos,
+            Redirection::OutputDuplicating(_, pos, _, _, _) => *pos,
+            Redirection::HereDocument(_, pos, _, _, _) => *pos,
         }
     }
 
-    fn fmt_and_add_here_doc(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn end_pos(&self) -> Position
     {
         match self {
-            Redirection::Input(_, _, n, word) => {
+            Redirection::Input(_, _, end_pos, _, _) => *end_pos,
+            Redirection::Output(_, _, end_pos, _, _, _) => *end_pos,
+            Redirection::InputAndOutput(_, _, end_pos, _, _) => *end_pos,
+            Redirection::Appending(_, _, end_pos, _, _) => *end_pos,
+            Redirection::InputDuplicating(_, _, end_pos, _, _) => *end_pos,
+            Redirection::OutputDuplicating(_, _, end_pos, _, _) => *end_pos,
+            Redirection::HereDocument(_, _, end_pos, _, _) => *end_pos,
+        }
+    }
+
+    pub fn fmt_and_add_here_doc(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    {
+        match self {
+            Redirection::Input(_, _, _, n, word) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
                 }
                 write!(f, "< {}", word)
             },
-            Redirection::Output(_, _, n, word, is_bar) => {
+            Redirection::Output(_, _, _, n, word, is_bar) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
@@ -158,35 +211,35 @@ impl Redirection
                     write!(f, ">| {}", word)
                 }
             },
-            Redirection::InputAndOutput(_, _, n, word) => {
+            Redirection::InputAndOutput(_, _, _, n, word) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
                 }
                 write!(f, "<> {}", word)
             },
-            Redirection::Appending(_, _, n, word) => {
+            Redirection::Appending(_, _, _, n, word) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
                 }
                 write!(f, ">> {}", word)
             },
-            Redirection::InputDuplicating(_, _, n, word) => {
+            Redirection::InputDuplicating(_, _, _, n, word) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
                 }
                 write!(f, "<& {}", word)
             },
-            Redirection::OutputDuplicating(_, _, n, word) => {
+            Redirection::OutputDuplicating(_, _, _, n, word) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
                 }
                 write!(f, ">& {}", word)
             },
-            Redirection::HereDocument(_, _, n, here_doc) => {
+            Redirection::HereDocument(_, _, _, n, here_doc) => {
                 match n {
                     Some(n) => write!(f, "{}", n)?,
                     None => (),
@@ -224,7 +277,7 @@ pub struct SimpleCommand
 
 impl SimpleCommand
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         let mut is_first = true;
         for word in &self.words {
@@ -297,7 +350,7 @@ pub enum CompoundCommand
 
 impl CompoundCommand
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         match self {
             CompoundCommand::BraceGroup(commands) => {
@@ -401,13 +454,14 @@ pub struct FunctionBody
 {
     pub path: String,
     pub pos: Position,
+    pub end_pos: Position,
     pub command: CompoundCommand,
     pub redirects: Vec<Rc<Redirection>>,
 }
 
 impl FunctionBody
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         self.command.fmt_and_add_here_docs(f, here_docs)?;
         for redirect in &self.redirects {
@@ -437,9 +491,9 @@ impl fmt::Display for FunctionBody
 #[derive(Clone)]
 pub enum Command
 {
-    Simple(String, Position, SimpleCommand),
-    Compound(String, Position, CompoundCommand, Vec<Rc<Redirection>>),
-    FunctionDefinition(String, Position, Rc<Word>, Rc<FunctionBody>),
+    Simple(String, Position, Position, SimpleCommand),
+    Compound(String, Position, Position, CompoundCommand, Vec<Rc<Redirection>>),
+    FunctionDefinition(String, Position, Position, Rc<Word>, Rc<FunctionBody>),
 }
 
 impl Command
@@ -447,26 +501,35 @@ impl Command
     pub fn path(&self) -> String
     {
         match self {
-            Command::Simple(path, _, _) => path.clone(),
-            Command::Compound(path, _, _, _) => path.clone(),
-            Command::FunctionDefinition(path, _, _, _) => path.clone(),
+            Command::Simple(path, _, _, _) => path.clone(),
+            Command::Compound(path, _, _, _, _) => path.clone(),
+            Command::FunctionDefinition(path, _, _, _, _) => path.clone(),
         }
     }
 
     pub fn pos(&self) -> Position
     {
         match self {
-            Command::Simple(_, pos, _) => *pos,
-            Command::Compound(_, pos, _, _) => *pos,
-            Command::FunctionDefinition(_, pos, _, _) => *pos,
+            Command::Simple(_, pos, _, _) => *pos,
+            Command::Compound(_, pos, _, _, _) => *pos,
+            Command::FunctionDefinition(_, pos, _, _, _) => *pos,
         }
     }
 
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn end_pos(&self) -> Position
     {
         match self {
-            Command::Simple(_, _, simple_command) => simple_command.fmt_and_add_here_docs(f, here_docs),
-            Command::Compound(_, _, compound_command, redirects) => {
+            Command::Simple(_, _, end_pos, _) => *end_pos,
+            Command::Compound(_, _, end_pos, _, _) => *end_pos,
+            Command::FunctionDefinition(_, _, end_pos, _, _) => *end_pos,
+        }
+    }
+
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    {
+        match self {
+            Command::Simple(_, _, _, simple_command) => simple_command.fmt_and_add_here_docs(f, here_docs),
+            Command::Compound(_, _, _, compound_command, redirects) => {
                 compound_command.fmt_and_add_here_docs(f, here_docs)?;
                 for redirect in redirects.iter() {
                     write!(f, " ")?;
@@ -474,7 +537,7 @@ impl Command
                 }
                 Ok(())
             },
-            Command::FunctionDefinition(_, _, name_word, fun_body) => {
+            Command::FunctionDefinition(_, _, _, name_word, fun_body) => {
                 write!(f, "{}() ", name_word)?;
                 fun_body.fmt_and_add_here_docs(f, here_docs)
             },
@@ -503,13 +566,14 @@ pub struct PipeCommand
 {
     pub path: String,
     pub pos: Position,
+    pub end_pos: Position,
     pub is_negative: bool,
     pub commands: Vec<Rc<Command>>,
 }
 
 impl PipeCommand
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         if self.is_negative {
             write!(f, "!")?;
@@ -575,6 +639,7 @@ pub struct LogicalCommand
 {
     pub path: String,
     pub pos: Position,
+    pub end_pos: Position,
     pub first_command: Rc<PipeCommand>,
     pub pairs: Vec<LogicalPair>,
     pub is_in_background: bool,
@@ -582,7 +647,7 @@ pub struct LogicalCommand
 
 impl LogicalCommand
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         self.first_command.fmt_and_add_here_docs(f, here_docs)?;
         for pair in &self.pairs {
@@ -614,7 +679,7 @@ pub struct LogicalCommandSlice<'a>(pub &'a [Rc<LogicalCommand>]);
 
 impl<'a> LogicalCommandSlice<'a>
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         for (i, command) in self.0.iter().enumerate() {
             command.fmt_and_add_here_docs(f, here_docs)?;
@@ -655,7 +720,7 @@ pub struct LogicalCommandSliceWithLastSemicolon<'a>(pub &'a [Rc<LogicalCommand>]
 
 impl<'a> LogicalCommandSliceWithLastSemicolon<'a>
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     {
         for command in self.0.iter() {
             command.fmt_and_add_here_docs(f, here_docs)?;
@@ -695,7 +760,7 @@ pub struct AliasCommand
 
 impl AliasCommand
 {
-    fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
+    pub fn fmt_and_add_here_docs(&self, f: &mut fmt::Formatter<'_>, here_docs: &mut Vec<Rc<RefCell<HereDocument>>>) -> fmt::Result
     { self.command.fmt_and_add_here_docs(f, here_docs) }
 }
 
@@ -810,11 +875,11 @@ impl fmt::Display for BinaryOperator
 #[derive(Clone)]
 pub enum ArithmeticExpression
 {
-    Number(String, Position, i64),
-    Parameter(String, Position, ParameterName),
-    Unary(String, Position, UnaryOperator, Rc<ArithmeticExpression>),
-    Binary(String, Position, Rc<ArithmeticExpression>, BinaryOperator, Rc<ArithmeticExpression>),
-    Conditional(String, Position, Rc<ArithmeticExpression>, Rc<ArithmeticExpression>, Rc<ArithmeticExpression>),
+    Number(String, Position, Position, i64),
+    Parameter(String, Position, Position, ParameterName),
+    Unary(String, Position, Position, UnaryOperator, Rc<ArithmeticExpression>),
+    Binary(String, Position, Position, Rc<ArithmeticExpression>, BinaryOperator, Rc<ArithmeticExpression>),
+    Conditional(String, Position, Position, Rc<ArithmeticExpression>, Rc<ArithmeticExpression>, Rc<ArithmeticExpression>),
 }
 
 impl ArithmeticExpression
@@ -822,75 +887,86 @@ impl ArithmeticExpression
     pub fn path(&self) -> String
     {
         match self {
-            ArithmeticExpression::Number(path, _, _) => path.clone(),
-            ArithmeticExpression::Parameter(path, _, _) => path.clone(),
-            ArithmeticExpression::Unary(path, _, _, _) => path.clone(),
-            ArithmeticExpression::Binary(path, _, _, _, _) => path.clone(),
-            ArithmeticExpression::Conditional(path, _, _, _, _) => path.clone(),
+            ArithmeticExpression::Number(path, _, _, _) => path.clone(),
+            ArithmeticExpression::Parameter(path, _, _, _) => path.clone(),
+            ArithmeticExpression::Unary(path, _, _, _, _) => path.clone(),
+            ArithmeticExpression::Binary(path, _, _, _, _, _) => path.clone(),
+            ArithmeticExpression::Conditional(path, _, _, _, _, _) => path.clone(),
         }
     }
 
     pub fn pos(&self) -> Position
     {
         match self {
-            ArithmeticExpression::Number(_, pos, _) => *pos,
-            ArithmeticExpression::Parameter(_, pos, _) => *pos,
-            ArithmeticExpression::Unary(_, pos, _, _) => *pos,
-            ArithmeticExpression::Binary(_, pos, _, _, _) => *pos,
-            ArithmeticExpression::Conditional(_, pos, _, _, _) => *pos,
+            ArithmeticExpression::Number(_, pos, _, _) => *pos,
+            ArithmeticExpression::Parameter(_, pos, _, _) => *pos,
+            ArithmeticExpression::Unary(_, pos, _, _, _) => *pos,
+            ArithmeticExpression::Binary(_, pos, _, _, _, _) => *pos,
+            ArithmeticExpression::Conditional(_, pos, _, _, _, _) => *pos,
+        }
+    }
+
+    pub fn end_pos(&self) -> Position
+    {
+        match self {
+            ArithmeticExpression::Number(_, _, end_pos, _) => *end_pos,
+            ArithmeticExpression::Parameter(_, _, end_pos, _) => *end_pos,
+            ArithmeticExpression::Unary(_, _, end_pos, _, _) => *end_pos,
+            ArithmeticExpression::Binary(_, _, end_pos, _, _, _) => *end_pos,
+            ArithmeticExpression::Conditional(_, _, end_pos, _, _, _) => *end_pos,
         }
     }
 
     fn fmt_with_prec(&self, f: &mut fmt::Formatter<'_>, expected_prec: i32) -> fmt::Result
     {
         let (prec, is_left_to_right) = match self {
-            ArithmeticExpression::Number(_, _, _) => (0, true),
-            ArithmeticExpression::Parameter(_, _, _) => (0, true),
-            ArithmeticExpression::Unary(_, _, UnaryOperator::Negate, _) => (1, false),
-            ArithmeticExpression::Unary(_, _, UnaryOperator::Not, _) => (1, false),
-            ArithmeticExpression::Unary(_, _, UnaryOperator::LogicalNot, _) => (1, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Multiply, _) => (2, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Divide, _) => (2, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Module, _) => (2, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Add, _) => (3, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Subtract, _) => (3, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ShiftLeft, _) => (4, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ShiftRight, _) => (4, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::LessThan, _) => (5, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::GreaterEqual, _) => (5, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::GreaterThan, _) => (5, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::LessEqual, _) => (5, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Equal, _) => (6, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::NotEqual, _) => (6, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::And, _) => (7, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ExclusiveOr, _) => (8, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Or, _) => (9, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::LogicalAnd, _) => (10, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::LogicalOr, _) => (11, true),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::Assign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::MultiplyAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::DivideAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ModuleAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::AddAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::SubtractAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ShiftLeftAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ShiftRightAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::AndAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::ExclusiveOrAssign, _) => (12, false),
-            ArithmeticExpression::Binary(_, _, _, BinaryOperator::OrAssign, _) => (12, false),
-            ArithmeticExpression::Conditional(_, _, _, _, _) => (12, false),
+            ArithmeticExpression::Number(_, _, _, _) => (0, true),
+            ArithmeticExpression::Parameter(_, _, _, _) => (0, true),
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::Negate, _) => (1, false),
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::Not, _) => (1, false),
+            ArithmeticExpression::Unary(_, _, _, UnaryOperator::LogicalNot, _) => (1, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Multiply, _) => (2, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Divide, _) => (2, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Module, _) => (2, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Add, _) => (3, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Subtract, _) => (3, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ShiftLeft, _) => (4, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ShiftRight, _) => (4, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::LessThan, _) => (5, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::GreaterEqual, _) => (5, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::GreaterThan, _) => (5, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::LessEqual, _) => (5, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Equal, _) => (6, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::NotEqual, _) => (6, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::And, _) => (7, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ExclusiveOr, _) => (8, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Or, _) => (9, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::LogicalAnd, _) => (10, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::LogicalOr, _) => (11, true),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::Assign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::MultiplyAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::DivideAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ModuleAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::AddAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::SubtractAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ShiftLeftAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ShiftRightAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::AndAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::ExclusiveOrAssign, _) => (12, false),
+            ArithmeticExpression::Binary(_, _, _, _, BinaryOperator::OrAssign, _) => (12, false),
+            ArithmeticExpression::Conditional(_, _, _, _, _, _) => (12, false),
         };
         if expected_prec < prec {
             write!(f, "(")?;
         }
         match self {
-            ArithmeticExpression::Number(_, _, n) => write!(f, "{}", n)?,
-            ArithmeticExpression::Parameter(_, _, param_name) => write!(f, "${}", param_name)?,
-            ArithmeticExpression::Unary(_, _, op, expr1) => {
+            ArithmeticExpression::Number(_, _, _, n) => write!(f, "{}", n)?,
+            ArithmeticExpression::Parameter(_, _, _, param_name) => write!(f, "${}", param_name)?,
+            ArithmeticExpression::Unary(_, _, _, op, expr1) => {
                 write!(f, "{}", op)?;
                 expr1.fmt_with_prec(f, prec)?;
             },
-            ArithmeticExpression::Binary(_, _, expr1, op, expr2) => {
+            ArithmeticExpression::Binary(_, _, _, expr1, op, expr2) => {
                 let prec1 = if is_left_to_right {
                     prec
                 } else {
@@ -905,7 +981,7 @@ impl ArithmeticExpression
                 write!(f, " {} ", op)?;
                 expr2.fmt_with_prec(f, prec2)?;
             },
-            ArithmeticExpression::Conditional(_, _, expr1, expr2, expr3) => {
+            ArithmeticExpression::Conditional(_, _, _, expr1, expr2, expr3) => {
                 expr1.fmt_with_prec(f, prec - 1)?;
                 write!(f, " ? ")?;
                 expr2.fmt_with_prec(f, prec)?;
@@ -932,18 +1008,30 @@ pub struct Parser
     has_first_word_or_third_word: bool,
     has_error_cont: bool,
     is_in_backquote: bool,
+    open_constructs: Vec<(&'static str, Position)>,
+    unclosed_constructs: Vec<(&'static str, Position)>,
 }
 
 impl Parser
 {
     pub fn new() -> Parser
-    { Parser { here_docs: Vec::new(), has_first_word_or_third_word: false, has_error_cont: true, is_in_backquote: false, } }
+    { Parser { here_docs: Vec::new(), has_first_word_or_third_word: false, has_error_cont: true, is_in_backquote: false, open_constructs: Vec::new(), unclosed_constructs: Vec::new(), } }
 
     pub fn set_error_cont(&mut self, b: bool)
     { self.has_error_cont = b; }
 
     pub fn set_backquote(&mut self, b: bool)
     { self.is_in_backquote = b; }
+
+    fn eof_error<'a>(&mut self, lexer: &Lexer<'a>, pos: Position, is_cont: bool) -> ParserError
+    {
+        // The innermost construct is reported by this error and the other constructs are left
+        // for error recovery.
+        match self.open_constructs.pop() {
+            Some((name, construct_pos)) => ParserError::Syntax(lexer.path().clone(), pos, format!("unterminated `{}` started at {}", name, construct_pos), is_cont),
+            None => ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), is_cont),
+        }
+    }
     
     fn parse_words_without_last_token<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> ParserResult<Vec<Rc<Word>>>
     {
@@ -954,6 +1042,7 @@ impl Parser
                     let word = Word {
                         path: lexer.path().clone(),
                         pos,
+                        end_pos: lexer.token_end_pos(),
                         word_elems,
                     };
                     words.push(Rc::new(word));
@@ -983,7 +1072,11 @@ impl Parser
             let mut here_doc = here_doc.borrow_mut();
             lexer.push_in_here_doc(here_doc.delim.as_str(), here_doc.has_minus, here_doc.has_quoted);
             match lexer.next_token(settings)? {
-                (Token::HereDoc(simple_word_elems, _, _), _) => here_doc.simple_word_elems = simple_word_elems,
+                (Token::HereDoc(simple_word_elems, _, _), pos) => {
+                    here_doc.pos = pos;
+                    here_doc.simple_word_elems = simple_word_elems;
+                    here_doc.end_pos = lexer.token_end_pos();
+                },
                 (_, _) => panic!("token isn't here document"), 
             }
             lexer.pop_state();
@@ -1013,11 +1106,12 @@ impl Parser
                 let word = Word {
                     path: lexer.path().clone(),
                     pos,
+                    end_pos: lexer.token_end_pos(),
                     word_elems: word_elems.clone(),
                 };
                 Ok(word)
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1032,7 +1126,7 @@ impl Parser
             },
             (Token::EOF, pos) => {
                 lexer.pop_state();
-                Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont))
+                Err(self.eof_error(lexer, pos, self.has_error_cont))
             },
             (_, pos) => {
                 lexer.pop_state();
@@ -1050,7 +1144,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::Input(lexer.path().clone(), pos, n, Rc::new(word))))
+                Ok(Some(Redirection::Input(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word))))
             },
             (Token::Greater(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1058,7 +1152,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::Output(lexer.path().clone(), pos, n, Rc::new(word), false)))
+                Ok(Some(Redirection::Output(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word), false)))
             },
             (Token::LessLess(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1068,13 +1162,15 @@ impl Parser
                 let (s, is_quoted) = self.parse_here_doc_word(lexer, settings)?;
                 let here_doc = HereDocument {
                     delim: s,
+                    pos,
                     has_minus: false,
                     has_quoted: is_quoted,
                     simple_word_elems: Vec::new(),
+                    end_pos: pos,
                 };
                 let here_doc = Rc::new(RefCell::new(here_doc));
                 self.here_docs.push(here_doc.clone());
-                Ok(Some(Redirection::HereDocument(lexer.path().clone(), pos, n, here_doc.clone())))
+                Ok(Some(Redirection::HereDocument(lexer.path().clone(), pos, lexer.token_end_pos(), n, here_doc.clone())))
             },
             (Token::LessLessMinus(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1084,13 +1180,15 @@ impl Parser
                 let (s, is_quoted) = self.parse_here_doc_word(lexer, settings)?;
                 let here_doc = HereDocument {
                     delim: s,
+                    pos,
                     has_minus: true,
                     has_quoted: is_quoted,
                     simple_word_elems: Vec::new(),
+                    end_pos: pos,
                 };
                 let here_doc = Rc::new(RefCell::new(here_doc));
                 self.here_docs.push(here_doc.clone());
-                Ok(Some(Redirection::HereDocument(lexer.path().clone(), pos, n, here_doc.clone())))
+                Ok(Some(Redirection::HereDocument(lexer.path().clone(), pos, lexer.token_end_pos(), n, here_doc.clone())))
             },
             (Token::LessGreater(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1098,7 +1196,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::InputAndOutput(lexer.path().clone(), pos, n, Rc::new(word))))
+                Ok(Some(Redirection::InputAndOutput(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word))))
             },
             (Token::LessAmp(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1106,7 +1204,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::InputDuplicating(lexer.path().clone(), pos, n, Rc::new(word))))
+                Ok(Some(Redirection::InputDuplicating(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word))))
             },
             (Token::GreaterGreater(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1114,7 +1212,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::Appending(lexer.path().clone(), pos, n, Rc::new(word))))
+                Ok(Some(Redirection::Appending(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word))))
             },
             (Token::GreaterAmp(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1122,7 +1220,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::OutputDuplicating(lexer.path().clone(), pos, n, Rc::new(word))))
+                Ok(Some(Redirection::OutputDuplicating(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word))))
             },
             (Token::GreaterBar(n), pos) => {
                 if self.has_first_word_or_third_word {
@@ -1130,7 +1228,7 @@ impl Parser
                     self.has_first_word_or_third_word = false;
                 }
                 let word = self.parse_redirect_word(lexer, settings)?;
-                Ok(Some(Redirection::Output(lexer.path().clone(), pos, n, Rc::new(word), true)))
+                Ok(Some(Redirection::Output(lexer.path().clone(), pos, lexer.token_end_pos(), n, Rc::new(word), true)))
             },
             (token, pos) => {
                 lexer.undo_token(&token, &pos);
@@ -1156,7 +1254,7 @@ impl Parser
         if !is_do_word {
             match lexer.next_token(settings)? {
                 (Token::Do, _) => (),
-                (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                 (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
             }
         }
@@ -1173,7 +1271,7 @@ impl Parser
                 }
                 Ok(commands)
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1186,6 +1284,7 @@ impl Parser
                 let first_word = Word {
                     path: lexer.path().clone(),
                     pos,
+                    end_pos: lexer.token_end_pos(),
                     word_elems: word_elems.clone(),
                 };
                 words.push(Rc::new(first_word));
@@ -1206,17 +1305,18 @@ impl Parser
                             let word = Word {
                                 path: lexer.path().clone(),
                                 pos,
+                                end_pos: lexer.token_end_pos(),
                                 word_elems: word_elems.clone(),
                             };
                             words.push(Rc::new(word));
                         },
-                        (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                        (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                         (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                     }
                 }
                 Ok(words)
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1239,7 +1339,7 @@ impl Parser
                 lexer.pop_state();
                 Ok(CompoundCommand::BraceGroup(commands))
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1262,7 +1362,7 @@ impl Parser
                 lexer.pop_state();
                 Ok(CompoundCommand::Subshell(commands))
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1274,6 +1374,7 @@ impl Parser
                 let word = Word {
                     path: lexer.path().clone(),
                     pos,
+                    end_pos: lexer.token_end_pos(),
                     word_elems,
                 };
                 lexer.push_third_word();
@@ -1295,7 +1396,7 @@ impl Parser
                                 let commands = self.parse_do_clause(lexer, false, settings)?;
                                 Ok(CompoundCommand::For(Rc::new(word), Some(words), commands))
                             },
-                            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
                             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                         }
                     },
@@ -1316,11 +1417,11 @@ impl Parser
                         let commands = self.parse_do_clause(lexer, false, settings)?;
                         Ok(CompoundCommand::For(Rc::new(word), None, commands))
                     },
-                    (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                    (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
                     (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                 }
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1332,6 +1433,7 @@ impl Parser
                 let word = Word {
                     path: lexer.path().clone(),
                     pos,
+                    end_pos: lexer.token_end_pos(),
                     word_elems,
                 };
                 lexer.push_third_word();
@@ -1363,7 +1465,7 @@ impl Parser
                             let pattern_words = self.parse_pattern_words(lexer, settings)?;
                             match lexer.next_token(settings)? {
                                 (Token::RParen, _) => (),
-                                (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                                (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                                 (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                             }
                             lexer.push_first_word();
@@ -1383,7 +1485,7 @@ impl Parser
                                         self.has_first_word_or_third_word = false;
                                     }
                                 },
-                                (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                                (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                                 (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                             }
                             let pair = CasePair {
@@ -1395,11 +1497,11 @@ impl Parser
                         lexer.pop_state();
                         Ok(CompoundCommand::Case(Rc::new(word), pairs))
                     },
-                    (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                    (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
                     (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                 }
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1411,7 +1513,7 @@ impl Parser
         let cond_commands = self.parse_logical_commands_without_last_token(lexer, settings)?;
         match lexer.next_token(settings)? {
             (Token::Then, _) => (),
-            (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
         let commands = self.parse_logical_commands_without_last_token(lexer, settings)?;
@@ -1427,7 +1529,7 @@ impl Parser
             let cond_commands = self.parse_logical_commands_without_last_token(lexer, settings)?;
             match lexer.next_token(settings)? {
                 (Token::Then, _) => (),
-                (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                 (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
             }
             let commands = self.parse_logical_commands_without_last_token(lexer, settings)?;
@@ -1449,7 +1551,7 @@ impl Parser
                 let commands2 = self.parse_logical_commands_without_last_token(lexer, settings)?;
                 match lexer.next_token(settings)? {
                     (Token::Fi, _) => (),
-                    (Token::EOF, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+                    (Token::EOF, pos) => return Err(self.eof_error(lexer, pos, self.has_error_cont)),
                     (_, pos) => return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                 }
                 if self.has_first_word_or_third_word {
@@ -1458,7 +1560,7 @@ impl Parser
                 }
                 Ok(CompoundCommand::If(cond_commands, commands, pairs, Some(commands2)))
             },
-            (Token::EOF, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), self.has_error_cont)),
+            (Token::EOF, pos) => Err(self.eof_error(lexer, pos, self.has_error_cont)),
             (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
         }
     }
@@ -1487,43 +1589,57 @@ impl Parser
             (Token::LBrace, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("{", pos));
                 let compound_command = self.parse_brace_group(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::LParen, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("(", pos));
                 let compound_command = self.parse_subshell(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::For, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("for", pos));
                 let compound_command = self.parse_for_clause(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::Case, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("case", pos));
                 let compound_command = self.parse_case_clause(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::If, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("if", pos));
                 let compound_command = self.parse_if_clause(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::While, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("while", pos));
                 let compound_command = self.parse_while_clause(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (Token::Until, pos) => {
                 lexer.pop_state();
                 self.has_first_word_or_third_word = false;
+                self.open_constructs.push(("until", pos));
                 let compound_command = self.parse_until_clause(lexer, settings)?;
+                self.open_constructs.pop();
                 Ok(Some((compound_command, pos)))
             },
             (token, pos) => {
@@ -1560,6 +1676,7 @@ impl Parser
                     let word = Word {
                         path: lexer.path().clone(),
                         pos,
+                        end_pos: lexer.token_end_pos(),
                         word_elems: word_elems.clone(),
                     };
                     words.push(Rc::new(word.clone()));
@@ -1601,6 +1718,7 @@ impl Parser
                 let fun_body = FunctionBody {
                     path: lexer.path().clone(),
                     pos,
+                    end_pos: lexer.token_end_pos(),
                     command,
                     redirects,
                 };
@@ -1613,7 +1731,7 @@ impl Parser
     fn parse_command<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> ParserResult<Option<Command>>
     {
         match self.parse_compound_command_and_redirects(lexer, settings)? {
-            Some((compound_command, redirects, pos)) => Ok(Some(Command::Compound(lexer.path().clone(), pos, compound_command, redirects))),
+            Some((compound_command, redirects, pos)) => Ok(Some(Command::Compound(lexer.path().clone(), pos, lexer.token_end_pos(), compound_command, redirects))),
             None => {
                 match lexer.next_token(settings)? {
                     (Token::Word(word_elems), pos) => {
@@ -1622,6 +1740,7 @@ impl Parser
                         let word = Word {
                             path: lexer.path().clone(),
                             pos,
+                            end_pos: lexer.token_end_pos(),
                             word_elems: word_elems.clone(),
                         };
                         match lexer.next_token(settings)? {
@@ -1634,14 +1753,13 @@ impl Parser
                                         self.has_first_word_or_third_word = true;
                                         self.skip_newlines(lexer, settings)?;
                                         match self.parse_fun_body(lexer, settings)? {
-                                            Some(fun_body) => Ok(Some(Command::FunctionDefinition(lexer.path().clone(), pos, Rc::new(word), Rc::new(fun_body)))),
+                                            Some(fun_body) => Ok(Some(Command::FunctionDefinition(lexer.path().clone(), pos, lexer.token_end_pos(), Rc::new(word), Rc::new(fun_body)))),
                                             None => {
                                                 let (token, pos) = lexer.next_token(settings)?;
-                                                let is_cont = match token {
-                                                    Token::EOF => true,
-                                                    _ => false,
+                                                return match token {
+                                                    Token::EOF => Err(self.eof_error(lexer, pos, true)),
+                                                    _ => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                                                 };
-                                                return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), is_cont));
                                             },
                                         }
                                     },
@@ -1653,7 +1771,7 @@ impl Parser
                                         lexer.push_first_word();
                                         self.has_first_word_or_third_word = true;
                                         match self.parse_simple_command(lexer, settings)? {
-                                            Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, simple_command))),
+                                            Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, lexer.token_end_pos(), simple_command))),
                                             None => Ok(None),
                                         }
                                     },
@@ -1665,7 +1783,7 @@ impl Parser
                                 lexer.push_first_word();
                                 self.has_first_word_or_third_word = true;
                                 match self.parse_simple_command(lexer, settings)? {
-                                    Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, simple_command))),
+                                    Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, lexer.token_end_pos(), simple_command))),
                                     None => Ok(None),
                                 }
                             },
@@ -1674,7 +1792,7 @@ impl Parser
                     (token, pos) => {
                         lexer.undo_token(&token, &pos);
                          match self.parse_simple_command(lexer, settings)? {
-                             Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, simple_command))),
+                             Some((simple_command, first_pos)) => Ok(Some(Command::Simple(lexer.path().clone(), first_pos, lexer.token_end_pos(), simple_command))),
                              None => Ok(None),
                          }
                     },
@@ -1724,17 +1842,17 @@ impl Parser
                         Some(command) => commands.push(Rc::new(command)),
                         None => {
                             let (token, pos) = lexer.next_token(settings)?;
-                            let is_cont = match token {
-                                Token::EOF => true,
-                                _ => false,
+                            return match token {
+                                Token::EOF => Err(self.eof_error(lexer, pos, true)),
+                                _ => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                             };
-                            return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), is_cont));
                         },
                     }
                 }
                 let pipe_command = PipeCommand {
                     path: lexer.path().clone(),
                     pos: first_pos,
+                    end_pos: lexer.token_end_pos(),
                     is_negative,
                     commands,
                 };
@@ -1743,11 +1861,10 @@ impl Parser
             None => {
                 if is_negative {
                     let (token, pos) = lexer.next_token(settings)?;
-                    let is_cont = match token {
-                        Token::EOF => true,
-                        _ => false,
-                    };
-                    Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), is_cont))
+                    match token {
+                        Token::EOF => Err(self.eof_error(lexer, pos, true)),
+                        _ => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
+                    }
                 } else {
                     Ok(None)
                 }
@@ -1786,17 +1903,17 @@ impl Parser
                         },
                         None => {
                             let (token, pos) = lexer.next_token(settings)?;
-                            let is_cont = match token {
-                                Token::EOF => true,
-                                _ => false,
+                            return match token {
+                                Token::EOF => Err(self.eof_error(lexer, pos, true)),
+                                _ => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                             };
-                            return Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), is_cont));
                         },
                     }
                 }
                 let logical_command = LogicalCommand {
                     path: lexer.path().clone(),
                     pos: first_command.pos,
+                    end_pos: lexer.token_end_pos(),
                     first_command: Rc::new(first_command),
                     pairs,
                     is_in_background: false,
@@ -1836,6 +1953,7 @@ impl Parser
                             commands.push(Rc::new(command));
                         },
                         (Token::Amp, _) => {
+                            command.end_pos = lexer.token_end_pos();
                             command.is_in_background = true;
                             commands.push(Rc::new(command));
                         },
@@ -1905,6 +2023,7 @@ impl Parser
                             commands.push(Rc::new(command));
                         },
                         (Token::Amp, _) => {
+                            command.end_pos = lexer.token_end_pos();
                             command.is_in_background = true;
                             commands.push(Rc::new(command));
                         },
@@ -1944,6 +2063,90 @@ impl Parser
         }
     }
     
+    fn skip_to_command_boundary<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> bool
+    {
+        loop {
+            match lexer.next_token(settings) {
+                Ok((Token::Newline | Token::Semi | Token::Amp, _)) => break true,
+                Ok((Token::EOF, _)) => break false,
+                Ok(_) => (),
+                Err(ParserError::Syntax(_, _, _, _)) => {
+                    lexer.reset_states();
+                    match lexer.skip_line(settings) {
+                        Ok(is_newline) => break is_newline,
+                        Err(_) => break false,
+                    }
+                },
+                Err(ParserError::IO(_, _)) => break false,
+            }
+        }
+    }
+
+    pub fn recover<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> bool
+    {
+        self.unclosed_constructs.append(&mut self.open_constructs);
+        self.here_docs.clear();
+        self.has_first_word_or_third_word = false;
+        lexer.reset_states();
+        self.skip_to_command_boundary(lexer, settings)
+    }
+
+    fn push_error_and_recover<'a>(&mut self, err: ParserError, errs: &mut Vec<ParserError>, lexer: &mut Lexer<'a>, settings: &Settings) -> bool
+    {
+        let is_io = match err {
+            ParserError::IO(_, _) => true,
+            ParserError::Syntax(_, _, _, _) => false,
+        };
+        errs.push(err);
+        !is_io && self.recover(lexer, settings)
+    }
+
+    pub fn parse_logical_commands_with_recovery<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> (Vec<Rc<LogicalCommand>>, Vec<ParserError>)
+    {
+        let mut commands: Vec<Rc<LogicalCommand>> = Vec::new();
+        let mut errs: Vec<ParserError> = Vec::new();
+        loop {
+            if !self.unclosed_constructs.is_empty() {
+                lexer.push_first_word();
+                let res = lexer.next_token(settings);
+                lexer.pop_state();
+                match res {
+                    Ok((Token::Fi | Token::Done | Token::Esac | Token::RBrace | Token::RParen, _)) => {
+                        self.unclosed_constructs.pop();
+                        if !self.skip_to_command_boundary(lexer, settings) {
+                            break;
+                        }
+                        continue;
+                    },
+                    Ok((Token::Then | Token::Do | Token::Elif | Token::Else, _)) => continue,
+                    Ok((token, pos)) => lexer.undo_token(&token, &pos),
+                    Err(err) => {
+                        if !self.push_error_and_recover(err, &mut errs, lexer, settings) {
+                            break;
+                        }
+                        continue;
+                    },
+                }
+            }
+            match self.parse_logical_commands_for_line(lexer, settings) {
+                Ok(None) => break,
+                Ok(Some(tmp_commands)) => commands.extend(tmp_commands),
+                Err(err) => {
+                    if !self.push_error_and_recover(err, &mut errs, lexer, settings) {
+                        break;
+                    }
+                },
+            }
+        }
+        if !errs.iter().any(|err| matches!(err, ParserError::IO(_, _))) {
+            let pos = lexer.pos();
+            while let Some((name, construct_pos)) = self.unclosed_constructs.pop() {
+                errs.push(ParserError::Syntax(lexer.path().clone(), pos, format!("unterminated `{}` started at {}", name, construct_pos), self.has_error_cont));
+            }
+        }
+        (commands, errs)
+    }
+
     pub fn parse_alias_command<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> ParserResult<AliasCommand>
     {
         if !self.has_first_word_or_third_word {
@@ -1981,15 +2184,15 @@ impl Parser
     fn parse_arith_expr12<'a>(&mut self, lexer: &mut Lexer<'a>, settings: &Settings) -> ParserResult<(ArithmeticExpression, Position)>
     {
         match lexer.next_arith_token(settings)? {
-            (ArithmeticToken::Number(n), pos) => Ok((ArithmeticExpression::Number(lexer.path().clone(), pos, n), pos)),
-            (ArithmeticToken::Parameter(param_name), pos) => Ok((ArithmeticExpression::Parameter(lexer.path().clone(), pos, param_name), pos)),
+            (ArithmeticToken::Number(n), pos) => Ok((ArithmeticExpression::Number(lexer.path().clone(), pos, lexer.token_end_pos(), n), pos)),
+            (ArithmeticToken::Parameter(param_name), pos) => Ok((ArithmeticExpression::Parameter(lexer.path().clone(), pos, lexer.token_end_pos(), param_name), pos)),
             (ArithmeticToken::Tylda, pos) => {
                 let (expr, _) = self.parse_arith_expr12(lexer, settings)?;
-                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, UnaryOperator::Not, Rc::new(expr)), pos))
+                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, lexer.token_end_pos(), UnaryOperator::Not, Rc::new(expr)), pos))
             },
             (ArithmeticToken::Excl, pos) => {
                 let (expr, _) = self.parse_arith_expr12(lexer, settings)?;
-                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, UnaryOperator::LogicalNot, Rc::new(expr)), pos))
+                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, lexer.token_end_pos(), UnaryOperator::LogicalNot, Rc::new(expr)), pos))
             },
             (ArithmeticToken::Plus, _) => {
                 let (expr, pos) = self.parse_arith_expr12(lexer, settings)?;
@@ -1997,7 +2200,7 @@ impl Parser
             },
             (ArithmeticToken::Minus, pos) => {
                 let (expr, _) = self.parse_arith_expr12(lexer, settings)?;
-                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, UnaryOperator::Negate, Rc::new(expr)), pos))
+                Ok((ArithmeticExpression::Unary(lexer.path().clone(), pos, lexer.token_end_pos(), UnaryOperator::Negate, Rc::new(expr)), pos))
             },
             (ArithmeticToken::LParen, pos) => {
                 lexer.push_in_arith_expr_and_paren();
@@ -2021,15 +2224,15 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Star, _) => {
                     let (expr2, _) = self.parse_arith_expr12(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Multiply, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Multiply, Rc::new(expr2))
                 },
                 (ArithmeticToken::Slash, _) => {
                     let (expr2, _) = self.parse_arith_expr12(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Divide, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Divide, Rc::new(expr2))
                 },
                 (ArithmeticToken::Perc, _) => {
                     let (expr2, _) = self.parse_arith_expr12(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Module, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Module, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2048,11 +2251,11 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Plus, _) => {
                     let expr2 = self.parse_arith_expr11(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Add, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Add, Rc::new(expr2))
                 },
                 (ArithmeticToken::Minus, _) => {
                     let expr2 = self.parse_arith_expr11(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Subtract, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Subtract, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2071,11 +2274,11 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::LessLess, _) => {
                     let expr2 = self.parse_arith_expr10(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::ShiftLeft, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ShiftLeft, Rc::new(expr2))
                 },
                 (ArithmeticToken::GreaterGreater, _) => {
                     let expr2 = self.parse_arith_expr10(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::ShiftRight, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ShiftRight, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2094,19 +2297,19 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Less, _) => {
                     let expr2 = self.parse_arith_expr9(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::LessThan, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::LessThan, Rc::new(expr2))
                 },
                 (ArithmeticToken::GreaterEqual, _) => {
                     let expr2 = self.parse_arith_expr9(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::GreaterEqual, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::GreaterEqual, Rc::new(expr2))
                 },
                 (ArithmeticToken::Greater, _) => {
                     let expr2 = self.parse_arith_expr9(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::GreaterThan, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::GreaterThan, Rc::new(expr2))
                 },
                 (ArithmeticToken::LessEqual, _) => {
                     let expr2 = self.parse_arith_expr9(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::LessEqual, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::LessEqual, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2125,11 +2328,11 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::EqualEqual, _) => {
                     let expr2 = self.parse_arith_expr8(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Equal, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Equal, Rc::new(expr2))
                 },
                 (ArithmeticToken::ExclEqual, _) => {
                     let expr2 = self.parse_arith_expr8(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::NotEqual, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::NotEqual, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2148,7 +2351,7 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Amp, _) => {
                     let expr2 = self.parse_arith_expr7(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::And, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::And, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2167,7 +2370,7 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Caret, _) => {
                     let expr2 = self.parse_arith_expr6(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::ExclusiveOr, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ExclusiveOr, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2186,7 +2389,7 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::Bar, _) => {
                     let expr2 = self.parse_arith_expr5(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::Or, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Or, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2205,7 +2408,7 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::AmpAmp, _) => {
                     let expr2 = self.parse_arith_expr4(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::LogicalAnd, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::LogicalAnd, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2224,7 +2427,7 @@ impl Parser
             match lexer.next_arith_token(settings)? {
                 (ArithmeticToken::BarBar, _) => {
                     let expr2 = self.parse_arith_expr3(lexer, settings)?;
-                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, Rc::new(expr1), BinaryOperator::LogicalOr, Rc::new(expr2))
+                    expr1 = ArithmeticExpression::Binary(lexer.path().clone(), first_pos, lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::LogicalOr, Rc::new(expr2))
                 },
                 (token, pos) => {
                     lexer.undo_arith_token(&token, &pos);
@@ -2241,54 +2444,54 @@ impl Parser
         match lexer.next_arith_token(settings)? {
             (ArithmeticToken::Equal, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::Assign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::Assign, Rc::new(expr2)))
             },
             (ArithmeticToken::StarEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::MultiplyAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::MultiplyAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::SlashEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::DivideAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::DivideAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::PercEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::ModuleAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ModuleAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::PlusEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::AddAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::AddAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::MinusEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::SubtractAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::SubtractAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::LessLessEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::ShiftLeftAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ShiftLeftAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::GreaterGreaterEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::ShiftRightAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ShiftRightAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::AmpEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::AndAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::AndAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::CaretEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::ExclusiveOrAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::ExclusiveOrAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::BarEqual, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
-                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), Rc::new(expr1), BinaryOperator::OrAssign, Rc::new(expr2)))
+                Ok(ArithmeticExpression::Binary(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), BinaryOperator::OrAssign, Rc::new(expr2)))
             },
             (ArithmeticToken::Ques, _) => {
                 let expr2 = self.parse_arith_expr1(lexer, settings)?;
                 match lexer.next_arith_token(settings)? {
                     (ArithmeticToken::Colon, _) => {
                         let expr3 = self.parse_arith_expr1(lexer, settings)?;
-                        Ok(ArithmeticExpression::Conditional(lexer.path().clone(), expr1.pos(), Rc::new(expr1), Rc::new(expr2), Rc::new(expr3)))
+                        Ok(ArithmeticExpression::Conditional(lexer.path().clone(), expr1.pos(), lexer.token_end_pos(), Rc::new(expr1), Rc::new(expr2), Rc::new(expr3)))
                     },
                     (_, pos) => Err(ParserError::Syntax(lexer.path().clone(), pos, String::from("unexpected token"), false)),
                 }
@@ -2316,12 +2519,16 @@ pub struct Position
 {
     pub line: u64,
     pub column: u64,
+    pub offset: u64,
 }
 
 impl Position
 {
     pub fn new(line: u64, column: u64) -> Position
-    { Position { line, column, } }
+    { Position { line, column, offset: 0, } }
+
+    pub fn new_with_offset(line: u64, column: u64, offset: u64) -> Position
+    { Position { line, column, offset, } }
 }
 
 impl fmt::Display for Position
@@ -2348,6 +2555,14 @@ impl ParserError
         }
     }
 
+    pub fn pos(&self) -> Option<Position>
+    {
+        match self {
+            ParserError::IO(_, _) => None,
+            ParserError::Syntax(_, pos, _, _) => Some(*pos),
+        }
+    }
+
     pub fn has_cont(&self) -> bool
     {
         match self {
@@ -2368,5 +2583,25 @@ impl fmt::Display for ParserError
     }
 }
 
+pub fn caret_lines(s: &str, pos: &Position, settings: &Settings) -> Option<String>
+{
+    let offset = pos.offset as usize;
+    if offset > s.len() || !s.is_char_boundary(offset) {
+        return None;
+    }
+    let line_start = s[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = s[offset..].find('\n').map(|i| offset + i).unwrap_or(s.len());
+    let mut cursor = Cursor::new(&s.as_bytes()[offset..line_end]);
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("", &Position::new(1, 1), &mut cr, 0, false);
+    let token_len = match lexer.next_token(settings) {
+        Ok(_) => lexer.token_end_pos().offset as usize,
+        Err(_) => 0,
+    };
+    let indent: String = s[line_start..offset].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let caret_count = max(s[offset..(offset + token_len)].chars().count(), 1);
+    Some(format!("{}\n{}{}", &s[line_start..line_end], indent, "^".repeat(caret_count)))
+}
+
 #[cfg(test)]
 mod tests;
@@ -16,7 +16,6 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
 use super::*;
-use crate::io::*;
 
 #[test]
 fn test_parser_parse_words_parses_words()
@@ -110,7 +109,7 @@ fn test_parser_parse_logical_commands_parses_command()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -178,7 +177,7 @@ fn test_parser_parse_logical_commands_parses_two_commands()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -218,7 +217,7 @@ fn test_parser_parse_logical_commands_parses_two_commands()
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(11, pos.column);
@@ -276,7 +275,7 @@ fn test_parser_parse_logical_commands_for_line_parses_command()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -344,7 +343,7 @@ fn test_parser_parse_logical_commands_for_line_parses_two_commands()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -384,7 +383,7 @@ fn test_parser_parse_logical_commands_for_line_parses_two_commands()
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(11, pos.column);
@@ -480,7 +479,7 @@ echo def
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -520,7 +519,7 @@ echo def
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(2, pos.line);
                     assert_eq!(1, pos.column);
@@ -584,7 +583,7 @@ echo def
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(2, pos.line);
                     assert_eq!(1, pos.column);
@@ -624,7 +623,7 @@ echo def
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(4, pos.line);
                     assert_eq!(1, pos.column);
@@ -686,7 +685,7 @@ echo def
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -726,7 +725,7 @@ echo def
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(2, pos.line);
                     assert_eq!(1, pos.column);
@@ -788,7 +787,7 @@ echo ghi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -824,7 +823,7 @@ echo ghi
             assert_eq!(false, logical_commands[0].pairs[0].command.is_negative);
             assert_eq!(1, logical_commands[0].pairs[0].command.commands.len());
             match &(*logical_commands[0].pairs[0].command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(13, pos.column);
@@ -860,7 +859,7 @@ echo ghi
             assert_eq!(false, logical_commands[0].pairs[1].command.is_negative);
             assert_eq!(1, logical_commands[0].pairs[1].command.commands.len());
             match &(*logical_commands[0].pairs[1].command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(2, pos.line);
                     assert_eq!(1, pos.column);
@@ -922,7 +921,7 @@ tee xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(3, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -952,7 +951,7 @@ tee xxx
                 _ => assert!(false),
             }
             match &(*logical_commands[0].first_command.commands[1]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(12, pos.column);
@@ -972,7 +971,7 @@ tee xxx
                 _ => assert!(false),
             }
             match &(*logical_commands[0].first_command.commands[2]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(2, pos.line);
                     assert_eq!(1, pos.column);
@@ -1033,7 +1032,7 @@ fn test_parser_parse_logical_commands_parses_commands_which_are_negative()
             assert_eq!(true, logical_commands[0].first_command.is_negative);
             assert_eq!(2, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(3, pos.column);
@@ -1063,7 +1062,7 @@ fn test_parser_parse_logical_commands_parses_commands_which_are_negative()
                 _ => assert!(false),
             }
             match &(*logical_commands[0].first_command.commands[1]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(14, pos.column);
@@ -1114,7 +1113,7 @@ echo abc > xxx 2>> yyy
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1141,7 +1140,7 @@ echo abc > xxx 2>> yyy
                     }
                     assert_eq!(2, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Output(path2, pos2, None, word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, None, word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1160,7 +1159,7 @@ echo abc > xxx 2>> yyy
                         _ => assert!(false),
                     }
                     match &(*simple_command.redirects[1]) {
-                        Redirection::Appending(path2, pos2, Some(n), word) => {
+                        Redirection::Appending(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(16, pos2.column);
@@ -1213,7 +1212,7 @@ echo abc < xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1240,7 +1239,7 @@ echo abc < xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Input(path2, pos2, None, word) => {
+                        Redirection::Input(path2, pos2, _, None, word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1292,7 +1291,7 @@ echo abc > xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1319,7 +1318,7 @@ echo abc > xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Output(path2, pos2, None, word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, None, word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1372,7 +1371,7 @@ echo abc >| xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1399,7 +1398,7 @@ echo abc >| xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Output(path2, pos2, None, word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, None, word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1452,7 +1451,7 @@ echo abc <> xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1479,7 +1478,7 @@ echo abc <> xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::InputAndOutput(path2, pos2, None, word) => {
+                        Redirection::InputAndOutput(path2, pos2, _, None, word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1531,7 +1530,7 @@ echo abc >> xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1558,7 +1557,7 @@ echo abc >> xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Appending(path2, pos2, None, word) => {
+                        Redirection::Appending(path2, pos2, _, None, word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1610,7 +1609,7 @@ echo abc <& 2
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1637,7 +1636,7 @@ echo abc <& 2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::InputDuplicating(path2, pos2, None, word) => {
+                        Redirection::InputDuplicating(path2, pos2, _, None, word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1689,7 +1688,7 @@ echo abc >& 2
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1716,7 +1715,7 @@ echo abc >& 2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::OutputDuplicating(path2, pos2, None, word) => {
+                        Redirection::OutputDuplicating(path2, pos2, _, None, word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -1771,7 +1770,7 @@ EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1788,7 +1787,7 @@ EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, None, here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, None, here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -1849,7 +1848,7 @@ cat <<- EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1866,7 +1865,7 @@ cat <<- EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, None, here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, None, here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -1927,7 +1926,7 @@ EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -1944,7 +1943,7 @@ EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, None, here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, None, here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -2002,7 +2001,7 @@ echo abc 2< xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2029,7 +2028,7 @@ echo abc 2< xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Input(path2, pos2, Some(n), word) => {
+                        Redirection::Input(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2082,7 +2081,7 @@ echo abc 2> xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2109,7 +2108,7 @@ echo abc 2> xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Output(path2, pos2, Some(n), word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, Some(n), word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2163,7 +2162,7 @@ echo abc 2>| xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2190,7 +2189,7 @@ echo abc 2>| xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Output(path2, pos2, Some(n), word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, Some(n), word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2244,7 +2243,7 @@ echo abc 2<> xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2271,7 +2270,7 @@ echo abc 2<> xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::InputAndOutput(path2, pos2, Some(n), word) => {
+                        Redirection::InputAndOutput(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2324,7 +2323,7 @@ echo abc 2>> xxx
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2351,7 +2350,7 @@ echo abc 2>> xxx
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::Appending(path2, pos2, Some(n), word) => {
+                        Redirection::Appending(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2404,7 +2403,7 @@ echo abc 1<& 2
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2431,7 +2430,7 @@ echo abc 1<& 2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::InputDuplicating(path2, pos2, Some(n), word) => {
+                        Redirection::InputDuplicating(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2484,7 +2483,7 @@ echo abc 1>& 2
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2511,7 +2510,7 @@ echo abc 1>& 2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::OutputDuplicating(path2, pos2, Some(n), word) => {
+                        Redirection::OutputDuplicating(path2, pos2, _, Some(n), word) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(10, pos2.column);
@@ -2567,7 +2566,7 @@ EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2584,7 +2583,7 @@ EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, Some(n), here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, Some(n), here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -2646,7 +2645,7 @@ cat 0<<- EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2663,7 +2662,7 @@ cat 0<<- EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, Some(n), here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, Some(n), here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -2725,7 +2724,7 @@ EOT
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2742,7 +2741,7 @@ EOT
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, Some(n), here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, Some(n), here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -2805,7 +2804,7 @@ EOT2
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2822,7 +2821,7 @@ EOT2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, None, here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, None, here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(5, pos2.column);
@@ -2853,7 +2852,7 @@ EOT2
             assert_eq!(false, logical_commands[1].first_command.is_negative);
             assert_eq!(1, logical_commands[1].first_command.commands.len());
             match &(*logical_commands[1].first_command.commands[0]) {
-                Command::Simple(path, pos, simple_command) => {
+                Command::Simple(path, pos, _, simple_command) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(13, pos.column);
@@ -2870,7 +2869,7 @@ EOT2
                     }
                     assert_eq!(1, simple_command.redirects.len());
                     match &(*simple_command.redirects[0]) {
-                        Redirection::HereDocument(path2, pos2, None, here_doc) => {
+                        Redirection::HereDocument(path2, pos2, _, None, here_doc) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(1, pos2.line);
                             assert_eq!(17, pos2.column);
@@ -2922,7 +2921,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -2938,7 +2937,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group()
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(3, pos.column);
@@ -2978,7 +2977,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group()
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(13, pos.column);
@@ -3046,7 +3045,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_newlin
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3062,7 +3061,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_newlin
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -3102,7 +3101,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_newlin
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -3170,7 +3169,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_redire
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3186,7 +3185,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_redire
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -3226,7 +3225,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_redire
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -3257,7 +3256,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_redire
                     }
                     assert_eq!(2, redirects.len());
                     match &(*redirects[0]) {
-                        Redirection::Output(path2, pos2, None, word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, None, word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(4, pos2.line);
                             assert_eq!(3, pos2.column);
@@ -3276,7 +3275,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_brace_group_with_redire
                         _ => assert!(false),
                     }
                     match &(*redirects[1]) {
-                        Redirection::Output(path2, pos2, Some(n), word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, Some(n), word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(4, pos2.line);
                             assert_eq!(9, pos2.column);
@@ -3330,7 +3329,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Subshell(logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Subshell(logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3346,7 +3345,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell()
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(2, pos.column);
@@ -3386,7 +3385,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell()
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(12, pos.column);
@@ -3454,7 +3453,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell_with_newlines(
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Subshell(logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Subshell(logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3470,7 +3469,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell_with_newlines(
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -3510,7 +3509,7 @@ fn test_parser_parse_logical_commands_parses_commands_in_subshell_with_newlines(
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -3578,7 +3577,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3635,7 +3634,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -3675,7 +3674,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -3744,7 +3743,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3801,7 +3800,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -3841,7 +3840,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -3909,7 +3908,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -3936,7 +3935,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -3976,7 +3975,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -4044,7 +4043,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, None, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, None, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4070,7 +4069,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -4110,7 +4109,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -4178,7 +4177,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, None, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, None, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4204,7 +4203,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -4244,7 +4243,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -4311,7 +4310,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::For(var_word, Some(words), logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4368,7 +4367,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -4384,7 +4383,7 @@ done
                             assert_eq!(false, logical_commands3[0].first_command.is_negative);
                             assert_eq!(1, logical_commands3[0].first_command.commands.len());
                             match &(*logical_commands3[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(7, pos.column);
@@ -4424,7 +4423,7 @@ done
                             assert_eq!(false, logical_commands3[1].first_command.is_negative);
                             assert_eq!(1, logical_commands3[1].first_command.commands.len());
                             match &(*logical_commands3[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(17, pos.column);
@@ -4496,7 +4495,7 @@ esac
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Case(word, pairs), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Case(word, pairs), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4544,7 +4543,7 @@ esac
                     assert_eq!(false, pairs[0].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[0].commands[0].first_command.commands.len());
                     match &(*pairs[0].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(16, pos.column);
@@ -4606,7 +4605,7 @@ esac
                     assert_eq!(false, pairs[1].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[1].commands[0].first_command.commands.len());
                     match &(*pairs[1].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(17, pos.column);
@@ -4675,7 +4674,7 @@ esac
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Case(word, pairs), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Case(word, pairs), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4713,7 +4712,7 @@ esac
                     assert_eq!(false, pairs[0].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[0].commands[0].first_command.commands.len());
                     match &(*pairs[0].commands[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands2), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(9, pos.column);
@@ -4729,7 +4728,7 @@ esac
                             assert_eq!(false, logical_commands2[0].first_command.is_negative);
                             assert_eq!(1, logical_commands2[0].first_command.commands.len());
                             match &(*logical_commands2[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(3, pos.line);
                                     assert_eq!(11, pos.column);
@@ -4769,7 +4768,7 @@ esac
                             assert_eq!(false, logical_commands2[1].first_command.is_negative);
                             assert_eq!(1, logical_commands2[1].first_command.commands.len());
                             match &(*logical_commands2[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(3, pos.line);
                                     assert_eq!(21, pos.column);
@@ -4841,7 +4840,7 @@ esac
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Case(word, pairs), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Case(word, pairs), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -4889,7 +4888,7 @@ esac
                     assert_eq!(false, pairs[0].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[0].commands[0].first_command.commands.len());
                     match &(*pairs[0].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(16, pos.column);
@@ -4951,7 +4950,7 @@ esac
                     assert_eq!(false, pairs[1].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[1].commands[0].first_command.commands.len());
                     match &(*pairs[1].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(17, pos.column);
@@ -5021,7 +5020,7 @@ fi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -5037,7 +5036,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -5077,7 +5076,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -5108,7 +5107,7 @@ fi
                     assert_eq!(false, logical_commands21[0].first_command.is_negative);
                     assert_eq!(1, logical_commands21[0].first_command.commands.len());
                     match &(*logical_commands21[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -5148,7 +5147,7 @@ fi
                     assert_eq!(false, logical_commands21[1].first_command.is_negative);
                     assert_eq!(1, logical_commands21[1].first_command.commands.len());
                     match &(*logical_commands21[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -5220,7 +5219,7 @@ fi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -5236,7 +5235,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -5276,7 +5275,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -5307,7 +5306,7 @@ fi
                     assert_eq!(false, logical_commands21[0].first_command.is_negative);
                     assert_eq!(1, logical_commands21[0].first_command.commands.len());
                     match &(*logical_commands21[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -5347,7 +5346,7 @@ fi
                     assert_eq!(false, logical_commands21[1].first_command.is_negative);
                     assert_eq!(1, logical_commands21[1].first_command.commands.len());
                     match &(*logical_commands21[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(6, pos.line);
                             assert_eq!(5, pos.column);
@@ -5419,7 +5418,7 @@ fi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, None), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -5435,7 +5434,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(4, pos.column);
@@ -5466,7 +5465,7 @@ fi
                     assert_eq!(false, logical_commands21[0].first_command.is_negative);
                     assert_eq!(1, logical_commands21[0].first_command.commands.len());
                     match &(*logical_commands21[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -5508,7 +5507,7 @@ fi
                     assert_eq!(false, pairs[0].cond_commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[0].cond_commands[0].first_command.commands.len());
                     match &(*pairs[0].cond_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(6, pos.column);
@@ -5539,7 +5538,7 @@ fi
                     assert_eq!(false, pairs[0].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[0].commands[0].first_command.commands.len());
                     match &(*pairs[0].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -5580,7 +5579,7 @@ fi
                     assert_eq!(false, pairs[1].cond_commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[1].cond_commands[0].first_command.commands.len());
                     match &(*pairs[1].cond_commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(6, pos.column);
@@ -5611,7 +5610,7 @@ fi
                     assert_eq!(false, pairs[1].commands[0].first_command.is_negative);
                     assert_eq!(1, pairs[1].commands[0].first_command.commands.len());
                     match &(*pairs[1].commands[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(6, pos.line);
                             assert_eq!(5, pos.column);
@@ -5684,7 +5683,7 @@ fi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, Some(logical_commands22)), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, Some(logical_commands22)), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -5700,7 +5699,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -5740,7 +5739,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -5771,7 +5770,7 @@ fi
                     assert_eq!(false, logical_commands21[0].first_command.is_negative);
                     assert_eq!(1, logical_commands21[0].first_command.commands.len());
                     match &(*logical_commands21[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -5811,7 +5810,7 @@ fi
                     assert_eq!(false, logical_commands21[1].first_command.is_negative);
                     assert_eq!(1, logical_commands21[1].first_command.commands.len());
                     match &(*logical_commands21[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -5853,7 +5852,7 @@ fi
                     assert_eq!(false, logical_commands22[0].first_command.is_negative);
                     assert_eq!(1, logical_commands22[0].first_command.commands.len());
                     match &(*logical_commands22[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(7, pos.line);
                             assert_eq!(5, pos.column);
@@ -5893,7 +5892,7 @@ fi
                     assert_eq!(false, logical_commands22[1].first_command.is_negative);
                     assert_eq!(1, logical_commands22[1].first_command.commands.len());
                     match &(*logical_commands22[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(8, pos.line);
                             assert_eq!(5, pos.column);
@@ -5962,7 +5961,7 @@ fi
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, Some(logical_commands22)), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::If(cond_logical_commands2, logical_commands21, pairs, Some(logical_commands22)), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -5978,7 +5977,7 @@ fi
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(4, pos.column);
@@ -6009,7 +6008,7 @@ fi
                     assert_eq!(false, logical_commands21[0].first_command.is_negative);
                     assert_eq!(1, logical_commands21[0].first_command.commands.len());
                     match &(*logical_commands21[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -6025,7 +6024,7 @@ fi
                             assert_eq!(false, logical_commands3[0].first_command.is_negative);
                             assert_eq!(1, logical_commands3[0].first_command.commands.len());
                             match &(*logical_commands3[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(7, pos.column);
@@ -6065,7 +6064,7 @@ fi
                             assert_eq!(false, logical_commands3[1].first_command.is_negative);
                             assert_eq!(1, logical_commands3[1].first_command.commands.len());
                             match &(*logical_commands3[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(17, pos.column);
@@ -6111,7 +6110,7 @@ fi
                     assert_eq!(false, logical_commands22[0].first_command.is_negative);
                     assert_eq!(1, logical_commands22[0].first_command.commands.len());
                     match &(*logical_commands22[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -6127,7 +6126,7 @@ fi
                             assert_eq!(false, logical_commands3[0].first_command.is_negative);
                             assert_eq!(1, logical_commands3[0].first_command.commands.len());
                             match &(*logical_commands3[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(4, pos.line);
                                     assert_eq!(7, pos.column);
@@ -6167,7 +6166,7 @@ fi
                             assert_eq!(false, logical_commands3[1].first_command.is_negative);
                             assert_eq!(1, logical_commands3[1].first_command.commands.len());
                             match &(*logical_commands3[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(4, pos.line);
                                     assert_eq!(17, pos.column);
@@ -6241,7 +6240,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -6257,7 +6256,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -6297,7 +6296,7 @@ done
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -6328,7 +6327,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -6368,7 +6367,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -6439,7 +6438,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -6455,7 +6454,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -6495,7 +6494,7 @@ done
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -6526,7 +6525,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -6566,7 +6565,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(6, pos.line);
                             assert_eq!(5, pos.column);
@@ -6633,7 +6632,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::While(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -6649,7 +6648,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(7, pos.column);
@@ -6680,7 +6679,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -6696,7 +6695,7 @@ done
                             assert_eq!(false, logical_commands3[0].first_command.is_negative);
                             assert_eq!(1, logical_commands3[0].first_command.commands.len());
                             match &(*logical_commands3[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(7, pos.column);
@@ -6736,7 +6735,7 @@ done
                             assert_eq!(false, logical_commands3[1].first_command.is_negative);
                             assert_eq!(1, logical_commands3[1].first_command.commands.len());
                             match &(*logical_commands3[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(17, pos.column);
@@ -6810,7 +6809,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -6826,7 +6825,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -6866,7 +6865,7 @@ done
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -6897,7 +6896,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(4, pos.line);
                             assert_eq!(5, pos.column);
@@ -6937,7 +6936,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -7008,7 +7007,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -7024,7 +7023,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -7064,7 +7063,7 @@ done
                     assert_eq!(false, cond_logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[1].first_command.commands.len());
                     match &(*cond_logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(3, pos.line);
                             assert_eq!(5, pos.column);
@@ -7095,7 +7094,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(5, pos.line);
                             assert_eq!(5, pos.column);
@@ -7135,7 +7134,7 @@ done
                     assert_eq!(false, logical_commands2[1].first_command.is_negative);
                     assert_eq!(1, logical_commands2[1].first_command.commands.len());
                     match &(*logical_commands2[1].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(6, pos.line);
                             assert_eq!(5, pos.column);
@@ -7202,7 +7201,7 @@ done
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Compound(path, pos, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
+                Command::Compound(path, pos, _, CompoundCommand::Until(cond_logical_commands2, logical_commands2), redirects) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -7218,7 +7217,7 @@ done
                     assert_eq!(false, cond_logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, cond_logical_commands2[0].first_command.commands.len());
                     match &(*cond_logical_commands2[0].first_command.commands[0]) {
-                        Command::Simple(path, pos, simple_command) => {
+                        Command::Simple(path, pos, _, simple_command) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(1, pos.line);
                             assert_eq!(7, pos.column);
@@ -7249,7 +7248,7 @@ done
                     assert_eq!(false, logical_commands2[0].first_command.is_negative);
                     assert_eq!(1, logical_commands2[0].first_command.commands.len());
                     match &(*logical_commands2[0].first_command.commands[0]) {
-                        Command::Compound(path, pos, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
+                        Command::Compound(path, pos, _, CompoundCommand::BraceGroup(logical_commands3), redirects) => {
                             assert_eq!(&String::from("test.sh"), path);
                             assert_eq!(2, pos.line);
                             assert_eq!(5, pos.column);
@@ -7265,7 +7264,7 @@ done
                             assert_eq!(false, logical_commands3[0].first_command.is_negative);
                             assert_eq!(1, logical_commands3[0].first_command.commands.len());
                             match &(*logical_commands3[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(7, pos.column);
@@ -7305,7 +7304,7 @@ done
                             assert_eq!(false, logical_commands3[1].first_command.is_negative);
                             assert_eq!(1, logical_commands3[1].first_command.commands.len());
                             match &(*logical_commands3[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(17, pos.column);
@@ -7377,7 +7376,7 @@ fun() {
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::FunctionDefinition(path, pos, name_word, fun_body) => {
+                Command::FunctionDefinition(path, pos, _, name_word, fun_body) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -7408,7 +7407,7 @@ fun() {
                             assert_eq!(false, logical_commands2[0].first_command.is_negative);
                             assert_eq!(1, logical_commands2[0].first_command.commands.len());
                             match &(*logical_commands2[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7448,7 +7447,7 @@ fun() {
                             assert_eq!(false, logical_commands2[1].first_command.is_negative);
                             assert_eq!(1, logical_commands2[1].first_command.commands.len());
                             match &(*logical_commands2[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(3, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7520,7 +7519,7 @@ fun()
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::FunctionDefinition(path, pos, name_word, fun_body) => {
+                Command::FunctionDefinition(path, pos, _, name_word, fun_body) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -7551,7 +7550,7 @@ fun()
                             assert_eq!(false, logical_commands2[0].first_command.is_negative);
                             assert_eq!(1, logical_commands2[0].first_command.commands.len());
                             match &(*logical_commands2[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(3, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7591,7 +7590,7 @@ fun()
                             assert_eq!(false, logical_commands2[1].first_command.is_negative);
                             assert_eq!(1, logical_commands2[1].first_command.commands.len());
                             match &(*logical_commands2[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(4, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7662,7 +7661,7 @@ fun() {
             assert_eq!(false, logical_commands[0].first_command.is_negative);
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::FunctionDefinition(path, pos, name_word, fun_body) => {
+                Command::FunctionDefinition(path, pos, _, name_word, fun_body) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(1, pos.column);
@@ -7693,7 +7692,7 @@ fun() {
                             assert_eq!(false, logical_commands2[0].first_command.is_negative);
                             assert_eq!(1, logical_commands2[0].first_command.commands.len());
                             match &(*logical_commands2[0].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(2, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7733,7 +7732,7 @@ fun() {
                             assert_eq!(false, logical_commands2[1].first_command.is_negative);
                             assert_eq!(1, logical_commands2[1].first_command.commands.len());
                             match &(*logical_commands2[1].first_command.commands[0]) {
-                                Command::Simple(path, pos, simple_command) => {
+                                Command::Simple(path, pos, _, simple_command) => {
                                     assert_eq!(&String::from("test.sh"), path);
                                     assert_eq!(3, pos.line);
                                     assert_eq!(5, pos.column);
@@ -7767,7 +7766,7 @@ fun() {
                     }
                     assert_eq!(2, fun_body.redirects.len());
                     match &(*fun_body.redirects[0]) {
-                        Redirection::Output(path2, pos2, None, word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, None, word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(4, pos2.line);
                             assert_eq!(3, pos2.column);
@@ -7786,7 +7785,7 @@ fun() {
                         _ => assert!(false),
                     }
                     match &(*fun_body.redirects[1]) {
-                        Redirection::Output(path2, pos2, Some(n), word, is_bar) => {
+                        Redirection::Output(path2, pos2, _, Some(n), word, is_bar) => {
                             assert_eq!(&String::from("test.sh"), path2);
                             assert_eq!(4, pos2.line);
                             assert_eq!(9, pos2.column);
@@ -7941,7 +7940,7 @@ fn test_parser_parse_logical_commands_parses_complains_on_unexpected_token_for_b
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(2, pos.line);
             assert_eq!(13, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `{` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -7991,7 +7990,7 @@ fn test_parser_parse_logical_commands_parses_complains_on_unexpected_token_for_s
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(2, pos.line);
             assert_eq!(13, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `(` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8042,7 +8041,7 @@ for i in 1 2 3; do
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(3, pos.line);
             assert_eq!(1, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `for` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8089,7 +8088,7 @@ for i";
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(6, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `for` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8140,7 +8139,7 @@ case abc in
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(3, pos.line);
             assert_eq!(1, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `case` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8186,7 +8185,7 @@ case abc";
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(9, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `case` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8236,7 +8235,7 @@ if true; then
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(2, pos.line);
             assert_eq!(13, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `if` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8286,7 +8285,7 @@ while true; do
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(2, pos.line);
             assert_eq!(13, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `while` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8336,7 +8335,7 @@ until true; do
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(2, pos.line);
             assert_eq!(13, pos.column);
-            assert_eq!(String::from("unexpected token"), msg);
+            assert_eq!(String::from("unterminated `until` started at 1.1"), msg);
             assert_eq!(true, is_cont);
         },
         _ => assert!(false),
@@ -8481,7 +8480,7 @@ fn test_parser_parse_alias_command_parses_command_with_redictions()
             }
             assert_eq!(2, alias_command.command.redirects.len());
             match &(*alias_command.command.redirects[0]) {
-                Redirection::Output(path, pos, None, word, is_bar) => {
+                Redirection::Output(path, pos, _, None, word, is_bar) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(10, pos.column);
@@ -8500,7 +8499,7 @@ fn test_parser_parse_alias_command_parses_command_with_redictions()
                 _ => assert!(false),
             }
             match &(*alias_command.command.redirects[1]) {
-                Redirection::Appending(path, pos, Some(n), word) => {
+                Redirection::Appending(path, pos, _, Some(n), word) => {
                     assert_eq!(&String::from("test.sh"), path);
                     assert_eq!(1, pos.line);
                     assert_eq!(16, pos.column);
@@ -8556,13 +8555,13 @@ fn test_parser_parse_arith_expr_parses_expression()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Add, op);
             match &(*expr1) {
-                ArithmeticExpression::Number(path1, pos1, n1) => {
+                ArithmeticExpression::Number(path1, pos1, _, n1) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
@@ -8571,7 +8570,7 @@ fn test_parser_parse_arith_expr_parses_expression()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(5, pos2.column);
@@ -8596,19 +8595,19 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Add, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::Multiply, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -8617,7 +8616,7 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -8629,13 +8628,13 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Binary(path2, pos2, expr5, op2, expr6) => {
+                ArithmeticExpression::Binary(path2, pos2, _, expr5, op2, expr6) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
                     assert_eq!(BinaryOperator::Divide, *op2);
                     match &(**expr5) {
-                        ArithmeticExpression::Number(path5, pos5, n5) => {
+                        ArithmeticExpression::Number(path5, pos5, _, n5) => {
                             assert_eq!(&String::from("test.sh"), path5);
                             assert_eq!(1, pos5.line);
                             assert_eq!(9, pos5.column);
@@ -8644,7 +8643,7 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions()
                         _ => assert!(false),
                     }
                     match &(**expr6) {
-                        ArithmeticExpression::Number(path6, pos6, n6) => {
+                        ArithmeticExpression::Number(path6, pos6, _, n6) => {
                             assert_eq!(&String::from("test.sh"), path6);
                             assert_eq!(1, pos6.line);
                             assert_eq!(13, pos6.column);
@@ -8672,19 +8671,19 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions_which_
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Multiply, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(2, pos1.column);
                     assert_eq!(BinaryOperator::Add, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(2, pos3.column);
@@ -8693,7 +8692,7 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions_which_
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(6, pos4.column);
@@ -8705,13 +8704,13 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions_which_
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Binary(path2, pos2, expr5, op2, expr6) => {
+                ArithmeticExpression::Binary(path2, pos2, _, expr5, op2, expr6) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(12, pos2.column);
                     assert_eq!(BinaryOperator::Subtract, *op2);
                     match &(**expr5) {
-                        ArithmeticExpression::Number(path5, pos5, n5) => {
+                        ArithmeticExpression::Number(path5, pos5, _, n5) => {
                             assert_eq!(&String::from("test.sh"), path5);
                             assert_eq!(1, pos5.line);
                             assert_eq!(12, pos5.column);
@@ -8720,7 +8719,7 @@ fn test_parser_parse_arith_expr_parses_expression_with_nested_expressions_which_
                         _ => assert!(false),
                     }
                     match &(**expr6) {
-                        ArithmeticExpression::Number(path6, pos6, n6) => {
+                        ArithmeticExpression::Number(path6, pos6, _, n6) => {
                             assert_eq!(&String::from("test.sh"), path6);
                             assert_eq!(1, pos6.line);
                             assert_eq!(16, pos6.column);
@@ -8748,7 +8747,7 @@ fn test_parser_parse_arith_expr_parses_number()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Number(path, pos, n)) => {
+        Ok(ArithmeticExpression::Number(path, pos, _, n)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
@@ -8770,7 +8769,7 @@ fn test_parser_parse_arith_expr_parses_variable()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Parameter(path, pos, ParameterName::Variable(var_name))) => {
+        Ok(ArithmeticExpression::Parameter(path, pos, _, ParameterName::Variable(var_name))) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
@@ -8792,19 +8791,19 @@ fn test_parser_parse_arith_expr_parses_expression12()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Unary(path, pos, op, expr1)) => {
+        Ok(ArithmeticExpression::Unary(path, pos, _, op, expr1)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(UnaryOperator::Not, op);
             match &(*expr1) {
-                ArithmeticExpression::Unary(path1, pos1, op1, expr2) => {
+                ArithmeticExpression::Unary(path1, pos1, _, op1, expr2) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(2, pos1.column);
                     assert_eq!(UnaryOperator::Negate, *op1);
                     match &(**expr2) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(3, pos3.column);
@@ -8832,7 +8831,7 @@ fn test_parser_parse_arith_expr_parses_expression12_with_plus()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Number(path, pos, n)) => {
+        Ok(ArithmeticExpression::Number(path, pos, _, n)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(2, pos.column);
@@ -8854,19 +8853,19 @@ fn test_parser_parse_arith_expr_parses_expression11()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Divide, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::Multiply, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -8875,7 +8874,7 @@ fn test_parser_parse_arith_expr_parses_expression11()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -8887,7 +8886,7 @@ fn test_parser_parse_arith_expr_parses_expression11()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -8912,19 +8911,19 @@ fn test_parser_parse_arith_expr_parses_expression10()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Subtract, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::Add, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -8933,7 +8932,7 @@ fn test_parser_parse_arith_expr_parses_expression10()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -8945,7 +8944,7 @@ fn test_parser_parse_arith_expr_parses_expression10()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -8970,19 +8969,19 @@ fn test_parser_parse_arith_expr_parses_expression9()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::ShiftRight, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::ShiftLeft, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -8991,7 +8990,7 @@ fn test_parser_parse_arith_expr_parses_expression9()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(6, pos4.column);
@@ -9003,7 +9002,7 @@ fn test_parser_parse_arith_expr_parses_expression9()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(11, pos2.column);
@@ -9028,19 +9027,19 @@ fn test_parser_parse_arith_expr_parses_expression8()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::GreaterThan, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::LessThan, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9049,7 +9048,7 @@ fn test_parser_parse_arith_expr_parses_expression8()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -9061,7 +9060,7 @@ fn test_parser_parse_arith_expr_parses_expression8()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -9086,19 +9085,19 @@ fn test_parser_parse_arith_expr_parses_expression7()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::NotEqual, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::Equal, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9107,7 +9106,7 @@ fn test_parser_parse_arith_expr_parses_expression7()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(6, pos4.column);
@@ -9119,7 +9118,7 @@ fn test_parser_parse_arith_expr_parses_expression7()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(11, pos2.column);
@@ -9144,19 +9143,19 @@ fn test_parser_parse_arith_expr_parses_expression6()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::And, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::And, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9165,7 +9164,7 @@ fn test_parser_parse_arith_expr_parses_expression6()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -9177,7 +9176,7 @@ fn test_parser_parse_arith_expr_parses_expression6()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -9202,19 +9201,19 @@ fn test_parser_parse_arith_expr_parses_expression5()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::ExclusiveOr, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::ExclusiveOr, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9223,7 +9222,7 @@ fn test_parser_parse_arith_expr_parses_expression5()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -9235,7 +9234,7 @@ fn test_parser_parse_arith_expr_parses_expression5()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -9260,19 +9259,19 @@ fn test_parser_parse_arith_expr_parses_expression4()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Or, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::Or, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9281,7 +9280,7 @@ fn test_parser_parse_arith_expr_parses_expression4()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -9293,7 +9292,7 @@ fn test_parser_parse_arith_expr_parses_expression4()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(9, pos2.column);
@@ -9318,19 +9317,19 @@ fn test_parser_parse_arith_expr_parses_expression3()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::LogicalAnd, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::LogicalAnd, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9339,7 +9338,7 @@ fn test_parser_parse_arith_expr_parses_expression3()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(6, pos4.column);
@@ -9351,7 +9350,7 @@ fn test_parser_parse_arith_expr_parses_expression3()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(11, pos2.column);
@@ -9376,19 +9375,19 @@ fn test_parser_parse_arith_expr_parses_expression2()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::LogicalOr, op);
             match &(*expr1) {
-                ArithmeticExpression::Binary(path1, pos1, expr3, op1, expr4) => {
+                ArithmeticExpression::Binary(path1, pos1, _, expr3, op1, expr4) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
                     assert_eq!(BinaryOperator::LogicalOr, *op1);
                     match &(**expr3) {
-                        ArithmeticExpression::Number(path3, pos3, n3) => {
+                        ArithmeticExpression::Number(path3, pos3, _, n3) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(1, pos3.column);
@@ -9397,7 +9396,7 @@ fn test_parser_parse_arith_expr_parses_expression2()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(6, pos4.column);
@@ -9409,7 +9408,7 @@ fn test_parser_parse_arith_expr_parses_expression2()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(11, pos2.column);
@@ -9434,13 +9433,13 @@ fn test_parser_parse_arith_expr_parses_expression1()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Assign, op);
             match &(*expr1) {
-                ArithmeticExpression::Parameter(path1, pos1, ParameterName::Variable(var_name1)) => {
+                ArithmeticExpression::Parameter(path1, pos1, _, ParameterName::Variable(var_name1)) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
@@ -9449,13 +9448,13 @@ fn test_parser_parse_arith_expr_parses_expression1()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Binary(path2, pos2, expr3, op2, expr4) => {
+                ArithmeticExpression::Binary(path2, pos2, _, expr3, op2, expr4) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(5, pos2.column);
                     assert_eq!(BinaryOperator::MultiplyAssign, *op2);
                     match &(**expr3) {
-                        ArithmeticExpression::Parameter(path3, pos3, ParameterName::Variable(var_name3)) => {
+                        ArithmeticExpression::Parameter(path3, pos3, _, ParameterName::Variable(var_name3)) => {
                             assert_eq!(&String::from("test.sh"), path3);
                             assert_eq!(1, pos3.line);
                             assert_eq!(5, pos3.column);
@@ -9464,7 +9463,7 @@ fn test_parser_parse_arith_expr_parses_expression1()
                         _ => assert!(false),
                     }
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(10, pos4.column);
@@ -9492,12 +9491,12 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Conditional(path, pos, expr1, expr2, expr3)) => {
+        Ok(ArithmeticExpression::Conditional(path, pos, _, expr1, expr2, expr3)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             match &(*expr1) {
-                ArithmeticExpression::Number(path1, pos1, n1) => {
+                ArithmeticExpression::Number(path1, pos1, _, n1) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
@@ -9506,12 +9505,12 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Conditional(path2, pos2, expr4, expr5, expr6) => {
+                ArithmeticExpression::Conditional(path2, pos2, _, expr4, expr5, expr6) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(5, pos2.column);
                     match &(**expr4) {
-                        ArithmeticExpression::Number(path4, pos4, n4) => {
+                        ArithmeticExpression::Number(path4, pos4, _, n4) => {
                             assert_eq!(&String::from("test.sh"), path4);
                             assert_eq!(1, pos4.line);
                             assert_eq!(5, pos4.column);
@@ -9520,7 +9519,7 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                         _ => assert!(false),
                     }
                     match &(**expr5) {
-                        ArithmeticExpression::Number(path5, pos5, n5) => {
+                        ArithmeticExpression::Number(path5, pos5, _, n5) => {
                             assert_eq!(&String::from("test.sh"), path5);
                             assert_eq!(1, pos5.line);
                             assert_eq!(9, pos5.column);
@@ -9529,7 +9528,7 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                         _ => assert!(false),
                     }
                     match &(**expr6) {
-                        ArithmeticExpression::Number(path6, pos6, n6) => {
+                        ArithmeticExpression::Number(path6, pos6, _, n6) => {
                             assert_eq!(&String::from("test.sh"), path6);
                             assert_eq!(1, pos6.line);
                             assert_eq!(13, pos6.column);
@@ -9541,12 +9540,12 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                 _ => assert!(false),
             }
             match &(*expr3) {
-                ArithmeticExpression::Conditional(path3, pos3, expr7, expr8, expr9) => {
+                ArithmeticExpression::Conditional(path3, pos3, _, expr7, expr8, expr9) => {
                     assert_eq!(&String::from("test.sh"), path3);
                     assert_eq!(1, pos3.line);
                     assert_eq!(17, pos3.column);
                     match &(**expr7) {
-                        ArithmeticExpression::Number(path7, pos7, n7) => {
+                        ArithmeticExpression::Number(path7, pos7, _, n7) => {
                             assert_eq!(&String::from("test.sh"), path7);
                             assert_eq!(1, pos7.line);
                             assert_eq!(17, pos7.column);
@@ -9555,7 +9554,7 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                         _ => assert!(false),
                     }
                     match &(**expr8) {
-                        ArithmeticExpression::Number(path8, pos8, n8) => {
+                        ArithmeticExpression::Number(path8, pos8, _, n8) => {
                             assert_eq!(&String::from("test.sh"), path8);
                             assert_eq!(1, pos8.line);
                             assert_eq!(21, pos8.column);
@@ -9564,7 +9563,7 @@ fn test_parser_parse_arith_expr_parses_expression1_with_conditionals()
                         _ => assert!(false),
                     }
                     match &(**expr9) {
-                        ArithmeticExpression::Number(path9, pos9, n9) => {
+                        ArithmeticExpression::Number(path9, pos9, _, n9) => {
                             assert_eq!(&String::from("test.sh"), path9);
                             assert_eq!(1, pos9.line);
                             assert_eq!(25, pos9.column);
@@ -9592,13 +9591,13 @@ fn test_parser_parse_arith_expr_parses_expression_for_in_arithmetic_expression_a
     let mut parser = Parser::new();
     let settings = Settings::new();
     match parser.parse_arith_expr(&mut lexer, &settings) {
-        Ok(ArithmeticExpression::Binary(path, pos, expr1, op, expr2)) => {
+        Ok(ArithmeticExpression::Binary(path, pos, _, expr1, op, expr2)) => {
             assert_eq!(String::from("test.sh"), path);
             assert_eq!(1, pos.line);
             assert_eq!(1, pos.column);
             assert_eq!(BinaryOperator::Add, op);
             match &(*expr1) {
-                ArithmeticExpression::Number(path1, pos1, n1) => {
+                ArithmeticExpression::Number(path1, pos1, _, n1) => {
                     assert_eq!(&String::from("test.sh"), path1);
                     assert_eq!(1, pos1.line);
                     assert_eq!(1, pos1.column);
@@ -9607,7 +9606,7 @@ fn test_parser_parse_arith_expr_parses_expression_for_in_arithmetic_expression_a
                 _ => assert!(false),
             }
             match &(*expr2) {
-                ArithmeticExpression::Number(path2, pos2, n2) => {
+                ArithmeticExpression::Number(path2, pos2, _, n2) => {
                     assert_eq!(&String::from("test.sh"), path2);
                     assert_eq!(1, pos2.line);
                     assert_eq!(5, pos2.column);
@@ -9890,7 +9889,7 @@ EOT
             assert_eq!(1, logical_commands.len());
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::Simple(_, _, simple_command) => {
+                Command::Simple(_, _, _, simple_command) => {
                     let s3 = "
 cat << EOT
 abcdef
@@ -9928,7 +9927,7 @@ EOT
             assert_eq!(1, logical_commands.len());
             assert_eq!(1, logical_commands[0].first_command.commands.len());
             match &(*logical_commands[0].first_command.commands[0]) {
-                Command::FunctionDefinition(_, _, _, fun_body) => {
+                Command::FunctionDefinition(_, _, _, _, fun_body) => {
                     let s3 = "
 { cat; } << EOT
 abcdef
@@ -11250,3 +11249,165 @@ fn test_format_with_arithmetic_expression_formats_expression1_with_conditionals(
         _ => assert!(false),
     }
 }
+
+#[test]
+fn test_parser_parse_logical_commands_sets_end_positions()
+{
+    let s = "echo abc >x | (cat) &\nf() { :; }";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_logical_commands(&mut lexer, &settings) {
+        Ok(logical_commands) => {
+            assert_eq!(2, logical_commands.len());
+            assert_eq!(1, logical_commands[0].end_pos.line);
+            assert_eq!(22, logical_commands[0].end_pos.column);
+            assert_eq!(21, logical_commands[0].end_pos.offset);
+            assert_eq!(20, logical_commands[0].first_command.end_pos.column);
+            match &(*logical_commands[0].first_command.commands[0]) {
+                Command::Simple(_, pos, end_pos, simple_command) => {
+                    assert_eq!(0, pos.offset);
+                    assert_eq!(1, end_pos.line);
+                    assert_eq!(12, end_pos.column);
+                    assert_eq!(11, end_pos.offset);
+                    assert_eq!(5, simple_command.words[0].end_pos.column);
+                    assert_eq!(9, simple_command.words[1].end_pos.column);
+                    assert_eq!(10, simple_command.redirects[0].pos().column);
+                    assert_eq!(12, simple_command.redirects[0].end_pos().column);
+                },
+                _ => assert!(false),
+            }
+            match &(*logical_commands[0].first_command.commands[1]) {
+                Command::Compound(_, pos, end_pos, _, _) => {
+                    assert_eq!(15, pos.column);
+                    assert_eq!(20, end_pos.column);
+                },
+                _ => assert!(false),
+            }
+            match &(*logical_commands[1].first_command.commands[0]) {
+                Command::FunctionDefinition(_, pos, end_pos, name_word, fun_body) => {
+                    assert_eq!(2, pos.line);
+                    assert_eq!(1, pos.column);
+                    assert_eq!(22, pos.offset);
+                    assert_eq!(2, end_pos.line);
+                    assert_eq!(11, end_pos.column);
+                    assert_eq!(32, end_pos.offset);
+                    assert_eq!(2, name_word.end_pos.column);
+                    assert_eq!(5, fun_body.pos.column);
+                    assert_eq!(11, fun_body.end_pos.column);
+                },
+                _ => assert!(false),
+            }
+        },
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn test_parser_parse_arith_expr_sets_end_positions()
+{
+    let s = "x + (y * 10)))";
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    lexer.push_in_arith_expr();
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    match parser.parse_arith_expr(&mut lexer, &settings) {
+        Ok(ArithmeticExpression::Binary(_, pos, end_pos, expr1, _, expr2)) => {
+            assert_eq!(1, pos.column);
+            assert_eq!(13, end_pos.column);
+            assert_eq!(2, expr1.end_pos().column);
+            assert_eq!(6, expr2.pos().column);
+            assert_eq!(12, expr2.end_pos().column);
+        },
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn test_caret_lines_underlines_token()
+{
+    let settings = Settings::new();
+    let s = "echo abc\n\tif x; done\n";
+    assert_eq!(Some(String::from("\tif x; done\n\t      ^^^^")), caret_lines(s, &Position::new_with_offset(2, 8, 16), &settings));
+    assert_eq!(Some(String::from("echo abc\n     ^^^")), caret_lines("echo abc", &Position::new_with_offset(1, 6, 5), &settings));
+    assert_eq!(Some(String::from("\n^")), caret_lines(s, &Position::new_with_offset(3, 1, 21), &settings));
+    assert_eq!(None, caret_lines(s, &Position::new_with_offset(3, 1, 22), &settings));
+}
+
+#[test]
+fn test_parser_parse_logical_commands_with_recovery_complains_on_all_syntax_errors()
+{
+    let s = "
+echo abc; echo )
+if true; then
+    echo (
+else
+    echo def
+fi
+echo ghi
+done
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    let (commands, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, &settings);
+    assert_eq!(2, commands.len());
+    assert_eq!(5, commands[0].pos.line);
+    assert_eq!(7, commands[1].pos.line);
+    let errs: Vec<String> = errs.iter().map(|err| format!("{}", err)).collect();
+    assert_eq!(vec![String::from("test.sh: 1.16: unexpected token"), String::from("test.sh: 3.10: unexpected token"), String::from("test.sh: 8.1: unexpected token")], errs);
+}
+
+#[test]
+fn test_parser_parse_logical_commands_with_recovery_complains_on_unterminated_construct()
+{
+    let s = "
+echo abc
+case x in
+    x)
+        for i in 1 2; do
+            echo $i
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    let (commands, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, &settings);
+    assert_eq!(1, commands.len());
+    let errs: Vec<String> = errs.iter().map(|err| format!("{}", err)).collect();
+    assert_eq!(vec![String::from("test.sh: 6.1: unterminated `for` started at 4.9"), String::from("test.sh: 6.1: unterminated `case` started at 2.1")], errs);
+}
+
+#[test]
+fn test_parser_parse_logical_commands_with_recovery_complains_on_nested_unterminated_constructs()
+{
+    let s = "
+if true; then
+    echo abc
+    echo def
+   ls )
+    echo ghi
+while true; do
+case x in
+";
+    let s2 = &s[1..];
+    let mut cursor = Cursor::new(s2.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("test.sh", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    let settings = Settings::new();
+    let (commands, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, &settings);
+    assert_eq!(1, commands.len());
+    assert_eq!(5, commands[0].pos.line);
+    let errs: Vec<String> = errs.iter().map(|err| format!("{}", err)).collect();
+    assert_eq!(vec![String::from("test.sh: 4.7: unexpected token"), String::from("test.sh: 8.1: unterminated `case` started at 7.1"), String::from("test.sh: 8.1: unterminated `while` started at 6.1"), String::from("test.sh: 8.1: unterminated `if` started at 1.1")], errs);
+}
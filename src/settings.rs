@@ -19,6 +19,27 @@ use std::fmt;
 use std::result;
 use crate::args::*;
 
+pub const OPTION_NAMES: &'static [&'static str] = &[
+    "allexport",
+    "errexit",
+    "ignoreeof",
+    "monitor",
+    "noclobber",
+    "noglob",
+    "noexec",
+    "nolog",
+    "notify",
+    "nounset",
+    "verbose",
+    "vi",
+    "emacs",
+    "xtrace",
+    "strlossy",
+    "extxtrace",
+    "stacktrace",
+    "histexpand"
+];
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum OptionType
 {
@@ -56,6 +77,8 @@ pub struct Settings
     pub xtrace_flag: bool,
     pub strlossy_flag: bool,
     pub extxtrace_flag: bool,
+    pub stacktrace_flag: bool,
+    pub histexpand_flag: bool,
     pub arg0: String,
     arg_vec_stack: Vec<Arguments>,
     current_args: Arguments,
@@ -83,6 +106,8 @@ impl Settings
             xtrace_flag: false,
             strlossy_flag: false,
             extxtrace_flag: false,
+            stacktrace_flag: false,
+            histexpand_flag: true,
             arg0: String::new(),
             arg_vec_stack: Vec::new(),
             current_args: Arguments::new(),
@@ -150,6 +175,7 @@ impl Settings
                                     Some((_, 'u')) => self.nounset_flag = opt_type == OptionType::Minus,
                                     Some((_, 'v')) => self.verbose_flag = opt_type == OptionType::Minus,
                                     Some((_, 'x')) => self.xtrace_flag = opt_type == OptionType::Minus,
+                                    Some((_, 'H')) => self.histexpand_flag = opt_type == OptionType::Minus,
                                     Some((_, 'h')) => (),
                                     Some((_, c2 @ 'o')) => {
                                         let opt_arg = match opt_iter.next() {
@@ -193,6 +219,10 @@ impl Settings
                                                     self.strlossy_flag = opt_type == OptionType::Minus;
                                                 } else if opt_arg == String::from("extxtrace") {
                                                     self.extxtrace_flag = opt_type == OptionType::Minus;
+                                                } else if opt_arg == String::from("stacktrace") {
+                                                    self.stacktrace_flag = opt_type == OptionType::Minus;
+                                                } else if opt_arg == String::from("histexpand") {
+                                                    self.histexpand_flag = opt_type == OptionType::Minus;
                                                 } else {
                                                     return Err(OptionError::InvalidArgument);
                                                 }
@@ -239,6 +269,7 @@ impl Settings
         if self.nounset_flag { s.push('u'); }
         if self.verbose_flag { s.push('v'); }
         if self.xtrace_flag { s.push('x'); }
+        if self.histexpand_flag && self.interactive_flag { s.push('H'); }
         s
     }
 }
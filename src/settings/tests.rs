@@ -480,6 +480,26 @@ fn test_settings_parse_options_parses_plus_h_option()
     }
 }
 
+#[test]
+fn test_settings_parse_options_parses_plus_capital_h_option()
+{
+    let mut settings = Settings::new();
+    settings.histexpand_flag = true;
+    let args = vec![
+        String::from("test"),
+        String::from("+H")
+    ];
+    let res = settings.parse_options(args.as_slice(), |_, _, _| { false });
+    match res {
+        Ok((i, is_minus_minus)) => {
+            assert_eq!(2, i);
+            assert_eq!(false, is_minus_minus);
+            assert_eq!(false, settings.histexpand_flag);
+        },
+        _ => assert!(false),
+    }
+}
+
 #[test]
 fn test_settings_parse_options_parses_options_with_arguments()
 {
@@ -1177,6 +1197,69 @@ fn test_settings_parse_options_parses_plus_o_options_with_extxtrace()
     }
 }
 
+#[test]
+fn test_settings_parse_options_parses_minus_o_options_with_stacktrace()
+{
+    let mut settings = Settings::new();
+    settings.stacktrace_flag = false;
+    let args = vec![
+        String::from("test"),
+        String::from("-o"),
+        String::from("stacktrace")
+    ];
+    let res = settings.parse_options(args.as_slice(), |_, _, _| { false });
+    match res {
+        Ok((i, is_minus_minus)) => {
+            assert_eq!(3, i);
+            assert_eq!(false, is_minus_minus);
+            assert_eq!(true, settings.stacktrace_flag);
+        },
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn test_settings_parse_options_parses_plus_o_options_with_stacktrace()
+{
+    let mut settings = Settings::new();
+    settings.stacktrace_flag = true;
+    let args = vec![
+        String::from("test"),
+        String::from("+o"),
+        String::from("stacktrace")
+    ];
+    let res = settings.parse_options(args.as_slice(), |_, _, _| { false });
+    match res {
+        Ok((i, is_minus_minus)) => {
+            assert_eq!(3, i);
+            assert_eq!(false, is_minus_minus);
+            assert_eq!(false, settings.stacktrace_flag);
+        },
+        _ => assert!(false),
+    }
+}
+
+#[test]
+fn test_settings_parse_options_parses_minus_o_options_with_histexpand()
+{
+    let mut settings = Settings::new();
+    settings.histexpand_flag = false;
+    let args = vec![
+        String::from("test"),
+        String::from("-o"),
+        String::from("histexpand")
+    ];
+    let res = settings.parse_options(args.as_slice(), |_, _, _| { false });
+    match res {
+        Ok((i, is_minus_minus)) => {
+            assert_eq!(3, i);
+            assert_eq!(false, is_minus_minus);
+            assert_eq!(true, settings.histexpand_flag);
+        },
+        _ => assert!(false),
+    }
+}
+
 #[test]
 fn test_settings_parse_options_parses_minus_o_option_with_separeted_argument()
 {
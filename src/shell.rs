@@ -0,0 +1,847 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::cell::*;
+use std::fs::*;
+use std::io::*;
+use std::mem::replace;
+use std::mem::swap;
+use std::os::unix::io::FromRawFd;
+use std::path::*;
+use std::rc::*;
+use rustyline;
+use rustyline::config::EditMode;
+use rustyline::error::ReadlineError;
+use rustyline::Config;
+use rustyline::Editor;
+use crate::args::*;
+use crate::builtins::*;
+use crate::env::*;
+use crate::exec::*;
+use crate::history::*;
+use crate::interp::*;
+use crate::io::*;
+use crate::lexer::*;
+use crate::line_editor::*;
+use crate::parser::*;
+use crate::settings::*;
+use crate::signals::set_signal_flag;
+use crate::signals::get_sigaction_for_interrupt;
+use crate::signals::set_sigaction_for_interrupt;
+use crate::signals::initialize_signals;
+use crate::utils::*;
+use crate::vars::initialize_vars;
+use crate::xsfprint;
+use crate::xsfprintln;
+
+const DEFAULT_PS2: &'static str = "> ";
+const DEFAULT_HISTORY_SIZE: usize = 500;
+
+struct EditModeFlags
+{
+    vi_flag: bool,
+    emacs_flag: bool,
+}
+
+impl EditModeFlags
+{
+    fn from_settings(settings: &Settings) -> EditModeFlags
+    {
+        EditModeFlags {
+            vi_flag: settings.vi_flag,
+            emacs_flag: settings.emacs_flag,
+        }
+    }
+}
+
+fn default_ps1() -> &'static str
+{
+    if getuid() == 0 {
+        "# "
+    } else {
+        "$ "
+    }
+}
+
+fn update_jobs(interp: &mut Interpreter, exec: &mut Executor, settings: &Settings)
+{
+    let jobs: Vec<(u32, Job)> = exec.jobs().iter().map(|p| (*(p.0), p.1.clone())).collect();
+    for (job_id, job) in &jobs {
+        let mut is_show = false;
+        for (i, (pid, status)) in job.pids.iter().zip(job.statuses.iter()).enumerate() {
+            match status {
+                WaitStatus::None | WaitStatus::Stopped(_) => {
+                    match exec.wait_for_process(Some(*pid), false, true, false, settings) {
+                        Ok(tmp_wait_status) => {
+                            match tmp_wait_status {
+                                WaitStatus::None => (),
+                                _ => is_show = true,
+                            }
+                            match tmp_wait_status {
+                                WaitStatus::None => (),
+                                _ => exec.set_job_status(*job_id, i, tmp_wait_status),
+                            }
+                        },
+                        Err(err) => xsfprint!(exec, 2, "{}", err),
+                    }
+                },
+                _ => (),
+            }
+        }
+        let mut wait_status = WaitStatus::None;
+        match job.last_status {
+            WaitStatus::None | WaitStatus::Stopped(_) => {
+                match exec.wait_for_process(Some(job.last_pid), false, true, false, settings) {
+                    Ok(tmp_wait_status) => {
+                        wait_status = if is_show || job.show_flag {
+                            match tmp_wait_status {
+                                WaitStatus::None => job.last_status,
+                                _ => tmp_wait_status,
+                            }
+                        } else {
+                            tmp_wait_status
+                        };
+                        match tmp_wait_status {
+                            WaitStatus::None => (),
+                            _ => exec.set_job_last_status(*job_id, tmp_wait_status),
+                        }
+                    },
+                    Err(err) => xsfprint!(exec, 2, "{}", err),
+                }
+            },
+            _ => {
+                if is_show || job.show_flag {
+                    wait_status = job.last_status;
+                }
+            },
+        }
+        exec.set_job_show_flag(*job_id, false);
+        if settings.notify_flag {
+            match wait_status {
+                WaitStatus::None => (),
+                _ => xsfprintln!(exec, 2, "{}", interp.job_to_string(exec, *job_id, job, Some(wait_status), JobFormatFlag::None)),
+            }
+        }
+    }
+    let jobs: Vec<(u32, Job)> = exec.jobs().iter().map(|p| (*(p.0), p.1.clone())).collect();
+    for (job_id, job) in &jobs {
+        if job.is_done() {
+            exec.remove_job(*job_id);
+        }
+    }
+}
+
+fn interpret_str(s: &str, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> i32
+{
+    interp.set_action_flag();
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("(command string)", &Position::new(1, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    parser.set_error_cont(false);
+    match parser.parse_logical_commands(&mut lexer, settings) {
+        Ok(commands) => interp.interpret_logical_commands(exec, commands.as_slice(), env, settings),
+        Err(err) => {
+            xsfprintln!(exec, 2, "{}", err);
+            match err.pos().map(|pos| caret_lines(s, &pos, settings)).flatten() {
+                Some(lines) => xsfprintln!(exec, 2, "{}", lines),
+                None => (),
+            }
+            1
+        },
+    }
+}
+
+fn interpret_stream(path: &str, cr: &mut dyn CharRead, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, is_ignored_eof: bool) -> (i32, bool)
+{
+    interp.set_action_flag();
+    let mut lexer = Lexer::new(path, &Position::new(1, 1), cr, 0, is_ignored_eof);
+    let mut parser = Parser::new();
+    loop {
+        match parser.parse_logical_commands_for_line(&mut lexer, settings) {
+            Ok(None) => {
+                let status = interp.last_status();
+                break (status, false);
+            },
+            Ok(Some(commands)) => {
+                if settings.verbose_flag {
+                    xsfprint!(exec, 2, "{}", lexer.content_for_verbose());
+                    lexer.clear_content_for_verbose();
+                }
+                let status = interp.interpret_logical_commands(exec, commands.as_slice(), env, settings);
+                if interp.has_break_or_continue_or_return_or_exit() {
+                    if interp.has_exit() {
+                        break (status, true);
+                    }
+                    interp.clear_return_state();
+                }
+                update_jobs(interp, exec, settings);
+            }
+            Err(err) => {
+                xsfprintln!(exec, 2, "{}", err);
+                if settings.noexec_flag {
+                    parser.recover(&mut lexer, settings);
+                    let (_, errs) = parser.parse_logical_commands_with_recovery(&mut lexer, settings);
+                    for err in &errs {
+                        xsfprintln!(exec, 2, "{}", err);
+                    }
+                }
+                break (1, false);
+            }
+        }
+    }
+}
+
+fn interpret_file(path: &str, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, is_ignored_eof: bool) -> Result<(i32, bool)>
+{
+    let mut file = File::open(path)?;
+    let mut br = BufReader::new(&mut file);
+    let mut cr = CharReader::new(&mut br);
+    Ok(interpret_stream(path, &mut cr, interp, exec, env, settings, is_ignored_eof))
+}
+
+fn new_rustyline_editor(settings: &Settings) -> rustyline::Result<Editor<LineEditorHelper>>
+{
+    let mut config_builder = Config::builder();
+    config_builder = config_builder.auto_add_history(false);
+    config_builder = config_builder.history_ignore_dups(false);
+    if settings.vi_flag {
+        config_builder = config_builder.edit_mode(EditMode::Vi);
+    } else if settings.emacs_flag {
+        config_builder = config_builder.edit_mode(EditMode::Emacs);
+    }
+    let config = config_builder.build();
+    let mut editor = Editor::<LineEditorHelper>::with_config(config)?;
+    editor.set_helper(Some(LineEditorHelper::new(settings)));
+    Ok(editor)
+}
+
+fn readline_with_helper(editor: &mut Editor<LineEditorHelper>, prompt: &str, interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings) -> rustyline::Result<String>
+{
+    let shell = Rc::new(RefCell::new(CompletionShell {
+            interp: replace(interp, Interpreter::new()),
+            exec: replace(exec, Executor::new()),
+            env: replace(env, Environment::new()),
+            settings: replace(settings, Settings::new()),
+    }));
+    match editor.helper_mut() {
+        Some(helper) => {
+            let shell_r = shell.borrow();
+            helper.refresh(&shell_r.env, &shell_r.exec, &shell_r.settings);
+            helper.set_shell(shell.clone());
+        },
+        None => (),
+    }
+    let res = editor.readline(prompt);
+    match editor.helper_mut() {
+        Some(helper) => helper.unset_shell(),
+        None => (),
+    }
+    let mut shell_r = shell.borrow_mut();
+    swap(interp, &mut shell_r.interp);
+    swap(exec, &mut shell_r.exec);
+    swap(env, &mut shell_r.env);
+    swap(settings, &mut shell_r.settings);
+    res
+}
+
+fn update_rustyline_edit_mode(editor: Editor<LineEditorHelper>, old_edit_mode_flags: &EditModeFlags, history_size: usize, settings: &Settings) -> rustyline::Result<Editor<LineEditorHelper>>
+{
+    if old_edit_mode_flags.vi_flag != settings.vi_flag || old_edit_mode_flags.emacs_flag != settings.emacs_flag {
+        let history: Vec<String> = editor.history().iter().map(|s| s.clone()).collect();
+        let mut new_editor = new_rustyline_editor(settings)?;
+        new_editor.history_mut().set_max_len(history_size);
+        for entry in history {
+            new_editor.add_history_entry(entry);
+        }
+        Ok(new_editor)
+    } else {
+        Ok(editor)
+    }
+}
+
+fn parse_stdin_str(s: &str, line: u64, settings: &Settings) -> ParserResult<Option<Vec<Rc<LogicalCommand>>>>
+{
+    let mut cursor = Cursor::new(s.as_bytes());
+    let mut cr = CharReader::new(&mut cursor);
+    let mut lexer = Lexer::new("(standard input)", &Position::new(line, 1), &mut cr, 0, false);
+    let mut parser = Parser::new();
+    parser.parse_logical_commands_for_line(&mut lexer, settings)
+}
+
+fn history_path(env: &Environment, home: &str) -> Option<String>
+{
+    match env.var("HISTFILE") {
+        Some(histfile) if histfile.is_empty() => None,
+        Some(histfile) => Some(histfile),
+        None => Some(format!("{}/.rsush_history", home)),
+    }
+}
+
+fn set_editor_history(editor: &mut Editor<LineEditorHelper>, history: &History)
+{
+    editor.history_mut().clear();
+    for entry in history.entries() {
+        editor.add_history_entry(entry.line.as_str());
+    }
+}
+
+fn load_history(editor: &mut Editor<LineEditorHelper>, history: &mut History, path: &Option<String>, exec: &mut Executor)
+{
+    match path {
+        Some(path) => {
+            match history.load(path) {
+                Ok(()) => (),
+                Err(err) if err.kind() == ErrorKind::NotFound => (),
+                Err(err) => xsfprintln!(exec, 2, "{}: {}", path, err),
+            }
+        },
+        None => (),
+    }
+    set_editor_history(editor, history);
+}
+
+fn add_history_entry(editor: &mut Editor<LineEditorHelper>, history: &mut History, line: &str, path: &Option<String>, env: &Environment, exec: &mut Executor)
+{
+    let control = HistoryControl::from_env(env);
+    match history.add(line, current_time(), &control) {
+        Some(entry) => {
+            if control.erase_dups_flag {
+                set_editor_history(editor, history);
+            } else {
+                editor.add_history_entry(line);
+            }
+            match path {
+                Some(path) => {
+                    match append_history_entry(path, &entry) {
+                        Ok(()) => (),
+                        Err(err) => xsfprintln!(exec, 2, "{}: {}", path, err),
+                    }
+                },
+                None => (),
+            }
+        },
+        None => (),
+    }
+}
+
+fn expand_history_line(line: &str, history: &History, exec: &mut Executor, settings: &Settings) -> (Option<String>, bool)
+{
+    if !settings.histexpand_flag || !line.contains(['!', '^']) {
+        return (Some(String::from(line)), false);
+    }
+    match expand_history(line, history.entries()) {
+        Ok((new_line, true, is_printed_only)) => {
+            xsfprintln!(exec, 1, "{}", new_line);
+            (Some(new_line), is_printed_only)
+        },
+        Ok((new_line, false, _)) => (Some(new_line), false),
+        Err(msg) => {
+            xsfprintln!(exec, 2, "{}", msg);
+            (None, false)
+        },
+    }
+}
+
+fn save_history(history: &History, path: &Option<String>, env: &Environment, exec: &mut Executor)
+{
+    let file_size = match env.var("HISTFILESIZE") {
+        Some(histfilesize) => {
+            match histfilesize.parse::<usize>() {
+                Ok(tmp_file_size) => tmp_file_size,
+                Err(_) => {
+                    xsfprintln!(exec, 2, "Invalid history file size");
+                    history.max_len()
+                },
+            }
+        },
+        None => history.max_len(),
+    };
+    match path {
+        Some(path) => {
+            match merge_history_file(path, file_size, HistoryControl::from_env(env).erase_dups_flag) {
+                Ok(()) => (),
+                Err(err) => xsfprintln!(exec, 2, "{}: {}", path, err),
+            }
+        },
+        None => (),
+    }
+}
+
+fn interactively_interpret(interp: &mut Interpreter, exec: &mut Executor, env: &mut Environment, settings: &mut Settings, is_signal_initializing: bool) -> i32
+{
+    settings.interactive_flag = true;
+    if is_signal_initializing {
+        initialize_signals(true);
+    }
+    interp.set_action_flag();
+    exec.set_foreground();
+    let _res = setpgid(exec.shell_pid(), exec.shell_pid());
+    exec.set_foreground_for_shell(settings);
+    exec.save_shell_termios();
+    match interpret_file("/etc/rsushrc", interp, exec, env, settings, false) {
+        Ok((status, is_exit)) => {
+            if is_exit { return status; }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => (),
+        Err(err) => xsfprintln!(exec, 2, "/etc/rsushrc: {}", err),
+    }
+    let home = env.var("HOME").unwrap_or(String::from("/"));
+    let path = format!("{}/.rsushrc", home);
+    match interpret_file(path.as_str(), interp, exec, env, settings, false) {
+        Ok((status, is_exit)) => {
+            if is_exit { return status; }
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => (),
+        Err(err) => xsfprintln!(exec, 2, "{}: {}", path, err),
+    }
+    let mut saved_shell_sigaction = get_sigaction_for_interrupt();
+    let mut editor = match new_rustyline_editor(settings) {
+        Ok(tmp_editor) => tmp_editor,
+        Err(err) => {
+            xsfprintln!(exec, 2, "{}", err);
+            return 1;
+        },
+    };
+    let history_size = match env.var("RSUSH_HISTSIZE") {
+        Some(rsush_histsize) => {
+            match rsush_histsize.parse::<usize>() {
+                Ok(tmp_history_size) => tmp_history_size,
+                Err(_) => {
+                    xsfprintln!(exec, 2, "Invalid history size");
+                    DEFAULT_HISTORY_SIZE
+                },
+            }
+        },
+        None => DEFAULT_HISTORY_SIZE,
+    };
+    let history_path = history_path(env, home.as_str());
+    let mut history = History::new(history_size, format!("{}", exec.shell_pid()).as_str());
+    editor.history_mut().set_max_len(history_size);
+    load_history(&mut editor, &mut history, &history_path, exec);
+    let mut line: u64 = 1;
+    loop {
+        let ps1 = env.var("PS1").unwrap_or(String::from(default_ps1()));
+        match readline_with_helper(&mut editor, ps1.as_str(), interp, exec, env, settings) {
+            Ok(buf) => {
+                let mut saved_editor_sigaction = get_sigaction_for_interrupt();
+                set_sigaction_for_interrupt(&saved_shell_sigaction);
+                let buf = match expand_history_line(buf.as_str(), &history, exec, settings) {
+                    (Some(new_buf), is_printed_only) => {
+                        if is_printed_only {
+                            if !settings.nolog_flag {
+                                add_history_entry(&mut editor, &mut history, new_buf.as_str(), &history_path, env, exec);
+                            }
+                            saved_shell_sigaction = get_sigaction_for_interrupt();
+                            set_sigaction_for_interrupt(&saved_editor_sigaction);
+                            continue;
+                        }
+                        new_buf
+                    },
+                    (None, _) => {
+                        saved_shell_sigaction = get_sigaction_for_interrupt();
+                        set_sigaction_for_interrupt(&saved_editor_sigaction);
+                        continue;
+                    },
+                };
+                if !settings.nolog_flag {
+                    add_history_entry(&mut editor, &mut history, buf.as_str(), &history_path, env, exec);
+                }
+                let mut new_line = line;
+                let mut lines = buf.clone();
+                lines.push('\n');
+                new_line += (buf.matches('\n').count() as u64) + 1;
+                let commands = match parse_stdin_str(lines.as_str(), line, settings) {
+                    Ok(None) => break interp.last_status(),
+                    Ok(Some(tmp_commands)) => Some(tmp_commands),
+                    Err(mut err @ ParserError::Syntax(_, _, _, true)) => {
+                        saved_shell_sigaction = get_sigaction_for_interrupt();
+                        set_sigaction_for_interrupt(&saved_editor_sigaction);
+                        loop {
+                            let ps2 = env.var("PS2").unwrap_or(String::from(DEFAULT_PS2));
+                            match readline_with_helper(&mut editor, ps2.as_str(), interp, exec, env, settings) {
+                                Ok(buf2) => {
+                                    saved_editor_sigaction = get_sigaction_for_interrupt();
+                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
+                                    let buf2 = match expand_history_line(buf2.as_str(), &history, exec, settings) {
+                                        (Some(new_buf2), false) => new_buf2,
+                                        (Some(new_buf2), true) => {
+                                            if !settings.nolog_flag {
+                                                add_history_entry(&mut editor, &mut history, new_buf2.as_str(), &history_path, env, exec);
+                                            }
+                                            break None;
+                                        },
+                                        (None, _) => break None,
+                                    };
+                                    if !settings.nolog_flag {
+                                        add_history_entry(&mut editor, &mut history, buf2.as_str(), &history_path, env, exec);
+                                    }
+                                    lines.push_str(buf2.as_str());
+                                    lines.push('\n');
+                                    new_line += (buf2.matches('\n').count() as u64) + 1;
+                                    match parse_stdin_str(lines.as_str(), line, settings) { 
+                                        Ok(None) => break None,
+                                        Ok(Some(tmp_commands)) => break Some(tmp_commands),
+                                        Err(err2 @ ParserError::Syntax(_, _, _, true)) => err = err2,
+                                        Err(err2 @ ParserError::Syntax(_, _, _, false)) => {
+                                            xsfprintln!(exec, 2, "{}", err2);
+                                            break None;
+                                        },
+                                        Err(err2) => {
+                                            xsfprintln!(exec, 2, "{}", err2);
+                                            save_history(&history, &history_path, env, exec);
+                                            return 1;
+                                        },
+                                    }
+                                    saved_shell_sigaction = get_sigaction_for_interrupt();
+                                    set_sigaction_for_interrupt(&saved_editor_sigaction);
+                                },
+                                Err(ReadlineError::Interrupted) => {
+                                    saved_editor_sigaction = get_sigaction_for_interrupt();
+                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
+                                    set_signal_flag(libc::SIGINT);
+                                    let saved_last_status = interp.last_status();
+                                    match interp.do_actions(exec, env, settings) {
+                                        Some(action_status) => {
+                                            if interp.has_exit_with_interactive() {
+                                                return action_status
+                                            }
+                                            interp.clear_return_state();
+                                            interp.set_last_status(saved_last_status);
+                                        },
+                                        None => (),
+                                    }
+                                    xsfprintln!(exec, 2, "{}", err);
+                                    break None
+                                },
+                                Err(ReadlineError::Eof) => {
+                                    saved_editor_sigaction = get_sigaction_for_interrupt();
+                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
+                                    xsfprintln!(exec, 2, "{}", err);
+                                    break None
+                                },
+                                Err(err2) => {
+                                    set_sigaction_for_interrupt(&saved_shell_sigaction);
+                                    xsfprintln!(exec, 2, "{}", err2);
+                                    save_history(&history, &history_path, env, exec);
+                                    return 1;
+                                },
+                            }
+                        }
+                    },
+                    Err(err @ ParserError::Syntax(_, _, _, false)) => {
+                        xsfprintln!(exec, 2, "{}", err);
+                        None
+                    },
+                    Err(err) => {
+                        xsfprintln!(exec, 2, "{}", err);
+                        save_history(&history, &history_path, env, exec);
+                        return 1;
+                    },
+                };
+                if settings.verbose_flag {
+                    xsfprint!(exec, 2, "{}", lines);
+                }
+                line = new_line;
+                match commands {
+                    Some(commands) => {
+                        let old_edit_mode_flags = EditModeFlags::from_settings(settings);
+                        let status = interp.interpret_logical_commands(exec, commands.as_slice(), env, settings);
+                        saved_shell_sigaction = get_sigaction_for_interrupt();
+                        set_sigaction_for_interrupt(&saved_editor_sigaction);
+                        match update_rustyline_edit_mode(editor, &old_edit_mode_flags, history_size, settings) {
+                            Ok(tmp_editor) => editor = tmp_editor,
+                            Err(err) => {
+                                set_sigaction_for_interrupt(&saved_shell_sigaction);
+                                xsfprintln!(exec, 2, "{}", err);
+                                return 1;
+                            }
+                        }
+                        saved_editor_sigaction = get_sigaction_for_interrupt();
+                        set_sigaction_for_interrupt(&saved_shell_sigaction);
+                        if interp.has_break_or_continue_or_return_or_exit() {
+                            if interp.has_exit_with_interactive() {
+                                save_history(&history, &history_path, env, exec);
+                                break status;
+                            }
+                            interp.clear_return_state();
+                        }
+                    },
+                    None => (),
+                }
+                saved_shell_sigaction = get_sigaction_for_interrupt();
+                set_sigaction_for_interrupt(&saved_editor_sigaction);
+                update_jobs(interp, exec, settings);
+            }
+            Err(ReadlineError::Interrupted) => {
+                let saved_editor_sigaction = get_sigaction_for_interrupt();
+                set_sigaction_for_interrupt(&saved_shell_sigaction);
+                set_signal_flag(libc::SIGINT);
+                let saved_last_status = interp.last_status();
+                match interp.do_actions(exec, env, settings) {
+                    Some(action_status) => {
+                        if interp.has_exit_with_interactive() {
+                            return action_status
+                        }
+                        interp.clear_return_state();
+                        interp.set_last_status(saved_last_status);
+                    },
+                    None => (),
+                }
+                saved_shell_sigaction = get_sigaction_for_interrupt();
+                set_sigaction_for_interrupt(&saved_editor_sigaction);
+                update_jobs(interp, exec, settings);
+            },
+            Err(ReadlineError::Eof) => {
+                if !settings.ignoreeof_flag {
+                    set_sigaction_for_interrupt(&saved_shell_sigaction);
+                    save_history(&history, &history_path, env, exec);
+                    break interp.last_status();
+                } else {
+                    update_jobs(interp, exec, settings);
+                }
+            },
+            Err(err) => {
+                set_sigaction_for_interrupt(&saved_shell_sigaction);
+                xsfprintln!(exec, 2, "{}", err);
+                save_history(&history, &history_path, env, exec);
+                return 1;
+            },
+        }
+    }
+}
+
+fn std_file(fd: i32) -> File
+{
+    match fcntl_f_dupfd_cloexec(fd, 3) {
+        Ok(new_fd) => unsafe { File::from_raw_fd(new_fd) },
+        Err(_) => unsafe { File::from_raw_fd(fd) },
+    }
+}
+
+fn take_captured_output(file: &Option<Rc<RefCell<File>>>) -> Result<String>
+{
+    match file {
+        Some(file) => {
+            let mut file_r = file.borrow_mut();
+            file_r.seek(SeekFrom::Start(0))?;
+            let mut s = String::new();
+            file_r.read_to_string(&mut s)?;
+            file_r.set_len(0)?;
+            file_r.seek(SeekFrom::Start(0))?;
+            Ok(s)
+        },
+        None => Ok(String::new()),
+    }
+}
+
+pub struct Shell
+{
+    interp: Interpreter,
+    exec: Executor,
+    env: Environment,
+    settings: Settings,
+    captured_stdout: Option<Rc<RefCell<File>>>,
+    captured_stderr: Option<Rc<RefCell<File>>>,
+    has_signals: bool,
+}
+
+impl Shell
+{
+    fn new_without_files() -> Shell
+    {
+        let mut shell = Shell {
+            interp: Interpreter::new(),
+            exec: Executor::new(),
+            env: Environment::new(),
+            settings: Settings::new(),
+            captured_stdout: None,
+            captured_stderr: None,
+            has_signals: false,
+        };
+        initialize_builtin_funs(&mut shell.env);
+        initialize_vars(&mut shell.env);
+        shell.settings.arg0 = String::from("rsush");
+        shell
+    }
+
+    pub fn new() -> Shell
+    {
+        let mut shell = Shell::new_without_files();
+        shell.exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(std_file(0))));
+        shell.exec.push_file_and_set_saved_file(1, Rc::new(RefCell::new(std_file(1))));
+        shell.exec.push_file_and_set_saved_file(2, Rc::new(RefCell::new(std_file(2))));
+        shell
+    }
+
+    pub fn new_with_captured_output() -> Result<Shell>
+    {
+        let mut shell = Shell::new_without_files();
+        let stdout_file = Rc::new(RefCell::new(create_capture_file()?));
+        let stderr_file = Rc::new(RefCell::new(create_capture_file()?));
+        shell.exec.push_file_and_set_saved_file(0, Rc::new(RefCell::new(std_file(0))));
+        shell.exec.push_file_and_set_saved_file(1, stdout_file.clone());
+        shell.exec.push_file_and_set_saved_file(2, stderr_file.clone());
+        shell.captured_stdout = Some(stdout_file);
+        shell.captured_stderr = Some(stderr_file);
+        Ok(shell)
+    }
+
+    pub fn initialize_signals(&mut self)
+    {
+        if !self.has_signals {
+            initialize_signals(false);
+            self.has_signals = true;
+        }
+    }
+
+    pub fn settings(&self) -> &Settings
+    { &self.settings }
+
+    pub fn settings_mut(&mut self) -> &mut Settings
+    { &mut self.settings }
+
+    pub fn has_exit(&self) -> bool
+    { self.interp.has_exit() }
+
+    pub fn last_status(&self) -> i32
+    { self.interp.last_status() }
+
+    pub fn take_stdout(&mut self) -> Result<String>
+    { take_captured_output(&self.captured_stdout) }
+
+    pub fn take_stderr(&mut self) -> Result<String>
+    { take_captured_output(&self.captured_stderr) }
+
+    pub fn get_var(&self, name: &str) -> Option<String>
+    { self.env.var(name) }
+
+    pub fn set_var(&mut self, name: &str, value: &str) -> bool
+    {
+        if self.env.read_only_var_attr(name) {
+            return false;
+        }
+        self.env.set_var(name, value, &self.settings);
+        true
+    }
+
+    pub fn run_str(&mut self, s: &str) -> i32
+    {
+        let status = interpret_str(s, &mut self.interp, &mut self.exec, &mut self.env, &mut self.settings);
+        if !self.interp.has_exit() {
+            self.interp.clear_return_state();
+        }
+        status
+    }
+
+    pub fn run_str_as_last(&mut self, s: &str) -> i32
+    {
+        let interp = &mut self.interp;
+        let env = &mut self.env;
+        let settings = &mut self.settings;
+        let status = self.exec.interpret_as_last(|exec| interpret_str(s, interp, exec, env, settings));
+        if !self.interp.has_exit() {
+            self.interp.clear_return_state();
+        }
+        status
+    }
+
+    pub fn run_file<P: AsRef<Path>>(&mut self, path: P) -> Result<i32>
+    {
+        let path_s = path.as_ref().to_string_lossy().into_owned();
+        let (status, _) = interpret_file(path_s.as_str(), &mut self.interp, &mut self.exec, &mut self.env, &mut self.settings, true)?;
+        Ok(status)
+    }
+
+    pub fn run_stdin(&mut self, interactive_flag: Option<bool>) -> i32
+    {
+        if interactive_flag.unwrap_or(isatty(0).unwrap_or(false)) {
+            let status = interactively_interpret(&mut self.interp, &mut self.exec, &mut self.env, &mut self.settings, self.has_signals);
+            self.exec.hang_up_jobs();
+            status
+        } else {
+            let mut br = BufReader::new(stdin());
+            let mut cr = CharReader::new(&mut br);
+            interpret_stream("(standard input)", &mut cr, &mut self.interp, &mut self.exec, &mut self.env, &mut self.settings, true).0
+        }
+    }
+
+    // Command substitutions and subshells that call this built-in command are executed in a new
+    // process, so side effects of the closure don't leave them.
+    pub fn set_builtin<F>(&mut self, name: &str, is_special: bool, f: F)
+        where F: FnMut(&mut Context, &[String]) -> i32 + 'static
+    {
+        self.env.unset_builtin_fun(name);
+        self.env.set_closure_builtin_fun(name, Box::new(f));
+        if is_special {
+            self.interp.add_special_builtin_fun_name(name);
+        } else {
+            self.interp.remove_special_builtin_fun_name(name);
+        }
+    }
+
+    // Command substitutions and subshells that call this built-in command can be executed in the
+    // shell process, so the closure is called there and its side effects are visible.
+    pub fn set_fork_free_builtin<F>(&mut self, name: &str, is_special: bool, f: F)
+        where F: FnMut(&mut Context, &[String]) -> i32 + 'static
+    {
+        self.set_builtin(name, is_special, f);
+        self.env.set_fork_free_closure_builtin_fun_flag(name);
+    }
+
+    pub fn unset_builtin(&mut self, name: &str)
+    {
+        self.env.unset_builtin_fun(name);
+        self.env.unset_closure_builtin_fun(name);
+        self.interp.remove_special_builtin_fun_name(name);
+    }
+
+    pub fn call_function(&mut self, name: &str, args: &[String]) -> Option<i32>
+    {
+        match self.env.fun(name) {
+            Some(fun_body) => {
+                let mut tmp_args = Arguments::new();
+                tmp_args.set_args(args.to_vec());
+                self.settings.push_args(tmp_args);
+                let status = self.interp.interpret_fun_body(&mut self.exec, name, &(*fun_body), &mut self.env, &mut self.settings);
+                self.settings.pop_args();
+                if !self.interp.has_exit() {
+                    self.interp.clear_return_state();
+                }
+                Some(status)
+            },
+            None => None,
+        }
+    }
+
+    pub fn do_exit_action(&mut self, status: i32) -> i32
+    {
+        match self.interp.do_action(&mut self.exec, 0, &mut self.env, &mut self.settings) {
+            Some(action_status) => action_status,
+            None => status,
+        }
+    }
+}
+
+impl Default for Shell
+{
+    fn default() -> Shell
+    { Shell::new() }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,254 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::process;
+use super::*;
+use crate::test_helpers::*;
+use sealed_test::prelude::*;
+
+#[sealed_test]
+fn test_shell_run_str_captures_output()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("echo abc; echo def >&2; echo ghi");
+    assert_eq!(0, status);
+    assert_eq!(false, shell.has_exit());
+    assert_eq!(String::from("abc\nghi\n"), shell.take_stdout().unwrap());
+    assert_eq!(String::from("def\n"), shell.take_stderr().unwrap());
+    let status = shell.run_str("echo jkl");
+    assert_eq!(0, status);
+    assert_eq!(String::from("jkl\n"), shell.take_stdout().unwrap());
+    assert_eq!(String::new(), shell.take_stderr().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_captures_output_for_syntax_error()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("echo abc )");
+    assert_eq!(1, status);
+    assert_eq!(String::new(), shell.take_stdout().unwrap());
+    assert_eq!(String::from("(command string): 1.10: unexpected token\necho abc )\n         ^\n"), shell.take_stderr().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_exits()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("exit 2; echo abc");
+    assert_eq!(2, status);
+    assert!(shell.has_exit());
+    assert_eq!(String::new(), shell.take_stdout().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_file_interprets_file()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    write_file("test.sh", "echo abc\nX=def\n");
+    match shell.run_file("test.sh") {
+        Ok(status) => assert_eq!(0, status),
+        Err(_) => assert!(false),
+    }
+    assert_eq!(String::from("abc\n"), shell.take_stdout().unwrap());
+    assert_eq!(Some(String::from("def")), shell.get_var("X"));
+}
+
+#[sealed_test]
+fn test_shell_run_file_complains_on_all_syntax_errors_for_noexec_flag()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    shell.settings_mut().noexec_flag = true;
+    write_file("test.sh", "echo abc\nif true; then\n    echo )\nfi\necho ) ; echo def\nwhile true; do\n    echo ghi\n");
+    match shell.run_file("test.sh") {
+        Ok(status) => assert_eq!(1, status),
+        Err(_) => assert!(false),
+    }
+    assert_eq!(String::new(), shell.take_stdout().unwrap());
+    assert_eq!(String::from("test.sh: 3.10: unexpected token\ntest.sh: 5.6: unexpected token\ntest.sh: 8.1: unterminated `while` started at 6.1\n"), shell.take_stderr().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_file_complains_on_non_existent_file()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    match shell.run_file("test.sh") {
+        Ok(_) => assert!(false),
+        Err(err) => assert_eq!(ErrorKind::NotFound, err.kind()),
+    }
+}
+
+#[sealed_test]
+fn test_shell_set_var_sets_variable()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    assert!(shell.set_var("X", "abc"));
+    assert_eq!(0, shell.run_str("echo $X; readonly Y=def"));
+    assert_eq!(String::from("abc\n"), shell.take_stdout().unwrap());
+    assert_eq!(false, shell.set_var("Y", "ghi"));
+    assert_eq!(Some(String::from("def")), shell.get_var("Y"));
+}
+
+#[sealed_test]
+fn test_shell_call_function_calls_function()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    assert_eq!(0, shell.run_str("set -- x y; f() { echo \"$# $1 $2\"; return 3; }"));
+    let args = vec![String::from("abc"), String::from("def")];
+    assert_eq!(Some(3), shell.call_function("f", args.as_slice()));
+    assert_eq!(false, shell.has_exit());
+    assert_eq!(None, shell.call_function("g", &[]));
+    assert_eq!(0, shell.run_str("echo \"$# $1 $2\""));
+    assert_eq!(String::from("2 abc def\n2 x y\n"), shell.take_stdout().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_set_builtin_sets_regular_builtin_function()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let mut count = 0;
+    shell.set_builtin("deploy_status", false, move |context, args| {
+            count += 1;
+            let x = context.get_var("X").unwrap_or(String::new());
+            context.print_stdout(format!("{} {} {}\n", count, args[1..].join(" "), x).as_str());
+            0
+    });
+    assert_eq!(0, shell.run_str("deploy_status abc def; X=ghi deploy_status; echo \"$X\"; command -v deploy_status"));
+    assert_eq!(String::from("1 abc def \n2  ghi\n\ndeploy_status\n"), shell.take_stdout().unwrap());
+    shell.unset_builtin("deploy_status");
+    assert_eq!(127, shell.run_str("deploy_status"));
+}
+
+#[sealed_test]
+fn test_shell_set_builtin_sets_special_builtin_function()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    shell.set_builtin("fail", true, |context, args| {
+            context.print_stderr(format!("{}: failed\n", args[0]).as_str());
+            context.exit(2)
+    });
+    assert_eq!(2, shell.run_str("X=abc fail; echo def"));
+    assert!(shell.has_exit());
+    assert_eq!(String::new(), shell.take_stdout().unwrap());
+    assert_eq!(String::from("fail: failed\n"), shell.take_stderr().unwrap());
+    assert_eq!(Some(String::from("abc")), shell.get_var("X"));
+}
+
+#[sealed_test]
+fn test_shell_run_str_waits_for_next_job()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("mkfifo fifo; read x < fifo & (exit 3) & pid=$!; wait -n -p next_pid; echo $?; test \"$next_pid\" = \"$pid\" && echo ok; echo > fifo; wait -n; echo $?; wait -n; echo $?");
+    assert_eq!(0, status);
+    assert_eq!(String::from("3\nok\n0\n127\n"), shell.take_stdout().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_waits_for_job_with_timeout()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("sleep 10 & wait -t 0.1 -p pid %1; echo $? ${pid-unset}; kill $!; wait $!");
+    assert_eq!(143, status);
+    assert_eq!(String::from("142 unset\n"), shell.take_stdout().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_substitutes_builtin_commands_without_fork()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let count = Rc::new(Cell::new(0));
+    let count2 = count.clone();
+    shell.set_fork_free_builtin("count", false, move |context, args| {
+            count2.set(count2.get() + 1);
+            context.print_stdout(format!("{}\n", args[1..].join(" ")).as_str());
+            0
+    });
+    let status = shell.run_str("X=abc; Y=$(X=def; count $X); (X=ghi; count $X > /dev/null); Z=$(count jkl | cat); echo \"$X $Y $Z\"");
+    assert_eq!(0, status);
+    assert_eq!(2, count.get());
+    assert_eq!(String::from("abc def jkl\n"), shell.take_stdout().unwrap());
+    assert_eq!(String::new(), shell.take_stderr().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_substitutes_builtin_commands_in_shell_process()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let pid = Rc::new(Cell::new(0));
+    let pid2 = pid.clone();
+    shell.set_fork_free_builtin("pid", false, move |context, _| {
+            pid2.set(process::id());
+            context.print_stdout(format!("{}\n", process::id()).as_str());
+            0
+    });
+    let status = shell.run_str("f() { set -- a; pid; }; X=$(f)");
+    assert_eq!(0, status);
+    assert_eq!(process::id(), pid.get());
+    assert_eq!(Some(format!("{}", process::id())), shell.get_var("X"));
+    assert_eq!(String::new(), shell.take_stderr().unwrap());
+}
+
+#[sealed_test]
+fn test_shell_run_str_substitutes_closure_builtin_commands_with_fork()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let count = Rc::new(Cell::new(0));
+    let count2 = count.clone();
+    shell.set_builtin("count", false, move |context, args| {
+            count2.set(count2.get() + 1);
+            context.print_stdout(format!("{} {}\n", args[1..].join(" "), process::id()).as_str());
+            0
+    });
+    let status = shell.run_str("X=$(count abc); (count def > /dev/null); count ghi");
+    assert_eq!(0, status);
+    assert_eq!(1, count.get());
+    assert_ne!(Some(format!("abc {}", process::id())), shell.get_var("X"));
+    assert_eq!(Some(String::from("abc")), shell.get_var("X").map(|x| String::from(x.split(' ').next().unwrap())));
+    assert_eq!(format!("ghi {}\n", process::id()), shell.take_stdout().unwrap());
+    assert_eq!(String::new(), shell.take_stderr().unwrap());
+}
+
+#[sealed_test(before=symlink_rsush_test(), after=remove_rsush_test())]
+fn test_shell_run_str_as_last_interprets_all_commands_from_sourced_file()
+{
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    write_file("test.sh", "./rsush_test exit 1\n./rsush_test args abc def\n");
+    let status = shell.run_str_as_last("./rsush_test args xyz; . ./test.sh");
+    assert_eq!(0, status);
+    assert_eq!(String::from("xyz\nabc\ndef\n"), shell.take_stdout().unwrap());
+    assert_eq!(String::new(), shell.take_stderr().unwrap());
+}
+
+fn signal_handler_for(sig: i32) -> libc::sighandler_t
+{
+    let mut sigact: libc::sigaction = unsafe { std::mem::zeroed() };
+    unsafe { libc::sigaction(sig, std::ptr::null(), &mut sigact as *mut libc::sigaction) };
+    sigact.sa_sigaction
+}
+
+#[sealed_test]
+fn test_shell_run_str_does_not_initialize_signals()
+{
+    unsafe { libc::signal(libc::SIGTTOU, libc::SIG_IGN) };
+    let mut shell = Shell::new_with_captured_output().unwrap();
+    let status = shell.run_str("echo abc");
+    assert_eq!(0, status);
+    assert_eq!(String::from("abc\n"), shell.take_stdout().unwrap());
+    assert_eq!(libc::SIG_IGN, signal_handler_for(libc::SIGTTOU));
+    shell.initialize_signals();
+    assert_eq!(libc::SIG_DFL, signal_handler_for(libc::SIGTTOU));
+}
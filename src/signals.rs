@@ -65,7 +65,7 @@ pub fn set_signal(sig: i32, is_handler: bool, is_interactive: bool) -> Result<()
 {
     let mut sigact: MaybeUninit<libc::sigaction> = MaybeUninit::uninit();
     if is_handler {
-        unsafe { sigact.assume_init_mut() }.sa_sigaction = signal_handler as libc::sighandler_t;
+        unsafe { sigact.assume_init_mut() }.sa_sigaction = signal_handler as *const () as libc::sighandler_t;
     } else {
         if is_interactive && (sig == libc::SIGINT || sig == libc::SIGTTIN || sig == libc::SIGTTOU) {
             unsafe { sigact.assume_init_mut() }.sa_sigaction = libc::SIG_IGN;
@@ -19,6 +19,7 @@ use std::env;
 use std::fs::*;
 use std::io::*;
 use std::os::unix::io::FromRawFd;
+use std::os::unix::process::parent_id;
 use std::process::exit;
 
 fn main()
@@ -50,6 +51,8 @@ fn main()
                         exit(1);
                     },
                 }
+            } else if applet_name == &String::from("ppid") {
+                println!("{}", parent_id());
             } else if applet_name == &String::from("read_fd") {
                 match args.get(2) {
                     Some(s) => {
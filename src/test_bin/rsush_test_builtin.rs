@@ -0,0 +1,83 @@
+//
+// Rsush - Rust single unix shell.
+// Copyright (C) 2022 Łukasz Szpakowski
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::os::raw::c_void;
+use std::slice;
+
+#[repr(C)]
+pub struct RsushContext
+{
+    data: *mut c_void,
+    get_var: extern "C" fn(*mut c_void, *const c_char) -> *const c_char,
+    set_var: extern "C" fn(*mut c_void, *const c_char, *const c_char) -> c_int,
+    write: extern "C" fn(*mut c_void, c_int, *const c_char, usize) -> isize,
+}
+
+#[repr(C)]
+pub struct RsushBuiltin
+{
+    name: *const c_char,
+    main: extern "C" fn(c_int, *const *const c_char, *mut RsushContext) -> c_int,
+}
+
+unsafe impl Sync for RsushBuiltin
+{}
+
+fn write_str(ctx: &mut RsushContext, fd: c_int, s: &str)
+{ (ctx.write)(ctx.data, fd, s.as_ptr() as *const c_char, s.len()); }
+
+extern "C" fn hello_main(argc: c_int, argv: *const *const c_char, ctx: *mut RsushContext) -> c_int
+{
+    let ctx = unsafe { &mut *ctx };
+    let args: Vec<String> = unsafe { slice::from_raw_parts(argv, argc as usize) }.iter().map(|a| unsafe { CStr::from_ptr(*a) }.to_string_lossy().into_owned()).collect();
+    write_str(ctx, 1, format!("Hello, {}!\n", args[1..].join(" ")).as_str());
+    let value = (ctx.get_var)(ctx.data, b"NAME\0".as_ptr() as *const c_char);
+    if !value.is_null() {
+        let value_s = unsafe { CStr::from_ptr(value) }.to_string_lossy().into_owned();
+        write_str(ctx, 1, format!("NAME={}\n", value_s).as_str());
+    }
+    (ctx.set_var)(ctx.data, b"HELLO\0".as_ptr() as *const c_char, b"world\0".as_ptr() as *const c_char);
+    0
+}
+
+extern "C" fn fail_main(_argc: c_int, _argv: *const *const c_char, ctx: *mut RsushContext) -> c_int
+{
+    let ctx = unsafe { &mut *ctx };
+    write_str(ctx, 2, "fail: error\n");
+    2
+}
+
+#[no_mangle]
+pub static rsush_builtin_hello: RsushBuiltin = RsushBuiltin {
+    name: b"hello\0".as_ptr() as *const c_char,
+    main: hello_main,
+};
+
+#[no_mangle]
+pub static rsush_builtin_fail: RsushBuiltin = RsushBuiltin {
+    name: b"fail\0".as_ptr() as *const c_char,
+    main: fail_main,
+};
+
+#[no_mangle]
+pub static rsush_builtin_bad: RsushBuiltin = RsushBuiltin {
+    name: b"other\0".as_ptr() as *const c_char,
+    main: fail_main,
+};
@@ -15,16 +15,23 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::env;
 use std::ffi::*;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::fs::remove_file;
 use std::io::*;
 use std::mem::MaybeUninit;
 use std::num::ParseIntError;
 use std::os::unix::ffi::OsStrExt;
 use std::os::unix::ffi::OsStringExt;
+use std::os::unix::io::FromRawFd;
 use std::path::*;
+use std::process;
 use std::ptr::null_mut;
 use std::result;
 use std::slice::*;
+use std::time::Duration;
 use fnmatch_sys;
 use libc;
 use crate::iter::*;
@@ -389,6 +396,75 @@ pub fn setpgid(pid: i32, pgid: i32) -> Result<()>
     }
 }
 
+pub fn create_capture_file() -> Result<File>
+{
+    let mut i = 0;
+    loop {
+        let mut path_buf = env::temp_dir();
+        path_buf.push(format!("rsush-{}-{}", process::id(), i));
+        match OpenOptions::new().read(true).write(true).create_new(true).open(path_buf.as_path()) {
+            Ok(file) => {
+                remove_file(path_buf.as_path())?;
+                return Ok(file);
+            },
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => i += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn create_memory_file() -> Result<File>
+{
+    let name_cstring = CString::new("rsush-here-doc").unwrap();
+    let res = unsafe { libc::memfd_create(name_cstring.as_ptr(), libc::MFD_CLOEXEC) };
+    if res != -1 {
+        Ok(unsafe { File::from_raw_fd(res) })
+    } else {
+        create_capture_file()
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_memory_file() -> Result<File>
+{ create_capture_file() }
+
+pub fn create_here_doc_file(s: &str) -> Result<File>
+{
+    if s.len() <= libc::PIPE_BUF {
+        let pipe_fds = pipe_with_cloexec()?;
+        let reading_file = unsafe { File::from_raw_fd(pipe_fds.reading_fd) };
+        let mut writing_file = unsafe { File::from_raw_fd(pipe_fds.writing_fd) };
+        writing_file.write_all(s.as_bytes())?;
+        Ok(reading_file)
+    } else {
+        let mut file = create_memory_file()?;
+        file.write_all(s.as_bytes())?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(file)
+    }
+}
+
+pub fn getsid(pid: i32) -> Result<i32>
+{
+    let res = unsafe { libc::getsid(pid) };
+    if res != -1 {
+        Ok(res)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+pub fn flock(fd: i32, op: i32) -> Result<()>
+{
+    let res = unsafe { libc::flock(fd, op) };
+    if res != -1 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
 pub fn kill(pid: i32, sig: i32) -> Result<()>
 {
     let res = unsafe { libc::kill(pid, sig) };
@@ -399,6 +475,61 @@ pub fn kill(pid: i32, sig: i32) -> Result<()>
     }
 }
 
+pub fn block_sigchld() -> Result<libc::sigset_t>
+{
+    let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+    let mut old_set = MaybeUninit::<libc::sigset_t>::uninit();
+    let res = unsafe {
+        libc::sigemptyset(set.as_mut_ptr());
+        libc::sigaddset(set.as_mut_ptr(), libc::SIGCHLD);
+        libc::pthread_sigmask(libc::SIG_BLOCK, set.as_ptr(), old_set.as_mut_ptr())
+    };
+    if res == 0 {
+        Ok(unsafe { old_set.assume_init() })
+    } else {
+        Err(Error::from_raw_os_error(res))
+    }
+}
+
+pub fn set_sigmask(set: &libc::sigset_t) -> Result<()>
+{
+    let res = unsafe { libc::pthread_sigmask(libc::SIG_SETMASK, set as *const libc::sigset_t, null_mut()) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(Error::from_raw_os_error(res))
+    }
+}
+
+pub fn wait_for_sigchld(timeout: Option<Duration>) -> Result<bool>
+{
+    let mut set = MaybeUninit::<libc::sigset_t>::uninit();
+    let res = unsafe {
+        libc::sigemptyset(set.as_mut_ptr());
+        libc::sigaddset(set.as_mut_ptr(), libc::SIGCHLD);
+        match timeout {
+            Some(timeout) => {
+                let timespec = libc::timespec {
+                    tv_sec: timeout.as_secs() as libc::time_t,
+                    tv_nsec: timeout.subsec_nanos() as libc::c_long,
+                };
+                libc::sigtimedwait(set.as_ptr(), null_mut(), &timespec as *const libc::timespec)
+            },
+            None => libc::sigwaitinfo(set.as_ptr(), null_mut()),
+        }
+    };
+    if res != -1 {
+        Ok(true)
+    } else {
+        let err = Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EAGAIN) => Ok(false),
+            Some(libc::EINTR) => Ok(true),
+            _ => Err(err),
+        }
+    }
+}
+
 pub fn umask(mask: u32) -> u32 
 { unsafe { libc::umask(mask as libc::mode_t) as u32 } }
 
@@ -442,6 +573,16 @@ pub unsafe fn fcntl_f_setfd(fd: i32, flags: i32) -> Result<()>
     }
 }
 
+pub fn fcntl_f_dupfd_cloexec(fd: i32, min_fd: i32) -> Result<i32>
+{
+    let res = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, min_fd) };
+    if res != -1 {
+        Ok(res)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
 pub fn pipe() -> Result<PipeFds>
 {
     let mut libc_pipe_fds: [i32; 2] = [-1, -1];
@@ -504,6 +645,27 @@ pub fn tcsetpgrp(fd: i32, pgrp: i32) -> Result<()>
     }
 }
 
+pub fn tcgetattr(fd: i32) -> Result<libc::termios>
+{
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    let res = unsafe { libc::tcgetattr(fd, &mut termios as *mut libc::termios) };
+    if res != -1 {
+        Ok(termios)
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+pub fn tcsetattr(fd: i32, action: i32, termios: &libc::termios) -> Result<()>
+{
+    let res = unsafe { libc::tcsetattr(fd, action, termios as *const libc::termios) };
+    if res != -1 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
 pub fn access<P: AsRef<Path>>(path: P, mode: i32) -> Result<bool>
 {
     let path_cstring = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();